@@ -38,7 +38,15 @@ impl RecordStart {
             (_, Some(id)) => cap_media::sources::list_windows()
                 .into_iter()
                 .find(|s| s.0.id == id)
-                .map(|(s, t)| (ScreenCaptureTarget::Window { id: s.id }, t))
+                .map(|(s, t)| {
+                    (
+                        ScreenCaptureTarget::Window {
+                            id: s.id,
+                            crop: None,
+                        },
+                        t,
+                    )
+                })
                 .ok_or(format!("Window with id '{id}' not found")),
             _ => Err("No target specified".to_string()),
         }?;
@@ -70,10 +78,24 @@ impl RecordStart {
             cap_recording::RecordingBaseInputs {
                 capture_target: target_info,
                 capture_system_audio: self.system_audio,
-                mic_feed: &None,
+                mic_feeds: &[],
+                system_audio_feed: &None,
+                audio_sample_rate: None,
+                excluded_apps: vec![],
+                capture_scale: 1.0,
+                skip_static_frames: false,
+                target_fps: None,
             },
             camera.map(|c| Arc::new(Mutex::new(c))),
             false,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+            Default::default(),
         )
         .await
         .map_err(|e| e.to_string())?;
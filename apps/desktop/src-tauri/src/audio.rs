@@ -15,17 +15,48 @@ fn play_audio(bytes: &'static [u8]) {
     });
 }
 
+// a short two-note chime, rising or falling
+fn play_tones(frequencies: [f32; 2]) {
+    use rodio::{
+        source::{SineWave, Source},
+        OutputStream, Sink,
+    };
+    use std::time::Duration;
+
+    std::thread::spawn(move || {
+        if let Ok((_, stream)) = OutputStream::try_default() {
+            let Ok(sink) = Sink::try_new(&stream) else {
+                return;
+            };
+            for frequency in frequencies {
+                sink.append(
+                    SineWave::new(frequency)
+                        .take_duration(Duration::from_millis(90))
+                        .fade_in(Duration::from_millis(10))
+                        .amplify(0.15),
+                );
+            }
+            sink.sleep_until_end();
+        }
+    });
+}
+
 pub enum AppSounds {
     StartRecording,
     StopRecording,
+    PauseRecording,
+    ResumeRecording,
     Screenshot,
     Notification,
 }
 
 impl AppSounds {
     pub fn play(&self) {
-        let bytes = self.get_sound_bytes();
-        play_audio(bytes);
+        match self {
+            AppSounds::PauseRecording => play_tones([880.0, 660.0]),
+            AppSounds::ResumeRecording => play_tones([660.0, 880.0]),
+            _ => play_audio(self.get_sound_bytes()),
+        }
     }
 
     fn get_sound_bytes(&self) -> &'static [u8] {
@@ -34,6 +65,8 @@ impl AppSounds {
             AppSounds::StopRecording => include_bytes!("../sounds/stop-recording.ogg"),
             AppSounds::Screenshot => include_bytes!("../sounds/screenshot.ogg"),
             AppSounds::Notification => include_bytes!("../sounds/action.ogg"),
+            // synthesized rather than recorded
+            AppSounds::PauseRecording | AppSounds::ResumeRecording => &[],
         }
     }
 }
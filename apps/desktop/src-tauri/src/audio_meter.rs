@@ -1,4 +1,4 @@
-use cap_media::feeds::{AudioInputSamples, AudioInputSamplesReceiver};
+use cap_media::feeds::{AudioInputFeed, AudioInputSamples, AudioInputSamplesReceiver};
 use cpal::{SampleFormat, StreamInstant};
 use keyed_priority_queue::KeyedPriorityQueue;
 use serde::{Deserialize, Serialize};
@@ -8,9 +8,13 @@ use std::time::Duration;
 use tauri::AppHandle;
 use tauri_specta::Event;
 
+use crate::{App, MutableState};
+
 const MAX_AMPLITUDE_F32: f64 = (u16::MAX / 2) as f64; // i16 max value
 const ZERO_AMPLITUDE: u16 = 0;
 const MIN_DB: f64 = -96.0;
+const WAVEFORM_POINTS: usize = 200;
+const MAX_WAVEFORM_PREVIEW_MS: u32 = 10_000;
 
 #[derive(Deserialize, specta::Type, Serialize, tauri_specta::Event, Debug, Clone)]
 pub struct AudioInputLevelChange(f64);
@@ -32,6 +36,111 @@ pub fn spawn_event_emitter(app_handle: AppHandle, audio_input_rx: AudioInputSamp
     });
 }
 
+#[derive(Serialize, specta::Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AudioWaveformPreview {
+    /// Peak amplitudes from 0 to 1, empty if the source produced no samples
+    mic: Option<Vec<f32>>,
+    system: Option<Vec<f32>>,
+}
+
+/// Captures `duration_ms` of the selected mic and/or system audio device and returns
+/// a waveform of each, so a muted output or the wrong mic is obvious before recording.
+#[tauri::command]
+#[specta::specta]
+pub async fn preview_audio_waveform(
+    state: MutableState<'_, App>,
+    duration_ms: u32,
+    include_mic: bool,
+    include_system: bool,
+    system_audio_device: Option<String>,
+) -> Result<AudioWaveformPreview, String> {
+    if duration_ms == 0 || duration_ms > MAX_WAVEFORM_PREVIEW_MS {
+        return Err(format!(
+            "Preview duration must be between 1 and {MAX_WAVEFORM_PREVIEW_MS}ms"
+        ));
+    }
+
+    if !include_mic && !include_system {
+        return Err("No audio sources to preview".to_string());
+    }
+
+    let mic_rx = if include_mic {
+        let mic_feed = state
+            .read()
+            .await
+            .mic_feed
+            .clone()
+            .ok_or("No microphone selected")?;
+        let (tx, rx) = AudioInputFeed::create_channel();
+        mic_feed.add_sender(tx).await.map_err(|e| e.to_string())?;
+        Some(rx)
+    } else {
+        None
+    };
+
+    // opened just for the preview, capture stops once it's dropped
+    let system_feed = match system_audio_device.filter(|_| include_system) {
+        Some(device) => Some(
+            AudioInputFeed::init(&device)
+                .await
+                .map_err(|e| format!("Failed to open system audio device '{device}': {e}"))?,
+        ),
+        None if include_system => return Err("No system audio device selected".to_string()),
+        None => None,
+    };
+    let system_rx = match &system_feed {
+        Some(feed) => {
+            let (tx, rx) = AudioInputFeed::create_channel();
+            feed.add_sender(tx).await.map_err(|e| e.to_string())?;
+            Some(rx)
+        }
+        None => None,
+    };
+
+    let duration = Duration::from_millis(duration_ms as u64);
+    let (mic, system) = tokio::join!(
+        capture_amplitudes(mic_rx, duration),
+        capture_amplitudes(system_rx, duration)
+    );
+
+    drop(system_feed);
+
+    Ok(AudioWaveformPreview {
+        mic: mic.map(|amplitudes| downsample_peaks(&amplitudes, WAVEFORM_POINTS)),
+        system: system.map(|amplitudes| downsample_peaks(&amplitudes, WAVEFORM_POINTS)),
+    })
+}
+
+async fn capture_amplitudes(
+    rx: Option<AudioInputSamplesReceiver>,
+    duration: Duration,
+) -> Option<Vec<f32>> {
+    let rx = rx?;
+    let mut amplitudes = vec![];
+
+    let _ = tokio::time::timeout(duration, async {
+        while let Ok(samples) = rx.recv_async().await {
+            amplitudes.extend(samples_to_f64(&samples).map(|s| s.abs().min(1.0) as f32));
+        }
+    })
+    .await;
+
+    Some(amplitudes)
+}
+
+// channels are left interleaved as only the peak of each bucket matters
+fn downsample_peaks(amplitudes: &[f32], points: usize) -> Vec<f32> {
+    if amplitudes.is_empty() {
+        return vec![];
+    }
+
+    amplitudes
+        .chunks(amplitudes.len().div_ceil(points))
+        .map(|bucket| bucket.iter().copied().fold(0.0, f32::max))
+        .collect()
+}
+
 // https://github.com/cgbur/meter/blob/master/src/time_window.rs
 struct VolumeMeter {
     keep_duration: Duration, // secs
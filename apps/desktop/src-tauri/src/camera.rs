@@ -1,5 +1,8 @@
 use cap_media::{feeds::RawCameraFrame, frame_ws::WSFrame};
 use flume::Sender;
+use tauri::AppHandle;
+
+use crate::general_settings::GeneralSettingsStore;
 
 pub async fn create_camera_preview_ws() -> (
     Sender<RawCameraFrame>,
@@ -71,3 +74,26 @@ pub async fn create_camera_preview_ws() -> (
 
     (camera_tx, camera_ws_port, _shutdown)
 }
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_camera_mirror(app: AppHandle) -> Result<bool, String> {
+    Ok(GeneralSettingsStore::get(&app)?
+        .map(|s| s.camera_mirror)
+        .unwrap_or_default())
+}
+
+/// Flips the camera preview and the camera in new studio recordings
+#[tauri::command]
+#[specta::specta]
+pub fn set_camera_mirror(app: AppHandle, mirror: bool) -> Result<(), String> {
+    GeneralSettingsStore::update(&app, |settings| settings.camera_mirror = mirror)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn toggle_camera_mirror(app: AppHandle) -> Result<bool, String> {
+    let mirror = !get_camera_mirror(app.clone())?;
+    set_camera_mirror(app, mirror)?;
+    Ok(mirror)
+}
@@ -1025,10 +1025,7 @@ fn app_captions_dir(app: &AppHandle, video_id: &str) -> Result<PathBuf, String>
     tracing::info!("Getting captions directory for video_id: {}", video_id);
 
     // Get the app data directory
-    let app_dir = app
-        .path()
-        .app_data_dir()
-        .map_err(|_| "Failed to get app data directory".to_string())?;
+    let app_dir = crate::app_data_dir(app)?;
 
     // Create a dedicated captions directory
     // Strip .cap extension if present in video_id
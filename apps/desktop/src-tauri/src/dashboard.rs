@@ -0,0 +1,124 @@
+use std::path::{Path, PathBuf};
+
+use cap_project::RecordingMetaInner;
+use cap_recording::RecordingMode;
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+
+use crate::{list_recordings, App, MutableState};
+
+// written by the final instant upload when it fails, removed once an upload succeeds
+const UPLOAD_FAILED_MARKER: &str = ".upload-failed";
+const DEFAULT_RECENT_LIMIT: usize = 10;
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingDashboard {
+    pub active: Option<ActiveRecordingStatus>,
+    pub recent: Vec<RecentRecording>,
+    /// All recordings whose upload failed, including ones not in `recent`
+    pub failed_uploads: Vec<RecentRecording>,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveRecordingStatus {
+    pub mode: RecordingMode,
+    pub recording_dir: PathBuf,
+    pub elapsed_secs: f64,
+    pub paused: bool,
+}
+
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentRecording {
+    pub path: PathBuf,
+    pub pretty_name: String,
+    pub mode: RecordingMode,
+    pub upload: UploadStatus,
+}
+
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum UploadStatus {
+    NotUploaded,
+    Uploaded { link: String },
+    Failed { link: Option<String> },
+}
+
+pub fn set_upload_failed(project_path: &Path, failed: bool) {
+    let marker = project_path.join(UPLOAD_FAILED_MARKER);
+
+    if failed {
+        std::fs::write(marker, "").ok();
+    } else {
+        std::fs::remove_file(marker).ok();
+    }
+}
+
+/// Everything a status panel needs in one call: the recording in progress,
+/// the most recent `limit` recordings and any uploads that need retrying.
+#[tauri::command]
+#[specta::specta]
+pub async fn recording_dashboard(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    limit: Option<u32>,
+) -> Result<RecordingDashboard, String> {
+    let active = state
+        .read()
+        .await
+        .current_recording
+        .as_ref()
+        .map(|recording| ActiveRecordingStatus {
+            mode: recording.mode(),
+            recording_dir: recording.recording_dir().clone(),
+            elapsed_secs: recording.clock().elapsed().as_secs_f64(),
+            paused: recording.clock().is_paused(),
+        });
+
+    let recordings = list_recordings(app)?
+        .into_iter()
+        // the active recording has no meta yet, but skip it in case it does
+        .filter(|(path, _)| active.as_ref().map_or(true, |a| &a.recording_dir != path))
+        .map(|(path, meta)| {
+            let meta = meta.inner;
+            let link = meta.sharing.map(|s| s.link);
+
+            RecentRecording {
+                upload: if path.join(UPLOAD_FAILED_MARKER).exists() {
+                    UploadStatus::Failed { link }
+                } else {
+                    match link {
+                        Some(link) => UploadStatus::Uploaded { link },
+                        None => UploadStatus::NotUploaded,
+                    }
+                },
+                mode: match meta.inner {
+                    RecordingMetaInner::Instant(_) => RecordingMode::Instant,
+                    RecordingMetaInner::Studio(_) => RecordingMode::Studio,
+                },
+                pretty_name: meta.pretty_name,
+                path,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let failed_uploads = recordings
+        .iter()
+        .filter(|r| matches!(r.upload, UploadStatus::Failed { .. }))
+        .cloned()
+        .collect();
+
+    let recent = recordings
+        .into_iter()
+        .take(limit.map_or(DEFAULT_RECENT_LIMIT, |l| l as usize))
+        .collect();
+
+    Ok(RecordingDashboard {
+        active,
+        recent,
+        failed_uploads,
+    })
+}
@@ -119,20 +119,38 @@ impl DeepLinkAction {
                     CaptureMode::Window(name) => cap_media::sources::list_windows()
                         .into_iter()
                         .find(|(w, _)| w.name == name)
-                        .map(|(w, _)| ScreenCaptureTarget::Window { id: w.id })
+                        .map(|(w, _)| ScreenCaptureTarget::Window {
+                            id: w.id,
+                            crop: None,
+                        })
                         .ok_or(format!("No window with name \"{}\"", &name))?,
                 };
 
                 let inputs = StartRecordingInputs {
                     capture_target,
                     capture_system_audio,
+                    system_audio_device: None,
                     mode,
+                    cursor_follow: None,
+                    camera_only: false,
+                    max_duration_secs: None,
+                    output_dir: None,
+                    countdown_secs: None,
+                    defer_upload: false,
+                    mic_device: None,
+                    camera_device: None,
+                    include_camera: true,
+                    target_fps: None,
                 };
 
-                crate::recording::start_recording(app.clone(), state, inputs).await
+                crate::recording::start_recording(app.clone(), state, inputs)
+                    .await
+                    .map(|_| ())
             }
             DeepLinkAction::StopRecording => {
-                crate::recording::stop_recording(app.clone(), app.state()).await
+                crate::recording::stop_recording(app.clone(), app.state(), None)
+                    .await
+                    .map(|_| ())
             }
             DeepLinkAction::OpenEditor { project_path } => {
                 crate::open_project_from_path(&project_path.into(), app.clone())
@@ -1,6 +1,9 @@
 use crate::{get_video_metadata, FramesRendered};
+use cap_audio::AudioData;
 use cap_export::ExporterBase;
 use cap_project::{RecordingMeta, XY};
+use cap_rendering::ProjectRecordingsMeta;
+use relative_path::RelativePathBuf;
 use serde::Deserialize;
 use specta::Type;
 use std::path::PathBuf;
@@ -153,3 +156,550 @@ pub async fn get_export_estimates(
         estimated_size_mb,
     })
 }
+
+#[derive(Deserialize, Clone, Copy, Debug, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum TimelineExportFormat {
+    Fcpxml,
+}
+
+/// Writes the project's edit as an interchange file next to the recording.
+///
+/// For FCPXML the following is exported:
+/// - one `asset` per recording segment, referencing its `display.mp4`
+/// - one `asset-clip` per timeline segment, with `start`/`duration` taken from its trim
+/// - a linear `timeMap` on clips whose timescale isn't 1
+/// - camera, mic and system audio as connected clips on lanes 1, -1 and -2
+/// - zoom segments as markers named with their zoom amount, as FCPXML has no
+///   equivalent of Cap's cursor-following zoom
+///
+/// Background, padding, cursor and caption settings are not exported.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_timeline(
+    project_path: PathBuf,
+    format: TimelineExportFormat,
+) -> Result<PathBuf, String> {
+    let meta = RecordingMeta::load_for_project(&project_path).map_err(|e| e.to_string())?;
+    let Some(studio_meta) = meta.studio_meta() else {
+        return Err("Only studio recordings have a timeline to export".to_string());
+    };
+
+    let recordings = ProjectRecordingsMeta::new(&project_path, studio_meta)?;
+    let config = meta.project_config();
+
+    let (contents, file_name) = match format {
+        TimelineExportFormat::Fcpxml => (
+            fcpxml::build(&meta, studio_meta, &recordings, &config)?,
+            "timeline.fcpxml",
+        ),
+    };
+
+    let output_path = project_path.join(file_name);
+    std::fs::write(&output_path, contents).map_err(|e| e.to_string())?;
+
+    info!("Exported timeline to {}", output_path.display());
+
+    Ok(output_path)
+}
+
+/// Bounces a studio recording's separate mic and system audio tracks down to a
+/// single WAV file, applying `mic_gain` and `system_gain` in dB.
+///
+/// Segments are joined end to end, with each padded with silence to the length
+/// of its video so the mix stays in sync with the recording.
+#[tauri::command]
+#[specta::specta]
+pub async fn mix_audio_tracks(
+    project_path: PathBuf,
+    output_path: PathBuf,
+    mic_gain: f32,
+    system_gain: f32,
+) -> Result<PathBuf, String> {
+    if !output_path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("wav"))
+    {
+        return Err("Mixed audio can only be written as a .wav file".to_string());
+    }
+
+    let meta = RecordingMeta::load_for_project(&project_path).map_err(|e| e.to_string())?;
+    let Some(studio_meta) = meta.studio_meta() else {
+        return Err("Only studio recordings have separate audio tracks".to_string());
+    };
+
+    let recordings = ProjectRecordingsMeta::new(&project_path, studio_meta)?;
+    let media = fcpxml::segment_media(studio_meta);
+
+    if media
+        .iter()
+        .all(|m| m.mic.is_none() && m.system_audio.is_none())
+    {
+        return Err("Recording has no audio tracks to mix".to_string());
+    }
+
+    let mixed = tokio::task::spawn_blocking({
+        let project_path = project_path.clone();
+        move || {
+            let mut segments = vec![];
+
+            for (i, (media, recording)) in media.iter().zip(&recordings.segments).enumerate() {
+                let load = |path: &RelativePathBuf| {
+                    AudioData::from_file(path.to_path(&project_path))
+                        .map_err(|e| format!("segment {i} / {e}"))
+                };
+
+                let mic = media.mic.as_ref().map(load).transpose()?;
+                let system_audio = media.system_audio.as_ref().map(load).transpose()?;
+
+                let tracks = mic
+                    .as_ref()
+                    .map(|mic| (mic, mic_gain))
+                    .into_iter()
+                    .chain(system_audio.as_ref().map(|system| (system, system_gain)))
+                    .collect::<Vec<_>>();
+
+                let mut mixed = cap_audio::mix_tracks(&tracks);
+                mixed.resize((recording.duration() * AudioData::SAMPLE_RATE as f64) as usize);
+                segments.push(mixed);
+            }
+
+            Ok::<_, String>(AudioData::concat(segments))
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    std::fs::write(&output_path, mixed.to_wav()).map_err(|e| e.to_string())?;
+
+    info!("Mixed audio tracks to {}", output_path.display());
+
+    Ok(output_path)
+}
+
+mod fcpxml {
+    use std::fmt::Write;
+
+    use cap_project::{ProjectConfiguration, RecordingMeta, StudioRecordingMeta, TimelineSegment};
+    use cap_rendering::ProjectRecordingsMeta;
+    use relative_path::RelativePathBuf;
+
+    /// `writeln!`, returning formatting errors as the `String`s `build` fails with
+    macro_rules! write_line {
+        ($($arg:tt)*) => {
+            writeln!($($arg)*).map_err(|e| e.to_string())?
+        };
+    }
+
+    pub struct SegmentMedia {
+        pub display: RelativePathBuf,
+        pub camera: Option<RelativePathBuf>,
+        pub mic: Option<RelativePathBuf>,
+        pub system_audio: Option<RelativePathBuf>,
+    }
+
+    pub fn segment_media(meta: &StudioRecordingMeta) -> Vec<SegmentMedia> {
+        match meta {
+            StudioRecordingMeta::SingleSegment { segment } => vec![SegmentMedia {
+                display: segment.display.path.clone(),
+                camera: segment.camera.as_ref().map(|c| c.path.clone()),
+                mic: segment.audio.as_ref().map(|a| a.path.clone()),
+                system_audio: None,
+            }],
+            StudioRecordingMeta::MultipleSegments { inner } => inner
+                .segments
+                .iter()
+                .map(|s| SegmentMedia {
+                    display: s.display.path.clone(),
+                    camera: s.camera.as_ref().map(|c| c.path.clone()),
+                    mic: s.mic.as_ref().map(|a| a.path.clone()),
+                    system_audio: s.system_audio.as_ref().map(|a| a.path.clone()),
+                })
+                .collect(),
+        }
+    }
+
+    fn escape(value: &str) -> String {
+        value
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    pub fn build(
+        meta: &RecordingMeta,
+        studio_meta: &StudioRecordingMeta,
+        recordings: &ProjectRecordingsMeta,
+        config: &ProjectConfiguration,
+    ) -> Result<String, String> {
+        let Some(first) = recordings.segments.first() else {
+            return Err("Recording has no segments".to_string());
+        };
+
+        let fps = studio_meta.max_fps().max(1);
+        // FCPXML wants rational times, so everything is snapped to whole frames
+        let time = |secs: f64| format!("{}/{fps}s", (secs * fps as f64).round() as i64);
+
+        let media = segment_media(studio_meta);
+        let src = |path: &RelativePathBuf| -> Result<String, String> {
+            tauri::Url::from_file_path(meta.path(path))
+                .map(|url| url.to_string())
+                .map_err(|_| format!("Invalid media path '{path}'"))
+        };
+
+        let timeline_segments = config
+            .timeline
+            .as_ref()
+            .map(|t| t.segments.clone())
+            .unwrap_or_else(|| {
+                recordings
+                    .segments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| TimelineSegment {
+                        recording_segment: i as u32,
+                        timescale: 1.0,
+                        start: 0.0,
+                        end: s.duration(),
+                    })
+                    .collect()
+            });
+        let zoom_segments = config
+            .timeline
+            .as_ref()
+            .map(|t| t.zoom_segments.as_slice())
+            .unwrap_or_default();
+
+        let mut resources = String::new();
+        write_line!(
+            resources,
+            r#"    <format id="r0" frameDuration="1/{fps}s" width="{}" height="{}"/>"#,
+            first.display.width,
+            first.display.height
+        );
+
+        let asset_id = |segment: usize, kind: &str| format!("s{segment}-{kind}");
+        let mut add_asset = |id: String,
+                             path: &RelativePathBuf,
+                             duration: f64,
+                             video: bool|
+         -> Result<(), String> {
+            write_line!(
+                resources,
+                r#"    <asset id="{id}" name="{}" start="0s" duration="{}" hasVideo="{}" hasAudio="{}"{}>
+      <media-rep kind="original-media" src="{}"/>
+    </asset>"#,
+                escape(path.as_str()),
+                time(duration),
+                video as u8,
+                !video as u8,
+                if video { r#" format="r0""# } else { "" },
+                escape(&src(path)?)
+            );
+            Ok(())
+        };
+
+        for (i, (files, recording)) in media.iter().zip(&recordings.segments).enumerate() {
+            add_asset(
+                asset_id(i, "display"),
+                &files.display,
+                recording.display.duration,
+                true,
+            )?;
+            if let (Some(path), Some(camera)) = (&files.camera, &recording.camera) {
+                add_asset(asset_id(i, "camera"), path, camera.duration, true)?;
+            }
+            if let (Some(path), Some(mic)) = (&files.mic, &recording.mic) {
+                add_asset(asset_id(i, "mic"), path, mic.duration, false)?;
+            }
+            if let (Some(path), Some(system_audio)) = (&files.system_audio, &recording.system_audio)
+            {
+                add_asset(
+                    asset_id(i, "system-audio"),
+                    path,
+                    system_audio.duration,
+                    false,
+                )?;
+            }
+        }
+
+        let mut spine = String::new();
+        let mut offset = 0.0;
+
+        for segment in &timeline_segments {
+            let i = segment.recording_segment as usize;
+            let (Some(files), Some(recording)) = (media.get(i), recordings.segments.get(i)) else {
+                return Err(format!("Timeline references missing segment {i}"));
+            };
+
+            let source_duration = segment.end - segment.start;
+            let timescale = if segment.timescale > 0.0 {
+                segment.timescale
+            } else {
+                1.0
+            };
+            let duration = source_duration / timescale;
+
+            write_line!(
+                spine,
+                r#"          <asset-clip ref="{}" name="Segment {}" offset="{}" start="{}" duration="{}" format="r0">"#,
+                asset_id(i, "display"),
+                i + 1,
+                time(offset),
+                time(segment.start),
+                time(duration)
+            );
+
+            if timescale != 1.0 {
+                write_line!(
+                    spine,
+                    r#"            <timeMap>
+              <timept time="0s" value="{}" interp="linear"/>
+              <timept time="{}" value="{}" interp="linear"/>
+            </timeMap>"#,
+                    time(segment.start),
+                    time(duration),
+                    time(segment.end)
+                );
+            }
+
+            let connected = [
+                (
+                    files.camera.is_some() && recording.camera.is_some(),
+                    "camera",
+                    1,
+                ),
+                (files.mic.is_some() && recording.mic.is_some(), "mic", -1),
+                (
+                    files.system_audio.is_some() && recording.system_audio.is_some(),
+                    "system-audio",
+                    -2,
+                ),
+            ];
+            for (_, kind, lane) in connected.into_iter().filter(|(present, ..)| *present) {
+                write_line!(
+                    spine,
+                    r#"            <asset-clip ref="{}" lane="{lane}" offset="{}" start="{}" duration="{}"/>"#,
+                    asset_id(i, kind),
+                    time(segment.start),
+                    time(segment.start),
+                    time(source_duration)
+                );
+            }
+
+            // zoom segments are in output time, markers are in the clip's source time
+            for zoom in zoom_segments {
+                let start = zoom.start.max(offset);
+                let end = zoom.end.min(offset + duration);
+                if end <= start {
+                    continue;
+                }
+
+                write_line!(
+                    spine,
+                    r#"            <marker start="{}" duration="{}" value="Zoom {}x"/>"#,
+                    time(segment.start + (start - offset) * timescale),
+                    time((end - start) * timescale),
+                    zoom.amount
+                );
+            }
+
+            write_line!(spine, "          </asset-clip>");
+
+            offset += duration;
+        }
+
+        let name = escape(&meta.pretty_name);
+
+        Ok(format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE fcpxml>
+<fcpxml version="1.9">
+  <resources>
+{resources}  </resources>
+  <library>
+    <event name="{name}">
+      <project name="{name}">
+        <sequence format="r0" duration="{}" tcStart="0s" tcFormat="NDF">
+          <spine>
+{spine}          </spine>
+        </sequence>
+      </project>
+    </event>
+  </library>
+</fcpxml>
+"#,
+            time(offset)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use cap_project::{
+        ProjectConfiguration, TimelineConfiguration, TimelineSegment, ZoomMode, ZoomSegment,
+    };
+    use cap_rendering::{Audio, SegmentRecordings, Video};
+
+    use super::*;
+
+    fn video(duration: f64) -> Video {
+        Video {
+            duration,
+            width: 1920,
+            height: 1080,
+            fps: 30,
+            start_time: 0.0,
+        }
+    }
+
+    fn project() -> (RecordingMeta, ProjectRecordingsMeta) {
+        let mut meta: RecordingMeta = serde_json::from_value(serde_json::json!({
+            "platform": null,
+            "pretty_name": "Demo & Test",
+            "segments": [
+                {
+                    "display": { "path": "content/segments/segment-0/display.mp4", "fps": 30 },
+                    "mic": { "path": "content/segments/segment-0/audio-input.ogg" },
+                },
+                {
+                    "display": { "path": "content/segments/segment-1/display.mp4", "fps": 30 },
+                    "camera": { "path": "content/segments/segment-1/camera.mp4", "fps": 30 },
+                },
+            ],
+        }))
+        .unwrap();
+        meta.project_path = std::env::temp_dir().join("Demo.cap");
+
+        let recordings = ProjectRecordingsMeta {
+            segments: vec![
+                SegmentRecordings {
+                    display: video(10.0),
+                    camera: None,
+                    mic: Some(Audio {
+                        duration: 10.0,
+                        sample_rate: 48_000,
+                        channels: 1,
+                        start_time: 0.0,
+                    }),
+                    system_audio: None,
+                },
+                SegmentRecordings {
+                    display: video(5.0),
+                    camera: Some(video(5.0)),
+                    mic: None,
+                    system_audio: None,
+                },
+            ],
+        };
+
+        (meta, recordings)
+    }
+
+    fn config(segments: Vec<TimelineSegment>) -> ProjectConfiguration {
+        ProjectConfiguration {
+            timeline: Some(TimelineConfiguration {
+                segments,
+                zoom_segments: vec![ZoomSegment {
+                    start: 2.0,
+                    end: 4.0,
+                    amount: 1.5,
+                    mode: ZoomMode::Auto,
+                    follow_path: vec![],
+                }],
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn fcpxml_matches_golden_output() {
+        let (meta, recordings) = project();
+        let config = config(vec![
+            TimelineSegment {
+                recording_segment: 0,
+                timescale: 1.0,
+                start: 1.0,
+                end: 9.0,
+            },
+            TimelineSegment {
+                recording_segment: 1,
+                timescale: 2.0,
+                start: 0.0,
+                end: 4.0,
+            },
+        ]);
+
+        let src = |path: &str| {
+            tauri::Url::from_file_path(meta.path(&RelativePathBuf::from(path)))
+                .unwrap()
+                .to_string()
+        };
+
+        let expected = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE fcpxml>
+<fcpxml version="1.9">
+  <resources>
+    <format id="r0" frameDuration="1/30s" width="1920" height="1080"/>
+    <asset id="s0-display" name="content/segments/segment-0/display.mp4" start="0s" duration="300/30s" hasVideo="1" hasAudio="0" format="r0">
+      <media-rep kind="original-media" src="{}"/>
+    </asset>
+    <asset id="s0-mic" name="content/segments/segment-0/audio-input.ogg" start="0s" duration="300/30s" hasVideo="0" hasAudio="1">
+      <media-rep kind="original-media" src="{}"/>
+    </asset>
+    <asset id="s1-display" name="content/segments/segment-1/display.mp4" start="0s" duration="150/30s" hasVideo="1" hasAudio="0" format="r0">
+      <media-rep kind="original-media" src="{}"/>
+    </asset>
+    <asset id="s1-camera" name="content/segments/segment-1/camera.mp4" start="0s" duration="150/30s" hasVideo="1" hasAudio="0" format="r0">
+      <media-rep kind="original-media" src="{}"/>
+    </asset>
+  </resources>
+  <library>
+    <event name="Demo &amp; Test">
+      <project name="Demo &amp; Test">
+        <sequence format="r0" duration="300/30s" tcStart="0s" tcFormat="NDF">
+          <spine>
+          <asset-clip ref="s0-display" name="Segment 1" offset="0/30s" start="30/30s" duration="240/30s" format="r0">
+            <asset-clip ref="s0-mic" lane="-1" offset="30/30s" start="30/30s" duration="240/30s"/>
+            <marker start="90/30s" duration="60/30s" value="Zoom 1.5x"/>
+          </asset-clip>
+          <asset-clip ref="s1-display" name="Segment 2" offset="240/30s" start="0/30s" duration="60/30s" format="r0">
+            <timeMap>
+              <timept time="0s" value="0/30s" interp="linear"/>
+              <timept time="60/30s" value="120/30s" interp="linear"/>
+            </timeMap>
+            <asset-clip ref="s1-camera" lane="1" offset="0/30s" start="0/30s" duration="120/30s"/>
+          </asset-clip>
+          </spine>
+        </sequence>
+      </project>
+    </event>
+  </library>
+</fcpxml>
+"#,
+            src("content/segments/segment-0/display.mp4"),
+            src("content/segments/segment-0/audio-input.ogg"),
+            src("content/segments/segment-1/display.mp4"),
+            src("content/segments/segment-1/camera.mp4"),
+        );
+
+        let built =
+            fcpxml::build(&meta, meta.studio_meta().unwrap(), &recordings, &config).unwrap();
+        assert_eq!(built, expected);
+    }
+
+    #[test]
+    fn fcpxml_rejects_missing_segments() {
+        let (meta, recordings) = project();
+        let config = config(vec![TimelineSegment {
+            recording_segment: 2,
+            timescale: 1.0,
+            start: 0.0,
+            end: 1.0,
+        }]);
+
+        assert!(fcpxml::build(&meta, meta.studio_meta().unwrap(), &recordings, &config).is_err());
+    }
+}
@@ -0,0 +1,160 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tokio::{sync::oneshot, task::JoinHandle};
+use tracing::error;
+
+const FOCUS_EVENTS_FILE: &str = "focus-events.json";
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A change of the focused window, timed against the recording with pauses removed.
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusEvent {
+    pub time_ms: f64,
+    pub app_name: String,
+    pub window_title: String,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct FocusEvents {
+    pub events: Vec<FocusEvent>,
+}
+
+impl FocusEvents {
+    pub fn load_for_project(project_path: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(project_path.join(FOCUS_EVENTS_FILE)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Distinct app names in order of first focus
+    pub fn app_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = vec![];
+
+        for event in &self.events {
+            if !names.contains(&event.app_name) {
+                names.push(event.app_name.clone());
+            }
+        }
+
+        names
+    }
+}
+
+/// Polls the focused window while recording, skipping time spent paused.
+pub struct FocusEventsRecorder {
+    paused: Arc<AtomicBool>,
+    stop_tx: oneshot::Sender<()>,
+    handle: JoinHandle<FocusEvents>,
+}
+
+impl FocusEventsRecorder {
+    pub fn spawn() -> Self {
+        let paused = Arc::new(AtomicBool::new(false));
+        let (stop_tx, mut stop_rx) = oneshot::channel();
+
+        let handle = tokio::spawn({
+            let paused = paused.clone();
+
+            async move {
+                let own_pid = std::process::id();
+                let mut events = FocusEvents::default();
+                let mut active_time = Duration::ZERO;
+                let mut interval = tokio::time::interval(POLL_INTERVAL);
+                let mut last_tick = Instant::now();
+
+                loop {
+                    tokio::select! {
+                        _ = &mut stop_rx => break,
+                        _ = interval.tick() => {}
+                    }
+
+                    // ticks run late under load, so time the gap rather than assuming POLL_INTERVAL
+                    let now = Instant::now();
+                    let elapsed = now - last_tick;
+                    last_tick = now;
+
+                    if paused.load(Ordering::Relaxed) {
+                        continue;
+                    }
+
+                    active_time += elapsed;
+
+                    let focused =
+                        tokio::task::spawn_blocking(cap_media::platform::get_focused_window)
+                            .await
+                            .ok()
+                            .flatten();
+
+                    // Cap's own windows get focus when pausing/stopping, they aren't interesting
+                    if let Some(window) = focused.filter(|w| w.process_id != own_pid) {
+                        let changed = events.events.last().map_or(true, |last| {
+                            last.app_name != window.owner_name || last.window_title != window.name
+                        });
+
+                        if changed {
+                            events.events.push(FocusEvent {
+                                time_ms: active_time.as_secs_f64() * 1000.0,
+                                app_name: window.owner_name,
+                                window_title: window.name,
+                            });
+                        }
+                    }
+                }
+
+                events
+            }
+        });
+
+        Self {
+            paused,
+            stop_tx,
+            handle,
+        }
+    }
+
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Stops polling and writes the collected events into the project directory.
+    pub async fn stop(self, project_path: &PathBuf) {
+        let _ = self.stop_tx.send(());
+
+        let Ok(events) = self.handle.await else {
+            return;
+        };
+
+        let result = serde_json::to_string_pretty(&events)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                std::fs::write(project_path.join(FOCUS_EVENTS_FILE), json)
+                    .map_err(|e| e.to_string())
+            });
+
+        if let Err(e) = result {
+            error!("Failed to write focus events: {e}");
+        }
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_focus_events(project_path: PathBuf) -> Vec<FocusEvent> {
+    FocusEvents::load_for_project(&project_path)
+        .map(|e| e.events)
+        .unwrap_or_default()
+}
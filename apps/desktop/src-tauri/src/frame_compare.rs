@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use cap_project::RecordingMeta;
+use image::GrayImage;
+use serde::Serialize;
+use specta::Type;
+
+use crate::{display_paths, extract_frame, get_duration_for_path};
+
+/// Size of the square windows SSIM is computed over
+const SSIM_WINDOW: u32 = 8;
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FrameComparison {
+    /// Structural dissimilarity, from 0 for identical frames to 1 for completely different ones
+    pub score: f64,
+    /// Whether `score` is within the threshold
+    pub passed: bool,
+}
+
+/// Compares the recorded frame at `timestamp` seconds against a reference image,
+/// for checking recordings in visual regression tests. The frame is scaled to the
+/// reference's size, and the comparison is of their luma so minor colour shifts
+/// from encoding don't count.
+#[tauri::command]
+#[specta::specta]
+pub async fn compare_frame(
+    project_path: PathBuf,
+    timestamp: f64,
+    reference_path: PathBuf,
+    threshold: f64,
+) -> Result<FrameComparison, String> {
+    let meta = RecordingMeta::load_for_project(&project_path)
+        .map_err(|e| format!("Failed to load recording meta: {e}"))?;
+
+    if timestamp < 0.0 {
+        return Err("Timestamp can't be negative".to_string());
+    }
+
+    // studio recordings are split into segments, so find the one the timestamp falls in
+    let mut segment_time = timestamp;
+    let mut source = None;
+    for path in display_paths(&meta) {
+        let duration = get_duration_for_path(path.clone())?;
+        if segment_time < duration {
+            source = Some(path);
+            break;
+        }
+        segment_time -= duration;
+    }
+    let source = source.ok_or_else(|| format!("{timestamp}s is past the end of the recording"))?;
+
+    let reference = image::open(&reference_path)
+        .map_err(|e| format!("Failed to open reference image: {e}"))?
+        .to_luma8();
+
+    tokio::task::spawn_blocking(move || {
+        let size = (reference.width(), reference.height());
+        let frame =
+            image::DynamicImage::ImageRgb8(extract_frame(&source, Some(size), Some(segment_time))?)
+                .to_luma8();
+
+        let score = structural_dissimilarity(&frame, &reference);
+
+        Ok(FrameComparison {
+            score,
+            passed: score <= threshold,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// (1 - SSIM) / 2, with SSIM averaged over `SSIM_WINDOW` sized windows.
+/// The images must be the same size.
+fn structural_dissimilarity(a: &GrayImage, b: &GrayImage) -> f64 {
+    const C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+    const C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+    let (width, height) = a.dimensions();
+    let mut total = 0.0;
+    let mut windows = 0;
+
+    for y in (0..height).step_by(SSIM_WINDOW as usize) {
+        for x in (0..width).step_by(SSIM_WINDOW as usize) {
+            let pixels = (y..(y + SSIM_WINDOW).min(height))
+                .flat_map(|y| (x..(x + SSIM_WINDOW).min(width)).map(move |x| (x, y)))
+                .map(|(x, y)| (a.get_pixel(x, y)[0] as f64, b.get_pixel(x, y)[0] as f64))
+                .collect::<Vec<_>>();
+            let n = pixels.len() as f64;
+
+            let mean_a = pixels.iter().map(|p| p.0).sum::<f64>() / n;
+            let mean_b = pixels.iter().map(|p| p.1).sum::<f64>() / n;
+
+            let (mut var_a, mut var_b, mut covariance) = (0.0, 0.0, 0.0);
+            for (pa, pb) in &pixels {
+                var_a += (pa - mean_a).powi(2);
+                var_b += (pb - mean_b).powi(2);
+                covariance += (pa - mean_a) * (pb - mean_b);
+            }
+            var_a /= n;
+            var_b /= n;
+            covariance /= n;
+
+            total += ((2.0 * mean_a * mean_b + C1) * (2.0 * covariance + C2))
+                / ((mean_a.powi(2) + mean_b.powi(2) + C1) * (var_a + var_b + C2));
+            windows += 1;
+        }
+    }
+
+    if windows == 0 {
+        return 0.0;
+    }
+
+    ((1.0 - total / windows as f64) / 2.0).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn checkerboard(width: u32, height: u32, inverted: bool) -> GrayImage {
+        GrayImage::from_fn(width, height, |x, y| {
+            image::Luma([if ((x + y) % 2 == 0) != inverted {
+                255
+            } else {
+                0
+            }])
+        })
+    }
+
+    #[test]
+    fn identical_frames_score_zero() {
+        // not a multiple of the window size, so the edge windows are partial
+        let frame = checkerboard(20, 13, false);
+
+        assert!(structural_dissimilarity(&frame, &frame).abs() < 1e-9);
+    }
+
+    #[test]
+    fn inverted_structure_scores_one() {
+        let score =
+            structural_dissimilarity(&checkerboard(16, 16, false), &checkerboard(16, 16, true));
+
+        assert!(score > 0.99, "{score}");
+    }
+
+    #[test]
+    fn brightness_alone_scores_half() {
+        let black = GrayImage::from_pixel(16, 16, image::Luma([0]));
+        let white = GrayImage::from_pixel(16, 16, image::Luma([255]));
+        let score = structural_dissimilarity(&black, &white);
+
+        assert!((0.49..=0.5).contains(&score), "{score}");
+    }
+
+    #[test]
+    fn small_differences_score_low() {
+        let frame = checkerboard(16, 16, false);
+        let mut noisy = frame.clone();
+        noisy.put_pixel(3, 3, image::Luma([128]));
+
+        let score = structural_dissimilarity(&frame, &noisy);
+        assert!(score > 0.0 && score < 0.05, "{score}");
+    }
+}
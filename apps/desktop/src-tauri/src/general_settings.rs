@@ -1,3 +1,8 @@
+use std::path::PathBuf;
+
+use cap_media::sources::ScreenCaptureTarget;
+use cap_project::{AspectFillMode, Color, InstantOutputFormat, VideoCodec};
+use cap_recording::RecordingMode;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use specta::Type;
@@ -5,7 +10,12 @@ use tauri::{AppHandle, Wry};
 use tauri_plugin_store::StoreExt;
 use uuid::Uuid;
 
-#[derive(Default, Serialize, Deserialize, Type, Debug)]
+use crate::{
+    system_pressure::{PressureLevel, PressureResponse},
+    transcode::RenditionProfile,
+};
+
+#[derive(Default, Serialize, Deserialize, Type, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum PostStudioRecordingBehaviour {
     #[default]
@@ -13,7 +23,7 @@ pub enum PostStudioRecordingBehaviour {
     ShowOverlay,
 }
 
-#[derive(Default, Serialize, Deserialize, Type, Debug)]
+#[derive(Default, Serialize, Deserialize, Type, Debug, Clone, Copy)]
 #[serde(rename_all = "camelCase")]
 pub enum MainWindowRecordingStartBehaviour {
     #[default]
@@ -21,6 +31,43 @@ pub enum MainWindowRecordingStartBehaviour {
     Minimise,
 }
 
+#[derive(Default, Serialize, Deserialize, Type, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum ShareLinkOpenBehaviour {
+    #[default]
+    Browser,
+    CapWindow,
+}
+
+/// When the camera window is hidden from screen capture, which keeps it out of the recording
+#[derive(Default, Serialize, Deserialize, Type, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum CameraContentProtection {
+    /// While studio recording, as studio recordings record the camera separately
+    #[default]
+    StudioOnly,
+    Always,
+    Never,
+}
+
+/// What the last recording that started successfully captured, to start from next time
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub struct LastRecordingInputs {
+    pub capture_target: ScreenCaptureTarget,
+    pub mode: RecordingMode,
+}
+
+impl CameraContentProtection {
+    pub fn is_protected(self, recording_mode: Option<RecordingMode>) -> bool {
+        match self {
+            Self::StudioOnly => matches!(recording_mode, Some(RecordingMode::Studio)),
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Type, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct GeneralSettingsStore {
@@ -38,6 +85,9 @@ pub struct GeneralSettingsStore {
     pub enable_notifications: bool,
     #[serde(default)]
     pub disable_auto_open_links: bool,
+    /// Where instant recordings' share links are opened once recording stops
+    #[serde(default)]
+    pub share_link_open_behaviour: ShareLinkOpenBehaviour,
     // first launch: store won't exist so show startup
     #[serde(default = "true_b")]
     pub has_completed_startup: bool,
@@ -55,11 +105,185 @@ pub struct GeneralSettingsStore {
     pub main_window_recording_start_behaviour: MainWindowRecordingStartBehaviour,
     #[serde(default)]
     pub custom_cursor_capture: bool,
+    /// Scales the cursor size new studio recordings start with, for viewers who
+    /// struggle to follow a small cursor. Only applies with custom cursor capture,
+    /// instant recordings use the system cursor as-is.
+    #[serde(default = "default_cursor_size_multiplier")]
+    pub cursor_size_multiplier: f32,
+    #[serde(default)]
+    pub camera_mirror: bool,
+    /// Skips everything non-essential to recording: per-recording log files,
+    /// cursor capture, progressive instant uploads (the file is uploaded once
+    /// recording stops instead), the display.jpg screenshot and auto-zoom generation.
+    #[serde(default)]
+    pub minimal_overhead: bool,
+    /// Off by default as window titles can contain sensitive information.
+    #[serde(default)]
+    pub record_focus_events: bool,
+    /// Part size for progressive instant uploads. Larger parts mean fewer requests
+    /// on high-latency links, smaller parts mean less to resend on flaky ones.
+    #[serde(default = "default_upload_chunk_size_mb")]
+    pub upload_chunk_size_mb: u32,
+    /// Rolls studio recordings over to a new segment every this many seconds,
+    /// so a crash only loses the segment that was being written.
+    #[serde(default)]
+    pub segment_duration_secs: Option<u32>,
+    /// Seconds captured before a studio recording is started to include at its start,
+    /// up to 5. Capture runs in the background while the main window is open to
+    /// make this possible, so it isn't free even when not recording.
+    #[serde(default)]
+    pub pre_roll_secs: u32,
+    /// Loudness in LUFS that studio recordings' system audio is normalized to
+    /// by setting its volume in the project config, eg. -16 for typical online video.
+    #[serde(default)]
+    pub system_audio_target_lufs: Option<f32>,
+    /// Skip the silence at the start of recordings, while getting ready to talk.
+    /// Instant recordings get a start trim in their meta and studio recordings
+    /// start their timeline later, the recorded files are left untouched.
+    #[serde(default)]
+    pub auto_trim_leading_silence: bool,
+    /// Fits instant recordings to this aspect ratio as (width, height), eg. (9, 16) for
+    /// vertical video, by re-encoding them once recording stops. Progressive uploads are
+    /// skipped as the uploaded file would be replaced.
+    #[serde(default)]
+    pub output_aspect: Option<(u32, u32)>,
+    /// How the space around recordings is filled when fitting them to `output_aspect`
+    #[serde(default)]
+    pub output_aspect_fill: AspectFillMode,
+    /// Colour of the bars with `AspectFillMode::Bars`
+    #[serde(default)]
+    pub output_aspect_bar_color: Color,
+    /// What to do while the machine is under thermal or CPU pressure, nothing if unset
+    #[serde(default)]
+    pub pressure_response: Option<PressureResponse>,
+    /// Pressure level at which `pressure_response` kicks in
+    #[serde(default)]
+    pub pressure_threshold: PressureLevel,
+    /// Frame rate studio recordings drop to with `PressureResponse::ReduceFps`
+    #[serde(default = "default_pressure_reduced_fps")]
+    pub pressure_reduced_fps: u32,
+    /// Sample rate recordings' mic and system audio are encoded at, 44100 or 48000.
+    /// Studio and WebM instant recordings' audio is Opus which doesn't support 44100,
+    /// so they can't be started with it.
+    #[serde(default = "default_audio_sample_rate")]
+    pub audio_sample_rate: u32,
+    #[serde(default)]
+    pub camera_content_protection: CameraContentProtection,
+    /// Apps, by owner name, whose windows are left out of recordings. Studio recordings
+    /// start a new segment when one of them opens a window so it's excluded too,
+    /// instant recordings only exclude the windows open when they start.
+    #[serde(default)]
+    pub excluded_apps: Vec<String>,
+    /// How long restarting a recording waits for the cancelled one to release its
+    /// capture sources and feeds before giving up with an error.
+    #[serde(default = "default_restart_teardown_timeout_ms")]
+    pub restart_teardown_timeout_ms: u64,
+    /// Fraction of its native size the screen is recorded at, from 0.25 to 1,
+    /// for smaller files without picking an exact resolution
+    #[serde(default = "default_capture_scale")]
+    pub capture_scale: f32,
+    /// Skip frames while the screen isn't changing, so static content takes up
+    /// far less space at the cost of a variable frame rate
+    #[serde(default)]
+    pub skip_static_frames: bool,
+    /// Where recordings are saved, `recordings` in the app data folder if unset.
+    /// Changed by `migrate_recordings_library` so existing recordings move along with it.
+    #[serde(default)]
+    pub recordings_directory: Option<PathBuf>,
+    /// Where recordings started without an `output_dir` are saved instead of
+    /// `recordings_directory`, set by applying a recording profile
+    #[serde(default)]
+    pub default_output_dir: Option<PathBuf>,
+    /// Extra copies encoded whenever a recording finishes, eg. a small one to share
+    /// alongside a full quality master
+    #[serde(default)]
+    pub renditions: Vec<RenditionProfile>,
+    /// How far generated zoom segments zoom in, kept between 1x and 4x when read
+    #[serde(default = "default_auto_zoom_amount")]
+    pub auto_zoom_amount: f64,
+    /// Record when keys are pressed while studio recording, but not which, so generated
+    /// zoom segments also zoom in on typing. Off by default as the timing of key presses
+    /// can give away what's typed.
+    #[serde(default)]
+    pub record_key_presses: bool,
+    /// Play sounds when recording starts, stops, pauses and resumes
+    #[serde(default = "true_b")]
+    pub recording_sound_effects: bool,
+    /// Name given to new recordings, with `{target}`, `{date}`, `{time}` and `{mode}`
+    /// filled in. Recordings are named "{target} {date} {time}" if it's empty or invalid.
+    #[serde(default)]
+    pub recording_name_template: String,
+    /// Emit `CursorClick` events while studio recording, for live annotations.
+    /// Turns on cursor capture for the recording if it's off.
+    #[serde(default)]
+    pub stream_cursor_clicks: bool,
+    /// Cover the rest of the display with an occluder while recording a window or area.
+    /// Turning it off doesn't change what's captured.
+    #[serde(default = "true_b")]
+    pub show_capture_occluder: bool,
+    /// JPEG quality of the screenshots made of finished recordings, from 1 to 100.
+    /// Uploaded screenshots are compressed further, to 40% of it.
+    #[serde(default = "default_screenshot_quality")]
+    pub screenshot_quality: u8,
+    /// Make a short looping GIF of the start of each recording next to its screenshot,
+    /// uploaded with instant recordings for a moving preview
+    #[serde(default)]
+    pub generate_preview_gif: bool,
+    /// Container instant recordings are written in. WebM is encoded as VP9 and Opus,
+    /// which is slower than the hardware MP4 encoder on macOS.
+    #[serde(default)]
+    pub instant_output_format: InstantOutputFormat,
+    /// Codec studio recordings' displays are encoded with. H265 files are smaller,
+    /// but if there's no H265 encoder the recording falls back to H264.
+    #[serde(default)]
+    pub studio_video_codec: VideoCodec,
+    /// Saved whenever a recording starts, see `get_last_recording_inputs`
+    #[serde(default)]
+    pub last_recording_inputs: Option<LastRecordingInputs>,
     #[serde(default = "default_server_url")]
     pub server_url: String,
     #[serde(default, alias = "open_editor_after_recording")]
     #[deprecated]
     _open_editor_after_recording: bool,
+    /// Replaced by `CameraContentProtection::Always`, which it's migrated to when read
+    #[serde(default, rename = "alwaysProtectCameraWindow", skip_serializing)]
+    always_protect_camera_window: bool,
+}
+
+fn default_cursor_size_multiplier() -> f32 {
+    1.0
+}
+
+fn default_upload_chunk_size_mb() -> u32 {
+    5
+}
+
+fn default_pressure_reduced_fps() -> u32 {
+    30
+}
+
+fn default_audio_sample_rate() -> u32 {
+    48_000
+}
+
+pub fn default_restart_teardown_timeout_ms() -> u64 {
+    5_000
+}
+
+pub(crate) fn default_capture_scale() -> f32 {
+    1.0
+}
+
+pub const DEFAULT_AUTO_ZOOM_AMOUNT: f64 = 2.0;
+
+fn default_auto_zoom_amount() -> f64 {
+    DEFAULT_AUTO_ZOOM_AMOUNT
+}
+
+pub const DEFAULT_SCREENSHOT_QUALITY: u8 = 75;
+
+fn default_screenshot_quality() -> u8 {
+    DEFAULT_SCREENSHOT_QUALITY
 }
 
 fn default_server_url() -> String {
@@ -87,6 +311,7 @@ impl Default for GeneralSettingsStore {
             auto_create_shareable_link: false,
             enable_notifications: true,
             disable_auto_open_links: false,
+            share_link_open_behaviour: ShareLinkOpenBehaviour::Browser,
             has_completed_startup: false,
             theme: AppTheme::System,
             commercial_license: None,
@@ -95,8 +320,44 @@ impl Default for GeneralSettingsStore {
             post_studio_recording_behaviour: PostStudioRecordingBehaviour::OpenEditor,
             main_window_recording_start_behaviour: MainWindowRecordingStartBehaviour::Close,
             custom_cursor_capture: false,
+            cursor_size_multiplier: default_cursor_size_multiplier(),
+            camera_mirror: false,
+            minimal_overhead: false,
+            record_focus_events: false,
+            upload_chunk_size_mb: default_upload_chunk_size_mb(),
+            segment_duration_secs: None,
+            pre_roll_secs: 0,
+            system_audio_target_lufs: None,
+            auto_trim_leading_silence: false,
+            output_aspect: None,
+            output_aspect_fill: AspectFillMode::default(),
+            output_aspect_bar_color: [0, 0, 0],
+            pressure_response: None,
+            pressure_threshold: PressureLevel::default(),
+            pressure_reduced_fps: default_pressure_reduced_fps(),
+            audio_sample_rate: default_audio_sample_rate(),
+            camera_content_protection: CameraContentProtection::default(),
+            excluded_apps: vec![],
+            restart_teardown_timeout_ms: default_restart_teardown_timeout_ms(),
+            capture_scale: default_capture_scale(),
+            skip_static_frames: false,
+            recordings_directory: None,
+            default_output_dir: None,
+            renditions: vec![],
+            auto_zoom_amount: DEFAULT_AUTO_ZOOM_AMOUNT,
+            record_key_presses: false,
+            recording_sound_effects: true,
+            recording_name_template: String::new(),
+            stream_cursor_clicks: false,
+            show_capture_occluder: true,
+            screenshot_quality: DEFAULT_SCREENSHOT_QUALITY,
+            generate_preview_gif: false,
+            instant_output_format: InstantOutputFormat::Mp4,
+            studio_video_codec: VideoCodec::H264,
+            last_recording_inputs: None,
             server_url: default_server_url(),
             _open_editor_after_recording: false,
+            always_protect_camera_window: false,
         }
     }
 }
@@ -110,7 +371,7 @@ pub enum AppTheme {
     Dark,
 }
 
-fn true_b() -> bool {
+pub(crate) fn true_b() -> bool {
     true
 }
 
@@ -119,8 +380,8 @@ impl GeneralSettingsStore {
         match app.store("store").map(|s| s.get("general_settings")) {
             Ok(Some(store)) => {
                 // Handle potential deserialization errors gracefully
-                match serde_json::from_value(store) {
-                    Ok(settings) => Ok(Some(settings)),
+                match serde_json::from_value::<Self>(store) {
+                    Ok(settings) => Ok(Some(settings.migrate())),
                     Err(e) => Err(format!("Failed to deserialize general settings store: {e}")),
                 }
             }
@@ -128,6 +389,16 @@ impl GeneralSettingsStore {
         }
     }
 
+    /// Moves replaced settings over to what replaced them, saved as such the next time
+    /// the settings are
+    fn migrate(mut self) -> Self {
+        if std::mem::take(&mut self.always_protect_camera_window) {
+            self.camera_content_protection = CameraContentProtection::Always;
+        }
+
+        self
+    }
+
     // i don't trust anyone to not overwrite the whole store lols
     pub fn update(app: &AppHandle, update: impl FnOnce(&mut Self)) -> Result<(), String> {
         let Ok(store) = app.store("store") else {
@@ -150,6 +421,27 @@ impl GeneralSettingsStore {
     }
 }
 
+#[tauri::command]
+#[specta::specta]
+pub fn get_post_studio_behaviour(app: AppHandle) -> Result<PostStudioRecordingBehaviour, String> {
+    Ok(GeneralSettingsStore::get(&app)?
+        .map(|s| s.post_studio_recording_behaviour)
+        .unwrap_or_default())
+}
+
+/// What happens once a studio recording stops, unless overridden when stopping it.
+/// Unknown behaviours are rejected when deserializing the argument.
+#[tauri::command]
+#[specta::specta]
+pub fn set_post_studio_behaviour(
+    app: AppHandle,
+    behaviour: PostStudioRecordingBehaviour,
+) -> Result<(), String> {
+    GeneralSettingsStore::update(&app, |settings| {
+        settings.post_studio_recording_behaviour = behaviour
+    })
+}
+
 pub fn init(app: &AppHandle) {
     println!("Initializing GeneralSettingsStore");
 
@@ -166,3 +458,34 @@ pub fn init(app: &AppHandle) {
 
     println!("GeneralSettingsState managed");
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn always_protect_camera_window_migrates_to_always() {
+        let settings = serde_json::from_value::<GeneralSettingsStore>(json!({
+            "cameraContentProtection": "never",
+            "alwaysProtectCameraWindow": true,
+        }))
+        .unwrap()
+        .migrate();
+
+        assert!(matches!(
+            settings.camera_content_protection,
+            CameraContentProtection::Always
+        ));
+        assert!(json!(settings).get("alwaysProtectCameraWindow").is_none());
+
+        let settings = serde_json::from_value::<GeneralSettingsStore>(json!({
+            "cameraContentProtection": "never",
+        }))
+        .unwrap()
+        .migrate();
+        assert!(matches!(
+            settings.camera_content_protection,
+            CameraContentProtection::Never
+        ));
+    }
+}
@@ -46,6 +46,7 @@ pub enum HotkeyAction {
     StartRecording,
     StopRecording,
     RestartRecording,
+    AddChapterMarker,
     // TakeScreenshot,
 }
 
@@ -103,10 +104,17 @@ async fn handle_hotkey(app: AppHandle, action: HotkeyAction) -> Result<(), Strin
             let _ = RequestStartRecording.emit(&app);
             Ok(())
         }
-        HotkeyAction::StopRecording => recording::stop_recording(app.clone(), app.state()).await,
+        HotkeyAction::StopRecording => recording::stop_recording(app.clone(), app.state(), None)
+            .await
+            .map(|_| ()),
         HotkeyAction::RestartRecording => {
             recording::restart_recording(app.clone(), app.state()).await
         }
+        HotkeyAction::AddChapterMarker => {
+            recording::add_chapter_marker(app.clone(), app.state(), None)
+                .await
+                .map(|_| ())
+        }
     }
 }
 
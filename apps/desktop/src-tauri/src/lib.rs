@@ -3,18 +3,29 @@ mod audio_meter;
 mod auth;
 mod camera;
 mod captions;
+mod dashboard;
 mod deeplink_actions;
 mod editor_window;
 mod export;
 mod fake_window;
 mod flags;
+mod focus_events;
+mod frame_compare;
 mod general_settings;
 mod hotkeys;
+mod library;
 mod notifications;
+mod pending_uploads;
 mod permissions;
 mod platform;
 mod presets;
+mod processing;
+mod profiles;
 mod recording;
+mod screenshot;
+mod support_bundle;
+mod system_pressure;
+mod transcode;
 mod tray;
 mod upload;
 mod web_api;
@@ -28,7 +39,10 @@ use cap_editor::EditorState;
 use cap_media::feeds::RawCameraFrame;
 use cap_media::feeds::{AudioInputFeed, AudioInputSamplesSender};
 use cap_media::platform::Bounds;
-use cap_media::{feeds::CameraFeed, sources::ScreenCaptureTarget};
+use cap_media::{
+    feeds::CameraFeed,
+    sources::{area_bounds, ScreenCaptureTarget},
+};
 use cap_project::RecordingMetaInner;
 use cap_project::XY;
 use cap_project::{ProjectConfiguration, RecordingMeta, SharingMeta, StudioRecordingMeta};
@@ -56,7 +70,7 @@ use std::{
     future::Future,
     io::{BufReader, BufWriter},
     marker::PhantomData,
-    path::PathBuf,
+    path::{Path, PathBuf},
     process::Command,
     str::FromStr,
     sync::Arc,
@@ -91,6 +105,9 @@ pub struct App {
     camera_feed: Option<Arc<Mutex<CameraFeed>>>,
     #[serde(skip)]
     mic_feed: Option<AudioInputFeed>,
+    /// Mics recorded alongside `mic_feed` into their own files, studio recordings only
+    #[serde(skip)]
+    additional_mic_feeds: Vec<AudioInputFeed>,
     #[serde(skip)]
     mic_samples_tx: AudioInputSamplesSender,
     #[serde(skip)]
@@ -98,6 +115,19 @@ pub struct App {
     #[serde(skip)]
     current_recording: Option<InProgressRecording>,
     #[serde(skip)]
+    armed_pre_roll: Option<recording::ArmedPreRoll>,
+    #[serde(skip)]
+    failed_start: Option<recording::FailedStart>,
+    /// Recordings started while another was in progress, see `start_recording`
+    #[serde(skip)]
+    recording_queue: recording::RecordingQueue,
+    /// Inputs of the last pending recording that was cancelled, kept for re-arming
+    #[serde(skip)]
+    cancelled_pending_inputs: Option<recording::StartRecordingInputs>,
+    /// Counting down to a recording starting, its actor hasn't been spawned yet
+    #[serde(skip)]
+    countdown: Option<recording::Countdown>,
+    #[serde(skip)]
     recording_logging_handle: LoggingHandle,
     server_url: String,
 }
@@ -132,8 +162,18 @@ pub struct VideoUploadInfo {
 }
 
 impl App {
+    /// Every mic that's recorded, the selected one first
+    pub fn mic_feeds(&self) -> Vec<AudioInputFeed> {
+        self.mic_feed
+            .iter()
+            .chain(&self.additional_mic_feeds)
+            .cloned()
+            .collect()
+    }
+
     pub fn set_current_recording(&mut self, actor: InProgressRecording) {
         self.current_recording = Some(actor);
+        self.cancelled_pending_inputs = None;
 
         CurrentRecordingChanged.emit(&self.handle).ok();
     }
@@ -179,6 +219,61 @@ async fn set_mic_input(state: MutableState<'_, App>, label: Option<String>) -> R
     }
 }
 
+/// Sets the mics recorded alongside the one from `set_mic_input`, each into its own file.
+/// Mics that are already open are kept rather than reopened.
+#[tauri::command]
+#[specta::specta]
+async fn set_additional_mic_inputs(
+    state: MutableState<'_, App>,
+    labels: Vec<String>,
+) -> Result<(), String> {
+    const RECORDING_ERROR: &str = "Mics can't be added or removed while recording";
+
+    let open = {
+        let app = state.read().await;
+
+        if app.current_recording.is_some() {
+            return Err(RECORDING_ERROR.to_string());
+        }
+
+        app.additional_mic_feeds
+            .iter()
+            .map(|feed| feed.device_name().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    // opened before anything's swapped so a mic that fails to open leaves the current ones as
+    // they are, and without holding the state so opening them doesn't block everything else
+    let mut opened = vec![];
+    for label in labels.iter().filter(|label| !open.contains(label)) {
+        opened.push(
+            AudioInputFeed::init(label)
+                .await
+                .map_err(|e| e.to_string())?,
+        );
+    }
+
+    let mut app = state.write().await;
+
+    if app.current_recording.is_some() {
+        return Err(RECORDING_ERROR.to_string());
+    }
+
+    let mut available = std::mem::take(&mut app.additional_mic_feeds);
+    available.extend(opened);
+    app.additional_mic_feeds = labels
+        .iter()
+        .filter_map(|label| {
+            let i = available
+                .iter()
+                .position(|feed| feed.device_name() == label)?;
+            Some(available.remove(i))
+        })
+        .collect();
+
+    Ok(())
+}
+
 #[tauri::command]
 #[specta::specta]
 async fn set_camera_input(
@@ -257,6 +352,14 @@ pub struct RecordingStarted;
 #[derive(Deserialize, specta::Type, Serialize, tauri_specta::Event, Debug, Clone)]
 pub struct RecordingStopped;
 
+/// Emitted once when a recording stops because of an error, before the error dialog is shown
+#[derive(Deserialize, specta::Type, Serialize, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingFailed {
+    pub error: String,
+    pub recording_id: String,
+}
+
 #[derive(Deserialize, specta::Type, Serialize, tauri_specta::Event, Debug, Clone)]
 pub struct RequestStartRecording;
 
@@ -310,6 +413,7 @@ enum CurrentRecordingTarget {
     Window { id: u32, bounds: Bounds },
     Screen { id: u32 },
     Area { screen: u32, bounds: Bounds },
+    AllScreens { bounds: Bounds },
 }
 
 #[derive(Serialize, Type)]
@@ -330,12 +434,20 @@ async fn get_current_recording(
 
         let target = match r.capture_target() {
             ScreenCaptureTarget::Screen { id } => CurrentRecordingTarget::Screen { id: *id },
-            ScreenCaptureTarget::Window { id } => CurrentRecordingTarget::Window {
+            ScreenCaptureTarget::Window { id, .. } => CurrentRecordingTarget::Window {
                 id: *id,
                 bounds: bounds.clone(),
             },
-            ScreenCaptureTarget::Area { screen, bounds } => CurrentRecordingTarget::Area {
+            ScreenCaptureTarget::Area {
+                screen,
+                bounds,
+                anchor_window,
+            } => CurrentRecordingTarget::Area {
                 screen: *screen,
+                // the window the area is anchored to can have closed mid-recording
+                bounds: area_bounds(*screen, bounds, *anchor_window).unwrap_or(*bounds),
+            },
+            ScreenCaptureTarget::AllScreens => CurrentRecordingTarget::AllScreens {
                 bounds: bounds.clone(),
             },
         };
@@ -357,111 +469,124 @@ async fn create_screenshot(
     input: PathBuf,
     output: PathBuf,
     size: Option<(u32, u32)>,
+    quality: u8,
+) -> Result<(), String> {
+    create_screenshot_at(input, output, size, None, quality).await
+}
+
+/// Like `create_screenshot`, but uses the first frame at or after `timestamp` seconds.
+async fn create_screenshot_at(
+    input: PathBuf,
+    output: PathBuf,
+    size: Option<(u32, u32)>,
+    timestamp: Option<f64>,
+    quality: u8,
 ) -> Result<(), String> {
     println!(
-        "Creating screenshot: input={:?}, output={:?}, size={:?}",
-        input, output, size
+        "Creating screenshot: input={:?}, output={:?}, size={:?}, timestamp={:?}",
+        input, output, size, timestamp
     );
 
-    let result: Result<(), String> = tokio::task::spawn_blocking(move || -> Result<(), String> {
-        ffmpeg::init().map_err(|e| {
-            eprintln!("Failed to initialize ffmpeg: {}", e);
-            e.to_string()
-        })?;
+    tokio::task::spawn_blocking(move || -> Result<(), String> {
+        let img = extract_frame(&input, size, timestamp)?;
+        println!("Saving image to {:?}", output);
 
-        let mut ictx = ffmpeg::format::input(&input).map_err(|e| {
-            eprintln!("Failed to create input context: {}", e);
-            e.to_string()
-        })?;
-        let input_stream = ictx
-            .streams()
-            .best(ffmpeg::media::Type::Video)
-            .ok_or("No video stream found")?;
-        let video_stream_index = input_stream.index();
-        println!("Found video stream at index {}", video_stream_index);
-
-        let mut decoder =
-            ffmpeg::codec::context::Context::from_parameters(input_stream.parameters())
-                .map_err(|e| {
-                    eprintln!("Failed to create decoder context: {}", e);
-                    e.to_string()
-                })?
-                .decoder()
-                .video()
-                .map_err(|e| {
-                    eprintln!("Failed to create video decoder: {}", e);
-                    e.to_string()
-                })?;
-
-        let mut scaler = ffmpeg::software::scaling::context::Context::get(
-            decoder.format(),
-            decoder.width(),
-            decoder.height(),
-            ffmpeg::format::Pixel::RGB24,
-            size.map_or(decoder.width(), |s| s.0),
-            size.map_or(decoder.height(), |s| s.1),
-            ffmpeg::software::scaling::flag::Flags::BILINEAR,
-        )
-        .map_err(|e| {
-            eprintln!("Failed to create scaler: {}", e);
-            e.to_string()
-        })?;
+        screenshot::save_rgb_jpeg(&output, img.width(), img.height(), img.as_raw(), quality)
+            .map_err(|e| {
+                eprintln!("Failed to save image: {}", e);
+                e
+            })?;
+
+        println!("Screenshot created successfully");
+        Ok(())
+    })
+    .await
+    .map_err(|e| format!("Task join error: {}", e))?
+}
+
+/// Decodes the first frame of `input` at or after `timestamp` seconds, scaled to `size` if given.
+/// Blocks while decoding.
+fn extract_frame(
+    input: &Path,
+    size: Option<(u32, u32)>,
+    timestamp: Option<f64>,
+) -> Result<image::RgbImage, String> {
+    ffmpeg::init().map_err(|e| format!("Failed to initialize ffmpeg: {e}"))?;
+
+    let mut ictx = ffmpeg::format::input(&input)
+        .map_err(|e| format!("Failed to create input context: {e}"))?;
+    let input_stream = ictx
+        .streams()
+        .best(ffmpeg::media::Type::Video)
+        .ok_or("No video stream found")?;
+    let video_stream_index = input_stream.index();
+    let time_base = f64::from(input_stream.time_base());
+
+    let mut decoder = ffmpeg::codec::context::Context::from_parameters(input_stream.parameters())
+        .map_err(|e| format!("Failed to create decoder context: {e}"))?
+        .decoder()
+        .video()
+        .map_err(|e| format!("Failed to create video decoder: {e}"))?;
+
+    let mut scaler = ffmpeg::software::scaling::context::Context::get(
+        decoder.format(),
+        decoder.width(),
+        decoder.height(),
+        ffmpeg::format::Pixel::RGB24,
+        size.map_or(decoder.width(), |s| s.0),
+        size.map_or(decoder.height(), |s| s.1),
+        ffmpeg::software::scaling::flag::Flags::BILINEAR,
+    )
+    .map_err(|e| format!("Failed to create scaler: {e}"))?;
+
+    if let Some(timestamp) = timestamp {
+        let position = (timestamp * ffmpeg::ffi::AV_TIME_BASE as f64) as i64;
+        ictx.seek(position, ..position)
+            .map_err(|e| format!("Failed to seek to {timestamp}s: {e}"))?;
+    }
 
-        println!("Decoder and scaler initialized");
-
-        let mut frame = ffmpeg::frame::Video::empty();
-        for (stream, packet) in ictx.packets() {
-            if stream.index() == video_stream_index {
-                decoder.send_packet(&packet).map_err(|e| {
-                    eprintln!("Failed to send packet to decoder: {}", e);
-                    e.to_string()
-                })?;
-                if decoder.receive_frame(&mut frame).is_ok() {
-                    println!("Frame received, scaling...");
-                    let mut rgb_frame = ffmpeg::frame::Video::empty();
-                    scaler.run(&frame, &mut rgb_frame).map_err(|e| {
-                        eprintln!("Failed to scale frame: {}", e);
-                        e.to_string()
-                    })?;
-
-                    let width = rgb_frame.width() as usize;
-                    let height = rgb_frame.height() as usize;
-                    let bytes_per_pixel = 3;
-                    let src_stride = rgb_frame.stride(0);
-                    let dst_stride = width * bytes_per_pixel;
-
-                    let mut img_buffer = vec![0u8; height * dst_stride];
-
-                    for y in 0..height {
-                        let src_slice =
-                            &rgb_frame.data(0)[y * src_stride..y * src_stride + dst_stride];
-                        let dst_slice = &mut img_buffer[y * dst_stride..(y + 1) * dst_stride];
-                        dst_slice.copy_from_slice(src_slice);
+    let mut frame = ffmpeg::frame::Video::empty();
+    for (stream, packet) in ictx.packets() {
+        if stream.index() == video_stream_index {
+            decoder
+                .send_packet(&packet)
+                .map_err(|e| format!("Failed to send packet to decoder: {e}"))?;
+            if decoder.receive_frame(&mut frame).is_ok() {
+                // seeking lands on the keyframe before the timestamp
+                if let (Some(timestamp), Some(pts)) = (timestamp, frame.timestamp()) {
+                    if (pts as f64) * time_base < timestamp {
+                        continue;
                     }
+                }
+
+                let mut rgb_frame = ffmpeg::frame::Video::empty();
+                scaler
+                    .run(&frame, &mut rgb_frame)
+                    .map_err(|e| format!("Failed to scale frame: {e}"))?;
 
-                    let img = image::RgbImage::from_raw(width as u32, height as u32, img_buffer)
-                        .ok_or("Failed to create image from frame data")?;
-                    println!("Saving image to {:?}", output);
+                let width = rgb_frame.width() as usize;
+                let height = rgb_frame.height() as usize;
+                let bytes_per_pixel = 3;
+                let src_stride = rgb_frame.stride(0);
+                let dst_stride = width * bytes_per_pixel;
 
-                    img.save_with_format(&output, image::ImageFormat::Jpeg)
-                        .map_err(|e| {
-                            eprintln!("Failed to save image: {}", e);
-                            e.to_string()
-                        })?;
+                let mut img_buffer = vec![0u8; height * dst_stride];
 
-                    println!("Screenshot created successfully");
-                    return Ok(());
+                for y in 0..height {
+                    let src_slice = &rgb_frame.data(0)[y * src_stride..y * src_stride + dst_stride];
+                    let dst_slice = &mut img_buffer[y * dst_stride..(y + 1) * dst_stride];
+                    dst_slice.copy_from_slice(src_slice);
                 }
+
+                let img = image::RgbImage::from_raw(width as u32, height as u32, img_buffer)
+                    .ok_or("Failed to create image from frame data")?;
+
+                return Ok(img);
             }
         }
+    }
 
-        eprintln!("Failed to create screenshot: No suitable frame found");
-        Err("Failed to create screenshot".to_string())
-    })
-    .await
-    .map_err(|e| format!("Task join error: {}", e))?;
-
-    result
+    Err("No frame found to extract".to_string())
 }
 
 async fn create_thumbnail(input: PathBuf, output: PathBuf, size: (u32, u32)) -> Result<(), String> {
@@ -812,49 +937,73 @@ async fn copy_video_to_clipboard(
     Ok(())
 }
 
-#[tauri::command]
-#[specta::specta]
-async fn get_video_metadata(path: PathBuf) -> Result<VideoRecordingMetadata, String> {
-    let recording_meta = RecordingMeta::load_for_project(&path).map_err(|v| v.to_string())?;
+/// Duration of an mp4 from its header, or of other containers like WebM from ffmpeg,
+/// or 0 if it can't be read
+fn get_duration_for_path(path: PathBuf) -> Result<f64, String> {
+    if path.extension().is_some_and(|ext| ext != "mp4") {
+        return Ok(container_duration(&path));
+    }
 
-    fn get_duration_for_path(path: PathBuf) -> Result<f64, String> {
-        let reader = BufReader::new(
-            File::open(&path).map_err(|e| format!("Failed to open video file: {}", e))?,
-        );
-        let file_size = path
-            .metadata()
-            .map_err(|e| format!("Failed to get file metadata: {}", e))?
-            .len();
+    let reader =
+        BufReader::new(File::open(&path).map_err(|e| format!("Failed to open video file: {}", e))?);
+    let file_size = path
+        .metadata()
+        .map_err(|e| format!("Failed to get file metadata: {}", e))?
+        .len();
 
-        let current_duration = match Mp4Reader::read_header(reader, file_size) {
-            Ok(mp4) => mp4.duration().as_secs_f64(),
-            Err(e) => {
-                println!(
-                    "Failed to read MP4 header: {}. Falling back to default duration.",
-                    e
-                );
-                0.0_f64
-            }
-        };
+    let current_duration = match Mp4Reader::read_header(reader, file_size) {
+        Ok(mp4) => mp4.duration().as_secs_f64(),
+        Err(e) => {
+            println!(
+                "Failed to read MP4 header: {}. Falling back to default duration.",
+                e
+            );
+            0.0_f64
+        }
+    };
 
-        Ok(current_duration)
-    }
+    Ok(current_duration)
+}
 
-    let display_paths = match &recording_meta.inner {
-        RecordingMetaInner::Instant(_) => {
-            vec![path.join("content/output.mp4")]
+fn container_duration(path: &Path) -> f64 {
+    match ffmpeg::format::input(&path) {
+        Ok(input) if input.duration() > 0 => {
+            input.duration() as f64 / ffmpeg::ffi::AV_TIME_BASE as f64
+        }
+        Ok(_) => 0.0,
+        Err(e) => {
+            println!(
+                "Failed to read duration of {}: {e}. Falling back to default duration.",
+                path.display()
+            );
+            0.0
         }
-        RecordingMetaInner::Studio(meta) => match meta {
+    }
+}
+
+/// The display recordings making up a recording, in order
+fn display_paths(meta: &RecordingMeta) -> Vec<PathBuf> {
+    match &meta.inner {
+        RecordingMetaInner::Instant(_) => vec![meta.output_path()],
+        RecordingMetaInner::Studio(studio_meta) => match studio_meta {
             StudioRecordingMeta::SingleSegment { segment } => {
-                vec![recording_meta.path(&segment.display.path)]
+                vec![meta.path(&segment.display.path)]
             }
             StudioRecordingMeta::MultipleSegments { inner, .. } => inner
                 .segments
                 .iter()
-                .map(|s| recording_meta.path(&s.display.path))
+                .map(|s| meta.path(&s.display.path))
                 .collect(),
         },
-    };
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+async fn get_video_metadata(path: PathBuf) -> Result<VideoRecordingMetadata, String> {
+    let recording_meta = RecordingMeta::load_for_project(&path).map_err(|v| v.to_string())?;
+
+    let display_paths = display_paths(&recording_meta);
 
     let duration = display_paths
         .into_iter()
@@ -1039,7 +1188,7 @@ async fn upload_exported_video(
             }
         };
 
-        create_or_get_video(&app, false, video_id, Some(meta.pretty_name.clone())).await
+        create_or_get_video(&app, false, video_id, Some(meta.pretty_name.clone()), None).await
     }
     .await?;
 
@@ -1062,6 +1211,7 @@ async fn upload_exported_video(
                 id: uploaded_video.id.clone(),
             });
             meta.save_for_project().ok();
+            dashboard::set_upload_failed(&meta.project_path, false);
 
             let _ = app
                 .state::<ArcLock<ClipboardContext>>()
@@ -1134,12 +1284,7 @@ async fn upload_screenshot(
 async fn take_screenshot(app: AppHandle, _state: MutableState<'_, App>) -> Result<(), String> {
     let id = uuid::Uuid::new_v4().to_string();
 
-    let recording_dir = app
-        .path()
-        .app_data_dir()
-        .unwrap()
-        .join("screenshots")
-        .join(format!("{id}.cap"));
+    let recording_dir = screenshot_path(&app, &id)?;
 
     std::fs::create_dir_all(&recording_dir).map_err(|e| e.to_string())?;
 
@@ -1223,6 +1368,11 @@ async fn take_screenshot(app: AppHandle, _state: MutableState<'_, App>) -> Resul
             project_path: recording_dir.clone(),
             sharing: None,
             pretty_name: screenshot_name,
+            cover: None,
+            start_marker: None,
+            chapters: vec![],
+            quality: None,
+            renditions: vec![],
             inner: RecordingMetaInner::Studio(cap_project::StudioRecordingMeta::SingleSegment {
                 segment: cap_project::SingleSegment {
                     display: VideoMeta {
@@ -1232,6 +1382,8 @@ async fn take_screenshot(app: AppHandle, _state: MutableState<'_, App>) -> Resul
                         .unwrap(),
                         fps: 0,
                         start_time: None,
+                        capture_scale: None,
+                        variable_frame_rate: false,
                     },
                     camera: None,
                     audio: None,
@@ -1335,6 +1487,8 @@ pub struct RecordingMetaWithType {
     #[serde(flatten)]
     pub inner: RecordingMeta,
     pub r#type: RecordingType,
+    /// apps that were focused during the recording, if focus events were recorded
+    pub focus_apps: Vec<String>,
 }
 
 impl RecordingMetaWithType {
@@ -1344,6 +1498,9 @@ impl RecordingMetaWithType {
                 RecordingMetaInner::Studio(_) => RecordingType::Studio,
                 RecordingMetaInner::Instant(_) => RecordingType::Instant,
             },
+            focus_apps: focus_events::FocusEvents::load_for_project(&inner.project_path)
+                .map(|e| e.app_names())
+                .unwrap_or_default(),
             inner,
         }
     }
@@ -1368,10 +1525,84 @@ fn get_recording_meta(
         .map_err(|e| format!("Failed to load recording meta: {}", e))
 }
 
+/// Renders the frame at `timestamp` (in output time) to `cover.jpg` and uses it as the library thumbnail.
+///
+/// The share screenshot is left alone unless `sync_share_thumbnail` is set.
+#[tauri::command]
+#[specta::specta]
+async fn set_cover_frame(
+    app: AppHandle,
+    project_path: PathBuf,
+    timestamp: f64,
+    sync_share_thumbnail: bool,
+) -> Result<PathBuf, String> {
+    let mut meta = RecordingMeta::load_for_project(&project_path)
+        .map_err(|e| format!("Failed to load recording meta: {}", e))?;
+
+    let (source, source_time) = match &meta.inner {
+        // playback starts after any trimmed leading silence
+        RecordingMetaInner::Instant(inner) => (
+            meta.output_path(),
+            timestamp + inner.trim_start_secs.unwrap_or_default(),
+        ),
+        RecordingMetaInner::Studio(studio_meta) => {
+            let config = meta.project_config();
+            let (source_time, segment) = match &config.timeline {
+                Some(timeline) => timeline
+                    .get_segment_time(timestamp)
+                    .ok_or("Timestamp is past the end of the recording")?,
+                None => (timestamp, 0),
+            };
+
+            let display = match studio_meta {
+                StudioRecordingMeta::SingleSegment { segment } => &segment.display,
+                StudioRecordingMeta::MultipleSegments { inner } => {
+                    &inner
+                        .segments
+                        .get(segment as usize)
+                        .ok_or("Timeline references a missing segment")?
+                        .display
+                }
+            };
+
+            (meta.path(&display.path), source_time)
+        }
+    };
+
+    let cover = RelativePathBuf::from("cover.jpg");
+    let cover_path = meta.path(&cover);
+
+    create_screenshot_at(
+        source,
+        cover_path.clone(),
+        None,
+        Some(source_time),
+        screenshot::screenshot_quality(GeneralSettingsStore::get(&app).ok().flatten().as_ref()),
+    )
+    .await?;
+
+    meta.cover = Some(cover);
+    meta.save_for_project()
+        .map_err(|e| format!("Failed to save recording meta: {e}"))?;
+
+    if sync_share_thumbnail {
+        if let Some(sharing) = &meta.sharing {
+            upload::prepare_screenshot_upload(
+                &app,
+                &S3UploadMeta::new(sharing.id.clone()),
+                cover_path.clone(),
+            )
+            .await?;
+        }
+    }
+
+    Ok(cover_path)
+}
+
 #[tauri::command]
 #[specta::specta]
 fn list_recordings(app: AppHandle) -> Result<Vec<(PathBuf, RecordingMetaWithType)>, String> {
-    let recordings_dir = recordings_path(&app);
+    let recordings_dir = recordings_path(&app)?;
 
     if !recordings_dir.exists() {
         return Ok(Vec::new());
@@ -1417,7 +1648,7 @@ fn list_recordings(app: AppHandle) -> Result<Vec<(PathBuf, RecordingMetaWithType
 #[tauri::command]
 #[specta::specta]
 fn list_screenshots(app: AppHandle) -> Result<Vec<(PathBuf, RecordingMeta)>, String> {
-    let screenshots_dir = screenshots_path(&app);
+    let screenshots_dir = screenshots_path(&app)?;
 
     let mut result = std::fs::read_dir(&screenshots_dir)
         .map_err(|e| format!("Failed to read screenshots directory: {}", e))?
@@ -1739,17 +1970,41 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
     let specta_builder = tauri_specta::Builder::new()
         .commands(tauri_specta::collect_commands![
             set_mic_input,
+            set_additional_mic_inputs,
+            audio_meter::preview_audio_waveform,
             set_camera_input,
             recording::start_recording,
+            recording::arm_pre_roll,
+            recording::disarm_pre_roll,
+            recording::cancel_pending_recording,
             recording::stop_recording,
+            recording::recording_quality_report,
+            recording::add_chapter_marker,
+            recording::platform_capture_capabilities,
+            recording::system_audio_available,
+            recording::get_last_recording_inputs,
             recording::pause_recording,
+            recording::switch_capture_target,
+            recording::pause_recording_with_card,
+            recording::pause_and_capture,
             recording::resume_recording,
             recording::restart_recording,
+            recording::cancel_recording,
             recording::delete_recording,
+            recording::reset_recording_state,
+            recording::regenerate_zoom_segments,
+            recording::get_recording_status,
+            recording::reveal_recording_in_finder,
+            recording::open_editor,
+            recording::validate_project_config,
+            recording::repair_project_config,
             recording::list_cameras,
+            recording::list_microphones,
+            recording::validate_recording_inputs,
             recording::list_capture_windows,
             recording::list_capture_screens,
             take_screenshot,
+            screenshot::capture_screenshot,
             list_audio_devices,
             close_recordings_overlay_window,
             fake_window::set_fake_window_bounds,
@@ -1758,11 +2013,33 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
             get_current_recording,
             export::export_video,
             export::get_export_estimates,
+            export::export_timeline,
+            export::mix_audio_tracks,
+            upload::verify_share_link,
+            upload::finalize_upload_now,
+            upload::retry_upload,
+            upload::pause_upload,
+            upload::resume_upload,
+            library::export_library_manifest,
+            library::migrate_recordings_library,
+            library::cleanup_recordings,
+            processing::cancel_recording_processing,
+            pending_uploads::list_pending_uploads,
+            support_bundle::create_support_bundle,
+            focus_events::get_focus_events,
+            transcode::transcode_recording,
+            camera::get_camera_mirror,
+            camera::set_camera_mirror,
+            camera::toggle_camera_mirror,
+            general_settings::get_post_studio_behaviour,
+            general_settings::set_post_studio_behaviour,
+            dashboard::recording_dashboard,
             copy_file_to_path,
             copy_video_to_clipboard,
             copy_screenshot_to_clipboard,
             open_file_path,
             get_video_metadata,
+            frame_compare::compare_frame,
             create_editor_instance,
             get_mic_waveforms,
             get_system_audio_waveforms,
@@ -1776,12 +2053,17 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
             upload_exported_video,
             upload_screenshot,
             get_recording_meta,
+            set_cover_frame,
             save_file_dialog,
             list_recordings,
             list_screenshots,
             check_upgraded_and_update,
             open_external_link,
             hotkeys::set_hotkey,
+            profiles::list_recording_profiles,
+            profiles::save_recording_profile,
+            profiles::delete_recording_profile,
+            profiles::apply_profile,
             reset_camera_permissions,
             reset_microphone_permissions,
             is_camera_window_open,
@@ -1817,6 +2099,7 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
             CurrentRecordingChanged,
             RecordingStarted,
             RecordingStopped,
+            RecordingFailed,
             RequestStartRecording,
             RequestNewScreenshot,
             RequestOpenSettings,
@@ -1824,7 +2107,19 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
             AuthenticationInvalid,
             audio_meter::AudioInputLevelChange,
             UploadProgress,
+            upload::MultipartUploadProgress,
             captions::DownloadProgress,
+            transcode::TranscodeProgress,
+            recording::RecordingSegmentCompleted,
+            recording::CaptureTargetLost,
+            recording::CursorClick,
+            recording::QueuedRecordingStarted,
+            recording::QueuedRecordingFailed,
+            recording::ChapterMarkerAdded,
+            recording::PendingRecordingCancelled,
+            recording::RecordingStartMarkerCaptured,
+            system_pressure::RecordingPressureChanged,
+            library::LibraryMigrationProgress,
         ])
         .error_handling(tauri_specta::ErrorHandlingMode::Throw)
         .typ::<ProjectConfiguration>()
@@ -1917,6 +2212,9 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
             general_settings::init(&app);
             fake_window::init(&app);
             app.manage(EditorWindowIds::default());
+            app.manage(upload::ActiveUploads::default());
+            app.manage(upload::CompletedUploads::default());
+            app.manage(processing::ProcessingTasks::default());
 
             if let Ok(Some(auth)) = AuthStore::load(&app) {
                 sentry::configure_scope(|scope| {
@@ -1935,7 +2233,13 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
                     camera_feed: None,
                     mic_samples_tx: audio_input_tx,
                     mic_feed: None,
+                    additional_mic_feeds: vec![],
                     current_recording: None,
+                    armed_pre_roll: None,
+                    failed_start: None,
+                    cancelled_pending_inputs: None,
+                    recording_queue: Default::default(),
+                    countdown: None,
                     recording_logging_handle,
                     server_url: GeneralSettingsStore::get(&app)
                         .ok()
@@ -1955,6 +2259,8 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
 
             tokio::spawn(check_notification_permissions(app.clone()));
 
+            pending_uploads::spawn_retry_loop(app.clone());
+
             println!("Checking startup completion and permissions...");
             let permissions = permissions::do_permissions_check(false);
             println!("Permissions check result: {:?}", permissions);
@@ -2020,6 +2326,7 @@ pub async fn run(recording_logging_handle: LoggingHandle) {
 
                                     if app_state.current_recording.is_none() {
                                         app_state.mic_feed.take();
+                                        app_state.additional_mic_feeds.clear();
                                         app_state.camera_feed.take();
 
                                         if let Some(camera) = CapWindowId::Camera.get(&app) {
@@ -2145,24 +2452,38 @@ async fn create_editor_instance_impl(
     Ok(instance)
 }
 
-fn recordings_path(app: &AppHandle) -> PathBuf {
-    let path = app.path().app_data_dir().unwrap().join("recordings");
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path().app_data_dir().map_err(|e| {
+        error!("Failed to resolve app data directory: {e}");
+        "Cap's storage location is unavailable. Check that Cap has access to its application data folder.".to_string()
+    })
+}
+
+fn recordings_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let path = match GeneralSettingsStore::get(app)
+        .ok()
+        .flatten()
+        .and_then(|s| s.recordings_directory)
+    {
+        Some(path) => path,
+        None => app_data_dir(app)?.join("recordings"),
+    };
     std::fs::create_dir_all(&path).unwrap_or_default();
-    path
+    Ok(path)
 }
 
-fn recording_path(app: &AppHandle, recording_id: &str) -> PathBuf {
-    recordings_path(app).join(format!("{}.cap", recording_id))
+fn recording_path(app: &AppHandle, recording_id: &str) -> Result<PathBuf, String> {
+    Ok(recordings_path(app)?.join(format!("{}.cap", recording_id)))
 }
 
-fn screenshots_path(app: &AppHandle) -> PathBuf {
-    let path = app.path().app_data_dir().unwrap().join("screenshots");
+fn screenshots_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let path = app_data_dir(app)?.join("screenshots");
     std::fs::create_dir_all(&path).unwrap_or_default();
-    path
+    Ok(path)
 }
 
-fn screenshot_path(app: &AppHandle, screenshot_id: &str) -> PathBuf {
-    screenshots_path(app).join(format!("{}.cap", screenshot_id))
+fn screenshot_path(app: &AppHandle, screenshot_id: &str) -> Result<PathBuf, String> {
+    Ok(screenshots_path(app)?.join(format!("{}.cap", screenshot_id)))
 }
 
 #[tauri::command]
@@ -2241,12 +2562,12 @@ fn open_project_from_path(path: &PathBuf, app: AppHandle) -> Result<(), String>
             tokio::spawn(async move { ShowCapWindow::Editor { project_path }.show(&app).await });
         }
         RecordingMetaInner::Instant(_) => {
-            let mp4_path = path.join("content/output.mp4");
+            let output_path = meta.output_path();
 
-            if mp4_path.exists() && mp4_path.is_file() {
+            if output_path.exists() && output_path.is_file() {
                 let _ = app
                     .opener()
-                    .open_path(mp4_path.to_str().unwrap_or_default(), None::<String>);
+                    .open_path(output_path.to_str().unwrap_or_default(), None::<String>);
                 if let Some(main_window) = CapWindowId::Main.get(&app) {
                     main_window.close().ok();
                 }
@@ -2256,3 +2577,44 @@ fn open_project_from_path(path: &PathBuf, app: AppHandle) -> Result<(), String>
 
     Ok(())
 }
+
+#[cfg(test)]
+mod test {
+    use cap_media::{
+        data::{RawVideoFormat, VideoInfo},
+        encoders::{H264Encoder, MP4File, VideoCodec},
+    };
+
+    use super::*;
+
+    #[test]
+    fn webm_duration_is_read() {
+        ffmpeg::init().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let info = VideoInfo::from_raw(RawVideoFormat::YUYV420, 64, 64, 30);
+
+        let mut file = MP4File::init_with_extension(
+            "screen",
+            dir.path().join("output.webm"),
+            "webm",
+            |o| {
+                H264Encoder::builder("screen", info)
+                    .with_codec(VideoCodec::Vp9)
+                    .build(o)
+            },
+            |_| None,
+        )
+        .unwrap();
+        // a second at 30fps
+        for i in 0..30 {
+            let mut frame = ffmpeg::frame::Video::new(info.pixel_format, info.width, info.height);
+            frame.set_pts(Some(i));
+            file.queue_video_frame(frame);
+        }
+        file.finish();
+
+        let duration = get_duration_for_path(dir.path().join("output.webm")).unwrap();
+        assert!((duration - 1.0).abs() < 0.1, "{duration}");
+    }
+}
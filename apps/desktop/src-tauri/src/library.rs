@@ -0,0 +1,666 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use cap_project::{Platform, RecordingMeta, RecordingMetaInner, RecordingStartMarker};
+use cap_recording::RecordingMode;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use specta::Type;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tracing::{info, warn};
+
+use crate::{
+    general_settings::GeneralSettingsStore,
+    get_video_metadata,
+    pending_uploads::{list_pending_uploads, PendingUploadsStore},
+    processing::ProcessingTasks,
+    recordings_path,
+    upload::ActiveUploads,
+    windows::EditorWindowIds,
+    App, MutableState,
+};
+
+// bump whenever a field is removed or changes meaning
+const MANIFEST_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryManifest {
+    pub schema_version: u32,
+    /// RFC 3339 timestamps, as are `created_at`s
+    pub generated_at: String,
+    pub recordings: Vec<ManifestRecording>,
+    pub skipped: Vec<SkippedRecording>,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ManifestRecording {
+    pub id: String,
+    pub pretty_name: String,
+    pub mode: RecordingMode,
+    pub platform: Option<Platform>,
+    pub duration_secs: Option<f64>,
+    pub share_link: Option<String>,
+    pub created_at: Option<String>,
+    pub start_marker: Option<RecordingStartMarker>,
+}
+
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SkippedRecording {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedLibraryManifest {
+    pub output_path: PathBuf,
+    pub recording_count: u32,
+    pub skipped: Vec<SkippedRecording>,
+}
+
+/// Writes the metadata of every recording in the library to a single JSON file
+/// at `output_path`. Recordings whose metadata can't be read are listed as
+/// skipped rather than failing the whole export.
+#[tauri::command]
+#[specta::specta]
+pub async fn export_library_manifest(
+    app: AppHandle,
+    output_path: PathBuf,
+) -> Result<ExportedLibraryManifest, String> {
+    let recordings_dir = recordings_path(&app)?;
+
+    let mut recordings = vec![];
+    let mut skipped = vec![];
+
+    let entries = std::fs::read_dir(&recordings_dir)
+        .map_err(|e| format!("Failed to read recordings directory: {e}"))?;
+
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(e) => {
+                skipped.push(SkippedRecording {
+                    path: recordings_dir.clone(),
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        if !path.is_dir() {
+            continue;
+        }
+
+        let meta = match RecordingMeta::load_for_project(&path) {
+            Ok(meta) => meta,
+            Err(e) => {
+                warn!("Skipping {} in library manifest: {e}", path.display());
+                skipped.push(SkippedRecording {
+                    path,
+                    error: e.to_string(),
+                });
+                continue;
+            }
+        };
+
+        recordings.push(ManifestRecording {
+            id: path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            mode: match meta.inner {
+                RecordingMetaInner::Instant(_) => RecordingMode::Instant,
+                RecordingMetaInner::Studio(_) => RecordingMode::Studio,
+            },
+            platform: meta.platform,
+            duration_secs: get_video_metadata(path.clone())
+                .await
+                .ok()
+                .map(|m| m.duration),
+            share_link: meta.sharing.map(|s| s.link),
+            created_at: path
+                .metadata()
+                .and_then(|m| m.created())
+                .ok()
+                .map(|time| DateTime::<Utc>::from(time).to_rfc3339()),
+            start_marker: meta.start_marker,
+            pretty_name: meta.pretty_name,
+        });
+    }
+
+    // RFC 3339 timestamps in the same timezone sort chronologically
+    recordings.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    let manifest = LibraryManifest {
+        schema_version: MANIFEST_SCHEMA_VERSION,
+        generated_at: Utc::now().to_rfc3339(),
+        recordings,
+        skipped,
+    };
+
+    std::fs::write(
+        &output_path,
+        serde_json::to_string_pretty(&manifest).map_err(|e| e.to_string())?,
+    )
+    .map_err(|e| format!("Failed to write manifest: {e}"))?;
+
+    info!(
+        "Exported {} recordings to {}, skipped {}",
+        manifest.recordings.len(),
+        output_path.display(),
+        manifest.skipped.len()
+    );
+
+    Ok(ExportedLibraryManifest {
+        output_path,
+        recording_count: manifest.recordings.len() as u32,
+        skipped: manifest.skipped,
+    })
+}
+
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LibraryMigrationProgress {
+    /// Where the recording that was just moved now lives
+    pub recording_path: PathBuf,
+    pub completed: u32,
+    pub total: u32,
+}
+
+/// Moves every recording in the library to `new_dir` and saves new recordings there from then on.
+///
+/// Each recording is moved and verified before the next is started, so if the migration is
+/// interrupted or a recording fails to move, running it again with the same directory picks up
+/// where it left off. The recordings directory setting is only changed once all of them have moved.
+#[tauri::command]
+#[specta::specta]
+pub async fn migrate_recordings_library(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    new_dir: PathBuf,
+) -> Result<PathBuf, String> {
+    if state.read().await.current_recording.is_some() {
+        return Err("Cannot move the recordings library while recording".to_string());
+    }
+
+    if !ProcessingTasks::get(&app).recording_ids().is_empty()
+        || !ActiveUploads::files(&app).is_empty()
+    {
+        return Err(
+            "Cannot move the recordings library while recordings are being processed or uploaded"
+                .to_string(),
+        );
+    }
+
+    let old_dir = recordings_path(&app)?;
+
+    std::fs::create_dir_all(&new_dir)
+        .map_err(|e| format!("Failed to create {}: {e}", new_dir.display()))?;
+    let new_dir = new_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {e}", new_dir.display()))?;
+
+    if old_dir
+        .canonicalize()
+        .is_ok_and(|old_dir| old_dir == new_dir)
+    {
+        return Err("Recordings are already saved to that directory".to_string());
+    }
+
+    let open_in_editor = EditorWindowIds::get(&app)
+        .ids
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|(path, _)| path.starts_with(&old_dir));
+    if open_in_editor {
+        return Err(
+            "Close all recordings open in the editor before moving the library".to_string(),
+        );
+    }
+
+    let failed = tokio::task::spawn_blocking({
+        let app = app.clone();
+        let old_dir = old_dir.clone();
+        let new_dir = new_dir.clone();
+        move || {
+            move_recordings(&old_dir, &new_dir, |progress| {
+                progress.emit(&app).ok();
+            })
+        }
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    if !failed.is_empty() {
+        return Err(format!(
+            "Failed to move {} recordings, run the migration again to retry them:\n{}",
+            failed.len(),
+            failed.join("\n")
+        ));
+    }
+
+    GeneralSettingsStore::update(&app, |settings| {
+        settings.recordings_directory = Some(new_dir.clone());
+    })?;
+
+    // the retry loop relocates them too, but shouldn't have to wait for its next attempt
+    if let Err(e) = PendingUploadsStore::relocate(&app, &new_dir) {
+        warn!("Failed to relocate pending uploads: {e}");
+    }
+
+    info!(
+        "Moved recordings library from {} to {}",
+        old_dir.display(),
+        new_dir.display()
+    );
+
+    Ok(new_dir)
+}
+
+/// Moves each recording in `old_dir` into `new_dir`, returning the ones that failed to move
+fn move_recordings(
+    old_dir: &Path,
+    new_dir: &Path,
+    mut on_moved: impl FnMut(LibraryMigrationProgress),
+) -> Result<Vec<String>, String> {
+    let mut recordings = std::fs::read_dir(old_dir)
+        .map_err(|e| format!("Failed to read recordings directory: {e}"))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.extension().is_some_and(|ext| ext == "cap"))
+        .collect::<Vec<_>>();
+    recordings.sort();
+
+    let total = recordings.len() as u32;
+    let mut failed = vec![];
+
+    for (i, path) in recordings.into_iter().enumerate() {
+        let destination = new_dir.join(path.file_name().unwrap_or_default());
+
+        if let Err(e) = move_recording(&path, &destination) {
+            warn!("Failed to move {}: {e}", path.display());
+            failed.push(format!("{}: {e}", path.display()));
+            continue;
+        }
+
+        on_moved(LibraryMigrationProgress {
+            recording_path: destination,
+            completed: i as u32 + 1,
+            total,
+        });
+    }
+
+    Ok(failed)
+}
+
+#[derive(Serialize, Type, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CleanedUpRecordings {
+    pub deleted_ids: Vec<String>,
+    pub freed_bytes: u64,
+}
+
+/// Deletes recordings made more than `older_than_days` ago, leaving ones that have
+/// been uploaded if `keep_uploaded` is set. Recordings whose meta can't be read, the one
+/// in progress, any open in the editor and any still being processed or uploaded are
+/// never deleted.
+#[tauri::command]
+#[specta::specta]
+pub async fn cleanup_recordings(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    older_than_days: u64,
+    keep_uploaded: bool,
+) -> Result<CleanedUpRecordings, String> {
+    let recordings_dir = recordings_path(&app)?;
+
+    let mut in_use = EditorWindowIds::get(&app)
+        .ids
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(path, _)| path.clone())
+        .collect::<Vec<_>>();
+    if let Some(recording) = &state.read().await.current_recording {
+        in_use.push(recording.recording_dir().clone());
+    }
+    in_use.extend(
+        list_pending_uploads(app.clone())?
+            .into_iter()
+            .map(|upload| upload.project_path),
+    );
+    in_use.extend(
+        ProcessingTasks::get(&app)
+            .recording_ids()
+            .into_iter()
+            .map(|id| recordings_dir.join(format!("{id}.cap"))),
+    );
+    in_use.extend(ActiveUploads::files(&app));
+
+    let older_than = Duration::from_secs(older_than_days.saturating_mul(24 * 60 * 60));
+
+    let cleaned_up = tokio::task::spawn_blocking(move || {
+        cleanup_recordings_in(
+            &recordings_dir,
+            older_than,
+            keep_uploaded,
+            &in_use,
+            recording_age,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    info!(
+        "Deleted {} recordings older than {older_than_days} days, freeing {} bytes",
+        cleaned_up.deleted_ids.len(),
+        cleaned_up.freed_bytes
+    );
+
+    Ok(cleaned_up)
+}
+
+/// `in_use` can be recordings or files in them, which keep the recording they're in
+fn cleanup_recordings_in(
+    recordings_dir: &Path,
+    older_than: Duration,
+    keep_uploaded: bool,
+    in_use: &[PathBuf],
+    age: impl Fn(&Path) -> Option<Duration>,
+) -> Result<CleanedUpRecordings, String> {
+    let mut recordings = std::fs::read_dir(recordings_dir)
+        .map_err(|e| format!("Failed to read recordings directory: {e}"))?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir() && path.extension().is_some_and(|ext| ext == "cap"))
+        .collect::<Vec<_>>();
+    recordings.sort();
+
+    let mut cleaned_up = CleanedUpRecordings::default();
+
+    for path in recordings {
+        if in_use.iter().any(|used| used.starts_with(&path))
+            || !age(&path).is_some_and(|age| age > older_than)
+        {
+            continue;
+        }
+
+        let meta = match RecordingMeta::load_for_project(&path) {
+            Ok(meta) => meta,
+            Err(e) => {
+                warn!("Not cleaning up {}: {e}", path.display());
+                continue;
+            }
+        };
+        if keep_uploaded && meta.sharing.is_some() {
+            continue;
+        }
+
+        let size = dir_files(&path)
+            .map(|files| files.iter().map(|(_, size)| size).sum::<u64>())
+            .unwrap_or_default();
+
+        if let Err(e) = std::fs::remove_dir_all(&path) {
+            warn!("Failed to delete {}: {e}", path.display());
+            continue;
+        }
+
+        cleaned_up
+            .deleted_ids
+            .push(crate::processing::recording_id(&path));
+        cleaned_up.freed_bytes += size;
+    }
+
+    Ok(cleaned_up)
+}
+
+/// How long ago the recording at `path` was made
+fn recording_age(path: &Path) -> Option<Duration> {
+    let metadata = path.metadata().ok()?;
+    metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .ok()?
+        .elapsed()
+        .ok()
+}
+
+/// Moves a recording directory, copying it when a rename isn't possible (eg. to another drive).
+/// A copy is made under a temporary name and only renamed into place once it's been verified,
+/// so `destination` existing means the copy completed even if removing `source` didn't.
+fn move_recording(source: &Path, destination: &Path) -> Result<(), String> {
+    if destination.exists() {
+        verify_recording_copy(source, destination)
+            .map_err(|e| format!("A different recording already exists there: {e}"))?;
+        return std::fs::remove_dir_all(source).map_err(|e| e.to_string());
+    }
+
+    if std::fs::rename(source, destination).is_ok() {
+        return Ok(());
+    }
+
+    let staging = destination.with_extension("cap-migrating");
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging).map_err(|e| e.to_string())?;
+    }
+
+    copy_dir(source, &staging)?;
+    verify_recording_copy(source, &staging)?;
+    std::fs::rename(&staging, destination).map_err(|e| e.to_string())?;
+    std::fs::remove_dir_all(source).map_err(|e| e.to_string())
+}
+
+fn verify_recording_copy(source: &Path, copy: &Path) -> Result<(), String> {
+    if dir_files(source)? != dir_files(copy)? {
+        return Err("Copied files don't match the original".to_string());
+    }
+
+    RecordingMeta::load_for_project(&copy.to_path_buf())
+        .map(|_| ())
+        .map_err(|e| format!("Copied recording can't be loaded: {e}"))
+}
+
+fn copy_dir(source: &Path, destination: &Path) -> Result<(), String> {
+    std::fs::create_dir_all(destination).map_err(|e| e.to_string())?;
+
+    for entry in std::fs::read_dir(source).map_err(|e| e.to_string())? {
+        let path = entry.map_err(|e| e.to_string())?.path();
+        let target = destination.join(path.file_name().unwrap_or_default());
+
+        if path.is_dir() {
+            copy_dir(&path, &target)?;
+        } else {
+            std::fs::copy(&path, &target)
+                .map_err(|e| format!("Failed to copy {}: {e}", path.display()))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Every file under `dir` relative to it, along with its size, in order
+fn dir_files(dir: &Path) -> Result<Vec<(PathBuf, u64)>, String> {
+    fn collect(root: &Path, dir: &Path, files: &mut Vec<(PathBuf, u64)>) -> Result<(), String> {
+        for entry in std::fs::read_dir(dir).map_err(|e| e.to_string())? {
+            let path = entry.map_err(|e| e.to_string())?.path();
+
+            if path.is_dir() {
+                collect(root, &path, files)?;
+            } else {
+                let size = path.metadata().map_err(|e| e.to_string())?.len();
+                files.push((path.strip_prefix(root).unwrap_or(&path).to_path_buf(), size));
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = vec![];
+    collect(dir, dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn fake_recording(dir: &Path, id: &str, uploaded: bool) -> PathBuf {
+        let path = dir.join(format!("{id}.cap"));
+        std::fs::create_dir_all(path.join("content")).unwrap();
+        std::fs::write(path.join("content/output.mp4"), [0; 100]).unwrap();
+
+        let sharing = uploaded
+            .then(|| serde_json::json!({ "id": id, "link": format!("https://cap.so/s/{id}") }));
+        std::fs::write(
+            path.join("recording-meta.json"),
+            serde_json::json!({
+                "pretty_name": id,
+                "sharing": sharing,
+                "fps": 30,
+                "sample_rate": null,
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        path
+    }
+
+    #[test]
+    fn deletes_only_old_recordings() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let old = fake_recording(&dir, "old", false);
+        let old_uploaded = fake_recording(&dir, "old-uploaded", true);
+        let open = fake_recording(&dir, "open", false);
+        let recent = fake_recording(&dir, "recent", false);
+        let broken = dir.join("broken.cap");
+        std::fs::create_dir_all(&broken).unwrap();
+
+        let age = |path: &Path| match path.file_stem()?.to_str()? {
+            "recent" => Some(DAY),
+            _ => Some(DAY * 30),
+        };
+
+        let old_size = dir_files(&old)
+            .unwrap()
+            .iter()
+            .map(|(_, size)| size)
+            .sum::<u64>();
+
+        let cleaned_up = cleanup_recordings_in(&dir, DAY * 7, true, &[open.clone()], age).unwrap();
+        assert_eq!(
+            cleaned_up,
+            CleanedUpRecordings {
+                deleted_ids: vec!["old".to_string()],
+                freed_bytes: old_size,
+            }
+        );
+        assert!(!old.exists());
+        assert!(old_uploaded.exists() && open.exists() && recent.exists() && broken.exists());
+
+        let cleaned_up = cleanup_recordings_in(&dir, DAY * 7, false, &[], age).unwrap();
+        assert_eq!(cleaned_up.deleted_ids, vec!["old-uploaded", "open"]);
+        assert!(recent.exists() && broken.exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn keeps_recordings_still_being_processed_or_uploaded() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let uploading = fake_recording(&dir, "uploading", false);
+        let pending = fake_recording(&dir, "pending", false);
+        let old = fake_recording(&dir, "old", false);
+
+        let in_use = [uploading.join("content/output.mp4"), pending.clone()];
+        let cleaned_up =
+            cleanup_recordings_in(&dir, DAY * 7, false, &in_use, |_| Some(DAY * 30)).unwrap();
+
+        assert_eq!(cleaned_up.deleted_ids, vec!["old"]);
+        assert!(!old.exists());
+        assert!(uploading.exists() && pending.exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn moves_every_recording() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let (old_dir, new_dir) = (root.join("old"), root.join("new"));
+        fake_recording(&old_dir, "a", false);
+        fake_recording(&old_dir, "b", true);
+        std::fs::create_dir_all(old_dir.join("not-a-recording")).unwrap();
+        std::fs::create_dir_all(&new_dir).unwrap();
+
+        let mut moved = vec![];
+        let failed = move_recordings(&old_dir, &new_dir, |progress| moved.push(progress)).unwrap();
+
+        assert!(failed.is_empty());
+        assert_eq!(
+            moved
+                .iter()
+                .map(|p| (p.recording_path.clone(), p.completed, p.total))
+                .collect::<Vec<_>>(),
+            vec![(new_dir.join("a.cap"), 1, 2), (new_dir.join("b.cap"), 2, 2)]
+        );
+        assert!(!old_dir.join("a.cap").exists() && !old_dir.join("b.cap").exists());
+        assert!(old_dir.join("not-a-recording").exists());
+        assert_eq!(
+            RecordingMeta::load_for_project(&new_dir.join("b.cap"))
+                .unwrap()
+                .sharing
+                .map(|s| s.id),
+            Some("b".to_string())
+        );
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn rerunning_a_migration_finishes_it() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let (old_dir, new_dir) = (root.join("old"), root.join("new"));
+        let interrupted = fake_recording(&old_dir, "interrupted", false);
+        fake_recording(&old_dir, "remaining", false);
+        // a copy that finished before its original could be removed
+        copy_dir(&interrupted, &new_dir.join("interrupted.cap")).unwrap();
+
+        let failed = move_recordings(&old_dir, &new_dir, |_| {}).unwrap();
+
+        assert!(failed.is_empty());
+        assert!(!interrupted.exists() && !old_dir.join("remaining.cap").exists());
+        assert!(new_dir.join("interrupted.cap").exists() && new_dir.join("remaining.cap").exists());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn different_recording_at_the_destination_isnt_overwritten() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let (old_dir, new_dir) = (root.join("old"), root.join("new"));
+        let original = fake_recording(&old_dir, "a", false);
+        let existing = fake_recording(&new_dir, "a", true);
+        std::fs::write(existing.join("content/output.mp4"), [1; 50]).unwrap();
+
+        let failed = move_recordings(&old_dir, &new_dir, |_| {}).unwrap();
+
+        assert_eq!(failed.len(), 1);
+        assert!(original.exists());
+        assert_eq!(
+            std::fs::read(existing.join("content/output.mp4")).unwrap(),
+            [1; 50]
+        );
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
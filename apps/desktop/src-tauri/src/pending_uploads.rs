@@ -0,0 +1,326 @@
+use std::{
+    future::Future,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use cap_project::{RecordingMeta, SharingMeta};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::StoreExt;
+use tracing::{error, info};
+
+use crate::{
+    auth::AuthStore,
+    dashboard, recordings_path,
+    upload::{create_or_get_video, forget_created_video, upload_video},
+    web_api::ManagerExt,
+};
+
+const RETRY_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Instant recordings made with `defer_upload`, waiting to be uploaded.
+/// Kept in the store so they're still uploaded after the app restarts.
+#[derive(Serialize, Deserialize, Type, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingUploadsStore {
+    uploads: Vec<PendingUpload>,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingUpload {
+    pub project_path: PathBuf,
+    /// the file that's uploaded, the share rendition if there is one
+    pub upload_path: PathBuf,
+    pub screenshot_path: Option<PathBuf>,
+    pub name: String,
+    /// the video an earlier attempt created, reused so failed attempts don't each
+    /// leave an empty video behind
+    #[serde(default)]
+    pub video_id: Option<String>,
+}
+
+impl PendingUpload {
+    /// `self` with its paths moved into `recordings_dir` if the recording's no longer where it
+    /// was recorded, eg. after the recordings library has been moved
+    pub fn relocated(self, recordings_dir: &Path) -> Self {
+        let Some(file_name) = self.project_path.file_name() else {
+            return self;
+        };
+        let project_path = recordings_dir.join(file_name);
+
+        if self.project_path.exists() || !project_path.exists() {
+            return self;
+        }
+
+        let rebase = |path: PathBuf| match path.strip_prefix(&self.project_path) {
+            Ok(relative) => project_path.join(relative),
+            Err(_) => path,
+        };
+
+        Self {
+            upload_path: rebase(self.upload_path),
+            screenshot_path: self.screenshot_path.map(rebase),
+            project_path,
+            ..self
+        }
+    }
+}
+
+impl PendingUploadsStore {
+    fn get(app: &AppHandle<Wry>) -> Result<Option<Self>, String> {
+        match app.store("store").map(|s| s.get("pending_uploads")) {
+            Ok(Some(store)) => match serde_json::from_value(store) {
+                Ok(uploads) => Ok(Some(uploads)),
+                Err(e) => Err(format!("Failed to deserialize pending uploads store: {e}")),
+            },
+            _ => Ok(None),
+        }
+    }
+
+    fn update(app: &AppHandle, update: impl FnOnce(&mut Self)) -> Result<(), String> {
+        let Ok(store) = app.store("store") else {
+            return Err("Store not found".to_string());
+        };
+
+        let mut uploads = Self::get(app)?.unwrap_or_default();
+        update(&mut uploads);
+        store.set("pending_uploads", json!(uploads));
+        store.save().map_err(|e| e.to_string())
+    }
+
+    pub fn add(app: &AppHandle, upload: PendingUpload) -> Result<(), String> {
+        Self::update(app, |store| {
+            store
+                .uploads
+                .retain(|u| u.project_path != upload.project_path);
+            store.uploads.push(upload);
+        })
+    }
+
+    /// Moves the paths of uploads whose recordings have been moved into `recordings_dir`
+    pub fn relocate(app: &AppHandle, recordings_dir: &Path) -> Result<(), String> {
+        Self::update(app, |store| {
+            store.uploads = std::mem::take(&mut store.uploads)
+                .into_iter()
+                .map(|upload| upload.relocated(recordings_dir))
+                .collect();
+        })
+    }
+
+    fn set_video_id(app: &AppHandle, project_path: &Path, video_id: &str) -> Result<(), String> {
+        Self::update(app, |store| {
+            for upload in &mut store.uploads {
+                if upload.project_path == project_path {
+                    upload.video_id = Some(video_id.to_string());
+                }
+            }
+        })
+    }
+}
+
+/// Instant recordings that were made to upload later and haven't been uploaded yet
+#[tauri::command]
+#[specta::specta]
+pub fn list_pending_uploads(app: AppHandle) -> Result<Vec<PendingUpload>, String> {
+    Ok(PendingUploadsStore::get(&app)?
+        .map(|store| store.uploads)
+        .unwrap_or_default())
+}
+
+/// Tries the pending uploads now, then again every minute while any are left
+pub fn spawn_retry_loop(app: AppHandle) {
+    tokio::spawn(async move {
+        loop {
+            retry_pending_uploads(&app).await;
+            tokio::time::sleep(RETRY_INTERVAL).await;
+        }
+    });
+}
+
+/// Uploads whatever's pending if signed in. Each gets a new share link, which is saved to its meta.
+pub async fn retry_pending_uploads(app: &AppHandle) {
+    if let Ok(recordings_dir) = recordings_path(app) {
+        if let Err(e) = PendingUploadsStore::relocate(app, &recordings_dir) {
+            error!("Failed to relocate pending uploads: {e}");
+        }
+    }
+
+    let Ok(Some(store)) = PendingUploadsStore::get(app) else {
+        return;
+    };
+    if store.uploads.is_empty() {
+        return;
+    }
+
+    let signed_in = AuthStore::get(app).ok().flatten().is_some();
+
+    let uploaded = upload_pending(store.uploads, signed_in, |pending| upload(app, pending)).await;
+
+    if let Err(e) = PendingUploadsStore::update(app, |store| {
+        store
+            .uploads
+            .retain(|u| !uploaded.iter().any(|p| p.project_path == u.project_path));
+    }) {
+        error!("Failed to update pending uploads: {e}");
+    }
+}
+
+/// Uploads each of `pending` in turn, returning the ones that succeeded
+async fn upload_pending<F, Fut>(
+    pending: Vec<PendingUpload>,
+    signed_in: bool,
+    mut upload: F,
+) -> Vec<PendingUpload>
+where
+    F: FnMut(PendingUpload) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let mut uploaded = vec![];
+    if !signed_in {
+        return uploaded;
+    }
+
+    for pending in pending {
+        match upload(pending.clone()).await {
+            Ok(()) => uploaded.push(pending),
+            Err(e) => error!(
+                "Failed to upload pending recording {}: {e}",
+                pending.project_path.display()
+            ),
+        }
+    }
+
+    uploaded
+}
+
+async fn upload(app: &AppHandle, pending: PendingUpload) -> Result<(), String> {
+    // kept pending rather than dropped, it may just be on a drive that isn't connected
+    let mut meta = RecordingMeta::load_for_project(&pending.project_path).map_err(|e| {
+        format!(
+            "Failed to load the meta of {}: {e}",
+            pending.project_path.display()
+        )
+    })?;
+
+    let recording_id = pending
+        .project_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string());
+    let s3_config = create_or_get_video(
+        app,
+        false,
+        pending.video_id.clone(),
+        Some(pending.name),
+        recording_id.as_deref(),
+    )
+    .await?;
+    let id = s3_config.id().to_string();
+
+    if pending.video_id.as_deref() != Some(id.as_str()) {
+        PendingUploadsStore::set_video_id(app, &pending.project_path, &id)?;
+    }
+
+    let uploaded = upload_video(
+        app,
+        id.clone(),
+        pending.upload_path,
+        Some(s3_config),
+        pending.screenshot_path.filter(|path| path.exists()),
+    )
+    .await;
+    dashboard::set_upload_failed(&pending.project_path, uploaded.is_err());
+    uploaded?;
+
+    if let Some(recording_id) = &recording_id {
+        forget_created_video(recording_id);
+    }
+
+    let link = app.make_app_url(format!("/s/{id}")).await;
+    info!("Uploaded pending recording to {link}");
+
+    meta.sharing = Some(SharingMeta { link, id });
+    meta.save_for_project().map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    fn pending(name: &str) -> PendingUpload {
+        PendingUpload {
+            project_path: PathBuf::from(format!("/recordings/{name}.cap")),
+            upload_path: PathBuf::from(format!("/recordings/{name}.cap/content/output.mp4")),
+            screenshot_path: None,
+            name: name.to_string(),
+            video_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn uploads_once_signed_in() {
+        let queue = vec![pending("a"), pending("b")];
+        let attempts = RefCell::new(vec![]);
+        let upload = |pending: PendingUpload| {
+            attempts.borrow_mut().push(pending.name.clone());
+            async move {
+                match pending.name.as_str() {
+                    "b" => Err("offline".to_string()),
+                    _ => Ok(()),
+                }
+            }
+        };
+
+        assert!(upload_pending(queue.clone(), false, upload)
+            .await
+            .is_empty());
+        assert!(attempts.borrow().is_empty());
+
+        assert_eq!(
+            upload_pending(queue, true, upload).await,
+            vec![pending("a")]
+        );
+        assert_eq!(*attempts.borrow(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn relocated_uploads_follow_the_moved_recording() {
+        let root = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let old_dir = root.join("old");
+        let new_dir = root.join("new");
+        std::fs::create_dir_all(new_dir.join("a.cap")).unwrap();
+
+        let upload = PendingUpload {
+            project_path: old_dir.join("a.cap"),
+            upload_path: old_dir.join("a.cap/content/output.mp4"),
+            screenshot_path: Some(old_dir.join("a.cap/screenshots/display.jpg")),
+            name: "a".to_string(),
+            video_id: Some("video".to_string()),
+        };
+
+        let relocated = upload.clone().relocated(&new_dir);
+        assert_eq!(relocated.project_path, new_dir.join("a.cap"));
+        assert_eq!(
+            relocated.upload_path,
+            new_dir.join("a.cap/content/output.mp4")
+        );
+        assert_eq!(
+            relocated.screenshot_path,
+            Some(new_dir.join("a.cap/screenshots/display.jpg"))
+        );
+        assert_eq!(relocated.video_id, upload.video_id);
+
+        // nowhere to move it to, so it's left for the upload to fail on
+        std::fs::remove_dir_all(new_dir.join("a.cap")).unwrap();
+        assert_eq!(upload.clone().relocated(&new_dir), upload);
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+}
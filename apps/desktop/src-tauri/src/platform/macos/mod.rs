@@ -48,3 +48,11 @@ pub fn make_webview_transparent(target: &tauri::WebviewWindow) -> tauri::Result<
         let _: id = msg_send![wkwebview, setValue:no forKey: NSString::alloc(nil).init_str("drawsBackground")];
     })
 }
+
+/// `NSProcessInfo.thermalState`, from 0 (nominal) to 3 (critical)
+pub fn thermal_state() -> isize {
+    unsafe {
+        let process_info: id = msg_send![class!(NSProcessInfo), processInfo];
+        msg_send![process_info, thermalState]
+    }
+}
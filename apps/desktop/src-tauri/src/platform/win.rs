@@ -1 +1,22 @@
+use windows::Win32::{Foundation::FILETIME, System::Threading::GetSystemTimes};
 
+/// Idle and total (kernel + user) CPU time across all cores, in 100ns units
+pub fn system_cpu_times() -> Option<(u64, u64)> {
+    let mut idle = FILETIME::default();
+    let mut kernel = FILETIME::default();
+    let mut user = FILETIME::default();
+
+    unsafe {
+        GetSystemTimes(
+            Some(&mut idle as *mut _),
+            Some(&mut kernel as *mut _),
+            Some(&mut user as *mut _),
+        )
+    }
+    .ok()?;
+
+    let to_u64 = |time: FILETIME| ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64;
+
+    // kernel time includes idle time
+    Some((to_u64(idle), to_u64(kernel) + to_u64(user)))
+}
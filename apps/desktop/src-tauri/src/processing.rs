@@ -0,0 +1,103 @@
+use std::{
+    collections::HashMap,
+    ops::Deref,
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use tauri::{AppHandle, Manager};
+use tokio_util::sync::CancellationToken;
+use tracing::info;
+
+/// Cancellation tokens for the work still running on recordings after they've stopped,
+/// like fitting, encoding renditions, transcoding and uploading, by recording id.
+///
+/// Managed separately from `App` as finalizing a recording holds its lock.
+#[derive(Default, Clone)]
+pub struct ProcessingTasks(Arc<Mutex<HashMap<String, (CancellationToken, usize)>>>);
+
+/// Keeps a recording's token registered until every operation using it has finished
+pub struct ProcessingGuard {
+    tasks: ProcessingTasks,
+    recording_id: String,
+    token: CancellationToken,
+}
+
+impl ProcessingTasks {
+    pub fn get(app: &AppHandle) -> Self {
+        app.state::<ProcessingTasks>().deref().clone()
+    }
+
+    /// Registers an operation on the recording at `project_path`.
+    /// Operations running on the same recording share a token, so they're all cancelled together.
+    pub fn start(&self, project_path: &Path) -> ProcessingGuard {
+        let recording_id = recording_id(project_path);
+
+        let mut tasks = self.0.lock().unwrap();
+        let (token, count) = tasks
+            .entry(recording_id.clone())
+            .or_insert_with(|| (CancellationToken::new(), 0));
+        *count += 1;
+
+        ProcessingGuard {
+            tasks: self.clone(),
+            recording_id,
+            token: token.clone(),
+        }
+    }
+
+    /// Ids of the recordings with operations running
+    pub fn recording_ids(&self) -> Vec<String> {
+        self.0.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn cancel(&self, recording_id: &str) -> bool {
+        match self.0.lock().unwrap().get(recording_id) {
+            Some((token, _)) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl ProcessingGuard {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for ProcessingGuard {
+    fn drop(&mut self) {
+        let mut tasks = self.tasks.0.lock().unwrap();
+        if let Some((_, count)) = tasks.get_mut(&self.recording_id) {
+            *count -= 1;
+            if *count == 0 {
+                tasks.remove(&self.recording_id);
+            }
+        }
+    }
+}
+
+pub fn recording_id(project_path: &Path) -> String {
+    project_path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Stops everything still processing the recording with `recording_id`, removing any files
+/// it had only partly written. Uploads that are cancelled are marked as failed so they can be retried.
+/// Returns whether anything was running.
+#[tauri::command]
+#[specta::specta]
+pub fn cancel_recording_processing(app: AppHandle, recording_id: String) -> bool {
+    let cancelled = ProcessingTasks::get(&app).cancel(&recording_id);
+
+    if cancelled {
+        info!("Cancelled processing of recording {recording_id}");
+    }
+
+    cancelled
+}
@@ -0,0 +1,188 @@
+use std::path::PathBuf;
+
+use cap_media::feeds::{AudioInputFeed, CameraFeed};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use specta::Type;
+use tauri::{AppHandle, Wry};
+use tauri_plugin_store::StoreExt;
+use tauri_specta::Event;
+use tracing::{error, warn};
+
+use crate::{
+    general_settings::{
+        default_capture_scale, GeneralSettingsStore, MainWindowRecordingStartBehaviour,
+        PostStudioRecordingBehaviour,
+    },
+    recording::{validate_capture_scale, validate_output_dir},
+    set_camera_input, set_mic_input, App, MutableState, RecordingOptionsChanged,
+};
+
+#[derive(Serialize, Deserialize, Type, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfilesStore {
+    profiles: Vec<RecordingProfile>,
+}
+
+/// A named recording environment that swaps devices, where recordings are saved, their
+/// quality and window behaviour in one go.
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingProfile {
+    pub name: String,
+    #[serde(default)]
+    pub camera_label: Option<String>,
+    #[serde(default)]
+    pub mic_label: Option<String>,
+    /// where recordings are saved, the recordings directory if unset
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// the `capture_scale` recordings are made at
+    #[serde(default = "default_capture_scale")]
+    pub capture_scale: f32,
+    #[serde(default)]
+    pub custom_cursor_capture: bool,
+    #[serde(default)]
+    pub post_studio_recording_behaviour: PostStudioRecordingBehaviour,
+    #[serde(default)]
+    pub main_window_recording_start_behaviour: MainWindowRecordingStartBehaviour,
+}
+
+impl ProfilesStore {
+    fn get(app: &AppHandle<Wry>) -> Result<Option<Self>, String> {
+        match app.store("store").map(|s| s.get("recording_profiles")) {
+            Ok(Some(store)) => {
+                // Handle potential deserialization errors gracefully
+                match serde_json::from_value(store) {
+                    Ok(settings) => Ok(Some(settings)),
+                    Err(_) => {
+                        error!("Failed to deserialize recording profiles store");
+                        Ok(None)
+                    }
+                }
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn update(app: &AppHandle, update: impl FnOnce(&mut Self)) -> Result<(), String> {
+        let Ok(store) = app.store("store") else {
+            return Err("Store not found".to_string());
+        };
+
+        let mut settings = Self::get(app)?.unwrap_or_default();
+        update(&mut settings);
+        store.set("recording_profiles", json!(settings));
+        store.save().map_err(|e| e.to_string())
+    }
+}
+
+impl RecordingProfile {
+    /// Checks everything the profile refers to is usable, before any of it's applied
+    fn validate(&self) -> Result<(), String> {
+        if let Some(camera_label) = &self.camera_label {
+            if !CameraFeed::list_cameras().contains(camera_label) {
+                return Err(format!(
+                    "Camera '{camera_label}' used by profile '{}' is not available",
+                    self.name
+                ));
+            }
+        }
+
+        if let Some(mic_label) = &self.mic_label {
+            if !AudioInputFeed::list_devices().contains_key(mic_label) {
+                return Err(format!(
+                    "Microphone '{mic_label}' used by profile '{}' is not available",
+                    self.name
+                ));
+            }
+        }
+
+        if let Some(output_dir) = &self.output_dir {
+            validate_output_dir(output_dir)?;
+        }
+
+        validate_capture_scale(self.capture_scale)
+    }
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn list_recording_profiles(app: AppHandle) -> Result<Vec<RecordingProfile>, String> {
+    Ok(ProfilesStore::get(&app)?
+        .map(|s| s.profiles)
+        .unwrap_or_default())
+}
+
+/// Creates a profile, or replaces the existing one with the same name.
+#[tauri::command]
+#[specta::specta]
+pub fn save_recording_profile(app: AppHandle, profile: RecordingProfile) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("Profile name cannot be empty".to_string());
+    }
+
+    ProfilesStore::update(&app, |store| {
+        match store.profiles.iter_mut().find(|p| p.name == profile.name) {
+            Some(existing) => *existing = profile,
+            None => store.profiles.push(profile),
+        }
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn delete_recording_profile(app: AppHandle, name: String) -> Result<(), String> {
+    ProfilesStore::update(&app, |store| store.profiles.retain(|p| p.name != name))
+}
+
+/// Switches devices and settings to those of the named profile.
+///
+/// Everything the profile refers to is checked before anything is changed, so a profile
+/// pointing at a disconnected device leaves the current setup untouched. The settings
+/// are written together once both devices have switched.
+#[tauri::command]
+#[specta::specta]
+pub async fn apply_profile(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    name: String,
+) -> Result<(), String> {
+    let profile = ProfilesStore::get(&app)?
+        .and_then(|s| s.profiles.into_iter().find(|p| p.name == name))
+        .ok_or_else(|| format!("Profile '{name}' not found"))?;
+
+    profile.validate()?;
+
+    let previous_mic = {
+        let state = state.read().await;
+        if state.current_recording.is_some() {
+            return Err("Cannot switch profiles while recording".to_string());
+        }
+        state
+            .mic_feed
+            .as_ref()
+            .map(|feed| feed.device_name().to_string())
+    };
+
+    set_mic_input(state.clone(), profile.mic_label.clone()).await?;
+    if let Err(e) = set_camera_input(state.clone(), profile.camera_label.clone()).await {
+        if let Err(e) = set_mic_input(state.clone(), previous_mic).await {
+            warn!("Failed to switch back to the previous mic: {e}");
+        }
+        return Err(e);
+    }
+
+    GeneralSettingsStore::update(&app, |settings| {
+        settings.default_output_dir = profile.output_dir;
+        settings.capture_scale = profile.capture_scale;
+        settings.custom_cursor_capture = profile.custom_cursor_capture;
+        settings.post_studio_recording_behaviour = profile.post_studio_recording_behaviour;
+        settings.main_window_recording_start_behaviour =
+            profile.main_window_recording_start_behaviour;
+    })?;
+
+    RecordingOptionsChanged.emit(&app).ok();
+
+    Ok(())
+}
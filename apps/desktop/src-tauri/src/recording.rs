@@ -1,59 +1,101 @@
-use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use crate::{
     audio::AppSounds,
     auth::AuthStore,
-    create_screenshot,
+    create_screenshot, dashboard,
+    focus_events::FocusEventsRecorder,
     general_settings::{
-        GeneralSettingsStore, MainWindowRecordingStartBehaviour, PostStudioRecordingBehaviour,
+        default_restart_teardown_timeout_ms, GeneralSettingsStore, LastRecordingInputs,
+        MainWindowRecordingStartBehaviour, PostStudioRecordingBehaviour, ShareLinkOpenBehaviour,
+        DEFAULT_AUTO_ZOOM_AMOUNT,
     },
     open_external_link,
+    pending_uploads::{PendingUpload, PendingUploadsStore},
     presets::PresetsStore,
+    processing::ProcessingTasks,
+    recordings_path,
+    screenshot::{create_preview_gif, screenshot_quality},
+    system_pressure::{spawn_pressure_monitor, PressureMonitorConfig},
+    transcode::{fit_to_aspect, render_renditions, AspectFit, RenditionProfile},
     upload::{
-        create_or_get_video, prepare_screenshot_upload, upload_video, InstantMultipartUpload,
+        create_or_get_video, forget_created_video, locate_recording_dir, prepare_chapters_upload,
+        prepare_gif_upload, prepare_screenshot_upload, upload_video, ActiveUploads,
+        InstantMultipartUpload,
     },
     web_api::ManagerExt,
-    windows::{CapWindowId, ShowCapWindow},
-    App, CurrentRecordingChanged, DynLoggingLayer, MutableState, NewStudioRecordingAdded,
-    RecordingStarted, RecordingStopped, VideoUploadInfo,
+    windows::{BeRightBackCard, CapWindowId, ShowCapWindow},
+    App, ArcLock, CurrentRecordingChanged, DynLoggingLayer, MutableState, NewStudioRecordingAdded,
+    RecordingFailed, RecordingStarted, RecordingStopped, VideoUploadInfo,
 };
+use cap_audio::AudioData;
 use cap_fail::fail;
-use cap_media::{feeds::CameraFeed, platform::display_for_window, sources::ScreenCaptureTarget};
+use cap_media::{
+    encoders::{OpusEncoder, SUPPORTED_SAMPLE_RATES},
+    feeds::{AudioInputFeed, CameraFeed},
+    platform::display_for_window,
+    sources::ScreenCaptureTarget,
+};
 use cap_media::{
     platform::Bounds,
     sources::{CaptureScreen, CaptureWindow},
 };
 use cap_project::{
-    Platform, ProjectConfiguration, RecordingMeta, RecordingMetaInner, SharingMeta,
-    StudioRecordingMeta, TimelineConfiguration, TimelineSegment, ZoomSegment,
+    chapters_webvtt, AspectFitMeta, ChapterMarker, Crop, CursorEvents, CursorFollowPath,
+    CursorMoveEvent, InstantOutputFormat, InstantRecordingMeta, Platform, ProjectConfiguration,
+    RecordingMeta, RecordingMetaInner, RecordingQuality, RecordingStartMarker, RenditionMeta,
+    SharingMeta, StudioRecordingMeta, TimelineConfiguration, TimelineSegment, VideoCodec,
+    ZoomFollowPoint, ZoomMode, ZoomSegment, XY,
 };
 use cap_recording::{
+    capabilities::CaptureCapabilities,
+    cursor::LiveCursorClick,
     instant_recording::{CompletedInstantRecording, InstantRecordingHandle},
-    CompletedStudioRecording, RecordingError, RecordingMode, StudioRecordingHandle,
+    quality::QualityInputs,
+    CompletedSegment, CompletedStudioRecording, RecordingError, RecordingMode,
+    StudioRecordingHandle, MIN_CAPTURE_SCALE,
 };
 use cap_rendering::ProjectRecordingsMeta;
 use cap_utils::{ensure_dir, spawn_actor};
-use serde::Deserialize;
+use futures::future::Either;
+use serde::{Deserialize, Serialize};
 use specta::Type;
 use tauri::{AppHandle, Manager};
 use tauri_plugin_dialog::{DialogExt, MessageDialogBuilder};
+use tauri_plugin_opener::OpenerExt;
 use tauri_specta::Event;
-use tracing::{error, info};
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
 
 pub enum InProgressRecording {
     Instant {
         target_name: String,
         handle: InstantRecordingHandle,
         progressive_upload: Option<InstantMultipartUpload>,
-        video_upload_info: VideoUploadInfo,
+        /// `None` if the upload was deferred
+        video_upload_info: Option<VideoUploadInfo>,
         inputs: StartRecordingInputs,
         recording_dir: PathBuf,
+        focus_events: Option<FocusEventsRecorder>,
+        clock: RecordingClock,
+        chapters: Vec<ChapterMarker>,
     },
     Studio {
         target_name: String,
         handle: StudioRecordingHandle,
         inputs: StartRecordingInputs,
         recording_dir: PathBuf,
+        focus_events: Option<FocusEventsRecorder>,
+        clock: RecordingClock,
+        chapters: Vec<ChapterMarker>,
     },
 }
 
@@ -72,17 +114,73 @@ impl InProgressRecording {
         }
     }
 
-    pub async fn pause(&self) -> Result<(), RecordingError> {
+    pub fn mode(&self) -> RecordingMode {
+        match self {
+            Self::Instant { .. } => RecordingMode::Instant,
+            Self::Studio { .. } => RecordingMode::Studio,
+        }
+    }
+
+    pub fn target_name(&self) -> &String {
         match self {
-            Self::Instant { handle, .. } => handle.pause().await,
-            Self::Studio { handle, .. } => handle.pause().await,
+            Self::Instant { target_name, .. } => target_name,
+            Self::Studio { target_name, .. } => target_name,
+        }
+    }
+
+    pub async fn pause(&self) -> Result<(), RecordingError> {
+        self.pause_segment().await.map(|_| ())
+    }
+
+    /// Pauses, returning the display recording of the segment that pausing ended.
+    /// Instant recordings only have the one output, which isn't finalized until they stop.
+    pub async fn pause_segment(&self) -> Result<Option<PathBuf>, RecordingError> {
+        if let Some(focus_events) = self.focus_events() {
+            focus_events.pause();
         }
+
+        let segment = match self {
+            Self::Instant { handle, .. } => handle.pause().await.map(|_| None),
+            Self::Studio { handle, .. } => handle.pause_segment().await.map(Some),
+        }?;
+
+        self.clock().pause();
+        Ok(segment)
     }
 
     pub async fn resume(&self) -> Result<(), RecordingError> {
+        if let Some(focus_events) = self.focus_events() {
+            focus_events.resume();
+        }
+
         match self {
             Self::Instant { handle, .. } => handle.resume().await,
             Self::Studio { handle, .. } => handle.resume().await,
+        }?;
+
+        self.clock().resume();
+        Ok(())
+    }
+
+    fn focus_events(&self) -> Option<&FocusEventsRecorder> {
+        match self {
+            Self::Instant { focus_events, .. } => focus_events.as_ref(),
+            Self::Studio { focus_events, .. } => focus_events.as_ref(),
+        }
+    }
+
+    /// The format the output's written in, studio recordings are always mp4s
+    pub fn output_format(&self) -> InstantOutputFormat {
+        match self {
+            Self::Instant { handle, .. } => handle.output_format,
+            Self::Studio { .. } => InstantOutputFormat::Mp4,
+        }
+    }
+
+    pub fn clock(&self) -> &RecordingClock {
+        match self {
+            Self::Instant { clock, .. } => clock,
+            Self::Studio { clock, .. } => clock,
         }
     }
 
@@ -93,6 +191,25 @@ impl InProgressRecording {
         }
     }
 
+    /// Adds a chapter marker at the current recording time, named "Chapter N" if unnamed
+    pub fn add_chapter(&mut self, name: Option<String>) -> ChapterMarker {
+        let time = self.clock().elapsed().as_secs_f64();
+        let chapters = match self {
+            Self::Instant { chapters, .. } => chapters,
+            Self::Studio { chapters, .. } => chapters,
+        };
+
+        let marker = ChapterMarker {
+            name: name
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| format!("Chapter {}", chapters.len() + 1)),
+            time,
+        };
+        chapters.push(marker.clone());
+
+        marker
+    }
+
     pub async fn stop(self) -> Result<CompletedRecording, RecordingError> {
         Ok(match self {
             Self::Instant {
@@ -100,21 +217,47 @@ impl InProgressRecording {
                 progressive_upload,
                 video_upload_info,
                 target_name,
+                recording_dir,
+                focus_events,
+                chapters,
                 ..
-            } => CompletedRecording::Instant {
-                recording: handle.stop().await?,
-                progressive_upload,
-                video_upload_info,
-                target_name,
-            },
+            } => {
+                let recording = handle.stop().await?;
+
+                if let Some(focus_events) = focus_events {
+                    focus_events.stop(&recording_dir).await;
+                }
+
+                CompletedRecording::Instant {
+                    recording,
+                    progressive_upload,
+                    video_upload_info,
+                    target_name,
+                    chapters,
+                }
+            }
             Self::Studio {
                 handle,
                 target_name,
+                recording_dir,
+                focus_events,
+                inputs,
+                chapters,
                 ..
-            } => CompletedRecording::Studio {
-                recording: handle.stop().await?,
-                target_name,
-            },
+            } => {
+                let recording = handle.stop().await?;
+
+                if let Some(focus_events) = focus_events {
+                    focus_events.stop(&recording_dir).await;
+                }
+
+                CompletedRecording::Studio {
+                    recording,
+                    target_name,
+                    cursor_follow: inputs.cursor_follow,
+                    chapters,
+                }
+            }
         })
     }
 
@@ -125,6 +268,14 @@ impl InProgressRecording {
         }
     }
 
+    /// Resolves once the recording's actor has released its capture sources and feeds
+    pub fn released(&self) -> impl Future<Output = ()> + Send + 'static {
+        match self {
+            Self::Instant { handle, .. } => Either::Left(handle.released()),
+            Self::Studio { handle, .. } => Either::Right(handle.released()),
+        }
+    }
+
     pub fn bounds(&self) -> &Bounds {
         match self {
             Self::Instant { handle, .. } => &handle.bounds,
@@ -133,16 +284,84 @@ impl InProgressRecording {
     }
 }
 
+/// Time spent recording, excluding pauses
+pub struct RecordingClock(std::sync::Mutex<RecordingClockState>);
+
+struct RecordingClockState {
+    started_at: Instant,
+    paused_at: Option<Instant>,
+    paused_for: Duration,
+}
+
+impl RecordingClock {
+    fn start() -> Self {
+        Self(std::sync::Mutex::new(RecordingClockState {
+            started_at: Instant::now(),
+            paused_at: None,
+            paused_for: Duration::ZERO,
+        }))
+    }
+
+    fn pause(&self) {
+        self.pause_at(Instant::now());
+    }
+
+    fn pause_at(&self, now: Instant) {
+        let mut state = self.0.lock().unwrap();
+        state.paused_at.get_or_insert(now);
+    }
+
+    fn resume(&self) {
+        self.resume_at(Instant::now());
+    }
+
+    fn resume_at(&self, now: Instant) {
+        let mut state = self.0.lock().unwrap();
+        if let Some(paused_at) = state.paused_at.take() {
+            state.paused_for += now.saturating_duration_since(paused_at);
+        }
+    }
+
+    /// When the recording will have been recording for `limit` if it isn't paused again,
+    /// `None` while it's paused
+    fn deadline(&self, limit: Duration) -> Option<Instant> {
+        let state = self.0.lock().unwrap();
+        state
+            .paused_at
+            .is_none()
+            .then(|| state.started_at + state.paused_for + limit)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.0.lock().unwrap().paused_at.is_some()
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed_at(Instant::now())
+    }
+
+    fn elapsed_at(&self, now: Instant) -> Duration {
+        let state = self.0.lock().unwrap();
+        let now = state.paused_at.unwrap_or(now);
+        now.saturating_duration_since(state.started_at)
+            .saturating_sub(state.paused_for)
+    }
+}
+
 pub enum CompletedRecording {
     Instant {
         recording: CompletedInstantRecording,
         target_name: String,
         progressive_upload: Option<InstantMultipartUpload>,
-        video_upload_info: VideoUploadInfo,
+        /// `None` if the upload was deferred
+        video_upload_info: Option<VideoUploadInfo>,
+        chapters: Vec<ChapterMarker>,
     },
     Studio {
         recording: CompletedStudioRecording,
         target_name: String,
+        cursor_follow: Option<XY<u32>>,
+        chapters: Vec<ChapterMarker>,
     },
 }
 
@@ -167,6 +386,34 @@ impl CompletedRecording {
             Self::Studio { target_name, .. } => target_name,
         }
     }
+
+    pub fn mode(&self) -> RecordingMode {
+        match self {
+            Self::Instant { .. } => RecordingMode::Instant,
+            Self::Studio { .. } => RecordingMode::Studio,
+        }
+    }
+
+    pub fn start_marker(&self) -> Option<RecordingStartMarker> {
+        match self {
+            Self::Instant { recording, .. } => recording.start_marker,
+            Self::Studio { recording, .. } => recording.start_marker,
+        }
+    }
+
+    pub fn quality_inputs(&self) -> QualityInputs {
+        match self {
+            Self::Instant { recording, .. } => recording.quality_inputs.clone(),
+            Self::Studio { recording, .. } => recording.quality_inputs.clone(),
+        }
+    }
+
+    pub fn chapters(&self) -> &[ChapterMarker] {
+        match self {
+            Self::Instant { chapters, .. } => chapters,
+            Self::Studio { chapters, .. } => chapters,
+        }
+    }
 }
 
 #[tauri::command(async)]
@@ -193,684 +440,5208 @@ pub fn list_cameras() -> Vec<String> {
     CameraFeed::list_cameras()
 }
 
-#[derive(Deserialize, Type, Clone)]
+/// Names of the usable microphones, the default first. Devices sharing a name are numbered.
+#[tauri::command(async)]
+#[specta::specta]
+pub fn list_microphones() -> Vec<String> {
+    AudioInputFeed::list_devices().keys().cloned().collect()
+}
+
+#[derive(Serialize, Deserialize, Type, Clone, Debug)]
 pub struct StartRecordingInputs {
     pub capture_target: ScreenCaptureTarget,
     #[serde(default)]
     pub capture_system_audio: bool,
+    /// Input device to record as system audio, eg. a virtual device
+    /// that specific apps' output has been routed to
+    #[serde(default)]
+    pub system_audio_device: Option<String>,
     pub mode: RecordingMode,
+    /// Size of a region that pans to follow the cursor. The whole screen is still
+    /// recorded, the region is applied when rendering so it can be changed later.
+    #[serde(default)]
+    pub cursor_follow: Option<XY<u32>>,
+    /// Records just the camera and audio, `capture_target` is ignored. Studio mode only.
+    #[serde(default)]
+    pub camera_only: bool,
+    /// Stops the recording automatically once it's been recording this long, not counting time paused
+    #[serde(default)]
+    pub max_duration_secs: Option<u64>,
+    /// Directory to save this recording to instead of the recordings directory.
+    /// Recordings saved elsewhere aren't listed in the library.
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+    /// Counts down for this many seconds before recording starts
+    #[serde(default)]
+    pub countdown_secs: Option<u32>,
+    /// Records without creating a share link, and uploads once signed in and online.
+    /// Instant mode only.
+    #[serde(default)]
+    pub defer_upload: bool,
+    /// Microphone to record, replacing the one selected with `set_mic_input`
+    #[serde(default)]
+    pub mic_device: Option<String>,
+    /// Camera to record, replacing the one selected with `set_camera_input`
+    #[serde(default)]
+    pub camera_device: Option<String>,
+    /// Records without the camera and hides its window, even if one's selected
+    #[serde(default = "crate::general_settings::true_b")]
+    pub include_camera: bool,
+    /// Frame rate to capture at instead of the mode's default. It's kept within what
+    /// the recorded display refreshes at, and the effective rate is saved in the meta.
+    #[serde(default)]
+    pub target_fps: Option<u32>,
 }
 
-#[tauri::command]
-#[specta::specta]
-#[tracing::instrument(name = "recording", skip_all)]
-pub async fn start_recording(
-    app: AppHandle,
-    state_mtx: MutableState<'_, App>,
-    inputs: StartRecordingInputs,
+impl StartRecordingInputs {
+    /// The device recorded as system audio, if system audio's being recorded at all
+    fn system_audio_device(&self) -> Option<&String> {
+        self.system_audio_device
+            .as_ref()
+            .filter(|_| self.capture_system_audio)
+    }
+}
+
+/// Opens the mic and camera named in `inputs`, if any, in place of the selected ones
+async fn select_input_devices(
+    state_mtx: &MutableState<'_, App>,
+    inputs: &StartRecordingInputs,
 ) -> Result<(), String> {
-    let id = uuid::Uuid::new_v4().to_string();
+    // both are found before either is switched to, so a missing one changes nothing
+    if let Some(mic_device) = &inputs.mic_device {
+        let mics = AudioInputFeed::list_devices()
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+        find_device("Microphone", mic_device, &mics)?;
+    }
 
-    let recording_dir = app
-        .path()
-        .app_data_dir()
-        .unwrap()
-        .join("recordings")
-        .join(format!("{id}.cap"));
+    if let Some(camera_device) = &inputs.camera_device {
+        find_device("Camera", camera_device, &CameraFeed::list_cameras())?;
+    }
 
-    ensure_dir(&recording_dir).map_err(|e| format!("Failed to create recording directory: {e}"))?;
-    let logfile = std::fs::File::create(recording_dir.join("recording-logs.log"))
-        .map_err(|e| format!("Failed to create logfile: {e}"))?;
+    if let Some(mic_device) = &inputs.mic_device {
+        crate::set_mic_input(state_mtx.clone(), Some(mic_device.clone())).await?;
+    }
 
-    state_mtx
-        .write()
-        .await
-        .recording_logging_handle
-        .reload(Some(Box::new(
-            tracing_subscriber::fmt::layer()
-                .with_ansi(false)
-                .with_target(true)
-                .with_writer(logfile),
-        ) as DynLoggingLayer))
-        .map_err(|e| format!("Failed to reload logging layer: {e}"))?;
-
-    let target_name = {
-        let title = inputs.capture_target.get_title();
+    if let Some(camera_device) = &inputs.camera_device {
+        crate::set_camera_input(state_mtx.clone(), Some(camera_device.clone())).await?;
+    }
 
-        match inputs.capture_target {
-            ScreenCaptureTarget::Area { .. } => "Area".to_string(),
-            ScreenCaptureTarget::Window { id, .. } => {
-                let platform_windows: HashMap<u32, cap_media::platform::Window> =
-                    cap_media::platform::get_on_screen_windows()
-                        .into_iter()
-                        .map(|window| (window.window_id, window))
-                        .collect();
+    Ok(())
+}
 
-                platform_windows
-                    .get(&id)
-                    .map(|v| v.owner_name.to_string())
-                    .unwrap_or_else(|| "Window".to_string())
-            }
-            ScreenCaptureTarget::Screen { .. } => title.unwrap_or_else(|| "Screen".to_string()),
-        }
+fn find_device(kind: &str, name: &str, available: &[String]) -> Result<(), String> {
+    if available.iter().any(|device| device == name) {
+        return Ok(());
+    }
+
+    Err(match available {
+        [] => format!("{kind} '{name}' wasn't found, no devices are connected"),
+        _ => format!(
+            "{kind} '{name}' wasn't found, available devices are: {}",
+            available.join(", ")
+        ),
+    })
+}
+
+#[derive(Serialize, Type, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidationSeverity {
+    /// Recording can start, but not quite as asked
+    Warning,
+    /// Starting the recording would fail
+    Error,
+}
+
+#[derive(Serialize, Type, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ValidationIssue {
+    pub severity: ValidationSeverity,
+    pub message: String,
+}
+
+/// What's available to record with when validating inputs
+struct RecordingEnvironment {
+    window_ids: Vec<u32>,
+    screen_ids: Vec<u32>,
+    mics: Vec<String>,
+    cameras: Vec<String>,
+    signed_in: bool,
+    /// free bytes where the recording would be saved, if they could be found out
+    available_space: Option<u64>,
+}
+
+/// Checks that `inputs` could be recorded right now, without starting anything:
+/// the target and devices still exist, instant recordings can be uploaded,
+/// and the output directory can be written to.
+#[tauri::command(async)]
+#[specta::specta]
+pub fn validate_recording_inputs(
+    app: AppHandle,
+    inputs: StartRecordingInputs,
+) -> Vec<ValidationIssue> {
+    let environment = RecordingEnvironment {
+        window_ids: cap_media::platform::get_on_screen_windows()
+            .into_iter()
+            .map(|window| window.window_id)
+            .collect(),
+        screen_ids: cap_media::sources::list_screens()
+            .into_iter()
+            .map(|(screen, _)| screen.id)
+            .collect(),
+        mics: AudioInputFeed::list_devices().keys().cloned().collect(),
+        cameras: CameraFeed::list_cameras(),
+        signed_in: AuthStore::get(&app).ok().flatten().is_some(),
+        available_space: output_dir_for(&app, &inputs)
+            .ok()
+            .and_then(|dir| available_space(&dir)),
     };
 
-    if let Some(window) = CapWindowId::Camera.get(&app) {
-        let _ = window.set_content_protected(matches!(inputs.mode, RecordingMode::Studio));
-    }
+    recording_input_issues(&inputs, &environment)
+}
 
-    let video_upload_info = match inputs.mode {
-        RecordingMode::Instant => {
-            match AuthStore::get(&app).ok().flatten() {
-                Some(_) => {
-                    // Pre-create the video and get the shareable link
-                    if let Ok(s3_config) = create_or_get_video(
-                        &app,
-                        false,
-                        None,
-                        Some(format!(
-                            "{target_name} {}",
-                            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
-                        )),
-                    )
-                    .await
-                    {
-                        let link = app.make_app_url(format!("/s/{}", s3_config.id())).await;
-                        info!("Pre-created shareable link: {}", link);
+fn recording_input_issues(
+    inputs: &StartRecordingInputs,
+    environment: &RecordingEnvironment,
+) -> Vec<ValidationIssue> {
+    let mut issues = vec![];
+    let mut error = |message: String| {
+        issues.push(ValidationIssue {
+            severity: ValidationSeverity::Error,
+            message,
+        })
+    };
 
-                        Some(VideoUploadInfo {
-                            id: s3_config.id().to_string(),
-                            link: link.clone(),
-                            config: s3_config,
-                        })
-                    } else {
-                        None
-                    }
-                }
-                // Allow the recording to proceed without error for any signed-in user
-                _ => {
-                    // User is not signed in
-                    return Err("Please sign in to use instant recording".to_string());
-                }
+    // nothing listed means we couldn't check, like without screen recording permission
+    if !inputs.camera_only {
+        match inputs.capture_target {
+            ScreenCaptureTarget::Window { id, .. }
+                if !environment.window_ids.is_empty() && !environment.window_ids.contains(&id) =>
+            {
+                error("The window being recorded has been closed".to_string())
             }
+            ScreenCaptureTarget::Screen { id } | ScreenCaptureTarget::Area { screen: id, .. }
+                if !environment.screen_ids.is_empty() && !environment.screen_ids.contains(&id) =>
+            {
+                error("The screen being recorded has been disconnected".to_string())
+            }
+            _ => {}
         }
-        RecordingMode::Studio => None,
-    };
-
-    match &inputs.capture_target {
-        ScreenCaptureTarget::Window { id } => {
-            #[cfg(target_os = "macos")]
-            let display = display_for_window(*id).unwrap().id;
+    }
 
-            #[cfg(windows)]
-            let display = {
-                let scap::Target::Window(target) = inputs.capture_target.get_target().unwrap()
-                else {
-                    unreachable!();
-                };
-                display_for_window(target.raw_handle).unwrap().0 as u32
-            };
+    if let Some(mic_device) = &inputs.mic_device {
+        if let Err(e) = find_device("Microphone", mic_device, &environment.mics) {
+            error(e);
+        }
+    }
 
-            let _ = ShowCapWindow::WindowCaptureOccluder { screen_id: display }
-                .show(&app)
-                .await;
+    if let Some(camera_device) = &inputs.camera_device {
+        if let Err(e) = find_device("Camera", camera_device, &environment.cameras) {
+            error(e);
         }
-        ScreenCaptureTarget::Area { screen, .. } => {
-            let _ = ShowCapWindow::WindowCaptureOccluder { screen_id: *screen }
-                .show(&app)
-                .await;
+    }
+
+    if let Some(output_dir) = &inputs.output_dir {
+        if let Err(e) = validate_output_dir(output_dir) {
+            error(e);
         }
-        _ => {}
     }
 
-    let (finish_upload_tx, finish_upload_rx) = flume::bounded(1);
-    let progressive_upload = video_upload_info
-        .as_ref()
-        .filter(|_| matches!(inputs.mode, RecordingMode::Instant))
-        .map(|video_upload_info| {
-            InstantMultipartUpload::spawn(
-                app.clone(),
-                id.clone(),
-                recording_dir.join("content/output.mp4"),
-                video_upload_info.clone(),
-                Some(finish_upload_rx),
-            )
+    if matches!(inputs.mode, RecordingMode::Instant) && !environment.signed_in {
+        issues.push(match inputs.defer_upload {
+            true => ValidationIssue {
+                severity: ValidationSeverity::Warning,
+                message: "The recording will be uploaded once you sign in".to_string(),
+            },
+            false => ValidationIssue {
+                severity: ValidationSeverity::Error,
+                message: "Sign in to make instant recordings".to_string(),
+            },
         });
+    }
 
-    println!("spawning actor");
+    issues.extend(environment.available_space.and_then(free_space_issue));
 
-    // done in spawn to catch panics just in case
-    let actor_done_rx = spawn_actor({
-        let state_mtx = Arc::clone(&state_mtx);
-        let app = app.clone();
-        async move {
-            fail!("recording::spawn_actor");
-            let mut state = state_mtx.write().await;
+    issues
+}
 
-            let base_inputs = cap_recording::RecordingBaseInputs {
-                capture_target: inputs.capture_target,
-                capture_system_audio: inputs.capture_system_audio,
-                mic_feed: &state.mic_feed,
-            };
+/// Rough upper bound of how quickly a recording fills the disk
+const ESTIMATED_RECORDING_BYTES_PER_SEC: u64 = 2 * 1024 * 1024;
+/// Recordings aren't started with less free space than this much recording takes up
+const MIN_FREE_SPACE_SECS: u64 = 60;
+/// Recording with less free space than this much recording takes up is warned about
+const LOW_FREE_SPACE_SECS: u64 = 15 * 60;
 
-            let (actor, actor_done_rx) = match inputs.mode {
-                RecordingMode::Studio => {
-                    let (handle, actor_done_rx) = cap_recording::spawn_studio_recording_actor(
-                        id.clone(),
-                        recording_dir.clone(),
-                        base_inputs,
-                        state.camera_feed.clone(),
-                        GeneralSettingsStore::get(&app)
-                            .ok()
-                            .flatten()
-                            .map(|s| s.custom_cursor_capture)
-                            .unwrap_or_default(),
-                    )
-                    .await
-                    .map_err(|e| {
-                        error!("Failed to spawn studio recording actor: {e}");
-                        e.to_string()
-                    })?;
+fn free_space_issue(available: u64) -> Option<ValidationIssue> {
+    let minutes = available / ESTIMATED_RECORDING_BYTES_PER_SEC / 60;
 
-                    (
-                        InProgressRecording::Studio {
-                            handle,
-                            target_name,
-                            inputs,
-                            recording_dir: recording_dir.clone(),
-                        },
-                        actor_done_rx,
-                    )
-                }
-                RecordingMode::Instant => {
-                    let Some(video_upload_info) = video_upload_info.clone() else {
-                        return Err("Video upload info not found".to_string());
-                    };
-
-                    let (handle, actor_done_rx) =
-                        cap_recording::instant_recording::spawn_instant_recording_actor(
-                            id.clone(),
-                            recording_dir.clone(),
-                            base_inputs,
-                        )
-                        .await
-                        .map_err(|e| {
-                            error!("Failed to spawn studio recording actor: {e}");
-                            e.to_string()
-                        })?;
+    if available < MIN_FREE_SPACE_SECS * ESTIMATED_RECORDING_BYTES_PER_SEC {
+        Some(ValidationIssue {
+            severity: ValidationSeverity::Error,
+            message: "There isn't enough disk space to record, free up some space first"
+                .to_string(),
+        })
+    } else if available < LOW_FREE_SPACE_SECS * ESTIMATED_RECORDING_BYTES_PER_SEC {
+        Some(ValidationIssue {
+            severity: ValidationSeverity::Warning,
+            message: format!(
+                "Disk space is running low, enough for about {minutes} minutes of recording"
+            ),
+        })
+    } else {
+        None
+    }
+}
 
-                    (
-                        InProgressRecording::Instant {
-                            handle,
-                            progressive_upload,
-                            video_upload_info,
-                            target_name,
-                            inputs,
-                            recording_dir: recording_dir.clone(),
-                        },
-                        actor_done_rx,
-                    )
-                }
-            };
+/// Free bytes on the disk `path` is on
+fn available_space(path: &Path) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        let stat = nix::sys::statvfs::statvfs(path).ok()?;
+        Some(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+    }
 
-            state.set_current_recording(actor);
+    #[cfg(windows)]
+    {
+        use windows::{core::HSTRING, Win32::Storage::FileSystem::GetDiskFreeSpaceExW};
 
-            Ok::<_, String>(actor_done_rx)
+        let mut available = 0u64;
+        unsafe {
+            GetDiskFreeSpaceExW(
+                &HSTRING::from(path),
+                Some(&mut available as *mut u64),
+                None,
+                None,
+            )
         }
-    })
-    .await
-    .map_err(|e| format!("Failed to spawn recording actor: {}", e))??;
+        .ok()?;
+        Some(available)
+    }
+}
 
-    spawn_actor({
-        let app = app.clone();
-        let state_mtx = Arc::clone(&state_mtx);
-        async move {
-            fail!("recording::wait_actor_done");
-            match actor_done_rx.await {
-                Ok(Ok(_)) => {
-                    let _ = finish_upload_tx.send(());
-                    return;
-                }
-                Ok(Err(e)) => {
-                    let mut state = state_mtx.write().await;
+/// Emitted when a studio recording with a segment duration finishes writing a segment.
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingSegmentCompleted {
+    pub project_path: PathBuf,
+    pub index: u32,
+    pub path: PathBuf,
+    pub duration: f64,
+}
 
-                    let mut dialog = MessageDialogBuilder::new(
-                        app.dialog().clone(),
-                        format!("An error occurred"),
-                        e,
-                    )
-                    .kind(tauri_plugin_dialog::MessageDialogKind::Error);
+/// Emitted as each click happens while studio recording with `stream_cursor_clicks` on.
+/// `x` and `y` are normalized to the captured area and `time_ms` is since recording started.
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorClick {
+    pub x: f64,
+    pub y: f64,
+    pub down: bool,
+    pub time_ms: f64,
+}
 
-                    if let Some(window) = CapWindowId::InProgressRecording.get(&app) {
-                        dialog = dialog.parent(&window);
-                    }
+/// Emitted when a studio recording's window closes or its screen is disconnected,
+/// so the user can stop or switch to another target.
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureTargetLost {
+    pub recording_id: String,
+}
 
-                    dialog.blocking_show();
+const MAX_PRE_ROLL_SECS: u32 = 5;
 
-                    // this clears the current recording for us
-                    handle_recording_end(app, None, &mut state).await.ok();
-                }
-                _ => {}
-            }
-        }
-    });
+/// A studio recording that's capturing before it's been started,
+/// so that it has a pre-roll to include once it is
+pub struct ArmedPreRoll {
+    id: String,
+    recording_dir: PathBuf,
+    inputs: StartRecordingInputs,
+    mic_count: usize,
+    with_camera: bool,
+    handle: StudioRecordingHandle,
+    actor_done_rx: tokio::sync::oneshot::Receiver<Result<(), String>>,
+}
 
-    if let Some(window) = CapWindowId::Main.get(&app) {
-        match GeneralSettingsStore::get(&app)
-            .ok()
-            .flatten()
-            .map(|s| s.main_window_recording_start_behaviour)
-            .unwrap_or_default()
-        {
-            MainWindowRecordingStartBehaviour::Close => {
-                let _ = window.close();
-            }
-            MainWindowRecordingStartBehaviour::Minimise => {
-                let _ = window.minimize();
-            }
-        }
+impl ArmedPreRoll {
+    fn matches(&self, inputs: &StartRecordingInputs, state: &App) -> bool {
+        matches!(inputs.mode, RecordingMode::Studio)
+            && self.inputs.capture_target == inputs.capture_target
+            && self.inputs.capture_system_audio == inputs.capture_system_audio
+            && self.inputs.system_audio_device == inputs.system_audio_device
+            && self.inputs.camera_only == inputs.camera_only
+            && self.inputs.include_camera == inputs.include_camera
+            && self.inputs.cursor_follow.is_some() == inputs.cursor_follow.is_some()
+            && self.inputs.target_fps == clamp_target_fps_to_display(inputs.clone()).target_fps
+            && self.mic_count == state.mic_feeds().len()
+            && self.with_camera == state.camera_feed.is_some()
     }
 
-    if let Some(window) = CapWindowId::InProgressRecording.get(&app) {
-        window.eval("window.location.reload()").ok();
-    } else {
-        let _ = ShowCapWindow::InProgressRecording { position: None }
-            .show(&app)
-            .await;
+    async fn disarm(self) {
+        self.handle.cancel().await.ok();
+        std::fs::remove_dir_all(&self.recording_dir).ok();
     }
+}
 
-    AppSounds::StartRecording.play();
+/// Disarms the pre-roll it holds if it's dropped before the pre-roll is taken,
+/// so a start that fails part way doesn't leave it capturing in the background
+struct PreRollGuard(Option<ArmedPreRoll>);
 
-    RecordingStarted.emit(&app).ok();
+impl PreRollGuard {
+    fn get(&self) -> Option<&ArmedPreRoll> {
+        self.0.as_ref()
+    }
 
-    Ok(())
+    fn take(&mut self) -> Option<ArmedPreRoll> {
+        self.0.take()
+    }
+}
+
+impl Drop for PreRollGuard {
+    fn drop(&mut self) {
+        if let Some(armed) = self.0.take() {
+            tokio::spawn(armed.disarm());
+        }
+    }
 }
 
+/// Starts capturing in the background so that starting a studio recording with the same
+/// inputs includes the `pre_roll_secs` before it. Does nothing if pre-roll is turned off.
 #[tauri::command]
 #[specta::specta]
-pub async fn pause_recording(state: MutableState<'_, App>) -> Result<(), String> {
-    let mut state = state.write().await;
+pub async fn arm_pre_roll(
+    app: AppHandle,
+    state_mtx: MutableState<'_, App>,
+    inputs: StartRecordingInputs,
+) -> Result<(), String> {
+    let mut state = state_mtx.write().await;
 
-    if let Some(recording) = state.current_recording.as_mut() {
-        recording.pause().await.map_err(|e| e.to_string())?;
+    if let Some(armed) = state.armed_pre_roll.take() {
+        armed.disarm().await;
+    }
+
+    let general_settings = GeneralSettingsStore::get(&app).ok().flatten();
+    let pre_roll_secs = general_settings
+        .as_ref()
+        .map(|s| s.pre_roll_secs.min(MAX_PRE_ROLL_SECS))
+        .unwrap_or_default();
+
+    if pre_roll_secs == 0 || !matches!(inputs.mode, RecordingMode::Studio) {
+        return Ok(());
+    }
+
+    if state.current_recording.is_some() {
+        return Err("Can't arm a pre-roll while recording".to_string());
+    }
+
+    if let Some(output_dir) = &inputs.output_dir {
+        validate_output_dir(output_dir)?;
     }
 
+    validate_system_audio(
+        &inputs,
+        &cap_recording::capabilities::capture_capabilities(),
+    )?;
+
+    // as the recording it's taken by would be, so it captures at the rate that's recorded
+    let inputs = clamp_target_fps_to_display(inputs);
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let recording_dir = recording_dir_for(&app, &inputs, &id)?;
+
+    let segment_duration = general_settings
+        .as_ref()
+        .and_then(|s| s.segment_duration_secs)
+        .filter(|secs| *secs > 0)
+        .map(|secs| Duration::from_secs(secs as u64));
+    let stream_cursor_clicks = general_settings
+        .as_ref()
+        .is_some_and(|s| s.stream_cursor_clicks);
+    let record_key_presses = general_settings
+        .as_ref()
+        .is_some_and(|s| s.record_key_presses && !s.minimal_overhead);
+
+    let system_audio_feed = match inputs.system_audio_device() {
+        Some(device) => Some(
+            AudioInputFeed::init(device)
+                .await
+                .map_err(|e| format!("Failed to open system audio device '{device}': {e}"))?,
+        ),
+        None => None,
+    };
+
+    let (handle, actor_done_rx) = cap_recording::spawn_studio_recording_actor(
+        id.clone(),
+        recording_dir.clone(),
+        cap_recording::RecordingBaseInputs {
+            capture_target: inputs.capture_target,
+            capture_system_audio: inputs.capture_system_audio,
+            mic_feeds: &state.mic_feeds(),
+            system_audio_feed: &system_audio_feed,
+            audio_sample_rate: Some(audio_sample_rate(
+                general_settings.as_ref(),
+                RecordingMode::Studio,
+            )?),
+            excluded_apps: general_settings
+                .as_ref()
+                .map(|s| s.excluded_apps.clone())
+                .unwrap_or_default(),
+            capture_scale: capture_scale(general_settings.as_ref())?,
+            skip_static_frames: general_settings
+                .as_ref()
+                .is_some_and(|s| s.skip_static_frames),
+            target_fps: inputs.target_fps,
+        },
+        recorded_camera(&inputs, state.camera_feed.clone()),
+        inputs.cursor_follow.is_some()
+            || stream_cursor_clicks
+            || general_settings
+                .as_ref()
+                .is_some_and(|s| s.custom_cursor_capture && !s.minimal_overhead),
+        segment_duration,
+        segment_duration.map(|_| segment_completed_events(&app, &recording_dir)),
+        Some(capture_target_lost_events(&app, &id)),
+        stream_cursor_clicks.then(|| cursor_click_events(&app)),
+        record_key_presses,
+        inputs.camera_only,
+        Some(Duration::from_secs(pre_roll_secs as u64)),
+        studio_video_codec(general_settings.as_ref())?,
+    )
+    .await
+    .map_err(|e| {
+        std::fs::remove_dir_all(&recording_dir).ok();
+        format!("Failed to start pre-roll: {e}")
+    })?;
+
+    info!("Armed {pre_roll_secs}s pre-roll");
+
+    state.armed_pre_roll = Some(ArmedPreRoll {
+        id,
+        recording_dir,
+        inputs,
+        mic_count: state.mic_feeds().len(),
+        with_camera: state.camera_feed.is_some(),
+        handle,
+        actor_done_rx,
+    });
+
     Ok(())
 }
 
+/// Stops the background capture started by `arm_pre_roll`
 #[tauri::command]
 #[specta::specta]
-pub async fn resume_recording(state: MutableState<'_, App>) -> Result<(), String> {
-    let mut state = state.write().await;
+pub async fn disarm_pre_roll(state: MutableState<'_, App>) -> Result<(), String> {
+    let armed = state.write().await.armed_pre_roll.take();
 
-    if let Some(recording) = state.current_recording.as_mut() {
-        recording.resume().await.map_err(|e| e.to_string())?;
+    if let Some(armed) = armed {
+        armed.disarm().await;
     }
 
     Ok(())
 }
 
-#[tauri::command]
-#[specta::specta]
-pub async fn stop_recording(app: AppHandle, state: MutableState<'_, App>) -> Result<(), String> {
-    let mut state = state.write().await;
-    let Some(current_recording) = state.clear_current_recording() else {
-        return Err("Recording not in progress".to_string())?;
+/// A countdown to a recording starting, see `StartRecordingInputs::countdown_secs`
+pub struct Countdown {
+    token: CancellationToken,
+    inputs: StartRecordingInputs,
+}
+
+/// Shows the countdown overlay and waits for it, returning whether it finished
+/// rather than being cancelled
+async fn count_down(
+    app: &AppHandle,
+    state_mtx: &MutableState<'_, App>,
+    inputs: &StartRecordingInputs,
+    secs: u32,
+) -> Result<bool, String> {
+    let token = CancellationToken::new();
+
+    {
+        let mut state = state_mtx.write().await;
+        if state.countdown.is_some() {
+            return Err("Already counting down to a recording".to_string());
+        }
+        state.countdown = Some(Countdown {
+            token: token.clone(),
+            inputs: inputs.clone(),
+        });
+    }
+
+    let screen_id = match inputs.capture_target {
+        ScreenCaptureTarget::Screen { id } => Some(id),
+        ScreenCaptureTarget::Area { screen, .. } => Some(screen),
+        ScreenCaptureTarget::Window { .. } | ScreenCaptureTarget::AllScreens => None,
+    };
+    let screen_id = match screen_id.filter(|_| !inputs.camera_only) {
+        Some(screen_id) => screen_id,
+        None => match ScreenCaptureTarget::primary_display() {
+            ScreenCaptureTarget::Screen { id } => id,
+            _ => unreachable!(),
+        },
     };
 
-    let completed_recording = current_recording.stop().await.map_err(|e| e.to_string())?;
+    if let Err(e) = (ShowCapWindow::Countdown { screen_id, secs })
+        .show(app)
+        .await
+    {
+        warn!("Failed to show countdown: {e}");
+    }
 
-    handle_recording_end(app, Some(completed_recording), &mut state).await?;
+    let finished = wait_for_countdown(Duration::from_secs(secs as u64), &token).await;
 
-    Ok(())
+    state_mtx.write().await.countdown = None;
+    if let Some(window) = (CapWindowId::Countdown { screen_id }).get(app) {
+        let _ = window.close();
+    }
+
+    Ok(finished)
 }
 
-#[tauri::command]
-#[specta::specta]
-pub async fn restart_recording(app: AppHandle, state: MutableState<'_, App>) -> Result<(), String> {
-    let Some(recording) = state.write().await.clear_current_recording() else {
-        return Err("No recording in progress".to_string());
-    };
+async fn wait_for_countdown(duration: Duration, token: &CancellationToken) -> bool {
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => true,
+        _ = token.cancelled() => false,
+    }
+}
 
-    let _ = CurrentRecordingChanged.emit(&app);
+/// Creates `recording_dir` once `countdown` finishes, so cancelling it leaves nothing
+/// behind. When `retrying`, whatever the failed attempt managed to write is cleared first.
+async fn prepare_recording_dir(
+    recording_dir: &Path,
+    countdown: impl Future<Output = Result<bool, String>>,
+    retrying: bool,
+) -> Result<bool, String> {
+    if !countdown.await? {
+        return Ok(false);
+    }
 
-    let inputs = recording.inputs().clone();
+    if retrying && recording_dir.exists() {
+        std::fs::remove_dir_all(recording_dir)
+            .map_err(|e| format!("Failed to clear recording directory: {e}"))?;
+    }
 
-    let _ = recording.cancel().await;
+    std::fs::create_dir_all(recording_dir)
+        .map_err(|e| format!("Failed to create recording directory: {e}"))?;
+
+    Ok(true)
+}
 
-    tokio::time::sleep(Duration::from_millis(1000)).await;
+async fn delete_pre_created_video(app: &AppHandle, video_id: &str) {
+    let _ = app
+        .authed_api_request(
+            format!("/api/desktop/video/delete?videoId={}", video_id),
+            |c, url| c.delete(url),
+        )
+        .await;
+}
 
-    start_recording(app.clone(), state, inputs).await
+/// Emitted when a pending recording is cancelled and the app is idle again
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PendingRecordingCancelled {
+    /// what the cancelled recording was set up with, to re-arm with
+    pub inputs: Option<StartRecordingInputs>,
 }
 
+/// Backs out of a recording that's set up but hasn't started, disarming its pre-roll
+/// and closing its windows. The inputs it was set up with are kept and returned so
+/// it can be re-armed. Recordings that have started need stopping or deleting instead.
 #[tauri::command]
 #[specta::specta]
-pub async fn delete_recording(app: AppHandle, state: MutableState<'_, App>) -> Result<(), String> {
-    let recording_data = {
-        let mut app_state = state.write().await;
-        if let Some(recording) = app_state.clear_current_recording() {
-            let recording_dir = recording.recording_dir().clone();
-            let video_id = match &recording {
-                InProgressRecording::Instant {
-                    video_upload_info, ..
-                } => Some(video_upload_info.id.clone()),
-                _ => None,
-            };
-            Some((recording, recording_dir, video_id))
-        } else {
-            None
-        }
-    };
+pub async fn cancel_pending_recording(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+) -> Result<Option<StartRecordingInputs>, String> {
+    let (armed, inputs) = {
+        let mut state = state.write().await;
 
-    if let Some((recording, recording_dir, video_id)) = recording_data {
-        CurrentRecordingChanged.emit(&app).ok();
-        RecordingStopped {}.emit(&app).ok();
+        if state.current_recording.is_some() {
+            return Err("A recording is in progress, stop or delete it instead".to_string());
+        }
 
-        let _ = recording.cancel().await;
+        state.close_occluder_windows();
 
-        std::fs::remove_dir_all(&recording_dir).ok();
+        let mut inputs = None;
 
-        if let Some(id) = video_id {
-            let _ = app
-                .authed_api_request(
-                    format!("/api/desktop/video/delete?videoId={}", id),
-                    |c, url| c.delete(url),
-                )
-                .await;
+        if let Some(countdown) = state.countdown.take() {
+            countdown.token.cancel();
+            inputs = Some(countdown.inputs);
         }
-    }
 
-    Ok(())
-}
+        let armed = state.armed_pre_roll.take();
+        if let Some(armed) = &armed {
+            inputs = Some(armed.inputs.clone());
+        }
 
-// runs when a recording ends, whether from success or failure
-async fn handle_recording_end(
-    handle: AppHandle,
-    recording: Option<CompletedRecording>,
-    app: &mut App,
-) -> Result<(), String> {
-    // Clear current recording, just in case :)
-    app.current_recording.take();
+        // an earlier cancellation's inputs aren't returned when there's nothing to cancel
+        if inputs.is_some() {
+            state.cancelled_pending_inputs = inputs.clone();
+        }
 
-    if let Some(recording) = recording {
-        handle_recording_finish(&handle, recording).await?;
+        (armed, inputs)
     };
 
-    let _ = RecordingStopped.emit(&handle);
+    if let Some(armed) = armed {
+        armed.disarm().await;
+    }
 
-    let _ = app.recording_logging_handle.reload(None);
+    if inputs.is_some() {
+        PendingRecordingCancelled {
+            inputs: inputs.clone(),
+        }
+        .emit(&app)
+        .ok();
+    }
 
-    if let Some(window) = CapWindowId::InProgressRecording.get(&handle) {
-        let _ = window.close();
+    Ok(inputs)
+}
+
+/// Takes the armed pre-roll if it was armed with the same inputs and commits it,
+/// otherwise it's disarmed so it isn't left capturing alongside the recording
+async fn take_armed_pre_roll(
+    state_mtx: &ArcLock<App>,
+    inputs: &StartRecordingInputs,
+) -> Option<ArmedPreRoll> {
+    let (armed, matches) = {
+        let mut state = state_mtx.write().await;
+        let armed = state.armed_pre_roll.take()?;
+        let matches = armed.matches(inputs, &state);
+        (armed, matches)
+    };
+
+    if !matches {
+        armed.disarm().await;
+        return None;
     }
 
-    if let Some(window) = CapWindowId::Main.get(&handle) {
-        window.unminimize().ok();
-    } else {
-        CapWindowId::Camera.get(&handle).map(|v| {
-            let _ = v.close();
-        });
-        app.camera_feed.take();
-        app.mic_feed.take();
+    match armed.handle.commit_pre_roll().await {
+        Ok(()) => Some(armed),
+        Err(e) => {
+            warn!("Failed to commit pre-roll, starting without it: {e}");
+            armed.disarm().await;
+            None
+        }
     }
+}
 
-    CurrentRecordingChanged.emit(&handle).ok();
+/// The camera feed to record, none if the camera's left out of this recording
+fn recorded_camera<T>(inputs: &StartRecordingInputs, camera_feed: Option<T>) -> Option<T> {
+    camera_feed.filter(|_| inputs.include_camera)
+}
 
-    Ok(())
+/// Hides the camera window from screen capture or not, per the setting and what's being recorded
+pub fn apply_camera_content_protection(app: &AppHandle, recording_mode: Option<RecordingMode>) {
+    let Some(window) = CapWindowId::Camera.get(app) else {
+        return;
+    };
+
+    let general_settings = GeneralSettingsStore::get(app).ok().flatten();
+    let protected = camera_window_protected(general_settings.as_ref(), recording_mode);
+
+    let _ = window.set_content_protected(protected);
 }
 
-// runs when a recording successfully finishes
-async fn handle_recording_finish(
-    app: &AppHandle,
-    completed_recording: CompletedRecording,
-) -> Result<(), String> {
-    let recording_dir = completed_recording.project_path().clone();
+fn camera_window_protected(
+    general_settings: Option<&GeneralSettingsStore>,
+    recording_mode: Option<RecordingMode>,
+) -> bool {
+    general_settings
+        .map(|s| s.camera_content_protection)
+        .unwrap_or_default()
+        .is_protected(recording_mode)
+}
 
-    let screenshots_dir = recording_dir.join("screenshots");
-    std::fs::create_dir_all(&screenshots_dir).ok();
+/// The configured audio sample rate, checked against the rates `mode` recordings' audio
+/// can be encoded at
+fn audio_sample_rate(
+    general_settings: Option<&GeneralSettingsStore>,
+    mode: RecordingMode,
+) -> Result<u32, String> {
+    let rate = general_settings.map_or_else(
+        || GeneralSettingsStore::default().audio_sample_rate,
+        |s| s.audio_sample_rate,
+    );
 
-    let display_output_path = match &completed_recording {
-        CompletedRecording::Studio { recording, .. } => match &recording.meta {
-            StudioRecordingMeta::SingleSegment { segment } => {
-                segment.display.path.to_path(&recording_dir)
-            }
-            StudioRecordingMeta::MultipleSegments { inner, .. } => {
-                inner.segments[0].display.path.to_path(&recording_dir)
-            }
-        },
-        CompletedRecording::Instant { recording, .. } => {
-            recording.project_path.join("./content/output.mp4")
+    if !SUPPORTED_SAMPLE_RATES.contains(&rate) {
+        return Err(format!(
+            "Unsupported audio sample rate {rate}Hz, must be one of {SUPPORTED_SAMPLE_RATES:?}"
+        ));
+    }
+
+    // recordings with Opus audio, which would quietly be encoded at another rate
+    let opus_recordings = match mode {
+        RecordingMode::Studio => Some("Studio"),
+        RecordingMode::Instant
+            if general_settings
+                .is_some_and(|s| s.instant_output_format == InstantOutputFormat::WebM) =>
+        {
+            Some("WebM instant")
         }
+        RecordingMode::Instant => None,
     };
+    if let Some(recordings) = opus_recordings.filter(|_| !OpusEncoder::SAMPLE_RATES.contains(&rate))
+    {
+        return Err(format!(
+            "{recordings} recordings' audio is Opus, which can't be {rate}Hz. Use 48000Hz instead"
+        ));
+    }
 
-    let display_screenshot = screenshots_dir.join("display.jpg");
-    let screenshot_task = tokio::spawn(create_screenshot(
-        display_output_path,
-        display_screenshot.clone(),
-        None,
-    ));
+    Ok(rate)
+}
 
-    let target_name = completed_recording.target_name().clone();
+/// Stops the recording in `recording_dir` once its clock reaches `limit`.
+/// Gives up as soon as that recording isn't the current one, eg. because it was stopped early.
+fn spawn_max_duration_timer(app: AppHandle, recording_dir: PathBuf, limit: Duration) {
+    // how often pauses and the recording stopping early are noticed
+    const POLL_INTERVAL: Duration = Duration::from_millis(500);
 
-    let (meta_inner, sharing) = match completed_recording {
-        CompletedRecording::Studio { recording, .. } => {
-            let recordings = ProjectRecordingsMeta::new(&recording_dir, &recording.meta)?;
+    spawn_actor(async move {
+        loop {
+            let deadline = {
+                let state = app.state::<ArcLock<App>>();
+                let state = state.read().await;
 
-            let config = project_config_from_recording(
-                &recording,
-                &recordings,
-                PresetsStore::get_default_preset(&app)?.map(|p| p.config),
-            );
+                let Some(recording) = state
+                    .current_recording
+                    .as_ref()
+                    .filter(|r| r.recording_dir() == &recording_dir)
+                else {
+                    return;
+                };
 
-            config.write(&recording_dir).map_err(|e| e.to_string())?;
+                recording.clock().deadline(limit)
+            };
 
-            (RecordingMetaInner::Studio(recording.meta), None)
+            let now = Instant::now();
+            match deadline {
+                Some(deadline) if deadline <= now => break,
+                Some(deadline) => tokio::time::sleep((deadline - now).min(POLL_INTERVAL)).await,
+                None => tokio::time::sleep(POLL_INTERVAL).await,
+            }
         }
-        CompletedRecording::Instant {
-            recording,
-            progressive_upload,
-            video_upload_info,
-            ..
-        } => {
-            // shareable_link = Some(video_upload_info.link.clone());
-            let app = app.clone();
-            let output_path = recording_dir.join("content/output.mp4");
 
-            let _ = open_external_link(app.clone(), video_upload_info.link.clone());
+        info!(
+            "Recording reached its maximum duration of {}s, stopping",
+            limit.as_secs()
+        );
 
-            spawn_actor({
-                let video_upload_info = video_upload_info.clone();
+        if let Err(e) = stop_recording(app.clone(), app.state(), None).await {
+            error!("Failed to stop recording at its maximum duration: {e}");
+        }
+    });
+}
 
-                async move {
-                    if let Some(progressive_upload) = progressive_upload {
-                        let video_upload_succeeded = match progressive_upload
-                            .handle
-                            .await
-                            .map_err(|e| e.to_string())
-                            .and_then(|r| r)
-                        {
-                            Ok(()) => {
-                                info!("Not attempting instant recording upload as progressive upload succeeded");
-                                true
-                            }
-                            Err(e) => {
-                                error!("Progressive upload failed: {}", e);
-                                false
-                            }
-                        };
+/// Where the recording with `id` is saved, in the inputs' output directory if they have one,
+/// otherwise the default one
+fn recording_dir_for(
+    app: &AppHandle,
+    inputs: &StartRecordingInputs,
+    id: &str,
+) -> Result<PathBuf, String> {
+    Ok(recording_dir_in(&output_dir_for(app, inputs)?, id))
+}
 
-                        let _ = screenshot_task.await;
+/// The directory recordings made with `inputs` are saved in
+fn output_dir_for(app: &AppHandle, inputs: &StartRecordingInputs) -> Result<PathBuf, String> {
+    let output_dir = inputs.output_dir.clone().or_else(|| {
+        GeneralSettingsStore::get(app)
+            .ok()
+            .flatten()
+            .and_then(|s| s.default_output_dir)
+    });
 
-                        if video_upload_succeeded {
-                            let resp = prepare_screenshot_upload(
-                                &app,
-                                &video_upload_info.config.clone(),
-                                display_screenshot,
-                            )
-                            .await;
+    match output_dir {
+        Some(output_dir) => Ok(output_dir),
+        None => recordings_path(app),
+    }
+}
 
-                            match resp {
-                                Ok(r)
-                                    if r.status().as_u16() >= 200 && r.status().as_u16() < 300 =>
-                                {
-                                    info!("Screenshot uploaded successfully");
-                                }
-                                Ok(r) => {
-                                    error!("Failed to upload screenshot: {}", r.status());
-                                }
-                                Err(e) => {
-                                    error!("Failed to upload screenshot: {e}");
-                                }
-                            }
-                        } else {
-                            // The upload_video function handles screenshot upload, so we can pass it along
-                            match upload_video(
-                                &app,
-                                video_upload_info.id.clone(),
-                                output_path,
-                                Some(video_upload_info.config.clone()),
-                                Some(display_screenshot.clone()),
-                            )
-                            .await
-                            {
-                                Ok(_) => {
-                                    info!(
-                                        "Final video upload with screenshot completed successfully"
-                                    )
-                                }
-                                Err(e) => {
-                                    error!("Error in final upload with screenshot: {}", e)
-                                }
-                            }
-                        }
-                    }
-                }
-            });
+fn recording_dir_in(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{id}.cap"))
+}
 
-            (
-                RecordingMetaInner::Instant(recording.meta),
-                Some(SharingMeta {
-                    link: video_upload_info.link,
-                    id: video_upload_info.id,
-                }),
-            )
-        }
-    };
+/// Shows the `.cap` directory of the recording with `recording_id` in Finder, Explorer
+/// or the file manager, for getting at its raw files
+///
+/// `output_dir` is where the recording was saved if it was started with one, otherwise
+/// it's looked for in the default output directory and the recordings directory.
+#[tauri::command]
+#[specta::specta]
+pub async fn reveal_recording_in_finder(
+    app: AppHandle,
+    recording_id: String,
+    output_dir: Option<PathBuf>,
+) -> Result<(), String> {
+    let recording_dir = locate_recording_dir(&app, &recording_id, output_dir)?;
 
-    let meta = RecordingMeta {
-        platform: Some(Platform::default()),
-        project_path: recording_dir.clone(),
-        sharing,
-        pretty_name: format!(
-            "{target_name} {}",
-            chrono::Local::now().format("%Y-%m-%d %H:%M:%S")
+    app.opener()
+        .reveal_item_in_dir(&recording_dir)
+        .map_err(|e| format!("Failed to reveal recording: {e}"))
+}
+
+/// Opens the editor for the studio recording with `recording_id`.
+/// Instant recordings are a single finished file, so they can't be edited.
+///
+/// `output_dir` is where the recording was saved if it was started with one, otherwise
+/// it's looked for in the default output directory and the recordings directory.
+#[tauri::command]
+#[specta::specta]
+pub async fn open_editor(
+    app: AppHandle,
+    recording_id: String,
+    output_dir: Option<PathBuf>,
+) -> Result<(), String> {
+    let project_path = studio_recording_dir(
+        locate_recording_dir(&app, &recording_id, output_dir)?,
+        &recording_id,
+    )?;
+
+    ShowCapWindow::Editor { project_path }
+        .show(&app)
+        .await
+        .map(|_| ())
+        .map_err(|e| format!("Failed to open editor: {e}"))
+}
+
+/// `recording_dir`, if the recording with `recording_id` in it is a studio recording
+fn studio_recording_dir(recording_dir: PathBuf, recording_id: &str) -> Result<PathBuf, String> {
+    let meta = RecordingMeta::load_for_project(&recording_dir)
+        .map_err(|e| format!("Failed to load recording {recording_id}: {e}"))?;
+
+    match meta.inner {
+        RecordingMetaInner::Studio(_) => Ok(recording_dir),
+        RecordingMetaInner::Instant(_) => Err(format!(
+            "Recording {recording_id} is an instant recording, only studio recordings can be edited"
+        )),
+    }
+}
+
+pub fn existing_recording_dir(
+    recordings_dir: &Path,
+    recording_id: &str,
+) -> Result<PathBuf, String> {
+    if recording_id.is_empty() || recording_id.contains(['/', '\\']) || recording_id == ".." {
+        return Err(format!("'{recording_id}' isn't a valid recording id"));
+    }
+
+    let recording_dir = recording_dir_in(recordings_dir, recording_id);
+    if !recording_dir.is_dir() {
+        return Err(format!("Recording {recording_id} wasn't found"));
+    }
+
+    Ok(recording_dir)
+}
+
+/// Rejects recording system audio where it can't be captured, rather than
+/// leaving the recording silent
+fn validate_system_audio(
+    inputs: &StartRecordingInputs,
+    capabilities: &CaptureCapabilities,
+) -> Result<(), String> {
+    if !inputs.capture_system_audio {
+        return Ok(());
+    }
+
+    match &inputs.system_audio_device {
+        Some(_) if !capabilities.system_audio_device => Err(
+            "Recording an input device as system audio isn't supported on this platform"
+                .to_string(),
         ),
-        inner: meta_inner,
-    };
+        None if !capabilities.system_audio => {
+            Err("System audio capture isn't supported on this platform".to_string())
+        }
+        _ => Ok(()),
+    }
+}
 
-    meta.save_for_project()
-        .map_err(|e| format!("Failed to save recording meta: {e}"))?;
+/// Rejects `inputs` that can't be recorded with what's set up in `state`, before anything's
+/// changed for them. `camera_device` is checked for separately when it's switched to.
+fn validate_start_inputs(inputs: &StartRecordingInputs, state: &App) -> Result<(), String> {
+    if inputs.cursor_follow.is_some() {
+        if !matches!(inputs.mode, RecordingMode::Studio) {
+            return Err("Cursor-follow regions are only available in studio mode".to_string());
+        }
 
-    if let RecordingMetaInner::Studio(_) = meta.inner {
-        match GeneralSettingsStore::get(&app)
-            .ok()
-            .flatten()
-            .map(|v| v.post_studio_recording_behaviour)
-            .unwrap_or(PostStudioRecordingBehaviour::OpenEditor)
-        {
-            PostStudioRecordingBehaviour::OpenEditor => {
-                let _ = ShowCapWindow::Editor {
-                    project_path: recording_dir,
-                }
-                .show(&app)
-                .await;
-            }
-            PostStudioRecordingBehaviour::ShowOverlay => {
-                let _ = ShowCapWindow::RecordingsOverlay.show(&app).await;
+        if !matches!(inputs.capture_target, ScreenCaptureTarget::Screen { .. }) {
+            return Err("Cursor-follow regions require a screen capture target".to_string());
+        }
+    }
 
-                let app = AppHandle::clone(app);
-                tokio::spawn(async move {
-                    tokio::time::sleep(Duration::from_millis(1000)).await;
+    if let ScreenCaptureTarget::Window { id, crop } = &inputs.capture_target {
+        if crop.as_ref().is_some_and(|crop| !crop.is_valid()) {
+            return Err("Window crop must lie within the window".to_string());
+        }
 
-                    let _ = NewStudioRecordingAdded {
-                        path: recording_dir.clone(),
-                    }
-                    .emit(&app);
-                });
-            }
-        };
+        // the window's display may have been unplugged since it was picked
+        if !inputs.camera_only {
+            window_display(target_window_display(*id, &inputs.capture_target))?;
+        }
     }
 
-    // Play sound to indicate recording has stopped
-    AppSounds::StopRecording.play();
+    if inputs.camera_only {
+        if !matches!(inputs.mode, RecordingMode::Studio) {
+            return Err("Camera-only recordings are only available in studio mode".to_string());
+        }
+
+        if inputs.cursor_follow.is_some() {
+            return Err("Cursor-follow regions require a screen capture target".to_string());
+        }
+
+        if !inputs.include_camera {
+            return Err("Camera-only recordings need the camera included".to_string());
+        }
+
+        if inputs.camera_device.is_none() && state.camera_feed.is_none() {
+            return Err("Select a camera to record camera-only".to_string());
+        }
+    }
+
+    if inputs.defer_upload && !matches!(inputs.mode, RecordingMode::Instant) {
+        return Err("Only instant recordings can be uploaded later".to_string());
+    }
+
+    if let Some(output_dir) = &inputs.output_dir {
+        validate_output_dir(output_dir)?;
+    }
+
+    validate_system_audio(inputs, &cap_recording::capabilities::capture_capabilities())?;
+
+    if matches!(inputs.mode, RecordingMode::Instant) && !state.additional_mic_feeds.is_empty() {
+        return Err("Instant recordings can only record one microphone".to_string());
+    }
+
+    if let Some(ValidationIssue {
+        severity: ValidationSeverity::Error,
+        message,
+    }) = output_dir_for(&state.handle, inputs)
+        .ok()
+        .and_then(|dir| available_space(&dir))
+        .and_then(free_space_issue)
+    {
+        return Err(message);
+    }
 
     Ok(())
 }
 
-fn generate_zoom_segments_from_clicks(
-    recording: &CompletedStudioRecording,
-    recordings: &ProjectRecordingsMeta,
-) -> Vec<ZoomSegment> {
-    let mut segments = vec![];
+/// Refresh rate of the display `target` is on, the fastest screen's for `AllScreens`
+fn target_refresh_rate(target: &ScreenCaptureTarget) -> Option<u32> {
+    let screens = || cap_media::sources::list_screens().into_iter();
 
-    let max_duration = recordings.duration();
-
-    const ZOOM_SEGMENT_AFTER_CLICK_PADDING: f64 = 1.5;
-
-    // single-segment only
-    // for click in &recording.cursor_data.clicks {
-    //     let time = click.process_time_ms / 1000.0;
-
-    //     if segments.last().is_none() {
-    //         segments.push(ZoomSegment {
-    //             start: (click.process_time_ms / 1000.0 - (ZOOM_DURATION + 0.2)).max(0.0),
-    //             end: click.process_time_ms / 1000.0 + ZOOM_SEGMENT_AFTER_CLICK_PADDING,
-    //             amount: 2.0,
-    //         });
-    //     } else {
-    //         let last_segment = segments.last_mut().unwrap();
-
-    //         if click.down {
-    //             if last_segment.end > time {
-    //                 last_segment.end =
-    //                     (time + ZOOM_SEGMENT_AFTER_CLICK_PADDING).min(recordings.duration());
-    //             } else if time < max_duration - ZOOM_DURATION {
-    //                 segments.push(ZoomSegment {
-    //                     start: (time - ZOOM_DURATION).max(0.0),
-    //                     end: time + ZOOM_SEGMENT_AFTER_CLICK_PADDING,
-    //                     amount: 2.0,
-    //                 });
-    //             }
-    //         } else {
-    //             last_segment.end =
-    //                 (time + ZOOM_SEGMENT_AFTER_CLICK_PADDING).min(recordings.duration());
-    //         }
-    //     }
-    // }
+    match *target {
+        ScreenCaptureTarget::Window { id, .. } => cap_media::sources::list_windows()
+            .into_iter()
+            .find(|(window, _)| window.id == id)
+            .map(|(window, _)| window.refresh_rate),
+        ScreenCaptureTarget::Screen { id } | ScreenCaptureTarget::Area { screen: id, .. } => {
+            screens()
+                .find(|(screen, _)| screen.id == id)
+                .map(|(screen, _)| screen.refresh_rate)
+        }
+        ScreenCaptureTarget::AllScreens => screens().map(|(screen, _)| screen.refresh_rate).max(),
+    }
+}
 
-    segments
+/// Clamps `inputs.target_fps` to what can be recorded at, so the manifest has the rate used
+fn clamp_target_fps(
+    inputs: StartRecordingInputs,
+    refresh_rate: Option<u32>,
+) -> StartRecordingInputs {
+    StartRecordingInputs {
+        target_fps: inputs
+            .target_fps
+            .map(|fps| cap_recording::clamp_fps(fps, refresh_rate)),
+        ..inputs
+    }
 }
 
-fn project_config_from_recording(
-    completed_recording: &CompletedStudioRecording,
-    recordings: &ProjectRecordingsMeta,
-    default_config: Option<ProjectConfiguration>,
-) -> ProjectConfiguration {
-    ProjectConfiguration {
-        timeline: Some(TimelineConfiguration {
-            segments: recordings
-                .segments
+/// [`clamp_target_fps`] to the refresh rate of the display that's being recorded
+fn clamp_target_fps_to_display(inputs: StartRecordingInputs) -> StartRecordingInputs {
+    let refresh_rate = inputs
+        .target_fps
+        .and_then(|_| target_refresh_rate(&inputs.capture_target));
+
+    clamp_target_fps(inputs, refresh_rate)
+}
+
+/// What a recording was started with and where, saved as `recording-manifest.json`
+/// in its directory so a user's setup can be reproduced from it
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct RecordingManifest {
+    inputs: StartRecordingInputs,
+    platform: Platform,
+    os_version: String,
+    app_version: String,
+    /// selected mic first
+    mic_devices: Vec<String>,
+    camera_device: Option<String>,
+}
+
+/// Failing to write the manifest is only logged, it isn't worth failing the recording over
+async fn write_recording_manifest(
+    app: &AppHandle,
+    state_mtx: &MutableState<'_, App>,
+    inputs: &StartRecordingInputs,
+    recording_dir: &Path,
+) {
+    let (mic_devices, camera_feed) = {
+        let state = state_mtx.read().await;
+        (
+            state
+                .mic_feeds()
                 .iter()
-                .enumerate()
-                .map(|(i, segment)| TimelineSegment {
-                    recording_segment: i as u32,
-                    start: 0.0,
-                    end: segment.duration(),
-                    timescale: 1.0,
-                })
+                .map(|feed| feed.device_name().to_string())
                 .collect(),
-            zoom_segments: generate_zoom_segments_from_clicks(&completed_recording, &recordings),
-        }),
-        ..default_config.unwrap_or_default()
+            recorded_camera(inputs, state.camera_feed.clone()),
+        )
+    };
+    let camera_device = match camera_feed {
+        Some(camera_feed) => Some(
+            camera_feed
+                .lock()
+                .await
+                .camera_info()
+                .human_name()
+                .to_string(),
+        ),
+        None => None,
+    };
+
+    let manifest = RecordingManifest {
+        inputs: inputs.clone(),
+        platform: Platform::default(),
+        os_version: tauri_plugin_os::version().to_string(),
+        app_version: app.package_info().version.to_string(),
+        mic_devices,
+        camera_device,
+    };
+
+    let result = serde_json::to_string_pretty(&manifest)
+        .map_err(|e| e.to_string())
+        .and_then(|json| {
+            std::fs::write(recording_dir.join("recording-manifest.json"), json)
+                .map_err(|e| e.to_string())
+        });
+
+    if let Err(e) = result {
+        warn!("Failed to write recording manifest: {e}");
+    }
+}
+
+/// Checks a recording can be saved to `dir`, so an unusable one is rejected before anything starts
+pub fn validate_output_dir(dir: &Path) -> Result<(), String> {
+    if !dir.is_dir() {
+        return Err(format!("Output directory {} doesn't exist", dir.display()));
+    }
+
+    let probe = dir.join(format!(".cap-write-test-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"")
+        .map_err(|e| format!("Output directory {} isn't writable: {e}", dir.display()))?;
+    std::fs::remove_file(&probe).ok();
+
+    Ok(())
+}
+
+fn capture_scale(general_settings: Option<&GeneralSettingsStore>) -> Result<f32, String> {
+    let scale = general_settings.map_or(1.0, |s| s.capture_scale);
+    validate_capture_scale(scale)?;
+    Ok(scale)
+}
+
+fn studio_video_codec(
+    general_settings: Option<&GeneralSettingsStore>,
+) -> Result<VideoCodec, String> {
+    match general_settings
+        .map(|s| s.studio_video_codec)
+        .unwrap_or_default()
+    {
+        VideoCodec::Vp9 => Err("Studio recordings can only be encoded as H264 or H265".to_string()),
+        codec => Ok(codec),
+    }
+}
+
+pub fn validate_capture_scale(scale: f32) -> Result<(), String> {
+    if !(MIN_CAPTURE_SCALE..=1.0).contains(&scale) {
+        return Err(format!(
+            "Capture scale must be between {MIN_CAPTURE_SCALE} and 1, got {scale}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Fitting to `output_aspect` re-encodes to H264 in an MP4, which can't replace a WebM
+fn validate_output_aspect(
+    general_settings: Option<&GeneralSettingsStore>,
+    mode: RecordingMode,
+) -> Result<(), String> {
+    let Some(settings) = general_settings else {
+        return Ok(());
+    };
+
+    if matches!(mode, RecordingMode::Instant)
+        && settings.output_aspect.is_some()
+        && matches!(settings.instant_output_format, InstantOutputFormat::WebM)
+    {
+        return Err(
+            "Instant recordings recorded as WebM can't be fitted to an output aspect ratio"
+                .to_string(),
+        );
+    }
+
+    Ok(())
+}
+
+/// What a start that failed after creating its recording directory left behind.
+/// The next start with the same inputs picks it up so retrying doesn't leave a
+/// directory and pre-created video behind for every failed attempt.
+pub struct FailedStart {
+    id: String,
+    recording_dir: PathBuf,
+    video_upload_info: Option<VideoUploadInfo>,
+    inputs: StartRecordingInputs,
+}
+
+impl FailedStart {
+    /// Picks up where an instant recording that's being restarted left off, so the
+    /// new recording keeps its id and share link
+    fn restart(
+        recording_dir: PathBuf,
+        video_upload_info: VideoUploadInfo,
+        inputs: StartRecordingInputs,
+    ) -> Self {
+        Self {
+            id: crate::processing::recording_id(&recording_dir),
+            recording_dir,
+            video_upload_info: Some(video_upload_info),
+            inputs,
+        }
+    }
+
+    /// Whether starting with `inputs` retries this start, `recording_dir` being where
+    /// the retry would be saved under this start's id
+    fn is_retried_by(&self, inputs: &StartRecordingInputs, recording_dir: &Path) -> bool {
+        self.inputs.mode == inputs.mode
+            && self.inputs.capture_target == inputs.capture_target
+            && self.inputs.camera_only == inputs.camera_only
+            && self.inputs.output_dir == inputs.output_dir
+            && self.recording_dir == recording_dir
+    }
+
+    /// Removes the directory and pre-created video the failed start left behind
+    async fn discard(self, app: &AppHandle) {
+        forget_created_video(&self.id);
+
+        if let Err(e) = std::fs::remove_dir_all(&self.recording_dir) {
+            warn!(
+                "Failed to remove directory of failed recording {}: {e}",
+                self.id
+            );
+        }
+
+        if let Some(video_upload_info) = self.video_upload_info {
+            delete_pre_created_video(app, &video_upload_info.id).await;
+        }
+    }
+}
+
+fn segment_completed_events(
+    app: &AppHandle,
+    recording_dir: &Path,
+) -> flume::Sender<CompletedSegment> {
+    let (tx, rx) = flume::unbounded::<CompletedSegment>();
+    let app = app.clone();
+    let recording_dir = recording_dir.to_path_buf();
+
+    tokio::spawn(async move {
+        while let Ok(segment) = rx.recv_async().await {
+            RecordingSegmentCompleted {
+                project_path: recording_dir.clone(),
+                index: segment.index as u32,
+                path: segment.display_path,
+                duration: segment.duration,
+            }
+            .emit(&app)
+            .ok();
+        }
+    });
+
+    tx
+}
+
+fn cursor_click_events(app: &AppHandle) -> flume::Sender<LiveCursorClick> {
+    let (tx, rx) = flume::unbounded::<LiveCursorClick>();
+    let app = app.clone();
+
+    tokio::spawn(async move {
+        while let Ok(click) = rx.recv_async().await {
+            CursorClick {
+                x: click.x,
+                y: click.y,
+                down: click.down,
+                time_ms: click.time_ms,
+            }
+            .emit(&app)
+            .ok();
+        }
+    });
+
+    tx
+}
+
+fn capture_target_lost_events(app: &AppHandle, recording_id: &str) -> flume::Sender<()> {
+    let (tx, rx) = flume::unbounded::<()>();
+    let app = app.clone();
+    let recording_id = recording_id.to_string();
+
+    tokio::spawn(async move {
+        while rx.recv_async().await.is_ok() {
+            CaptureTargetLost {
+                recording_id: recording_id.clone(),
+            }
+            .emit(&app)
+            .ok();
+        }
+    });
+
+    tx
+}
+
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChapterMarkerAdded {
+    pub marker: ChapterMarker,
+}
+
+/// Emitted once a finished recording's meta is saved, if its first frame's time is known.
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStartMarkerCaptured {
+    pub project_path: PathBuf,
+    pub marker: RecordingStartMarker,
+}
+
+/// Identifies a recording that's started, for matching it up with later events
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StartedRecording {
+    pub id: String,
+    /// the `.cap` directory it's being saved to, named after `id`
+    pub recording_dir: PathBuf,
+    /// another recording was in progress, so this one starts once it's ended
+    pub queued: bool,
+}
+
+/// Most recordings that can be waiting for the one in progress to end
+pub const MAX_QUEUED_RECORDINGS: usize = 3;
+
+/// A recording started while another was in progress, with the id it'll get
+pub struct QueuedRecording {
+    id: String,
+    inputs: StartRecordingInputs,
+}
+
+/// Recordings waiting to start, in the order they were started
+#[derive(Default)]
+pub struct RecordingQueue(VecDeque<QueuedRecording>);
+
+impl RecordingQueue {
+    fn push(&mut self, recording: QueuedRecording) -> Result<(), String> {
+        if self.0.len() >= MAX_QUEUED_RECORDINGS {
+            return Err(format!(
+                "A recording is in progress and {MAX_QUEUED_RECORDINGS} more are already queued"
+            ));
+        }
+
+        self.0.push_back(recording);
+        Ok(())
+    }
+
+    /// The queued recording to start now that the current one has `ended`, if any
+    fn next_after(&mut self, ended: RecordingEnd) -> Option<QueuedRecording> {
+        match ended {
+            RecordingEnd::Ended => self.0.pop_front(),
+            RecordingEnd::Reset => {
+                if !self.0.is_empty() {
+                    warn!("Discarding {} queued recordings", self.0.len());
+                }
+                self.0.clear();
+                None
+            }
+        }
+    }
+}
+
+/// How the recording in progress ended, which decides what happens to those queued behind it
+#[derive(Clone, Copy, Debug)]
+enum RecordingEnd {
+    /// Stopped, cancelled, deleted or failed, so the next queued recording starts
+    Ended,
+    /// The app was reset to idle, which discards the queued recordings too
+    Reset,
+}
+
+/// Emitted when a recording that was queued behind another fails to start
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedRecordingFailed {
+    pub id: String,
+    pub error: String,
+}
+
+/// Emitted when a recording that was queued behind another starts
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedRecordingStarted {
+    pub id: String,
+    pub recording_dir: PathBuf,
+}
+
+/// Starts recording with `inputs`. If a recording's already in progress it's queued
+/// instead, and starts once that one has ended.
+#[tauri::command]
+#[specta::specta]
+#[tracing::instrument(name = "recording", skip_all)]
+pub async fn start_recording(
+    app: AppHandle,
+    state_mtx: MutableState<'_, App>,
+    inputs: StartRecordingInputs,
+) -> Result<StartedRecording, String> {
+    {
+        let mut state = state_mtx.write().await;
+        if state.current_recording.is_some() {
+            // checked now too, so a problem's reported to whoever's starting it
+            validate_start_inputs(&inputs, &state)?;
+
+            let id = uuid::Uuid::new_v4().to_string();
+            let recording_dir = recording_dir_for(&app, &inputs, &id)?;
+
+            state.recording_queue.push(QueuedRecording {
+                id: id.clone(),
+                inputs,
+            })?;
+            info!("Queued recording {id} until the current one ends");
+
+            return Ok(StartedRecording {
+                id,
+                recording_dir,
+                queued: true,
+            });
+        }
+    }
+
+    start_recording_with_id(app, state_mtx, inputs, None).await
+}
+
+// boxed as starting a recording is what eventually starts the next queued one
+fn start_queued_recording(
+    app: AppHandle,
+    queued: QueuedRecording,
+) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        let state = app.state::<ArcLock<App>>();
+        let id = queued.id.clone();
+
+        match start_recording_with_id(app.clone(), state.clone(), queued.inputs, Some(queued.id))
+            .await
+        {
+            Ok(started) => {
+                QueuedRecordingStarted {
+                    id: started.id,
+                    recording_dir: started.recording_dir,
+                }
+                .emit(&app)
+                .ok();
+            }
+            Err(e) => {
+                error!("Failed to start queued recording {id}: {e}");
+                QueuedRecordingFailed { id, error: e }.emit(&app).ok();
+
+                // those queued after it would otherwise wait on a recording that never started
+                start_next_queued_recording(&app, &mut *state.write().await, RecordingEnd::Ended);
+            }
+        }
+    })
+}
+
+fn start_next_queued_recording(app: &AppHandle, state: &mut App, ended: RecordingEnd) {
+    // it'll start when whatever's started since ends
+    if state.current_recording.is_some() {
+        return;
+    }
+
+    // the lock on `state` is held by the caller, so it starts once that's released
+    if let Some(queued) = state.recording_queue.next_after(ended) {
+        tokio::spawn(start_queued_recording(app.clone(), queued));
+    }
+}
+
+/// Starts recording, as `queued_id` if it was queued
+async fn start_recording_with_id(
+    app: AppHandle,
+    state_mtx: MutableState<'_, App>,
+    inputs: StartRecordingInputs,
+    queued_id: Option<String>,
+) -> Result<StartedRecording, String> {
+    let general_settings = GeneralSettingsStore::get(&app).ok().flatten();
+    let audio_sample_rate = audio_sample_rate(general_settings.as_ref(), inputs.mode)?;
+    let capture_scale = capture_scale(general_settings.as_ref())?;
+    validate_output_aspect(general_settings.as_ref(), inputs.mode)?;
+
+    validate_start_inputs(&inputs, &*state_mtx.read().await)?;
+    // only once the inputs are known to be good, so a failed start leaves the selection alone
+    select_input_devices(&state_mtx, &inputs).await?;
+
+    let mut armed_pre_roll = PreRollGuard(take_armed_pre_roll(&state_mtx, &inputs).await);
+
+    let mut retry = state_mtx.write().await.failed_start.take();
+    if let Some(failed) = retry.take() {
+        // queued recordings keep the id they were given when queued,
+        // and a failed start with other inputs isn't being retried
+        let retried = armed_pre_roll.get().is_none()
+            && queued_id.is_none()
+            && recording_dir_for(&app, &inputs, &failed.id)
+                .is_ok_and(|dir| failed.is_retried_by(&inputs, &dir));
+
+        if retried {
+            retry = Some(failed);
+        } else {
+            failed.discard(&app).await;
+        }
+    }
+
+    let id = match (armed_pre_roll.get(), &retry) {
+        (Some(armed), _) => armed.id.clone(),
+        (None, Some(retry)) => {
+            info!("Retrying failed recording {}", retry.id);
+            retry.id.clone()
+        }
+        (None, None) => queued_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+    };
+
+    let recording_dir = recording_dir_for(&app, &inputs, &id)?;
+
+    let countdown = async {
+        match inputs.countdown_secs.filter(|secs| *secs > 0) {
+            Some(secs) => count_down(&app, &state_mtx, &inputs, secs).await,
+            None => Ok(true),
+        }
+    };
+    if !prepare_recording_dir(&recording_dir, countdown, retry.is_some()).await? {
+        if let Some(armed) = armed_pre_roll.take() {
+            armed.disarm().await;
+        }
+
+        if let Some(retry) = retry {
+            retry.discard(&app).await;
+        }
+
+        return Err("Recording was cancelled during the countdown".to_string());
+    }
+
+    let minimal_overhead = general_settings
+        .as_ref()
+        .map(|s| s.minimal_overhead)
+        .unwrap_or_default();
+    let excluded_apps = general_settings
+        .as_ref()
+        .map(|s| s.excluded_apps.clone())
+        .unwrap_or_default();
+    let skip_static_frames = general_settings
+        .as_ref()
+        .is_some_and(|s| s.skip_static_frames);
+    let record_focus_events = general_settings
+        .as_ref()
+        .map(|s| s.record_focus_events && !s.minimal_overhead)
+        .unwrap_or_default();
+    let segment_duration = general_settings
+        .as_ref()
+        .and_then(|s| s.segment_duration_secs)
+        .filter(|secs| *secs > 0)
+        .map(|secs| Duration::from_secs(secs as u64));
+    let stream_cursor_clicks = general_settings
+        .as_ref()
+        .is_some_and(|s| s.stream_cursor_clicks);
+    let record_key_presses = general_settings
+        .as_ref()
+        .is_some_and(|s| s.record_key_presses && !s.minimal_overhead);
+    let instant_output_format = general_settings
+        .as_ref()
+        .map(|s| s.instant_output_format)
+        .unwrap_or_default();
+    let studio_video_codec = studio_video_codec(general_settings.as_ref())?;
+    // the output is re-encoded once recording stops, so there's no point uploading it as it's written
+    let fit_output_aspect = matches!(inputs.mode, RecordingMode::Instant)
+        && general_settings
+            .as_ref()
+            .is_some_and(|s| s.output_aspect.is_some());
+    let pressure_monitor = general_settings.as_ref().and_then(|s| {
+        Some(PressureMonitorConfig {
+            response: s.pressure_response?,
+            threshold: s.pressure_threshold,
+            reduced_fps: s.pressure_reduced_fps,
+        })
+    });
+
+    ensure_dir(&recording_dir).map_err(|e| format!("Failed to create recording directory: {e}"))?;
+
+    let inputs = clamp_target_fps_to_display(inputs);
+
+    write_recording_manifest(&app, &state_mtx, &inputs, &recording_dir).await;
+
+    if !minimal_overhead {
+        let logfile = std::fs::File::create(recording_dir.join("recording-logs.log"))
+            .map_err(|e| format!("Failed to create logfile: {e}"))?;
+
+        state_mtx
+            .write()
+            .await
+            .recording_logging_handle
+            .reload(Some(Box::new(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_target(true)
+                    .with_writer(logfile),
+            ) as DynLoggingLayer))
+            .map_err(|e| format!("Failed to reload logging layer: {e}"))?;
+    }
+
+    let target_name = if inputs.camera_only {
+        "Camera".to_string()
+    } else {
+        let title = inputs.capture_target.get_title();
+
+        match inputs.capture_target {
+            ScreenCaptureTarget::Area { .. } => "Area".to_string(),
+            ScreenCaptureTarget::Window { id, .. } => {
+                let platform_windows: HashMap<u32, cap_media::platform::Window> =
+                    cap_media::platform::get_on_screen_windows()
+                        .into_iter()
+                        .map(|window| (window.window_id, window))
+                        .collect();
+
+                platform_windows
+                    .get(&id)
+                    .map(|v| v.owner_name.to_string())
+                    .unwrap_or_else(|| "Window".to_string())
+            }
+            ScreenCaptureTarget::Screen { .. } => title.unwrap_or_else(|| "Screen".to_string()),
+            ScreenCaptureTarget::AllScreens => "All Screens".to_string(),
+        }
+    };
+
+    apply_camera_content_protection(&app, Some(inputs.mode));
+    if !inputs.include_camera {
+        if let Some(window) = CapWindowId::Camera.get(&app) {
+            window.hide().ok();
+        }
+    }
+
+    let retry_video_upload_info = retry.and_then(|retry| retry.video_upload_info);
+
+    let video_upload_info = match inputs.mode {
+        RecordingMode::Instant if retry_video_upload_info.is_some() => retry_video_upload_info,
+        // the video's created when it's uploaded
+        RecordingMode::Instant if inputs.defer_upload => None,
+        RecordingMode::Instant => {
+            match AuthStore::get(&app).ok().flatten() {
+                Some(_) => {
+                    // Pre-create the video and get the shareable link
+                    if let Ok(s3_config) = create_or_get_video(
+                        &app,
+                        false,
+                        None,
+                        Some(recording_name(
+                            general_settings.as_ref(),
+                            &target_name,
+                            inputs.mode,
+                        )),
+                        Some(&id),
+                    )
+                    .await
+                    {
+                        let link = app.make_app_url(format!("/s/{}", s3_config.id())).await;
+                        info!("Pre-created shareable link: {}", link);
+
+                        Some(VideoUploadInfo {
+                            id: s3_config.id().to_string(),
+                            link: link.clone(),
+                            config: s3_config,
+                        })
+                    } else {
+                        None
+                    }
+                }
+                // Allow the recording to proceed without error for any signed-in user
+                _ => {
+                    // User is not signed in
+                    return Err("Please sign in to use instant recording".to_string());
+                }
+            }
+        }
+        RecordingMode::Studio => {
+            if let Some(video_upload_info) = retry_video_upload_info {
+                delete_pre_created_video(&app, &video_upload_info.id).await;
+            }
+
+            None
+        }
+    };
+
+    let failed_start = FailedStart {
+        id: id.clone(),
+        recording_dir: recording_dir.clone(),
+        video_upload_info: video_upload_info.clone(),
+        inputs: inputs.clone(),
+    };
+
+    if !inputs.camera_only {
+        if let Err(e) = show_capture_occluder(&app, &inputs.capture_target).await {
+            state_mtx.write().await.failed_start = Some(failed_start);
+            return Err(e);
+        }
+    }
+
+    let (finish_upload_tx, finish_upload_rx) = flume::bounded(1);
+
+    let system_audio_feed = match inputs.system_audio_device() {
+        // the pre-roll already has its own
+        Some(_) if armed_pre_roll.get().is_some() => None,
+        Some(device) => match AudioInputFeed::init(device).await {
+            Ok(feed) => Some(feed),
+            Err(e) => {
+                state_mtx.write().await.failed_start = Some(failed_start);
+                return Err(format!(
+                    "Failed to open system audio device '{device}': {e}"
+                ));
+            }
+        },
+        None => None,
+    };
+
+    println!("spawning actor");
+
+    let recording_id = id.clone();
+    let max_duration = inputs
+        .max_duration_secs
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs);
+    let last_inputs = LastRecordingInputs {
+        capture_target: inputs.capture_target,
+        mode: inputs.mode,
+    };
+
+    // done in spawn to catch panics just in case
+    let actor_done_rx = spawn_actor({
+        let state_mtx = Arc::clone(&state_mtx);
+        let app = app.clone();
+        let id = id.clone();
+        let recording_dir = recording_dir.clone();
+        async move {
+            fail!("recording::spawn_actor");
+            let mut state = state_mtx.write().await;
+
+            let mic_feeds = state.mic_feeds();
+            let base_inputs = cap_recording::RecordingBaseInputs {
+                capture_target: inputs.capture_target,
+                capture_system_audio: inputs.capture_system_audio,
+                mic_feeds: &mic_feeds,
+                system_audio_feed: &system_audio_feed,
+                audio_sample_rate: Some(audio_sample_rate),
+                excluded_apps,
+                capture_scale,
+                skip_static_frames,
+                target_fps: inputs.target_fps,
+            };
+
+            let (actor, actor_done_rx) = match inputs.mode {
+                RecordingMode::Studio => {
+                    let (handle, actor_done_rx) = match armed_pre_roll.take() {
+                        Some(armed) => (armed.handle, armed.actor_done_rx),
+                        None => cap_recording::spawn_studio_recording_actor(
+                            id.clone(),
+                            recording_dir.clone(),
+                            base_inputs,
+                            recorded_camera(&inputs, state.camera_feed.clone()),
+                            // following the cursor needs its movement samples
+                            inputs.cursor_follow.is_some()
+                                || stream_cursor_clicks
+                                || general_settings
+                                    .map(|s| s.custom_cursor_capture && !s.minimal_overhead)
+                                    .unwrap_or_default(),
+                            segment_duration,
+                            segment_duration
+                                .map(|_| segment_completed_events(&app, &recording_dir)),
+                            Some(capture_target_lost_events(&app, &id)),
+                            stream_cursor_clicks.then(|| cursor_click_events(&app)),
+                            record_key_presses,
+                            inputs.camera_only,
+                            None,
+                            studio_video_codec,
+                        )
+                        .await
+                        .map_err(|e| {
+                            error!("Failed to spawn studio recording actor: {e}");
+                            e.to_string()
+                        })?,
+                    };
+
+                    (
+                        InProgressRecording::Studio {
+                            handle,
+                            target_name,
+                            inputs,
+                            recording_dir: recording_dir.clone(),
+                            focus_events: record_focus_events.then(FocusEventsRecorder::spawn),
+                            clock: RecordingClock::start(),
+                            chapters: vec![],
+                        },
+                        actor_done_rx,
+                    )
+                }
+                RecordingMode::Instant => {
+                    if video_upload_info.is_none() && !inputs.defer_upload {
+                        return Err("Video upload info not found".to_string());
+                    }
+
+                    let (handle, actor_done_rx) =
+                        cap_recording::instant_recording::spawn_instant_recording_actor(
+                            id.clone(),
+                            recording_dir.clone(),
+                            base_inputs,
+                            instant_output_format,
+                        )
+                        .await
+                        .map_err(|e| {
+                            error!("Failed to spawn studio recording actor: {e}");
+                            e.to_string()
+                        })?;
+
+                    // only started once the actor is, so a failed start doesn't leave
+                    // an upload waiting for a file that's never written
+                    let progressive_upload = video_upload_info
+                        .clone()
+                        .filter(|_| !minimal_overhead && !fit_output_aspect)
+                        .map(|video_upload_info| {
+                            InstantMultipartUpload::spawn(
+                                app.clone(),
+                                id.clone(),
+                                recording_dir.join(handle.output_format.output_path()),
+                                video_upload_info,
+                                Some(finish_upload_rx),
+                            )
+                        });
+
+                    (
+                        InProgressRecording::Instant {
+                            handle,
+                            progressive_upload,
+                            video_upload_info: video_upload_info.clone(),
+                            target_name,
+                            inputs,
+                            recording_dir: recording_dir.clone(),
+                            focus_events: record_focus_events.then(FocusEventsRecorder::spawn),
+                            clock: RecordingClock::start(),
+                            chapters: vec![],
+                        },
+                        actor_done_rx,
+                    )
+                }
+            };
+
+            state.set_current_recording(actor);
+
+            Ok::<_, String>(actor_done_rx)
+        }
+    })
+    .await
+    .map_err(|e| format!("Failed to spawn recording actor: {}", e))
+    .and_then(|result| result);
+
+    let actor_done_rx = match actor_done_rx {
+        Ok(actor_done_rx) => actor_done_rx,
+        Err(e) => {
+            state_mtx.write().await.failed_start = Some(failed_start);
+            return Err(e);
+        }
+    };
+
+    if let Some(config) = pressure_monitor {
+        spawn_pressure_monitor(app.clone(), recording_dir.clone(), config);
+    }
+
+    if let Some(max_duration) = max_duration {
+        spawn_max_duration_timer(app.clone(), recording_dir.clone(), max_duration);
+    }
+
+    spawn_actor({
+        let app = app.clone();
+        let state_mtx = Arc::clone(&state_mtx);
+        async move {
+            fail!("recording::wait_actor_done");
+            match actor_done_rx.await {
+                Ok(Ok(_)) => {
+                    let _ = finish_upload_tx.send(());
+                    return;
+                }
+                Ok(Err(e)) => {
+                    RecordingFailed {
+                        error: e.clone(),
+                        recording_id,
+                    }
+                    .emit(&app)
+                    .ok();
+
+                    let mut state = state_mtx.write().await;
+
+                    let (title, message) = if RecordingError::is_disk_full_message(&e) {
+                        (
+                            "Out of disk space".to_string(),
+                            "Your disk filled up, so recording stopped. Free up some space before recording again.".to_string(),
+                        )
+                    } else {
+                        ("An error occurred".to_string(), e)
+                    };
+
+                    let mut dialog =
+                        MessageDialogBuilder::new(app.dialog().clone(), title, message)
+                            .kind(tauri_plugin_dialog::MessageDialogKind::Error);
+
+                    if let Some(window) = CapWindowId::InProgressRecording.get(&app) {
+                        dialog = dialog.parent(&window);
+                    }
+
+                    dialog.blocking_show();
+
+                    // this clears the current recording for us
+                    handle_recording_end(app, None, None, RecordingEnd::Ended, &mut state)
+                        .await
+                        .ok();
+                }
+                _ => {}
+            }
+        }
+    });
+
+    if let Some(window) = CapWindowId::Main.get(&app) {
+        match GeneralSettingsStore::get(&app)
+            .ok()
+            .flatten()
+            .map(|s| s.main_window_recording_start_behaviour)
+            .unwrap_or_default()
+        {
+            MainWindowRecordingStartBehaviour::Close => {
+                let _ = window.close();
+            }
+            MainWindowRecordingStartBehaviour::Minimise => {
+                let _ = window.minimize();
+            }
+        }
+    }
+
+    if let Some(window) = CapWindowId::InProgressRecording.get(&app) {
+        window.eval("window.location.reload()").ok();
+    } else {
+        let _ = ShowCapWindow::InProgressRecording { position: None }
+            .show(&app)
+            .await;
+    }
+
+    play_recording_sound(&app, AppSounds::StartRecording);
+
+    GeneralSettingsStore::update(&app, |settings| {
+        settings.last_recording_inputs = Some(last_inputs)
+    })
+    .map_err(|e| warn!("Failed to save last recording inputs: {e}"))
+    .ok();
+
+    RecordingStarted.emit(&app).ok();
+
+    Ok(StartedRecording {
+        id,
+        recording_dir,
+        queued: false,
+    })
+}
+
+/// The capture target and mode of the last recording that started, unless it
+/// was a window that's since closed
+#[tauri::command]
+#[specta::specta]
+pub fn get_last_recording_inputs(app: AppHandle) -> Option<LastRecordingInputs> {
+    let last = GeneralSettingsStore::get(&app)
+        .ok()
+        .flatten()
+        .and_then(|s| s.last_recording_inputs)?;
+
+    let open_windows = cap_media::platform::get_on_screen_windows()
+        .into_iter()
+        .map(|window| window.window_id)
+        .collect::<Vec<_>>();
+
+    last_recording_inputs_available(last, &open_windows)
+}
+
+fn last_recording_inputs_available(
+    last: LastRecordingInputs,
+    open_windows: &[u32],
+) -> Option<LastRecordingInputs> {
+    match last.capture_target {
+        ScreenCaptureTarget::Window { id, .. } if !open_windows.contains(&id) => None,
+        _ => Some(last),
+    }
+}
+
+/// What recording supports on this platform, for hiding options that aren't available
+#[tauri::command]
+#[specta::specta]
+pub fn platform_capture_capabilities() -> CaptureCapabilities {
+    cap_recording::capabilities::capture_capabilities()
+}
+
+/// Whether `capture_system_audio` can be used on this platform
+#[tauri::command]
+#[specta::specta]
+pub fn system_audio_available() -> bool {
+    cap_recording::capabilities::capture_capabilities().system_audio
+}
+
+/// Marks the current point in the recording as the start of a chapter.
+/// Unnamed markers are numbered from 1 for each recording.
+#[tauri::command]
+#[specta::specta]
+pub async fn add_chapter_marker(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    name: Option<String>,
+) -> Result<ChapterMarker, String> {
+    let mut state = state.write().await;
+
+    let Some(recording) = state.current_recording.as_mut() else {
+        return Err("No recording in progress".to_string());
+    };
+
+    let marker = recording.add_chapter(name);
+
+    ChapterMarkerAdded {
+        marker: marker.clone(),
+    }
+    .emit(&app)
+    .ok();
+
+    Ok(marker)
+}
+
+/// Shows the occluder over the display of a window or area target,
+/// unless it's been turned off with `show_capture_occluder`.
+/// Fails if the display a window target is on can't be found, eg. it was just unplugged.
+pub async fn show_capture_occluder(
+    app: &AppHandle,
+    capture_target: &ScreenCaptureTarget,
+) -> Result<(), String> {
+    if !occluder_enabled(GeneralSettingsStore::get(app).ok().flatten().as_ref()) {
+        return Ok(());
+    }
+
+    match capture_target {
+        ScreenCaptureTarget::Window { id, .. } => {
+            let display = window_display(target_window_display(*id, capture_target))?;
+
+            let _ = ShowCapWindow::WindowCaptureOccluder { screen_id: display }
+                .show(app)
+                .await;
+        }
+        ScreenCaptureTarget::Area { screen, .. } => {
+            let _ = ShowCapWindow::WindowCaptureOccluder { screen_id: *screen }
+                .show(app)
+                .await;
+        }
+        ScreenCaptureTarget::AllScreens => {
+            for (screen, _) in cap_media::sources::list_screens() {
+                let _ = ShowCapWindow::WindowCaptureOccluder {
+                    screen_id: screen.id,
+                }
+                .show(app)
+                .await;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// The display the window `id` is on, none if it's been disconnected
+fn target_window_display(id: u32, capture_target: &ScreenCaptureTarget) -> Option<u32> {
+    #[cfg(target_os = "macos")]
+    {
+        let _ = capture_target;
+        display_for_window(id).map(|display| display.id)
+    }
+
+    #[cfg(windows)]
+    {
+        let _ = id;
+        match capture_target.get_target() {
+            Some(scap::Target::Window(target)) => {
+                display_for_window(target.raw_handle).map(|display| display.0 as u32)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn window_display(display: Option<u32>) -> Result<u32, String> {
+    display.ok_or_else(|| {
+        "Couldn't find the display the window is on, it may have been disconnected".to_string()
+    })
+}
+
+fn occluder_enabled(general_settings: Option<&GeneralSettingsStore>) -> bool {
+    general_settings.map_or(true, |s| s.show_capture_occluder)
+}
+
+/// Changes what a studio recording captures, finishing the current segment and
+/// continuing in a new one with `new_target`. Paused recordings use it once they resume.
+/// Instant recordings are a single continuous file, so their target can't change.
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_capture_target(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    new_target: ScreenCaptureTarget,
+) -> Result<(), String> {
+    let mut state = state.write().await;
+
+    let Some(InProgressRecording::Studio { handle, inputs, .. }) = state.current_recording.as_mut()
+    else {
+        return Err(match state.current_recording {
+            Some(_) => "Only studio recordings can switch capture target".to_string(),
+            None => "No recording in progress".to_string(),
+        });
+    };
+
+    if inputs.camera_only {
+        return Err("Camera only recordings have no capture target".to_string());
+    }
+
+    handle
+        .switch_capture_target(new_target)
+        .await
+        .map_err(|e| format!("Failed to switch capture target: {e}"))?;
+    inputs.capture_target = new_target;
+
+    state.close_occluder_windows();
+    if let Err(e) = show_capture_occluder(&app, &new_target).await {
+        warn!("Failed to show occluder for new capture target: {e}");
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn pause_recording(app: AppHandle, state: MutableState<'_, App>) -> Result<(), String> {
+    let mut state = state.write().await;
+
+    if let Some(recording) = state.current_recording.as_mut() {
+        recording.pause().await.map_err(|e| e.to_string())?;
+        play_recording_sound(&app, AppSounds::PauseRecording);
+    }
+
+    Ok(())
+}
+
+/// Pauses the recording and saves the last frame it recorded as a PNG in its
+/// `screenshots` directory, returning the image's path. The frame comes from the
+/// finalized recording rather than a separate capture, so it's exactly where it paused.
+///
+/// Studio only, as instant recordings can't be read until they're stopped.
+#[tauri::command]
+#[specta::specta]
+pub async fn pause_and_capture(state: MutableState<'_, App>) -> Result<PathBuf, String> {
+    let state = state.write().await;
+
+    let Some(recording) = state.current_recording.as_ref() else {
+        return Err("No recording in progress".to_string());
+    };
+
+    if matches!(recording, InProgressRecording::Instant { .. }) {
+        return Err("Only studio recordings can be captured while paused".to_string());
+    }
+
+    if recording.clock().is_paused() {
+        return Err("Recording is already paused".to_string());
+    }
+
+    let display_path = recording
+        .pause_segment()
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or("Paused segment has no display recording")?;
+
+    let screenshots_dir = recording.recording_dir().join("screenshots");
+    let output_path = screenshots_dir.join(format!(
+        "paused-{}.png",
+        recording.clock().elapsed().as_millis()
+    ));
+
+    drop(state);
+
+    ensure_dir(&screenshots_dir)
+        .map_err(|e| format!("Failed to create screenshots directory: {e}"))?;
+
+    tokio::task::spawn_blocking({
+        let output_path = output_path.clone();
+        move || save_last_frame(&display_path, &output_path)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    AppSounds::Screenshot.play();
+
+    Ok(output_path)
+}
+
+/// Decodes the last frame of `video` and saves it to `output`, in the format its extension implies
+fn save_last_frame(video: &Path, output: &Path) -> Result<(), String> {
+    ffmpeg::init().map_err(|e| e.to_string())?;
+
+    let mut input = ffmpeg::format::input(&video).map_err(|e| e.to_string())?;
+    let stream = input
+        .streams()
+        .best(ffmpeg::media::Type::Video)
+        .ok_or("No video stream found")?;
+    let stream_index = stream.index();
+
+    let mut decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+        .and_then(|context| context.decoder().video())
+        .map_err(|e| format!("Failed to create video decoder: {e}"))?;
+
+    // only what's after the last keyframe before this needs decoding
+    let position = input.duration() - 2 * ffmpeg::ffi::AV_TIME_BASE as i64;
+    if position > 0 {
+        input.seek(position, ..position).ok();
+    }
+
+    let mut decoded = ffmpeg::frame::Video::empty();
+    let mut last_frame = None;
+
+    for (stream, packet) in input.packets() {
+        if stream.index() != stream_index {
+            continue;
+        }
+
+        decoder.send_packet(&packet).map_err(|e| e.to_string())?;
+        while decoder.receive_frame(&mut decoded).is_ok() {
+            last_frame = Some(std::mem::replace(
+                &mut decoded,
+                ffmpeg::frame::Video::empty(),
+            ));
+        }
+    }
+
+    decoder.send_eof().ok();
+    while decoder.receive_frame(&mut decoded).is_ok() {
+        last_frame = Some(std::mem::replace(
+            &mut decoded,
+            ffmpeg::frame::Video::empty(),
+        ));
+    }
+
+    let frame = last_frame.ok_or("Nothing was recorded before pausing")?;
+
+    let mut scaler = ffmpeg::software::scaling::Context::get(
+        frame.format(),
+        frame.width(),
+        frame.height(),
+        ffmpeg::format::Pixel::RGB24,
+        frame.width(),
+        frame.height(),
+        ffmpeg::software::scaling::Flags::BILINEAR,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut rgb_frame = ffmpeg::frame::Video::empty();
+    scaler
+        .run(&frame, &mut rgb_frame)
+        .map_err(|e| e.to_string())?;
+
+    let row_len = rgb_frame.width() as usize * 3;
+    let stride = rgb_frame.stride(0);
+    let pixels = rgb_frame
+        .data(0)
+        .chunks(stride)
+        .take(rgb_frame.height() as usize)
+        .flat_map(|row| &row[..row_len])
+        .copied()
+        .collect();
+
+    image::RgbImage::from_raw(rgb_frame.width(), rgb_frame.height(), pixels)
+        .ok_or("Failed to create image from frame")?
+        .save(output)
+        .map_err(|e| format!("Failed to save frame: {e}"))
+}
+
+/// Pauses by covering the captured screen with a "Be Right Back" card rather than stopping capture,
+/// so the unedited output shows the card instead of a cut. Capture, including audio, keeps running.
+///
+/// Window targets are hard paused instead, as the card isn't part of the captured window.
+#[tauri::command]
+#[specta::specta]
+pub async fn pause_recording_with_card(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    text: Option<String>,
+    image_path: Option<PathBuf>,
+) -> Result<(), String> {
+    let mut state = state.write().await;
+
+    let Some(recording) = state.current_recording.as_mut() else {
+        return Err("Recording not in progress".to_string());
+    };
+
+    let screen_id = match recording.capture_target() {
+        ScreenCaptureTarget::Screen { id } => *id,
+        ScreenCaptureTarget::Area { screen, .. } => *screen,
+        ScreenCaptureTarget::AllScreens => match ScreenCaptureTarget::primary_display() {
+            ScreenCaptureTarget::Screen { id } => id,
+            _ => unreachable!(),
+        },
+        ScreenCaptureTarget::Window { .. } => {
+            return recording.pause().await.map_err(|e| e.to_string());
+        }
+    };
+
+    ShowCapWindow::BeRightBack {
+        card: BeRightBackCard {
+            screen_id,
+            text: text.unwrap_or_else(|| "Be right back".to_string()),
+            image_path,
+        },
+    }
+    .show(&app)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn resume_recording(app: AppHandle, state: MutableState<'_, App>) -> Result<(), String> {
+    let mut state = state.write().await;
+
+    // a card pause never paused the actor
+    if close_be_right_back_cards(&app) {
+        return Ok(());
+    }
+
+    if let Some(recording) = state.current_recording.as_mut() {
+        recording.resume().await.map_err(|e| e.to_string())?;
+        play_recording_sound(&app, AppSounds::ResumeRecording);
+    }
+
+    Ok(())
+}
+
+/// Plays `sound` unless recording sounds have been turned off
+fn play_recording_sound(app: &AppHandle, sound: AppSounds) {
+    let enabled = GeneralSettingsStore::get(app)
+        .ok()
+        .flatten()
+        .map_or(true, |s| s.recording_sound_effects);
+
+    if enabled {
+        sound.play();
+    }
+}
+
+fn close_be_right_back_cards(app: &AppHandle) -> bool {
+    let mut closed = false;
+
+    for (label, window) in app.webview_windows() {
+        if let Ok(CapWindowId::BeRightBack { .. }) = CapWindowId::from_str(&label) {
+            let _ = window.close();
+            closed = true;
+        }
+    }
+
+    closed
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStatus {
+    /// time spent recording, not counting time paused
+    pub elapsed_secs: f64,
+    pub paused: bool,
+    pub mode: RecordingMode,
+    pub target_name: String,
+    /// how much has been written to disk so far
+    pub current_file_size_bytes: u64,
+}
+
+/// How the recording in progress is going, `None` when nothing's recording
+#[tauri::command]
+#[specta::specta]
+pub async fn get_recording_status(
+    state: MutableState<'_, App>,
+) -> Result<Option<RecordingStatus>, String> {
+    Ok(state
+        .read()
+        .await
+        .current_recording
+        .as_ref()
+        .map(|recording| RecordingStatus {
+            elapsed_secs: recording.clock().elapsed().as_secs_f64(),
+            paused: recording.clock().is_paused(),
+            mode: recording.mode(),
+            target_name: recording.target_name().clone(),
+            current_file_size_bytes: recording_file_size(
+                recording.recording_dir(),
+                recording.mode(),
+                recording.output_format(),
+            ),
+        }))
+}
+
+/// Size of the output written so far, `0` before anything's been written.
+/// Studio recordings are the sum of every segment's files.
+fn recording_file_size(
+    recording_dir: &Path,
+    mode: RecordingMode,
+    output_format: InstantOutputFormat,
+) -> u64 {
+    match mode {
+        RecordingMode::Instant => {
+            std::fs::metadata(recording_dir.join(output_format.output_path()))
+                .map(|m| m.len())
+                .unwrap_or_default()
+        }
+        RecordingMode::Studio => dir_size(&recording_dir.join("content/segments")),
+    }
+}
+
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(m) if m.is_dir() => dir_size(&entry.path()),
+            Ok(m) => m.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn stop_recording(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    // overrides the post-studio-recording behaviour setting for this recording only
+    post_studio_behaviour: Option<PostStudioRecordingBehaviour>,
+) -> Result<StoppedRecording, String> {
+    let mut state = state.write().await;
+    let Some(current_recording) = state.clear_current_recording() else {
+        return Err("Recording not in progress".to_string())?;
+    };
+
+    let completed_recording = current_recording.stop().await.map_err(|e| e.to_string())?;
+
+    let stopped = StoppedRecording {
+        project_path: completed_recording.project_path().clone(),
+        mode: match &completed_recording {
+            CompletedRecording::Instant { .. } => RecordingMode::Instant,
+            CompletedRecording::Studio { .. } => RecordingMode::Studio,
+        },
+        share_link: match &completed_recording {
+            CompletedRecording::Instant {
+                video_upload_info, ..
+            } => video_upload_info.as_ref().map(|info| info.link.clone()),
+            CompletedRecording::Studio { .. } => None,
+        },
+    };
+
+    handle_recording_end(
+        app,
+        Some(completed_recording),
+        post_studio_behaviour,
+        RecordingEnd::Ended,
+        &mut state,
+    )
+    .await?;
+
+    Ok(stopped)
+}
+
+#[derive(Serialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedRecording {
+    pub project_path: PathBuf,
+    pub mode: RecordingMode,
+    /// only instant recordings have a link as soon as they stop
+    pub share_link: Option<String>,
+}
+
+/// Dropped frames and audio underruns of a finished recording, to help decide whether to re-record
+#[tauri::command]
+#[specta::specta]
+pub async fn recording_quality_report(project_path: PathBuf) -> Result<RecordingQuality, String> {
+    let meta = RecordingMeta::load_for_project(&project_path)
+        .map_err(|e| format!("Failed to load recording meta: {e}"))?;
+
+    meta.quality
+        .ok_or_else(|| "Recording has no quality report".to_string())
+}
+
+/// Analyzes a finished recording's files and saves the result into its meta
+async fn save_quality_report(project_path: &Path, inputs: QualityInputs) {
+    let quality = match tokio::task::spawn_blocking(move || inputs.analyze())
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|r| r)
+    {
+        Ok(quality) => quality,
+        Err(e) => {
+            warn!("Failed to analyze recording quality: {e}");
+            return;
+        }
+    };
+
+    match RecordingMeta::load_for_project(project_path) {
+        Ok(mut meta) => {
+            meta.quality = Some(quality);
+            if let Err(e) = meta.save_for_project() {
+                error!("Failed to save quality report to recording meta: {e}");
+            }
+        }
+        Err(e) => error!("Failed to load recording meta to add quality report: {e}"),
+    }
+}
+
+/// Waits for a cancelled recording to release its capture sources and feeds,
+/// returning as soon as it has
+async fn wait_for_release(
+    released: impl Future<Output = ()>,
+    timeout: Duration,
+) -> Result<(), String> {
+    tokio::time::timeout(timeout, released).await.map_err(|_| {
+        format!(
+            "The camera, microphone or display is still in use by the previous recording after {}ms, try again",
+            timeout.as_millis()
+        )
+    })
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn restart_recording(app: AppHandle, state: MutableState<'_, App>) -> Result<(), String> {
+    let Some(recording) = state.write().await.clear_current_recording() else {
+        return Err("No recording in progress".to_string());
+    };
+
+    let _ = CurrentRecordingChanged.emit(&app);
+
+    let inputs = recording.inputs().clone();
+    let released = recording.released();
+
+    // the link may have been shared already, so the new recording keeps using it
+    let restart = match &recording {
+        InProgressRecording::Instant {
+            recording_dir,
+            video_upload_info: Some(video_upload_info),
+            progressive_upload,
+            ..
+        } => {
+            // the restarted recording uploads from scratch in a new multipart upload
+            if let Some(progressive_upload) = progressive_upload {
+                progressive_upload.handle.abort();
+                ActiveUploads::remove(&app, &video_upload_info.id);
+            }
+
+            Some(FailedStart::restart(
+                recording_dir.clone(),
+                video_upload_info.clone(),
+                inputs.clone(),
+            ))
+        }
+        InProgressRecording::Instant { .. } => None,
+        InProgressRecording::Studio { .. } => None,
+    };
+
+    let _ = recording.cancel().await;
+
+    if let Some(restart) = restart {
+        state.write().await.failed_start = Some(restart);
+    }
+
+    // starting again while the old actor still holds the capture sources and feeds
+    // fails in ways that are hard to make sense of, so give up instead
+    let teardown_timeout = GeneralSettingsStore::get(&app)
+        .ok()
+        .flatten()
+        .map(|s| s.restart_teardown_timeout_ms)
+        .unwrap_or_else(default_restart_teardown_timeout_ms);
+    wait_for_release(released, Duration::from_millis(teardown_timeout)).await?;
+
+    start_recording(app.clone(), state, inputs)
+        .await
+        .map(|_| ())
+}
+
+/// Stops the recording in progress or counting down without finishing it, returning the
+/// directory it was saved to. Unlike `delete_recording`, what was recorded so far and any
+/// pre-created share link are left as they are, eg. for looking into a problem with it.
+#[tauri::command]
+#[specta::specta]
+pub async fn cancel_recording(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+) -> Result<Option<PathBuf>, String> {
+    Ok(abandon_recording(&app, &state, AbandonedFiles::Keep)
+        .await
+        .map(|(recording_dir, _)| recording_dir))
+}
+
+/// Stops the recording in progress or counting down without finishing it, removing its
+/// directory and any pre-created share link. See `cancel_recording` to keep them.
+#[tauri::command]
+#[specta::specta]
+pub async fn delete_recording(app: AppHandle, state: MutableState<'_, App>) -> Result<(), String> {
+    if let Some((_, Some(video_id))) = abandon_recording(&app, &state, AbandonedFiles::Delete).await
+    {
+        delete_pre_created_video(&app, &video_id).await;
+    }
+
+    Ok(())
+}
+
+/// What becomes of an abandoned recording's directory
+#[derive(Clone, Copy, Debug)]
+enum AbandonedFiles {
+    Keep,
+    Delete,
+}
+
+impl AbandonedFiles {
+    fn apply(self, recording_dir: &Path) {
+        if let Self::Delete = self {
+            remove_recording_files(recording_dir);
+        }
+    }
+}
+
+/// Cancels any countdown and the current recording, returning the recording's directory
+/// and pre-created video id
+async fn abandon_recording(
+    app: &AppHandle,
+    state: &MutableState<'_, App>,
+    files: AbandonedFiles,
+) -> Option<(PathBuf, Option<String>)> {
+    let recording = {
+        let mut app_state = state.write().await;
+
+        if let Some(countdown) = app_state.countdown.take() {
+            countdown.token.cancel();
+        }
+
+        app_state.clear_current_recording()
+    }?;
+
+    let recording_dir = recording.recording_dir().clone();
+    let video_id = match &recording {
+        InProgressRecording::Instant {
+            video_upload_info, ..
+        } => video_upload_info.as_ref().map(|info| info.id.clone()),
+        _ => None,
+    };
+
+    CurrentRecordingChanged.emit(app).ok();
+    RecordingStopped {}.emit(app).ok();
+
+    let _ = recording.cancel().await;
+    close_be_right_back_cards(app);
+    files.apply(&recording_dir);
+    forget_created_video(&crate::processing::recording_id(&recording_dir));
+
+    start_next_queued_recording(app, &mut *state.write().await, RecordingEnd::Ended);
+
+    Some((recording_dir, video_id))
+}
+
+fn remove_recording_files(recording_dir: &Path) {
+    if let Err(e) = std::fs::remove_dir_all(recording_dir) {
+        warn!(
+            "Failed to remove recording directory {}: {e}",
+            recording_dir.display()
+        );
+    }
+}
+
+/// Forcibly returns the app to its idle state, for the UI to recover when it suspects
+/// it's out of sync with the backend. A recording that's somehow still running is discarded.
+#[tauri::command]
+#[specta::specta]
+pub async fn reset_recording_state(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+) -> Result<(), String> {
+    let mut state = state.write().await;
+
+    if let Some(recording) = state.clear_current_recording() {
+        warn!("Discarding in-progress recording while resetting recording state");
+        let _ = recording.cancel().await;
+    }
+
+    handle_recording_end(app, None, None, RecordingEnd::Reset, &mut state).await
+}
+
+// runs when a recording ends, whether from success or failure
+async fn handle_recording_end(
+    handle: AppHandle,
+    recording: Option<CompletedRecording>,
+    post_studio_behaviour: Option<PostStudioRecordingBehaviour>,
+    ended: RecordingEnd,
+    app: &mut App,
+) -> Result<(), String> {
+    // Clear current recording, just in case :)
+    app.current_recording.take();
+
+    // what's queued starts even if finishing this one fails
+    let finished = match recording {
+        Some(recording) => handle_recording_finish(&handle, recording, post_studio_behaviour).await,
+        None => Ok(()),
+    };
+
+    let _ = RecordingStopped.emit(&handle);
+
+    let _ = app.recording_logging_handle.reload(None);
+
+    if let Some(window) = CapWindowId::InProgressRecording.get(&handle) {
+        let _ = window.close();
+    }
+
+    close_be_right_back_cards(&handle);
+
+    if let Some(window) = CapWindowId::Main.get(&handle) {
+        window.unminimize().ok();
+        // hidden if the camera was left out of the recording
+        if let Some(camera) = CapWindowId::Camera.get(&handle) {
+            camera.show().ok();
+        }
+    } else {
+        CapWindowId::Camera.get(&handle).map(|v| {
+            let _ = v.close();
+        });
+        app.camera_feed.take();
+        app.mic_feed.take();
+        app.additional_mic_feeds.clear();
+    }
+
+    CurrentRecordingChanged.emit(&handle).ok();
+
+    start_next_queued_recording(&handle, app, ended);
+
+    finished
+}
+
+/// Encodes a finished recording's renditions and adds them to its meta
+async fn add_renditions(
+    recording_dir: PathBuf,
+    inner: RecordingMetaInner,
+    profiles: Vec<RenditionProfile>,
+    cancel: CancellationToken,
+) -> Vec<RenditionMeta> {
+    let renditions = tokio::task::spawn_blocking({
+        let recording_dir = recording_dir.clone();
+        let cancel = cancel.clone();
+        move || render_renditions(&recording_dir, &inner, &profiles, &cancel)
+    })
+    .await
+    .unwrap_or_default();
+
+    if cancel.is_cancelled() {
+        return vec![];
+    }
+
+    match RecordingMeta::load_for_project(&recording_dir) {
+        Ok(mut meta) => {
+            meta.renditions = renditions.clone();
+            if let Err(e) = meta.save_for_project() {
+                error!("Failed to save renditions to recording meta: {e}");
+            }
+        }
+        Err(e) => error!("Failed to load recording meta to add renditions: {e}"),
+    }
+
+    renditions
+}
+
+// runs when a recording successfully finishes
+async fn handle_recording_finish(
+    app: &AppHandle,
+    completed_recording: CompletedRecording,
+    post_studio_behaviour: Option<PostStudioRecordingBehaviour>,
+) -> Result<(), String> {
+    let recording_dir = completed_recording.project_path().clone();
+    // it's been created if it's going to be, retries of the upload use its id
+    forget_created_video(&crate::processing::recording_id(&recording_dir));
+
+    let minimal_overhead = GeneralSettingsStore::get(&app)
+        .ok()
+        .flatten()
+        .map(|s| s.minimal_overhead)
+        .unwrap_or_default();
+
+    let screenshots_dir = recording_dir.join("screenshots");
+
+    let display_output_path = match &completed_recording {
+        CompletedRecording::Studio { recording, .. } => match &recording.meta {
+            StudioRecordingMeta::SingleSegment { segment } => {
+                segment.display.path.to_path(&recording_dir)
+            }
+            StudioRecordingMeta::MultipleSegments { inner, .. } => {
+                inner.segments[0].display.path.to_path(&recording_dir)
+            }
+        },
+        CompletedRecording::Instant { recording, .. } => recording
+            .project_path
+            .join(recording.meta.output_format.output_path()),
+    };
+
+    let generate_preview_gif = GeneralSettingsStore::get(app)
+        .ok()
+        .flatten()
+        .is_some_and(|s| s.generate_preview_gif);
+    let preview_gif = screenshots_dir.join("preview.gif");
+    let preview_gif_task = (!minimal_overhead && generate_preview_gif).then(|| {
+        std::fs::create_dir_all(&screenshots_dir).ok();
+
+        tokio::spawn(create_preview_gif(
+            display_output_path.clone(),
+            preview_gif.clone(),
+        ))
+    });
+
+    let display_screenshot = screenshots_dir.join("display.jpg");
+    let screenshot_task = (!minimal_overhead).then(|| {
+        std::fs::create_dir_all(&screenshots_dir).ok();
+
+        tokio::spawn(create_screenshot(
+            display_output_path,
+            display_screenshot.clone(),
+            None,
+            screenshot_quality(GeneralSettingsStore::get(app).ok().flatten().as_ref()),
+        ))
+    });
+
+    let pretty_name = recording_name(
+        GeneralSettingsStore::get(app).ok().flatten().as_ref(),
+        completed_recording.target_name(),
+        completed_recording.mode(),
+    );
+    let start_marker = completed_recording.start_marker();
+    let chapters = completed_recording.chapters().to_vec();
+    let quality_inputs = completed_recording.quality_inputs();
+
+    let (meta_inner, sharing) = match completed_recording {
+        CompletedRecording::Studio {
+            mut recording,
+            cursor_follow,
+            ..
+        } => {
+            if let StudioRecordingMeta::MultipleSegments { inner } = &mut recording.meta {
+                inner.camera_mirror = inner.segments.iter().any(|s| s.camera.is_some())
+                    && GeneralSettingsStore::get(app)
+                        .ok()
+                        .flatten()
+                        .is_some_and(|s| s.camera_mirror);
+            }
+
+            let recordings = ProjectRecordingsMeta::new(&recording_dir, &recording.meta)?;
+
+            let mut config = project_config_from_recording(
+                &recording.project_path,
+                &recording.meta,
+                &recordings,
+                PresetsStore::get_default_preset(&app)?.map(|p| p.config),
+                (!minimal_overhead).then(ZoomAlgoVersion::default),
+                auto_zoom_amount(GeneralSettingsStore::get(app).ok().flatten().as_ref()),
+            );
+
+            if let Some(offset) = recording.pre_roll_offset.filter(|o| *o > 0.0) {
+                trim_timeline_start(&mut config, offset);
+            }
+
+            if GeneralSettingsStore::get(app)
+                .ok()
+                .flatten()
+                .is_some_and(|s| s.auto_trim_leading_silence)
+            {
+                let project_path = recording_dir.clone();
+                let meta = recording.meta.clone();
+                if let Ok(Some(offset)) = tokio::task::spawn_blocking(move || {
+                    studio_leading_silence(&project_path, &meta)
+                })
+                .await
+                {
+                    info!("Trimming {offset:.2}s of leading silence");
+                    trim_timeline_start(&mut config, offset);
+                }
+            }
+
+            if let Some(size) = cursor_follow {
+                config.background.crop =
+                    add_cursor_follow_paths(&recording_dir, &mut recording.meta, &recordings, size);
+            }
+
+            if let Some(multiplier) = GeneralSettingsStore::get(app)
+                .ok()
+                .flatten()
+                .filter(|s| s.custom_cursor_capture)
+                .map(|s| s.cursor_size_multiplier)
+                .filter(|m| *m != 1.0)
+            {
+                config.cursor.size = (config.cursor.size as f32
+                    * multiplier.clamp(MIN_CURSOR_SIZE_MULTIPLIER, MAX_CURSOR_SIZE_MULTIPLIER))
+                .round() as u32;
+            }
+
+            if let Some(target_lufs) = GeneralSettingsStore::get(app)
+                .ok()
+                .flatten()
+                .and_then(|s| s.system_audio_target_lufs)
+            {
+                let project_path = recording_dir.clone();
+                let meta = recording.meta.clone();
+                if let Ok(Some(gain)) = tokio::task::spawn_blocking(move || {
+                    system_audio_normalization_gain(&project_path, &meta, target_lufs)
+                })
+                .await
+                {
+                    config.audio.system_volume_db = gain;
+                }
+            }
+
+            config.write(&recording_dir).map_err(|e| e.to_string())?;
+
+            (RecordingMetaInner::Studio(recording.meta), None)
+        }
+        CompletedRecording::Instant {
+            recording,
+            progressive_upload,
+            video_upload_info,
+            ..
+        } => {
+            // shareable_link = Some(video_upload_info.link.clone());
+            let app = app.clone();
+            let output_path = recording_dir.join(recording.meta.output_format.output_path());
+
+            // a progressive upload means the setting was turned on mid-recording,
+            // and the uploaded video can't be swapped for a fitted one
+            let mut aspect_fit = GeneralSettingsStore::get(&app)
+                .ok()
+                .flatten()
+                .filter(|_| progressive_upload.is_none())
+                .and_then(|s| {
+                    Some(AspectFit {
+                        aspect: s.output_aspect?,
+                        fill_mode: s.output_aspect_fill,
+                        bar_color: s.output_aspect_bar_color,
+                    })
+                });
+
+            let mut meta = recording.meta;
+            if GeneralSettingsStore::get(&app)
+                .ok()
+                .flatten()
+                .is_some_and(|s| s.auto_trim_leading_silence)
+            {
+                let output_path = output_path.clone();
+                meta = tokio::task::spawn_blocking(move || {
+                    trim_instant_leading_silence(&mut meta, &output_path);
+                    meta
+                })
+                .await
+                .map_err(|e| e.to_string())?;
+            }
+
+            if let Some(fit) = aspect_fit {
+                match fit.output_size(&output_path) {
+                    Ok(size) => {
+                        meta.aspect_fit = Some(AspectFitMeta {
+                            width: size.x,
+                            height: size.y,
+                            fill_mode: fit.fill_mode,
+                        })
+                    }
+                    Err(e) => {
+                        error!("Failed to get size of fitted recording: {e}");
+                        aspect_fit = None;
+                    }
+                }
+            }
+
+            // chapter times are already pause-adjusted, as is the instant recording itself
+            let chapters_path = (!chapters.is_empty())
+                .then(|| {
+                    let duration = crate::get_duration_for_path(output_path.clone())
+                        .ok()
+                        .filter(|d| *d > 0.0)?;
+                    let path = recording_dir.join("content/chapters.vtt");
+                    std::fs::write(&path, chapters_webvtt(&chapters, duration))
+                        .map_err(|e| error!("Failed to write chapters: {e}"))
+                        .ok()?;
+                    Some(path)
+                })
+                .flatten();
+
+            let rendition_profiles = GeneralSettingsStore::get(&app)
+                .ok()
+                .flatten()
+                .map(|s| s.renditions)
+                .unwrap_or_default();
+            let rendition_source = RecordingMetaInner::Instant(meta.clone());
+
+            if let Some(video_upload_info) = &video_upload_info {
+                open_share_link(&app, &video_upload_info.link).await;
+            }
+
+            let processing = ProcessingTasks::get(&app).start(&recording_dir);
+            let progressive_upload_abort = progressive_upload
+                .as_ref()
+                .map(|upload| upload.handle.abort_handle());
+
+            spawn_actor({
+                let video_upload_info = video_upload_info.clone();
+                let recording_dir = recording_dir.clone();
+                let pretty_name = pretty_name.clone();
+                let quality_inputs = quality_inputs.clone();
+
+                async move {
+                    let cancel = processing.token().clone();
+
+                    let process = async {
+                        // before fitting, which re-encodes the frames it's looking for gaps between
+                        save_quality_report(&recording_dir, quality_inputs).await;
+
+                        if let Some(fit) = aspect_fit {
+                            let output_path = output_path.clone();
+                            let cancel = cancel.clone();
+                            let result = tokio::task::spawn_blocking(move || {
+                                fit_to_aspect(&output_path, &fit, &cancel)
+                            })
+                            .await
+                            .map_err(|e| e.to_string())
+                            .and_then(|r| r);
+
+                            match result {
+                                Ok(()) => info!("Fitted recording to {:?}", fit.aspect),
+                                Err(e) => {
+                                    error!("Failed to fit recording to {:?}: {e}", fit.aspect);
+
+                                    // the original gets uploaded instead
+                                    if let Ok(mut meta) =
+                                        RecordingMeta::load_for_project(&recording_dir)
+                                    {
+                                        if let RecordingMetaInner::Instant(inner) = &mut meta.inner
+                                        {
+                                            inner.aspect_fit = None;
+                                        }
+                                        meta.save_for_project().ok();
+                                    }
+                                }
+                            }
+                        }
+
+                        // made from the fitted video, so they're fitted too
+                        let share_rendition = if rendition_profiles.is_empty() {
+                            None
+                        } else {
+                            add_renditions(
+                                recording_dir.clone(),
+                                rendition_source,
+                                rendition_profiles,
+                                cancel.clone(),
+                            )
+                            .await
+                            .into_iter()
+                            .find(|rendition| rendition.share)
+                        };
+
+                        let video_upload_succeeded = match progressive_upload {
+                            Some(progressive_upload) => match progressive_upload
+                                .handle
+                                .await
+                                .map_err(|e| e.to_string())
+                                .and_then(|r| r)
+                            {
+                                Ok(()) => {
+                                    info!("Not attempting instant recording upload as progressive upload succeeded");
+                                    dashboard::set_upload_failed(&recording_dir, false);
+                                    true
+                                }
+                                Err(e) => {
+                                    error!("Progressive upload failed: {}", e);
+                                    // stays marked unless the fallback upload below succeeds
+                                    dashboard::set_upload_failed(&recording_dir, true);
+                                    false
+                                }
+                            },
+                            None => false,
+                        };
+
+                        let screenshot = match screenshot_task {
+                            Some(task) => {
+                                let _ = task.await;
+                                Some(display_screenshot)
+                            }
+                            None => None,
+                        };
+
+                        // the progressively uploaded original is replaced by the share rendition
+                        let uploads_share_rendition = share_rendition.is_some();
+                        let upload_path = share_rendition
+                            .map(|rendition| rendition.path.to_path(&recording_dir))
+                            .unwrap_or(output_path);
+
+                        let Some(video_upload_info) = &video_upload_info else {
+                            let pending = PendingUpload {
+                                project_path: recording_dir.clone(),
+                                upload_path,
+                                screenshot_path: screenshot,
+                                name: pretty_name,
+                                video_id: None,
+                            };
+                            match PendingUploadsStore::add(&app, pending) {
+                                Ok(()) => info!("Queued {} for upload", recording_dir.display()),
+                                Err(e) => error!("Failed to queue recording for upload: {e}"),
+                            }
+                            return;
+                        };
+
+                        if let Some(chapters_path) = chapters_path {
+                            match prepare_chapters_upload(
+                                &app,
+                                &video_upload_info.config,
+                                chapters_path,
+                            )
+                            .await
+                            {
+                                Ok(r) if r.status().is_success() => {
+                                    info!("Chapters uploaded successfully");
+                                }
+                                Ok(r) => {
+                                    error!("Failed to upload chapters: {}", r.status());
+                                }
+                                Err(e) => {
+                                    error!("Failed to upload chapters: {e}");
+                                }
+                            }
+                        }
+
+                        let preview_gif_created = match preview_gif_task {
+                            Some(task) => match task.await.map_err(|e| e.to_string()) {
+                                Ok(Ok(())) => true,
+                                Ok(Err(e)) | Err(e) => {
+                                    error!("Failed to create preview GIF: {e}");
+                                    false
+                                }
+                            },
+                            None => false,
+                        };
+
+                        if preview_gif_created {
+                            match prepare_gif_upload(&app, &video_upload_info.config, preview_gif)
+                                .await
+                            {
+                                Ok(r) if r.status().is_success() => {
+                                    info!("Preview GIF uploaded successfully");
+                                }
+                                Ok(r) => {
+                                    error!("Failed to upload preview GIF: {}", r.status());
+                                }
+                                Err(e) => {
+                                    error!("Failed to upload preview GIF: {e}");
+                                }
+                            }
+                        }
+
+                        if video_upload_succeeded && !uploads_share_rendition {
+                            let Some(screenshot) = screenshot else {
+                                return;
+                            };
+
+                            let resp = prepare_screenshot_upload(
+                                &app,
+                                &video_upload_info.config.clone(),
+                                screenshot,
+                            )
+                            .await;
+
+                            match resp {
+                                Ok(r)
+                                    if r.status().as_u16() >= 200 && r.status().as_u16() < 300 =>
+                                {
+                                    info!("Screenshot uploaded successfully");
+                                }
+                                Ok(r) => {
+                                    error!("Failed to upload screenshot: {}", r.status());
+                                }
+                                Err(e) => {
+                                    error!("Failed to upload screenshot: {e}");
+                                }
+                            }
+                        } else {
+                            // The upload_video function handles screenshot upload, so we can pass it along
+                            match upload_video(
+                                &app,
+                                video_upload_info.id.clone(),
+                                upload_path,
+                                Some(video_upload_info.config.clone()),
+                                screenshot,
+                            )
+                            .await
+                            {
+                                Ok(_) => {
+                                    info!("Final video upload completed successfully");
+                                    dashboard::set_upload_failed(&recording_dir, false);
+                                }
+                                Err(e) => {
+                                    error!("Error in final video upload: {}", e);
+                                    dashboard::set_upload_failed(&recording_dir, true);
+                                }
+                            }
+                        }
+                    };
+
+                    tokio::select! {
+                        _ = process => {}
+                        _ = cancel.cancelled() => {
+                            info!("Processing of {} cancelled", recording_dir.display());
+
+                            if let (Some(abort), Some(video_upload_info)) =
+                                (progressive_upload_abort, &video_upload_info)
+                            {
+                                abort.abort();
+                                ActiveUploads::remove(&app, &video_upload_info.id);
+                            }
+                            dashboard::set_upload_failed(&recording_dir, true);
+                        }
+                    }
+
+                    drop(processing);
+                }
+            });
+
+            (
+                RecordingMetaInner::Instant(meta),
+                video_upload_info.map(|info| SharingMeta {
+                    link: info.link,
+                    id: info.id,
+                }),
+            )
+        }
+    };
+
+    let meta = RecordingMeta {
+        platform: Some(Platform::default()),
+        project_path: recording_dir.clone(),
+        sharing,
+        pretty_name,
+        cover: None,
+        start_marker,
+        chapters,
+        // analyzed once the meta's saved, see save_quality_report
+        quality: None,
+        renditions: vec![],
+        inner: meta_inner,
+    };
+
+    meta.save_for_project()
+        .map_err(|e| format!("Failed to save recording meta: {e}"))?;
+
+    if let Some(marker) = start_marker {
+        RecordingStartMarkerCaptured {
+            project_path: recording_dir.clone(),
+            marker,
+        }
+        .emit(app)
+        .ok();
+    }
+
+    if let RecordingMetaInner::Studio(_) = meta.inner {
+        let rendition_profiles = GeneralSettingsStore::get(app)
+            .ok()
+            .flatten()
+            .map(|s| s.renditions)
+            .unwrap_or_default();
+        let processing = (!rendition_profiles.is_empty())
+            .then(|| ProcessingTasks::get(app).start(&recording_dir));
+        let inner = meta.inner.clone();
+        let recording_dir = recording_dir.clone();
+
+        // one after the other, as both save into the meta
+        tokio::spawn(async move {
+            save_quality_report(&recording_dir, quality_inputs).await;
+
+            if let Some(processing) = processing {
+                add_renditions(
+                    recording_dir,
+                    inner,
+                    rendition_profiles,
+                    processing.token().clone(),
+                )
+                .await;
+            }
+        });
+
+        match post_studio_behaviour.unwrap_or_else(|| {
+            GeneralSettingsStore::get(&app)
+                .ok()
+                .flatten()
+                .map(|v| v.post_studio_recording_behaviour)
+                .unwrap_or(PostStudioRecordingBehaviour::OpenEditor)
+        }) {
+            PostStudioRecordingBehaviour::OpenEditor => {
+                let _ = ShowCapWindow::Editor {
+                    project_path: recording_dir,
+                }
+                .show(&app)
+                .await;
+            }
+            PostStudioRecordingBehaviour::ShowOverlay => {
+                let _ = ShowCapWindow::RecordingsOverlay.show(&app).await;
+
+                let app = AppHandle::clone(app);
+                tokio::spawn(async move {
+                    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+                    let _ = NewStudioRecordingAdded {
+                        path: recording_dir.clone(),
+                    }
+                    .emit(&app);
+                });
+            }
+        };
+    }
+
+    // Play sound to indicate recording has stopped
+    play_recording_sound(app, AppSounds::StopRecording);
+
+    Ok(())
+}
+
+/// Version of the heuristic used to turn cursor activity into zoom segments.
+#[derive(Deserialize, Type, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ZoomAlgoVersion {
+    /// Pads each click and merges segments that overlap.
+    #[default]
+    ClickPaddingMerge,
+    /// Zooms in while the cursor is moving and releases once it settles.
+    MovementFollow,
+}
+
+const MIN_CURSOR_SIZE_MULTIPLIER: f32 = 0.5;
+const MAX_CURSOR_SIZE_MULTIPLIER: f32 = 4.0;
+
+const ZOOM_DURATION: f64 = 1.0;
+const ZOOM_SEGMENT_AFTER_CLICK_PADDING: f64 = 1.5;
+const MIN_AUTO_ZOOM_AMOUNT: f64 = 1.0;
+const MAX_AUTO_ZOOM_AMOUNT: f64 = 4.0;
+const ZOOM_FOLLOW_INTERVAL: f64 = 0.1;
+const ZOOM_FOLLOW_SMOOTHING_SECS: f64 = 0.3;
+
+/// The zoom amount for generated zoom segments, clamped to a sensible range
+fn auto_zoom_amount(general_settings: Option<&GeneralSettingsStore>) -> f64 {
+    let amount = general_settings.map_or(DEFAULT_AUTO_ZOOM_AMOUNT, |s| s.auto_zoom_amount);
+
+    if amount.is_finite() {
+        amount.clamp(MIN_AUTO_ZOOM_AMOUNT, MAX_AUTO_ZOOM_AMOUNT)
+    } else {
+        DEFAULT_AUTO_ZOOM_AMOUNT
+    }
+}
+
+/// Loads the cursor events of every segment, with `time_ms` shifted onto the editor timeline.
+fn timeline_cursor_events(
+    project_path: &Path,
+    meta: &StudioRecordingMeta,
+    recordings: &ProjectRecordingsMeta,
+) -> CursorEvents {
+    let mut events = CursorEvents::default();
+
+    let StudioRecordingMeta::MultipleSegments { inner } = meta else {
+        return events;
+    };
+
+    let mut timeline_offset = 0.0;
+
+    for (segment, segment_recordings) in inner.segments.iter().zip(&recordings.segments) {
+        if let Some(cursor_path) = &segment.cursor {
+            match CursorEvents::load_from_file(&cursor_path.to_path(project_path)) {
+                Ok(segment_events) => {
+                    // cursor times are relative to the start of the whole recording
+                    let shift_ms =
+                        (timeline_offset - segment.display.start_time.unwrap_or(0.0)) * 1000.0;
+
+                    events
+                        .clicks
+                        .extend(segment_events.clicks.into_iter().map(|mut click| {
+                            click.time_ms += shift_ms;
+                            click
+                        }));
+                    events
+                        .moves
+                        .extend(segment_events.moves.into_iter().map(|mut mv| {
+                            mv.time_ms += shift_ms;
+                            mv
+                        }));
+                    events
+                        .keys
+                        .extend(segment_events.keys.into_iter().map(|mut key| {
+                            key.time_ms += shift_ms;
+                            key
+                        }));
+                }
+                Err(e) => warn!("Failed to load cursor events for zoom generation: {e}"),
+            }
+        }
+
+        timeline_offset += segment_recordings.duration();
+    }
+
+    events
+        .clicks
+        .sort_by(|a, b| a.time_ms.total_cmp(&b.time_ms));
+    events.moves.sort_by(|a, b| a.time_ms.total_cmp(&b.time_ms));
+    events.keys.sort_by(|a, b| a.time_ms.total_cmp(&b.time_ms));
+
+    events
+}
+
+fn generate_zoom_segments_from_clicks(
+    project_path: &Path,
+    meta: &StudioRecordingMeta,
+    recordings: &ProjectRecordingsMeta,
+    algorithm: ZoomAlgoVersion,
+    amount: f64,
+) -> Vec<ZoomSegment> {
+    let events = timeline_cursor_events(project_path, meta, recordings);
+
+    auto_zoom_segments(&events, recordings.duration(), algorithm, amount)
+}
+
+fn auto_zoom_segments(
+    events: &CursorEvents,
+    max_duration: f64,
+    algorithm: ZoomAlgoVersion,
+    amount: f64,
+) -> Vec<ZoomSegment> {
+    let mut segments = merged_auto_zoom_segments(events, max_duration, algorithm, amount);
+
+    for segment in segments
+        .iter_mut()
+        .filter(|s| matches!(s.mode, ZoomMode::Auto))
+    {
+        segment.follow_path = zoom_follow_path(&events.moves, segment.start, segment.end);
+    }
+
+    segments
+}
+
+fn merged_auto_zoom_segments(
+    events: &CursorEvents,
+    max_duration: f64,
+    algorithm: ZoomAlgoVersion,
+    amount: f64,
+) -> Vec<ZoomSegment> {
+    let cursor_segments = match algorithm {
+        ZoomAlgoVersion::ClickPaddingMerge => {
+            click_padding_merge_zoom_segments(events, max_duration, amount)
+        }
+        ZoomAlgoVersion::MovementFollow => {
+            movement_follow_zoom_segments(events, max_duration, amount)
+        }
+    };
+
+    // typing zooms in half as far, it's usually around a larger area than a click
+    let typing_amount = 1.0 + (amount - 1.0) / 2.0;
+    let typing_segments = typing_zoom_segments(events, max_duration, typing_amount);
+
+    if typing_segments.is_empty() {
+        return cursor_segments;
+    }
+
+    let mut all = cursor_segments
+        .into_iter()
+        .chain(typing_segments)
+        .collect::<Vec<_>>();
+    all.sort_by(|a, b| a.start.total_cmp(&b.start));
+
+    let mut segments: Vec<ZoomSegment> = vec![];
+    for segment in all {
+        match segments.last_mut() {
+            Some(last) if last.end >= segment.start => {
+                last.end = last.end.max(segment.end);
+                last.amount = last.amount.max(segment.amount);
+            }
+            _ => segments.push(segment),
+        }
+    }
+
+    segments
+}
+
+/// Samples where a zoom from `start` to `end` should be centred every `ZOOM_FOLLOW_INTERVAL`,
+/// easing towards the cursor so the zoom pans smoothly rather than tracking every jitter
+fn zoom_follow_path(moves: &[CursorMoveEvent], start: f64, end: f64) -> Vec<ZoomFollowPoint> {
+    if moves.is_empty() || end <= start {
+        return vec![];
+    }
+
+    let target_for = |time: f64| {
+        let index = moves
+            .partition_point(|m| m.time_ms / 1000.0 <= time)
+            .saturating_sub(1);
+        XY::new(moves[index].x, moves[index].y)
+    };
+
+    let smoothing = 1.0 - (-ZOOM_FOLLOW_INTERVAL / ZOOM_FOLLOW_SMOOTHING_SECS).exp();
+    let mut position = target_for(start);
+    let mut points = vec![];
+
+    for i in 0.. {
+        let time = (start + i as f64 * ZOOM_FOLLOW_INTERVAL).min(end);
+
+        position = position + (target_for(time) - position) * smoothing;
+        points.push(ZoomFollowPoint { time, position });
+
+        if time >= end {
+            break;
+        }
+    }
+
+    points
+}
+
+/// Zooms in while the user's typing steadily, at least `TYPING_BURST_KEYS` keys
+/// within `TYPING_BURST_WINDOW` seconds
+fn typing_zoom_segments(events: &CursorEvents, max_duration: f64, amount: f64) -> Vec<ZoomSegment> {
+    const TYPING_BURST_KEYS: usize = 6;
+    const TYPING_BURST_WINDOW: f64 = 2.0;
+
+    let mut segments = vec![];
+
+    for burst in events.keys.windows(TYPING_BURST_KEYS) {
+        let start = burst[0].time_ms / 1000.0;
+        let end = burst[TYPING_BURST_KEYS - 1].time_ms / 1000.0;
+
+        if end - start > TYPING_BURST_WINDOW || start < 0.0 || start > max_duration {
+            continue;
+        }
+
+        push_or_extend_zoom_segment(
+            &mut segments,
+            (start - ZOOM_DURATION).max(0.0),
+            (end + ZOOM_SEGMENT_AFTER_CLICK_PADDING).min(max_duration),
+            amount,
+        );
+    }
+
+    segments
+}
+
+fn push_or_extend_zoom_segment(segments: &mut Vec<ZoomSegment>, start: f64, end: f64, amount: f64) {
+    match segments.last_mut() {
+        Some(last) if last.end >= start => last.end = last.end.max(end),
+        _ => segments.push(ZoomSegment {
+            start,
+            end,
+            amount,
+            mode: ZoomMode::Auto,
+            follow_path: vec![],
+        }),
+    }
+}
+
+fn click_padding_merge_zoom_segments(
+    events: &CursorEvents,
+    max_duration: f64,
+    amount: f64,
+) -> Vec<ZoomSegment> {
+    let mut segments = vec![];
+
+    for click in events.clicks.iter().filter(|c| c.down) {
+        let time = click.time_ms / 1000.0;
+
+        if time < 0.0 || time > max_duration {
+            continue;
+        }
+
+        push_or_extend_zoom_segment(
+            &mut segments,
+            (time - ZOOM_DURATION).max(0.0),
+            (time + ZOOM_SEGMENT_AFTER_CLICK_PADDING).min(max_duration),
+            amount,
+        );
+    }
+
+    segments
+}
+
+fn movement_follow_zoom_segments(
+    events: &CursorEvents,
+    max_duration: f64,
+    amount: f64,
+) -> Vec<ZoomSegment> {
+    // gap between moves after which the cursor is considered settled
+    const SETTLE_GAP: f64 = 0.5;
+    // minimum distance (in normalized screen space) for a burst to count as movement
+    const MIN_TRAVEL: f64 = 0.05;
+
+    let mut segments = vec![];
+    let mut bursts: Vec<(f64, f64, f64)> = vec![];
+
+    for pair in events.moves.windows(2) {
+        let (prev, next) = (&pair[0], &pair[1]);
+        let (prev_time, next_time) = (prev.time_ms / 1000.0, next.time_ms / 1000.0);
+        let distance = ((next.x - prev.x).powi(2) + (next.y - prev.y).powi(2)).sqrt();
+
+        match bursts.last_mut() {
+            Some((_, end, travel)) if next_time - *end <= SETTLE_GAP => {
+                *end = next_time;
+                *travel += distance;
+            }
+            // moves are only recorded when the cursor moves, so after a pause the
+            // movement starts at the next move rather than the last one before it
+            _ if next_time - prev_time > SETTLE_GAP => {
+                bursts.push((next_time, next_time, distance))
+            }
+            _ => bursts.push((prev_time, next_time, distance)),
+        }
+    }
+
+    for (start, end, travel) in bursts {
+        if travel < MIN_TRAVEL || start < 0.0 || start > max_duration {
+            continue;
+        }
+
+        push_or_extend_zoom_segment(
+            &mut segments,
+            (start - ZOOM_DURATION).max(0.0),
+            (end + SETTLE_GAP).min(max_duration),
+            amount,
+        );
+    }
+
+    segments
+}
+
+/// Re-runs zoom generation for an existing project without touching its saved configuration.
+#[tauri::command]
+#[specta::specta]
+pub async fn regenerate_zoom_segments(
+    app: AppHandle,
+    project_path: PathBuf,
+    algorithm: ZoomAlgoVersion,
+) -> Result<Vec<ZoomSegment>, String> {
+    let meta = RecordingMeta::load_for_project(&project_path).map_err(|e| e.to_string())?;
+    let Some(studio_meta) = meta.studio_meta() else {
+        return Err("Zoom segments can only be generated for studio recordings".to_string());
+    };
+
+    let recordings = ProjectRecordingsMeta::new(&project_path, studio_meta)?;
+
+    Ok(generate_zoom_segments_from_clicks(
+        &project_path,
+        studio_meta,
+        &recordings,
+        algorithm,
+        auto_zoom_amount(GeneralSettingsStore::get(&app).ok().flatten().as_ref()),
+    ))
+}
+
+/// Stores the pan path of each segment in the meta, returning the crop the editor should start with
+fn add_cursor_follow_paths(
+    project_path: &Path,
+    meta: &mut StudioRecordingMeta,
+    recordings: &ProjectRecordingsMeta,
+    size: XY<u32>,
+) -> Option<Crop> {
+    let StudioRecordingMeta::MultipleSegments { inner } = meta else {
+        return None;
+    };
+
+    for (segment, recording) in inner.segments.iter_mut().zip(&recordings.segments) {
+        let moves = segment
+            .cursor
+            .as_ref()
+            .and_then(|cursor| CursorEvents::load_from_file(&cursor.to_path(project_path)).ok())
+            .map(|events| events.moves)
+            .unwrap_or_default();
+
+        segment.cursor_follow = Some(CursorFollowPath::from_cursor_moves(
+            &moves,
+            XY::new(recording.display.width, recording.display.height),
+            size,
+        ));
+    }
+
+    let path = inner.segments.first()?.cursor_follow.as_ref()?;
+
+    Some(Crop {
+        position: path.position_at(0.0),
+        size: path.size,
+    })
+}
+
+/// Opens an instant recording's share link wherever the user's settings ask for it
+async fn open_share_link(app: &AppHandle, link: &str) {
+    let settings = GeneralSettingsStore::get(app)
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+
+    if settings.disable_auto_open_links {
+        return;
+    }
+
+    match settings.share_link_open_behaviour {
+        ShareLinkOpenBehaviour::Browser => {
+            open_external_link(app.clone(), link.to_string()).ok();
+        }
+        ShareLinkOpenBehaviour::CapWindow => {
+            let url = link.to_string();
+            if let Err(e) = (ShowCapWindow::SharePage { url }).show(app).await {
+                error!("Failed to open share link {link} in Cap: {e}");
+            }
+        }
+    }
+}
+
+/// Volume in dB that brings the system audio of all segments to `target_lufs`
+fn system_audio_normalization_gain(
+    project_path: &Path,
+    meta: &StudioRecordingMeta,
+    target_lufs: f32,
+) -> Option<f32> {
+    // limits how much near-silent tracks get boosted
+    const MAX_GAIN_DB: f32 = 20.0;
+
+    let StudioRecordingMeta::MultipleSegments { inner } = meta else {
+        return None;
+    };
+
+    let mut channels = None;
+    let mut samples = vec![];
+
+    for audio in inner
+        .segments
+        .iter()
+        .filter_map(|s| s.system_audio.as_ref())
+    {
+        let data = match AudioData::from_file(audio.path.to_path(project_path)) {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Failed to load system audio for normalization: {e}");
+                return None;
+            }
+        };
+
+        if *channels.get_or_insert(data.channels()) != data.channels() {
+            warn!("System audio channel count differs between segments, not normalizing");
+            return None;
+        }
+
+        samples.extend_from_slice(data.samples());
+    }
+
+    let loudness = cap_audio::integrated_loudness(&samples, channels?, AudioData::SAMPLE_RATE)?;
+
+    let gain = (target_lufs - loudness as f32).clamp(-MAX_GAIN_DB, MAX_GAIN_DB);
+    info!("System audio is {loudness:.1} LUFS, applying {gain:.1}dB");
+
+    Some(gain)
+}
+
+/// The name for a recording of `target_name` starting now, from `recording_name_template`
+fn recording_name(
+    general_settings: Option<&GeneralSettingsStore>,
+    target_name: &str,
+    mode: RecordingMode,
+) -> String {
+    let now = chrono::Local::now().naive_local();
+
+    general_settings
+        .map(|s| s.recording_name_template.trim())
+        .filter(|template| !template.is_empty())
+        .and_then(|template| render_recording_name(template, target_name, mode, now))
+        .unwrap_or_else(|| format!("{target_name} {}", now.format("%Y-%m-%d %H:%M:%S")))
+}
+
+/// None if the template has an unknown placeholder or an unclosed brace
+fn render_recording_name(
+    template: &str,
+    target_name: &str,
+    mode: RecordingMode,
+    time: chrono::NaiveDateTime,
+) -> Option<String> {
+    let mut name = String::new();
+    let mut rest = template;
+
+    while let Some(open) = rest.find('{') {
+        name.push_str(&rest[..open]);
+        let close = rest[open..].find('}')? + open;
+
+        match &rest[open + 1..close] {
+            "target" => name.push_str(target_name),
+            "date" => name.push_str(&time.format("%Y-%m-%d").to_string()),
+            "time" => name.push_str(&time.format("%H:%M:%S").to_string()),
+            "mode" => name.push_str(match mode {
+                RecordingMode::Studio => "Studio",
+                RecordingMode::Instant => "Instant",
+            }),
+            _ => return None,
+        }
+
+        rest = &rest[close + 1..];
+    }
+
+    if rest.contains('}') {
+        return None;
+    }
+    name.push_str(rest);
+
+    Some(name)
+}
+
+/// Audio quieter than this RMS amplitude, about -40dBFS, counts as silence
+const SILENCE_THRESHOLD: f32 = 0.01;
+/// Kept before the first sound when trimming leading silence, so it doesn't start abruptly
+const SILENCE_TRIM_LEAD_IN_SECS: f64 = 0.25;
+
+/// Seconds of silence at the start of the first segment, in the display's time.
+/// Uses the mic if there is one, otherwise the system audio.
+fn studio_leading_silence(project_path: &Path, meta: &StudioRecordingMeta) -> Option<f64> {
+    let (display_start, audio) = match meta {
+        StudioRecordingMeta::SingleSegment { segment } => {
+            (segment.display.start_time, segment.audio.as_ref()?)
+        }
+        StudioRecordingMeta::MultipleSegments { inner } => {
+            let first = inner.segments.first()?;
+            (
+                first.display.start_time,
+                first.mic.as_ref().or(first.system_audio.as_ref())?,
+            )
+        }
+    };
+
+    // the audio may have started a little before or after the display
+    let audio_offset = match (audio.start_time, display_start) {
+        (Some(audio_start), Some(display_start)) => audio_start - display_start,
+        _ => 0.0,
+    };
+
+    let silence = audio_leading_silence(&audio.path.to_path(project_path))?;
+
+    Some(silence + audio_offset).filter(|offset| *offset > 0.0)
+}
+
+/// Sets a finished instant recording's start trim to skip the silence at the start of its output
+fn trim_instant_leading_silence(meta: &mut InstantRecordingMeta, output_path: &Path) {
+    meta.trim_start_secs = audio_leading_silence(output_path);
+
+    if let Some(offset) = meta.trim_start_secs {
+        info!("Trimming {offset:.2}s of leading silence");
+    }
+}
+
+fn audio_leading_silence(path: &Path) -> Option<f64> {
+    match AudioData::from_file(path) {
+        Ok(audio) => {
+            leading_silence_secs(audio.samples(), audio.channels(), AudioData::SAMPLE_RATE)
+        }
+        Err(e) => {
+            warn!("Failed to load audio to trim leading silence: {e}");
+            None
+        }
+    }
+}
+
+/// Seconds before the first 10ms of interleaved `samples` louder than `SILENCE_THRESHOLD`,
+/// less a short lead-in. None if there's nothing to trim or no sound at all.
+fn leading_silence_secs(samples: &[f32], channels: u16, sample_rate: u32) -> Option<f64> {
+    let channels = channels.max(1) as usize;
+    let window = (sample_rate as usize / 100).max(1) * channels;
+
+    let first_sound = samples.chunks(window).position(|chunk| {
+        let rms = (chunk.iter().map(|s| s * s).sum::<f32>() / chunk.len() as f32).sqrt();
+        rms > SILENCE_THRESHOLD
+    })?;
+
+    let secs =
+        (first_sound * window / channels) as f64 / sample_rate as f64 - SILENCE_TRIM_LEAD_IN_SECS;
+
+    (secs > 0.0).then_some(secs)
+}
+
+/// Starts the timeline `offset` seconds into the first segment, unless it already starts later
+fn trim_timeline_start(config: &mut ProjectConfiguration, offset: f64) {
+    let Some(timeline) = &mut config.timeline else {
+        return;
+    };
+
+    let Some(first) = timeline.segments.first_mut() else {
+        return;
+    };
+
+    let start = offset.min(first.end);
+    if start <= first.start {
+        return;
+    }
+    let offset = start - first.start;
+    first.start = start;
+
+    // zoom segments are in timeline time, which now starts later
+    timeline.zoom_segments.retain_mut(|zoom| {
+        zoom.start = (zoom.start - offset).max(0.0);
+        zoom.end -= offset;
+        zoom.end > zoom.start
+    });
+}
+
+fn project_config_from_recording(
+    project_path: &Path,
+    meta: &StudioRecordingMeta,
+    recordings: &ProjectRecordingsMeta,
+    default_config: Option<ProjectConfiguration>,
+    zoom_algorithm: Option<ZoomAlgoVersion>,
+    zoom_amount: f64,
+) -> ProjectConfiguration {
+    let mut default_config = default_config.unwrap_or_default();
+
+    if let StudioRecordingMeta::MultipleSegments { inner } = meta {
+        if inner.camera_mirror {
+            default_config.camera.mirror = true;
+        }
+    }
+
+    ProjectConfiguration {
+        timeline: Some(TimelineConfiguration {
+            segments: recordings
+                .segments
+                .iter()
+                .enumerate()
+                .map(|(i, segment)| TimelineSegment {
+                    recording_segment: i as u32,
+                    start: 0.0,
+                    end: segment.duration(),
+                    timescale: 1.0,
+                })
+                .collect(),
+            zoom_segments: zoom_algorithm
+                .map(|algorithm| {
+                    generate_zoom_segments_from_clicks(
+                        project_path,
+                        meta,
+                        &recordings,
+                        algorithm,
+                        zoom_amount,
+                    )
+                })
+                .unwrap_or_default(),
+        }),
+        ..default_config
+    }
+}
+
+fn project_config_issues(
+    config: &ProjectConfiguration,
+    recordings: &ProjectRecordingsMeta,
+) -> Vec<String> {
+    let mut issues = vec![];
+
+    let Some(timeline) = &config.timeline else {
+        issues.push("Project has no timeline".to_string());
+        return issues;
+    };
+
+    if timeline.segments.is_empty() {
+        issues.push("Timeline has no segments".to_string());
+    }
+
+    for (i, segment) in timeline.segments.iter().enumerate() {
+        let Some(recording) = recordings.segments.get(segment.recording_segment as usize) else {
+            issues.push(format!(
+                "Timeline segment {i} references recording segment {} which doesn't exist",
+                segment.recording_segment
+            ));
+            continue;
+        };
+
+        if !(segment.timescale.is_finite() && segment.timescale > 0.0) {
+            issues.push(format!(
+                "Timeline segment {i} has invalid timescale {}",
+                segment.timescale
+            ));
+        }
+
+        if !(segment.start.is_finite() && segment.end.is_finite()) {
+            issues.push(format!(
+                "Timeline segment {i} has invalid trim {}s-{}s",
+                segment.start, segment.end
+            ));
+            continue;
+        }
+
+        if segment.start < 0.0 || segment.end > recording.duration() {
+            issues.push(format!(
+                "Timeline segment {i} trim {:.3}s-{:.3}s is outside of the recording's 0s-{:.3}s",
+                segment.start,
+                segment.end,
+                recording.duration()
+            ));
+        }
+
+        if segment.start >= segment.end {
+            issues.push(format!("Timeline segment {i} has no duration"));
+        }
+    }
+
+    let timeline_duration = timeline.duration();
+    for (i, zoom) in timeline.zoom_segments.iter().enumerate() {
+        if !(zoom.start.is_finite() && zoom.end.is_finite()) {
+            issues.push(format!(
+                "Zoom segment {i} has invalid range {}s-{}s",
+                zoom.start, zoom.end
+            ));
+        } else if zoom.start >= zoom.end || zoom.start < 0.0 || zoom.end > timeline_duration {
+            issues.push(format!(
+                "Zoom segment {i} {:.3}s-{:.3}s is outside of the timeline's 0s-{:.3}s",
+                zoom.start, zoom.end, timeline_duration
+            ));
+        }
+
+        if !(zoom.amount.is_finite() && zoom.amount >= 1.0) {
+            issues.push(format!(
+                "Zoom segment {i} has invalid amount {}",
+                zoom.amount
+            ));
+        }
+    }
+
+    issues
+}
+
+fn load_studio_project(
+    project_path: &PathBuf,
+) -> Result<(RecordingMeta, StudioRecordingMeta, ProjectRecordingsMeta), String> {
+    let meta = RecordingMeta::load_for_project(project_path).map_err(|e| e.to_string())?;
+    let Some(studio_meta) = meta.studio_meta().cloned() else {
+        return Err("Only studio recordings have a project configuration".to_string());
+    };
+
+    let recordings = ProjectRecordingsMeta::new(project_path, &studio_meta)?;
+
+    Ok((meta, studio_meta, recordings))
+}
+
+/// Cross-checks the project configuration against the recorded media, returning any problems found.
+#[tauri::command]
+#[specta::specta]
+pub async fn validate_project_config(project_path: PathBuf) -> Result<Vec<String>, String> {
+    let (meta, _, recordings) = load_studio_project(&project_path)?;
+
+    Ok(project_config_issues(&meta.project_config(), &recordings))
+}
+
+/// Clamps or removes invalid timeline and zoom entries and saves the result.
+///
+/// If no usable timeline segments remain, the timeline is regenerated as it would be
+/// for a freshly finished recording. Returns the issues that were repaired.
+#[tauri::command]
+#[specta::specta]
+pub async fn repair_project_config(
+    app: AppHandle,
+    project_path: PathBuf,
+) -> Result<Vec<String>, String> {
+    let (_, studio_meta, recordings) = load_studio_project(&project_path)?;
+
+    let mut config = ProjectConfiguration::load(&project_path).unwrap_or_default();
+    let issues = project_config_issues(&config, &recordings);
+
+    if issues.is_empty() {
+        return Ok(issues);
+    }
+
+    if let Some(timeline) = &mut config.timeline {
+        timeline.segments.retain_mut(|segment| {
+            let Some(recording) = recordings.segments.get(segment.recording_segment as usize)
+            else {
+                return false;
+            };
+
+            if !(segment.start.is_finite() && segment.end.is_finite()) {
+                return false;
+            }
+
+            if !(segment.timescale.is_finite() && segment.timescale > 0.0) {
+                segment.timescale = 1.0;
+            }
+
+            segment.start = segment.start.max(0.0);
+            segment.end = segment.end.min(recording.duration());
+
+            segment.start < segment.end
+        });
+
+        let timeline_duration = timeline.duration();
+        timeline.zoom_segments.retain_mut(|zoom| {
+            if !(zoom.start.is_finite() && zoom.end.is_finite()) {
+                return false;
+            }
+
+            zoom.start = zoom.start.max(0.0);
+            zoom.end = zoom.end.min(timeline_duration);
+
+            if !(zoom.amount.is_finite() && zoom.amount >= 1.0) {
+                zoom.amount = 1.0;
+            }
+
+            zoom.start < zoom.end
+        });
+    }
+
+    if config
+        .timeline
+        .as_ref()
+        .map(|t| t.segments.is_empty())
+        .unwrap_or(true)
+    {
+        config = project_config_from_recording(
+            &project_path,
+            &studio_meta,
+            &recordings,
+            Some(config),
+            Some(ZoomAlgoVersion::default()),
+            auto_zoom_amount(GeneralSettingsStore::get(&app).ok().flatten().as_ref()),
+        );
+    }
+
+    config.write(&project_path).map_err(|e| e.to_string())?;
+
+    Ok(issues)
+}
+
+#[cfg(test)]
+mod test {
+    use cap_project::{CursorClickEvent, CursorEvents};
+
+    use super::*;
+    use crate::{general_settings::CameraContentProtection, upload::S3UploadMeta};
+
+    fn clicks(times: &[(f64, bool)]) -> CursorEvents {
+        CursorEvents {
+            clicks: times
+                .iter()
+                .map(|(time, down)| CursorClickEvent {
+                    active_modifiers: vec![],
+                    cursor_num: 0,
+                    cursor_id: "0".to_string(),
+                    time_ms: time * 1000.0,
+                    down: *down,
+                })
+                .collect(),
+            moves: vec![],
+            keys: vec![],
+        }
+    }
+
+    fn inputs(mode: RecordingMode) -> StartRecordingInputs {
+        StartRecordingInputs {
+            capture_target: ScreenCaptureTarget::Screen { id: 1 },
+            capture_system_audio: false,
+            system_audio_device: None,
+            mode,
+            cursor_follow: None,
+            camera_only: false,
+            max_duration_secs: None,
+            output_dir: None,
+            countdown_secs: None,
+            defer_upload: false,
+            mic_device: None,
+            camera_device: None,
+            include_camera: true,
+            target_fps: None,
+        }
+    }
+
+    fn bounds(segments: &[ZoomSegment]) -> Vec<(f64, f64, f64)> {
+        segments
+            .iter()
+            .map(|s| (s.start, s.end, s.amount))
+            .collect()
+    }
+
+    #[test]
+    fn click_zoom_segments() {
+        let events = clicks(&[
+            (0.5, true),
+            (0.6, false),
+            (4.0, true),
+            (4.2, true),
+            (4.3, false),
+            (9.0, true),
+            (11.0, true),
+        ]);
+
+        assert_eq!(
+            bounds(&click_padding_merge_zoom_segments(&events, 10.0, 2.0)),
+            vec![
+                (0.0, 2.0, 2.0),
+                // clicks 200ms apart share a segment
+                (3.0, 4.2 + ZOOM_SEGMENT_AFTER_CLICK_PADDING, 2.0),
+                // near the end is cut short, past it is ignored
+                (8.0, 10.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn click_zoom_segments_merge_overlapping() {
+        let events = clicks(&[(2.0, true), (4.0, true), (6.5, true)]);
+
+        assert_eq!(
+            bounds(&click_padding_merge_zoom_segments(&events, 30.0, 2.0)),
+            vec![(1.0, 6.5 + ZOOM_SEGMENT_AFTER_CLICK_PADDING, 2.0)]
+        );
+    }
+
+    #[test]
+    fn auto_zoom_amount_clamped() {
+        let settings = |auto_zoom_amount| GeneralSettingsStore {
+            auto_zoom_amount,
+            ..Default::default()
+        };
+
+        assert_eq!(auto_zoom_amount(None), DEFAULT_AUTO_ZOOM_AMOUNT);
+        assert_eq!(auto_zoom_amount(Some(&settings(1.5))), 1.5);
+        assert_eq!(
+            auto_zoom_amount(Some(&settings(10.0))),
+            MAX_AUTO_ZOOM_AMOUNT
+        );
+        assert_eq!(auto_zoom_amount(Some(&settings(0.5))), MIN_AUTO_ZOOM_AMOUNT);
+        assert_eq!(
+            auto_zoom_amount(Some(&settings(f64::NAN))),
+            DEFAULT_AUTO_ZOOM_AMOUNT
+        );
+
+        // generated segments never end up with an amount the editor would reject
+        let events = clicks(&[(2.0, true)]);
+        let amount = auto_zoom_amount(Some(&settings(-3.0)));
+        assert!(click_padding_merge_zoom_segments(&events, 10.0, amount)
+            .iter()
+            .all(|zoom| zoom.amount.is_finite() && zoom.amount >= 1.0));
+    }
+
+    #[test]
+    fn output_dir_must_be_writable_directory() {
+        let dir = std::env::temp_dir().join(format!("cap-output-dir-{}", uuid::Uuid::new_v4()));
+
+        assert!(validate_output_dir(&dir).is_err());
+
+        std::fs::write(&dir, b"").unwrap();
+        assert!(validate_output_dir(&dir).is_err());
+        std::fs::remove_file(&dir).unwrap();
+
+        std::fs::create_dir(&dir).unwrap();
+        assert!(validate_output_dir(&dir).is_ok());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    fn started_recording_dir_is_named_after_id() {
+        let id = uuid::Uuid::new_v4().to_string();
+        let started = StartedRecording {
+            recording_dir: recording_dir_in(&std::env::temp_dir(), &id),
+            id,
+            queued: false,
+        };
+
+        assert_eq!(
+            started.recording_dir.file_stem().and_then(|s| s.to_str()),
+            Some(started.id.as_str())
+        );
+        assert_eq!(
+            crate::processing::recording_id(&started.recording_dir),
+            started.id
+        );
+    }
+
+    #[test]
+    fn max_duration_deadline_excludes_paused_time() {
+        let start = Instant::now();
+        let clock = RecordingClock(std::sync::Mutex::new(RecordingClockState {
+            started_at: start,
+            paused_at: None,
+            paused_for: Duration::ZERO,
+        }));
+        let limit = Duration::from_secs(10);
+
+        assert_eq!(clock.deadline(limit), Some(start + limit));
+
+        clock.pause_at(start + Duration::from_secs(2));
+        assert_eq!(clock.deadline(limit), None);
+
+        clock.resume_at(start + Duration::from_secs(5));
+        assert_eq!(
+            clock.deadline(limit),
+            Some(start + limit + Duration::from_secs(3))
+        );
+    }
+
+    #[test]
+    fn elapsed_excludes_paused_time() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let clock = RecordingClock(std::sync::Mutex::new(RecordingClockState {
+            started_at: start,
+            paused_at: None,
+            paused_for: Duration::ZERO,
+        }));
+
+        assert_eq!(clock.elapsed_at(at(4)), Duration::from_secs(4));
+
+        clock.pause_at(at(4));
+        assert_eq!(clock.elapsed_at(at(9)), Duration::from_secs(4));
+
+        clock.resume_at(at(10));
+        assert_eq!(clock.elapsed_at(at(12)), Duration::from_secs(6));
+    }
+
+    #[test]
+    fn system_audio_requires_platform_support() {
+        let supported = cap_recording::capabilities::capture_capabilities();
+        let supported = CaptureCapabilities {
+            system_audio: true,
+            system_audio_device: true,
+            ..supported
+        };
+        let unsupported = CaptureCapabilities {
+            system_audio: false,
+            system_audio_device: false,
+            ..supported.clone()
+        };
+
+        let without_audio = inputs(RecordingMode::Studio);
+        assert!(validate_system_audio(&without_audio, &supported).is_ok());
+        assert!(validate_system_audio(&without_audio, &unsupported).is_ok());
+
+        let with_audio = StartRecordingInputs {
+            capture_system_audio: true,
+            ..inputs(RecordingMode::Studio)
+        };
+        assert!(validate_system_audio(&with_audio, &supported).is_ok());
+        assert!(validate_system_audio(&with_audio, &unsupported).is_err());
+
+        let with_device = StartRecordingInputs {
+            system_audio_device: Some("BlackHole 2ch".to_string()),
+            ..with_audio
+        };
+        assert!(validate_system_audio(&with_device, &supported).is_ok());
+        assert!(validate_system_audio(&with_device, &unsupported).is_err());
+    }
+
+    #[test]
+    fn last_recording_inputs_skip_closed_windows() {
+        let window = LastRecordingInputs {
+            capture_target: ScreenCaptureTarget::Window { id: 7, crop: None },
+            mode: RecordingMode::Studio,
+        };
+        assert!(last_recording_inputs_available(window, &[3, 7]).is_some());
+        assert!(last_recording_inputs_available(window, &[3]).is_none());
+
+        let screen = LastRecordingInputs {
+            capture_target: ScreenCaptureTarget::Screen { id: 1 },
+            mode: RecordingMode::Instant,
+        };
+        assert!(last_recording_inputs_available(screen, &[]).is_some());
+    }
+
+    #[tokio::test]
+    async fn cancelled_countdown_doesnt_finish() {
+        let token = CancellationToken::new();
+        let countdown = tokio::spawn({
+            let token = token.clone();
+            async move { wait_for_countdown(Duration::from_secs(60), &token).await }
+        });
+
+        token.cancel();
+        assert!(!countdown.await.unwrap());
+
+        assert!(wait_for_countdown(Duration::from_millis(10), &CancellationToken::new()).await);
+    }
+
+    #[test]
+    fn restart_keeps_instant_link() {
+        let video_upload_info = VideoUploadInfo {
+            id: "video-id".to_string(),
+            link: "https://cap.so/s/video-id".to_string(),
+            config: S3UploadMeta::new("video-id".to_string()),
+        };
+        let recording_dir = std::env::temp_dir().join("recording-id.cap");
+
+        let restart = FailedStart::restart(
+            recording_dir.clone(),
+            video_upload_info.clone(),
+            inputs(RecordingMode::Instant),
+        );
+
+        assert_eq!(restart.id, "recording-id");
+        assert_eq!(restart.recording_dir, recording_dir);
+        let reused = restart.video_upload_info.unwrap();
+        assert_eq!(reused.id, video_upload_info.id);
+        assert_eq!(reused.link, video_upload_info.link);
+    }
+
+    #[tokio::test]
+    async fn restart_waits_only_until_released() {
+        let start = Instant::now();
+        let released = tokio::time::sleep(Duration::from_millis(200));
+
+        assert!(wait_for_release(released, Duration::from_secs(5))
+            .await
+            .is_ok());
+        assert!(start.elapsed() < Duration::from_secs(1));
+
+        let never_released = std::future::pending();
+        assert!(wait_for_release(never_released, Duration::from_millis(50))
+            .await
+            .is_err());
+    }
+
+    #[test]
+    fn delete_removes_recording_files() {
+        let recording_dir = std::env::temp_dir().join(format!("{}.cap", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(recording_dir.join("content")).unwrap();
+        std::fs::write(recording_dir.join("content/output.mp4"), b"partial").unwrap();
+
+        AbandonedFiles::Delete.apply(&recording_dir);
+        assert!(!recording_dir.exists());
+    }
+
+    #[test]
+    fn cancel_keeps_recording_files() {
+        let recording_dir = std::env::temp_dir().join(format!("{}.cap", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(recording_dir.join("content")).unwrap();
+        std::fs::write(recording_dir.join("content/output.mp4"), b"partial").unwrap();
+
+        AbandonedFiles::Keep.apply(&recording_dir);
+        assert_eq!(
+            std::fs::read(recording_dir.join("content/output.mp4")).unwrap(),
+            b"partial"
+        );
+
+        std::fs::remove_dir_all(&recording_dir).ok();
+    }
+
+    #[test]
+    fn recording_manifest_round_trips_inputs() {
+        let inputs = StartRecordingInputs {
+            capture_system_audio: true,
+            output_dir: Some(std::env::temp_dir()),
+            countdown_secs: Some(3),
+            ..inputs(RecordingMode::Instant)
+        };
+        let manifest = RecordingManifest {
+            inputs: inputs.clone(),
+            platform: Platform::MacOS,
+            os_version: "15.0".to_string(),
+            app_version: "0.3.0".to_string(),
+            mic_devices: vec!["MacBook Pro Microphone".to_string()],
+            camera_device: None,
+        };
+
+        let json = serde_json::to_value(&manifest).unwrap();
+        let restored: StartRecordingInputs =
+            serde_json::from_value(json["inputs"].clone()).unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&restored).unwrap(),
+            serde_json::to_value(&inputs).unwrap()
+        );
+        assert_eq!(json["micDevices"][0], "MacBook Pro Microphone");
+    }
+
+    #[test]
+    fn unknown_device_is_named_in_error() {
+        let mics = vec!["MacBook Pro Microphone".to_string()];
+
+        assert!(find_device("Microphone", "MacBook Pro Microphone", &mics).is_ok());
+        assert_eq!(
+            find_device("Microphone", "Shure MV7", &mics).unwrap_err(),
+            "Microphone 'Shure MV7' wasn't found, available devices are: MacBook Pro Microphone"
+        );
+        assert_eq!(
+            find_device("Camera", "FaceTime HD Camera", &[]).unwrap_err(),
+            "Camera 'FaceTime HD Camera' wasn't found, no devices are connected"
+        );
+    }
+
+    #[test]
+    fn camera_off_records_no_camera() {
+        let camera_off = StartRecordingInputs {
+            include_camera: false,
+            ..inputs(RecordingMode::Studio)
+        };
+
+        assert_eq!(recorded_camera(&camera_off, Some("camera")), None);
+        assert_eq!(
+            recorded_camera(&inputs(RecordingMode::Studio), Some("camera")),
+            Some("camera")
+        );
+
+        // inputs from before the option existed keep recording the camera
+        let json = serde_json::to_value(inputs(RecordingMode::Studio)).unwrap();
+        let mut json = json.as_object().unwrap().clone();
+        json.remove("include_camera");
+        let restored: StartRecordingInputs = serde_json::from_value(json.into()).unwrap();
+        assert!(restored.include_camera);
+    }
+
+    #[test]
+    fn always_protecting_camera_window_covers_instant_mode() {
+        let mut settings = GeneralSettingsStore::default();
+
+        assert!(!camera_window_protected(
+            Some(&settings),
+            Some(RecordingMode::Instant)
+        ));
+        assert!(camera_window_protected(
+            Some(&settings),
+            Some(RecordingMode::Studio)
+        ));
+
+        settings.camera_content_protection = CameraContentProtection::Always;
+        assert!(camera_window_protected(
+            Some(&settings),
+            Some(RecordingMode::Instant)
+        ));
+
+        settings.camera_content_protection = CameraContentProtection::Never;
+        assert!(!camera_window_protected(
+            Some(&settings),
+            Some(RecordingMode::Studio)
+        ));
+    }
+
+    fn environment() -> RecordingEnvironment {
+        RecordingEnvironment {
+            window_ids: vec![10],
+            screen_ids: vec![1],
+            mics: vec!["MacBook Pro Microphone".to_string()],
+            cameras: vec!["FaceTime HD Camera".to_string()],
+            signed_in: true,
+            available_space: None,
+        }
+    }
+
+    fn issue_severities(inputs: StartRecordingInputs) -> Vec<ValidationSeverity> {
+        recording_input_issues(&inputs, &environment())
+            .into_iter()
+            .map(|issue| issue.severity)
+            .collect()
+    }
+
+    #[test]
+    fn validates_recording_inputs() {
+        use ValidationSeverity::*;
+
+        assert!(issue_severities(StartRecordingInputs {
+            mic_device: Some("MacBook Pro Microphone".to_string()),
+            camera_device: Some("FaceTime HD Camera".to_string()),
+            output_dir: Some(std::env::temp_dir()),
+            ..inputs(RecordingMode::Instant)
+        })
+        .is_empty());
+
+        assert_eq!(
+            issue_severities(StartRecordingInputs {
+                capture_target: ScreenCaptureTarget::Window { id: 11, crop: None },
+                ..inputs(RecordingMode::Studio)
+            }),
+            vec![Error]
+        );
+        assert_eq!(
+            issue_severities(StartRecordingInputs {
+                capture_target: ScreenCaptureTarget::Screen { id: 2 },
+                ..inputs(RecordingMode::Studio)
+            }),
+            vec![Error]
+        );
+        assert_eq!(
+            issue_severities(StartRecordingInputs {
+                mic_device: Some("Shure MV7".to_string()),
+                ..inputs(RecordingMode::Studio)
+            }),
+            vec![Error]
+        );
+        assert_eq!(
+            issue_severities(StartRecordingInputs {
+                camera_device: Some("Elgato Facecam".to_string()),
+                ..inputs(RecordingMode::Studio)
+            }),
+            vec![Error]
+        );
+        assert_eq!(
+            issue_severities(StartRecordingInputs {
+                output_dir: Some(std::env::temp_dir().join(uuid::Uuid::new_v4().to_string())),
+                ..inputs(RecordingMode::Studio)
+            }),
+            vec![Error]
+        );
+
+        let unlisted = RecordingEnvironment {
+            window_ids: vec![],
+            screen_ids: vec![],
+            ..environment()
+        };
+        for capture_target in [
+            ScreenCaptureTarget::Window { id: 11, crop: None },
+            ScreenCaptureTarget::Screen { id: 2 },
+        ] {
+            assert!(recording_input_issues(
+                &StartRecordingInputs {
+                    capture_target,
+                    ..inputs(RecordingMode::Studio)
+                },
+                &unlisted
+            )
+            .is_empty());
+        }
+
+        let signed_out = RecordingEnvironment {
+            signed_in: false,
+            ..environment()
+        };
+        let issues = recording_input_issues(&inputs(RecordingMode::Instant), &signed_out);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Error);
+        assert!(recording_input_issues(&inputs(RecordingMode::Studio), &signed_out).is_empty());
+        assert_eq!(
+            recording_input_issues(
+                &StartRecordingInputs {
+                    defer_upload: true,
+                    ..inputs(RecordingMode::Instant)
+                },
+                &signed_out
+            )[0]
+            .severity,
+            Warning
+        );
+    }
+
+    #[test]
+    fn typing_bursts_zoom_in_less_than_clicks() {
+        let mut events = clicks(&[(2.0, true), (2.1, false)]);
+        let key_presses = |times: &[f64]| {
+            times
+                .iter()
+                .map(|time| cap_project::KeyPressEvent {
+                    time_ms: time * 1000.0,
+                })
+                .collect::<Vec<_>>()
+        };
+        // a burst right after the click, a couple of stray keys, then a burst of its own
+        events.keys = key_presses(&[
+            3.0, 3.2, 3.4, 3.6, 3.8, 4.0, 8.0, 9.5, 20.0, 20.3, 20.6, 20.9, 21.2, 21.5, 21.8,
+        ]);
+
+        let segments = auto_zoom_segments(&events, 30.0, ZoomAlgoVersion::ClickPaddingMerge, 2.0);
+
+        assert_eq!(bounds(&segments), vec![(1.0, 5.5, 2.0), (19.0, 23.3, 1.5)]);
+    }
+
+    #[test]
+    fn auto_zooms_follow_a_downsampled_cursor_path() {
+        let mut events = clicks(&[(2.0, true), (2.1, false)]);
+        // the cursor sweeping across the screen, polled every 10ms
+        events.moves = (0..1000)
+            .map(|i| CursorMoveEvent {
+                active_modifiers: vec![],
+                cursor_id: "0".to_string(),
+                time_ms: i as f64 * 10.0,
+                x: i as f64 / 1000.0,
+                y: 0.5,
+            })
+            .collect();
+
+        let segments = auto_zoom_segments(&events, 30.0, ZoomAlgoVersion::ClickPaddingMerge, 2.0);
+        let segment = &segments[0];
+        let path = &segment.follow_path;
+
+        assert!(path.windows(2).all(|w| w[0].time < w[1].time));
+        assert!(path.len() <= ((segment.end - segment.start) / ZOOM_FOLLOW_INTERVAL) as usize + 2);
+        assert_eq!(path.first().unwrap().time, segment.start);
+        assert_eq!(path.last().unwrap().time, segment.end);
+        // it pans along behind the cursor
+        assert!(path.windows(2).all(|w| w[0].position.x <= w[1].position.x));
+        assert!(segment.follow_position_at(3.0).unwrap().x < 0.3);
+    }
+
+    #[test]
+    fn leading_silence_is_trimmed_to_first_sound() {
+        let sample_rate = 48_000;
+        // 1.5s of near-silence, then a tone, in stereo
+        let samples = (0..sample_rate * 3)
+            .flat_map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                let sample = if t < 1.5 {
+                    0.001
+                } else {
+                    0.5 * (t * 440.0 * std::f32::consts::TAU).sin()
+                };
+                [sample, sample]
+            })
+            .collect::<Vec<_>>();
+
+        let trim = leading_silence_secs(&samples, 2, sample_rate).unwrap();
+        assert!((trim - (1.5 - SILENCE_TRIM_LEAD_IN_SECS)).abs() < 0.011);
+
+        // starting with sound leaves nothing to trim
+        assert_eq!(
+            leading_silence_secs(&samples[sample_rate as usize * 3..], 2, sample_rate),
+            None
+        );
+        assert_eq!(leading_silence_secs(&[0.0; 9600], 2, sample_rate), None);
+    }
+
+    #[test]
+    fn renders_recording_name_template() {
+        let time = chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(9, 26, 53)
+            .unwrap();
+
+        assert_eq!(
+            render_recording_name(
+                "{date}T{time} {mode} - {target}",
+                "Safari",
+                RecordingMode::Instant,
+                time
+            )
+            .as_deref(),
+            Some("2025-03-14T09:26:53 Instant - Safari")
+        );
+        assert_eq!(
+            render_recording_name("{target} {when}", "Safari", RecordingMode::Studio, time),
+            None
+        );
+        assert_eq!(
+            render_recording_name("{target", "Safari", RecordingMode::Studio, time),
+            None
+        );
+    }
+
+    #[test]
+    fn file_size_grows_with_output() {
+        let recording_dir = std::env::temp_dir().join(format!("{}.cap", uuid::Uuid::new_v4()));
+
+        assert_eq!(
+            recording_file_size(
+                &recording_dir,
+                RecordingMode::Instant,
+                InstantOutputFormat::Mp4
+            ),
+            0
+        );
+        assert_eq!(
+            recording_file_size(
+                &recording_dir,
+                RecordingMode::Studio,
+                InstantOutputFormat::Mp4
+            ),
+            0
+        );
+
+        std::fs::create_dir_all(recording_dir.join("content")).unwrap();
+        let output = recording_dir.join("content/output.mp4");
+        std::fs::write(&output, [0; 100]).unwrap();
+        assert_eq!(
+            recording_file_size(
+                &recording_dir,
+                RecordingMode::Instant,
+                InstantOutputFormat::Mp4
+            ),
+            100
+        );
+
+        std::fs::write(&output, [0; 250]).unwrap();
+        assert_eq!(
+            recording_file_size(
+                &recording_dir,
+                RecordingMode::Instant,
+                InstantOutputFormat::Mp4
+            ),
+            250
+        );
+
+        for segment in ["segment-0", "segment-1"] {
+            let dir = recording_dir.join("content/segments").join(segment);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("display.mp4"), [0; 40]).unwrap();
+            std::fs::write(dir.join("audio-input.ogg"), [0; 10]).unwrap();
+        }
+        assert_eq!(
+            recording_file_size(
+                &recording_dir,
+                RecordingMode::Studio,
+                InstantOutputFormat::Mp4
+            ),
+            100
+        );
+
+        std::fs::remove_dir_all(&recording_dir).ok();
+    }
+
+    #[test]
+    fn resolves_existing_recording_dir() {
+        let recordings_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let id = uuid::Uuid::new_v4().to_string();
+
+        assert!(existing_recording_dir(&recordings_dir, &id).is_err());
+
+        std::fs::create_dir_all(recordings_dir.join(format!("{id}.cap"))).unwrap();
+        assert_eq!(
+            existing_recording_dir(&recordings_dir, &id).unwrap(),
+            recordings_dir.join(format!("{id}.cap"))
+        );
+        assert!(existing_recording_dir(&recordings_dir, "../other").is_err());
+
+        std::fs::remove_dir_all(&recordings_dir).ok();
+    }
+
+    #[test]
+    fn occluder_is_skipped_when_disabled() {
+        assert!(occluder_enabled(None));
+        assert!(occluder_enabled(Some(&GeneralSettingsStore::default())));
+
+        let settings = GeneralSettingsStore {
+            show_capture_occluder: false,
+            ..Default::default()
+        };
+        assert!(!occluder_enabled(Some(&settings)));
+    }
+
+    #[test]
+    fn missing_window_display_is_an_error() {
+        assert_eq!(window_display(Some(2)), Ok(2));
+        assert!(window_display(None)
+            .unwrap_err()
+            .contains("may have been disconnected"));
+    }
+
+    #[test]
+    fn instant_recordings_cant_be_edited() {
+        let recordings_dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        let recording_dir = recordings_dir.join("instant.cap");
+        std::fs::create_dir_all(&recording_dir).unwrap();
+        std::fs::write(
+            recording_dir.join("recording-meta.json"),
+            serde_json::json!({ "pretty_name": "Instant", "fps": 30, "sample_rate": null })
+                .to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            studio_recording_dir(recording_dir, "instant"),
+            Err(
+                "Recording instant is an instant recording, only studio recordings can be edited"
+                    .to_string()
+            )
+        );
+
+        std::fs::remove_dir_all(&recordings_dir).ok();
+    }
+
+    #[test]
+    fn queued_recordings_start_in_order_once_one_ends() {
+        let mut queue = RecordingQueue::default();
+        let queued = |id: &str| QueuedRecording {
+            id: id.to_string(),
+            inputs: inputs(RecordingMode::Studio),
+        };
+
+        // nothing's queued until a recording's in progress
+        assert!(queue.next_after(RecordingEnd::Ended).is_none());
+
+        for i in 0..MAX_QUEUED_RECORDINGS {
+            queue.push(queued(&i.to_string())).unwrap();
+        }
+        assert!(queue.push(queued("too many")).is_err());
+
+        // the current recording ends
+        assert_eq!(
+            queue.next_after(RecordingEnd::Ended).map(|q| q.id),
+            Some("0".to_string())
+        );
+        queue.push(queued("later")).unwrap();
+
+        let started = std::iter::from_fn(|| queue.next_after(RecordingEnd::Ended))
+            .map(|q| q.id)
+            .collect::<Vec<_>>();
+        assert_eq!(started, vec!["1", "2", "later"]);
+    }
+
+    #[test]
+    fn cancelling_starts_the_next_queued_recording_but_resetting_discards_them() {
+        let mut queue = RecordingQueue::default();
+        for id in ["a", "b", "c"] {
+            queue
+                .push(QueuedRecording {
+                    id: id.to_string(),
+                    inputs: inputs(RecordingMode::Studio),
+                })
+                .unwrap();
+        }
+
+        // cancelling or deleting the recording in progress ends it like stopping does
+        assert_eq!(
+            queue.next_after(RecordingEnd::Ended).map(|q| q.id),
+            Some("a".to_string())
+        );
+
+        // resetting to idle doesn't start anything, and leaves nothing to start later
+        assert!(queue.next_after(RecordingEnd::Reset).is_none());
+        assert!(queue.next_after(RecordingEnd::Ended).is_none());
+        queue
+            .push(QueuedRecording {
+                id: "d".to_string(),
+                inputs: inputs(RecordingMode::Studio),
+            })
+            .unwrap();
+        assert_eq!(
+            queue.next_after(RecordingEnd::Ended).map(|q| q.id),
+            Some("d".to_string())
+        );
+    }
+
+    #[test]
+    fn unsupported_target_fps_is_clamped() {
+        let clamped = |target_fps, refresh_rate| {
+            clamp_target_fps(
+                StartRecordingInputs {
+                    target_fps,
+                    ..inputs(RecordingMode::Studio)
+                },
+                refresh_rate,
+            )
+            .target_fps
+        };
+
+        assert_eq!(clamped(Some(240), Some(60)), Some(60));
+        assert_eq!(clamped(Some(0), Some(60)), Some(1));
+        assert_eq!(clamped(Some(1000), None), Some(120));
+        assert_eq!(clamped(Some(30), Some(144)), Some(30));
+        assert_eq!(clamped(None, Some(60)), None);
+
+        let inputs = clamp_target_fps(
+            StartRecordingInputs {
+                target_fps: Some(240),
+                ..inputs(RecordingMode::Instant)
+            },
+            Some(60),
+        );
+        let manifest = RecordingManifest {
+            inputs,
+            platform: Platform::MacOS,
+            os_version: "15.0".to_string(),
+            app_version: "0.3.0".to_string(),
+            mic_devices: vec![],
+            camera_device: None,
+        };
+        let json = serde_json::to_value(&manifest).unwrap();
+        assert_eq!(json["inputs"]["target_fps"], 60);
+    }
+
+    #[test]
+    fn webm_instant_recordings_cant_be_fitted() {
+        let settings = |output_aspect, instant_output_format| GeneralSettingsStore {
+            output_aspect,
+            instant_output_format,
+            ..Default::default()
+        };
+
+        let webm_fitted = settings(Some((9, 16)), InstantOutputFormat::WebM);
+        assert!(validate_output_aspect(Some(&webm_fitted), RecordingMode::Instant).is_err());
+        // studio recordings aren't fitted
+        assert!(validate_output_aspect(Some(&webm_fitted), RecordingMode::Studio).is_ok());
+
+        let mp4_fitted = settings(Some((9, 16)), InstantOutputFormat::Mp4);
+        assert!(validate_output_aspect(Some(&mp4_fitted), RecordingMode::Instant).is_ok());
+        let webm = settings(None, InstantOutputFormat::WebM);
+        assert!(validate_output_aspect(Some(&webm), RecordingMode::Instant).is_ok());
+    }
+
+    #[test]
+    fn studio_recordings_are_never_vp9() {
+        let mut settings = GeneralSettingsStore::default();
+        assert_eq!(studio_video_codec(None), Ok(VideoCodec::H264));
+
+        settings.studio_video_codec = VideoCodec::H265;
+        assert_eq!(studio_video_codec(Some(&settings)), Ok(VideoCodec::H265));
+
+        settings.studio_video_codec = VideoCodec::Vp9;
+        assert!(studio_video_codec(Some(&settings)).is_err());
+    }
+
+    #[tokio::test]
+    async fn cancelled_countdown_creates_no_recording_dir() {
+        let dir = std::env::temp_dir().join(format!("cap-countdown-{}", uuid::Uuid::new_v4()));
+
+        let token = CancellationToken::new();
+        token.cancel();
+        let cancelled = recording_dir_in(&dir, "cancelled");
+        let countdown = async { Ok(wait_for_countdown(Duration::from_secs(60), &token).await) };
+        assert!(!prepare_recording_dir(&cancelled, countdown, false)
+            .await
+            .unwrap());
+        assert!(!cancelled.exists());
+
+        let finished = recording_dir_in(&dir, "finished");
+        let countdown = async {
+            Ok(wait_for_countdown(Duration::from_millis(10), &CancellationToken::new()).await)
+        };
+        assert!(prepare_recording_dir(&finished, countdown, false)
+            .await
+            .unwrap());
+        assert!(finished.exists());
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn system_audio_device_needs_system_audio() {
+        let mut inputs = StartRecordingInputs {
+            system_audio_device: Some("BlackHole 2ch".to_string()),
+            ..inputs(RecordingMode::Studio)
+        };
+        assert_eq!(inputs.system_audio_device(), None);
+
+        inputs.capture_system_audio = true;
+        assert_eq!(
+            inputs.system_audio_device().map(String::as_str),
+            Some("BlackHole 2ch")
+        );
+    }
+
+    fn sweep(from_ms: u32, to_ms: u32, from_x: f64, to_x: f64) -> Vec<CursorMoveEvent> {
+        let steps = (to_ms - from_ms) / 100;
+
+        (0..=steps)
+            .map(|i| CursorMoveEvent {
+                active_modifiers: vec![],
+                cursor_id: "0".to_string(),
+                time_ms: (from_ms + i * 100) as f64,
+                x: from_x + (to_x - from_x) * i as f64 / steps as f64,
+                y: 0.5,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn movement_follow_zooms_while_the_cursor_moves() {
+        let mut events = clicks(&[]);
+        events.moves = [
+            sweep(2000, 3000, 0.1, 0.5),
+            // jitter while the cursor's resting isn't movement
+            sweep(6000, 6200, 0.5, 0.501),
+            // movement starts once it's picked up again, and is cut short at the end
+            sweep(9500, 10500, 0.5, 0.9),
+        ]
+        .concat();
+
+        assert_eq!(
+            bounds(&movement_follow_zoom_segments(&events, 10.0, 2.0)),
+            vec![(1.0, 3.5, 2.0), (8.5, 10.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn movement_follow_merges_bursts_close_together() {
+        let mut events = clicks(&[]);
+        events.moves = [sweep(2000, 3000, 0.1, 0.5), sweep(3800, 4500, 0.5, 0.2)].concat();
+
+        assert_eq!(
+            bounds(&movement_follow_zoom_segments(&events, 30.0, 2.0)),
+            vec![(1.0, 5.0, 2.0)]
+        );
+    }
+
+    #[test]
+    fn low_disk_space_is_reported() {
+        let with_space = |secs: u64| {
+            recording_input_issues(
+                &inputs(RecordingMode::Studio),
+                &RecordingEnvironment {
+                    available_space: Some(secs * ESTIMATED_RECORDING_BYTES_PER_SEC),
+                    ..environment()
+                },
+            )
+        };
+
+        let too_little = with_space(30);
+        assert_eq!(too_little.len(), 1);
+        assert_eq!(too_little[0].severity, ValidationSeverity::Error);
+
+        assert_eq!(
+            with_space(5 * 60),
+            vec![ValidationIssue {
+                severity: ValidationSeverity::Warning,
+                message: "Disk space is running low, enough for about 5 minutes of recording"
+                    .to_string(),
+            }]
+        );
+
+        assert!(with_space(60 * 60).is_empty());
+    }
+
+    #[test]
+    fn instant_recording_meta_gets_the_leading_silence_trim() {
+        let dir = tempfile::tempdir().unwrap();
+        let output_path = dir.path().join("output.wav");
+
+        // 2s of near-silence then a tone, as 16-bit mono PCM
+        let sample_rate = 48_000u32;
+        let samples = (0..sample_rate * 4)
+            .map(|i| {
+                let t = i as f32 / sample_rate as f32;
+                let sample = if t < 2.0 {
+                    0.001
+                } else {
+                    0.5 * (t * 440.0 * std::f32::consts::TAU).sin()
+                };
+                (sample * i16::MAX as f32) as i16
+            })
+            .collect::<Vec<_>>();
+        let data_len = samples.len() as u32 * 2;
+        let mut wav = vec![];
+        wav.extend(b"RIFF");
+        wav.extend((36 + data_len).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(sample_rate.to_le_bytes());
+        wav.extend((sample_rate * 2).to_le_bytes());
+        wav.extend(2u16.to_le_bytes());
+        wav.extend(16u16.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend(data_len.to_le_bytes());
+        wav.extend(samples.iter().flat_map(|s| s.to_le_bytes()));
+        std::fs::write(&output_path, wav).unwrap();
+
+        let mut meta = InstantRecordingMeta {
+            fps: 30,
+            sample_rate: Some(sample_rate),
+            aspect_fit: None,
+            capture_scale: None,
+            variable_frame_rate: false,
+            trim_start_secs: None,
+            output_format: InstantOutputFormat::Mp4,
+        };
+        trim_instant_leading_silence(&mut meta, &output_path);
+
+        let trim = meta.trim_start_secs.unwrap();
+        assert!(
+            (trim - (2.0 - SILENCE_TRIM_LEAD_IN_SECS)).abs() < 0.011,
+            "{trim}"
+        );
+        // the recording itself is left as it was
+        assert_eq!(
+            std::fs::metadata(&output_path).unwrap().len(),
+            44 + data_len as u64
+        );
+    }
+
+    #[tokio::test]
+    async fn retried_start_reuses_the_failed_ones_directory_and_video() {
+        let output_dir = tempfile::tempdir().unwrap();
+        let mut instant = inputs(RecordingMode::Instant);
+        instant.output_dir = Some(output_dir.path().to_path_buf());
+
+        // the first attempt creates its directory and video, then the actor fails to spawn
+        let recording_dir = recording_dir_in(output_dir.path(), "recording-id");
+        assert!(
+            prepare_recording_dir(&recording_dir, async { Ok(true) }, false)
+                .await
+                .unwrap()
+        );
+        std::fs::write(recording_dir.join("recording-logs.log"), b"failed").unwrap();
+        let failed = FailedStart {
+            id: "recording-id".to_string(),
+            recording_dir: recording_dir.clone(),
+            video_upload_info: Some(VideoUploadInfo {
+                id: "video-id".to_string(),
+                link: "https://cap.so/s/video-id".to_string(),
+                config: S3UploadMeta::new("video-id".to_string()),
+            }),
+            inputs: instant.clone(),
+        };
+
+        // other inputs, or saving somewhere else, start afresh
+        assert!(!failed.is_retried_by(&inputs(RecordingMode::Studio), &recording_dir));
+        let elsewhere = tempfile::tempdir().unwrap();
+        assert!(!failed.is_retried_by(&instant, &recording_dir_in(elsewhere.path(), &failed.id)));
+
+        let retry_dir = recording_dir_in(output_dir.path(), &failed.id);
+        assert!(failed.is_retried_by(&instant, &retry_dir));
+        assert!(prepare_recording_dir(&retry_dir, async { Ok(true) }, true)
+            .await
+            .unwrap());
+
+        // the retry records over what the failed attempt left, with its video
+        assert_eq!(std::fs::read_dir(output_dir.path()).unwrap().count(), 1);
+        assert_eq!(std::fs::read_dir(&retry_dir).unwrap().count(), 0);
+        assert_eq!(failed.video_upload_info.unwrap().id, "video-id");
+    }
+
+    #[test]
+    fn audio_sample_rate_is_checked_against_the_encoder() {
+        let settings = |audio_sample_rate, instant_output_format| GeneralSettingsStore {
+            audio_sample_rate,
+            instant_output_format,
+            ..Default::default()
+        };
+
+        // studio audio is Opus, which has no 44.1kHz rate
+        let cd_rate = settings(44_100, InstantOutputFormat::Mp4);
+        assert!(audio_sample_rate(Some(&cd_rate), RecordingMode::Studio).is_err());
+        assert_eq!(
+            audio_sample_rate(Some(&cd_rate), RecordingMode::Instant),
+            Ok(44_100)
+        );
+        assert!(audio_sample_rate(
+            Some(&settings(44_100, InstantOutputFormat::WebM)),
+            RecordingMode::Instant
+        )
+        .is_err());
+
+        let default_rate = settings(48_000, InstantOutputFormat::WebM);
+        assert_eq!(
+            audio_sample_rate(Some(&default_rate), RecordingMode::Studio),
+            Ok(48_000)
+        );
+        assert_eq!(audio_sample_rate(None, RecordingMode::Studio), Ok(48_000));
     }
 }
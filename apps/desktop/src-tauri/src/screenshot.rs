@@ -0,0 +1,371 @@
+use std::{path::Path, path::PathBuf, time::SystemTime};
+
+use cap_media::sources::{AVFrameCapture, ScreenCaptureSource, ScreenCaptureTarget};
+use image::{
+    codecs::{
+        gif::{GifEncoder, Repeat},
+        jpeg::JpegEncoder,
+    },
+    Delay, ExtendedColorType, Frame, RgbaImage,
+};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::{
+    general_settings::{GeneralSettingsStore, DEFAULT_SCREENSHOT_QUALITY},
+    recording::show_capture_occluder,
+    screenshots_path, App, MutableState,
+};
+
+const SCREENSHOT_QUALITY: u8 = 90;
+// only limits how long the first frame takes to arrive
+const SCREENSHOT_FPS: u32 = 30;
+
+const PREVIEW_GIF_SECS: f64 = 3.0;
+const PREVIEW_GIF_FPS: u32 = 10;
+const PREVIEW_GIF_MAX_WIDTH: u32 = 480;
+
+/// Saves a still of `target` to the screenshots folder as a JPEG, without recording.
+/// Window and area targets are cropped just like they would be when recording, and
+/// show the occluder while they're captured.
+#[tauri::command]
+#[specta::specta]
+pub async fn capture_screenshot(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    target: ScreenCaptureTarget,
+) -> Result<PathBuf, String> {
+    let excluded_apps = GeneralSettingsStore::get(&app)
+        .ok()
+        .flatten()
+        .map(|s| s.excluded_apps)
+        .unwrap_or_default();
+
+    let (video_tx, _) = flume::bounded(1);
+    let source = ScreenCaptureSource::<AVFrameCapture>::init(
+        &target,
+        None,
+        false,
+        false,
+        SCREENSHOT_FPS,
+        &excluded_apps,
+        video_tx,
+        None,
+        SystemTime::now(),
+    )
+    .await?;
+
+    if let Err(e) = show_capture_occluder(&app, &target).await {
+        warn!("Failed to show occluder for screenshot: {e}");
+    }
+
+    let frame = tokio::task::spawn_blocking(move || source.capture_frame())
+        .await
+        .map_err(|e| e.to_string());
+
+    state.read().await.close_occluder_windows();
+
+    let (width, height, bgra) = frame??;
+
+    let path = screenshots_path(&app)?.join(format!("{}.jpg", uuid::Uuid::new_v4()));
+    tokio::task::spawn_blocking({
+        let path = path.clone();
+        move || save_jpeg(&path, width, height, &bgra)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+
+    info!("Saved screenshot to {}", path.display());
+
+    Ok(path)
+}
+
+/// The configured quality of screenshots made of finished recordings, kept between 1 and 100
+pub fn screenshot_quality(general_settings: Option<&GeneralSettingsStore>) -> u8 {
+    general_settings
+        .map_or(DEFAULT_SCREENSHOT_QUALITY, |s| s.screenshot_quality)
+        .clamp(1, 100)
+}
+
+/// Quality screenshots are uploaded at, 30 by default
+pub fn upload_screenshot_quality(screenshot_quality: u8) -> u8 {
+    (screenshot_quality as u32 * 2 / 5).clamp(1, 100) as u8
+}
+
+/// Makes a small looping GIF of the first few seconds of the video at `input`
+pub async fn create_preview_gif(input: PathBuf, output: PathBuf) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let frames = extract_preview_frames(&input)?;
+        save_gif(&output, frames, PREVIEW_GIF_FPS)?;
+
+        info!("Saved preview GIF to {}", output.display());
+        Ok(())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Picks frames a GIF frame apart until the preview's long enough
+#[derive(Default)]
+struct PreviewSampler {
+    /// timestamp of the first frame, as videos don't necessarily start at 0
+    start: Option<f64>,
+    next: f64,
+}
+
+impl PreviewSampler {
+    /// Whether the frame at `timestamp` seconds is kept, `None` once the preview's full
+    fn sample(&mut self, timestamp: f64) -> Option<bool> {
+        let timestamp = timestamp - *self.start.get_or_insert(timestamp);
+
+        if timestamp >= PREVIEW_GIF_SECS {
+            return None;
+        }
+
+        if timestamp < self.next {
+            return Some(false);
+        }
+
+        self.next += 1.0 / PREVIEW_GIF_FPS as f64;
+        Some(true)
+    }
+}
+
+fn extract_preview_frames(input: &Path) -> Result<Vec<RgbaImage>, String> {
+    ffmpeg::init().map_err(|e| e.to_string())?;
+
+    let mut ictx = ffmpeg::format::input(&input)
+        .map_err(|e| format!("Failed to open {}: {e}", input.display()))?;
+    let stream = ictx
+        .streams()
+        .best(ffmpeg::media::Type::Video)
+        .ok_or("No video stream found")?;
+    let stream_index = stream.index();
+    let time_base = f64::from(stream.time_base());
+
+    let mut decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+        .and_then(|context| context.decoder().video())
+        .map_err(|e| format!("Failed to create video decoder: {e}"))?;
+
+    let width = decoder.width().min(PREVIEW_GIF_MAX_WIDTH);
+    let height = (decoder.height() * width / decoder.width().max(1)).max(1);
+    let mut scaler = ffmpeg::software::scaling::context::Context::get(
+        decoder.format(),
+        decoder.width(),
+        decoder.height(),
+        ffmpeg::format::Pixel::RGBA,
+        width,
+        height,
+        ffmpeg::software::scaling::flag::Flags::BILINEAR,
+    )
+    .map_err(|e| format!("Failed to create scaler: {e}"))?;
+
+    let mut sampler = PreviewSampler::default();
+    let mut frames = vec![];
+    let mut frame = ffmpeg::frame::Video::empty();
+
+    // takes the frames the decoder has ready, returning false once the preview's full
+    let mut receive_frames = |decoder: &mut ffmpeg::decoder::Video| -> Result<bool, String> {
+        while decoder.receive_frame(&mut frame).is_ok() {
+            let timestamp = frame.timestamp().unwrap_or_default() as f64 * time_base;
+            match sampler.sample(timestamp) {
+                Some(true) => {}
+                Some(false) => continue,
+                None => return Ok(false),
+            }
+
+            let mut rgba = ffmpeg::frame::Video::empty();
+            scaler
+                .run(&frame, &mut rgba)
+                .map_err(|e| format!("Failed to scale frame: {e}"))?;
+
+            let row_len = width as usize * 4;
+            let pixels = rgba
+                .data(0)
+                .chunks(rgba.stride(0))
+                .take(height as usize)
+                .flat_map(|row| &row[..row_len])
+                .copied()
+                .collect();
+            frames.push(RgbaImage::from_raw(width, height, pixels).ok_or("Failed to read frame")?);
+        }
+
+        Ok(true)
+    };
+
+    let mut full = false;
+    for (stream, packet) in ictx.packets() {
+        if stream.index() != stream_index {
+            continue;
+        }
+
+        decoder
+            .send_packet(&packet)
+            .map_err(|e| format!("Failed to decode frame: {e}"))?;
+
+        if !receive_frames(&mut decoder)? {
+            full = true;
+            break;
+        }
+    }
+
+    // videos shorter than the preview still have frames buffered in the decoder
+    if !full {
+        decoder
+            .send_eof()
+            .map_err(|e| format!("Failed to flush decoder: {e}"))?;
+        receive_frames(&mut decoder)?;
+    }
+
+    if frames.is_empty() {
+        return Err("No frames to make a preview from".to_string());
+    }
+
+    Ok(frames)
+}
+
+fn save_gif(path: &Path, frames: Vec<RgbaImage>, fps: u32) -> Result<(), String> {
+    let file =
+        std::fs::File::create(path).map_err(|e| format!("Failed to create preview GIF: {e}"))?;
+
+    let mut encoder = GifEncoder::new(std::io::BufWriter::new(file));
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .and_then(|_| {
+            encoder.encode_frames(
+                frames.into_iter().map(|frame| {
+                    Frame::from_parts(frame, 0, 0, Delay::from_numer_denom_ms(1000, fps))
+                }),
+            )
+        })
+        .map_err(|e| format!("Failed to encode preview GIF: {e}"))
+}
+
+fn save_jpeg(path: &Path, width: u32, height: u32, bgra: &[u8]) -> Result<(), String> {
+    let rgb = bgra
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[2], pixel[1], pixel[0]])
+        .collect::<Vec<_>>();
+
+    save_rgb_jpeg(path, width, height, &rgb, SCREENSHOT_QUALITY)
+}
+
+/// Writes packed RGB pixels to `path` as a JPEG at `quality`, from 1 to 100
+pub fn save_rgb_jpeg(
+    path: &Path,
+    width: u32,
+    height: u32,
+    rgb: &[u8],
+    quality: u8,
+) -> Result<(), String> {
+    let file =
+        std::fs::File::create(path).map_err(|e| format!("Failed to create screenshot: {e}"))?;
+
+    JpegEncoder::new_with_quality(std::io::BufWriter::new(file), quality)
+        .encode(rgb, width, height, ExtendedColorType::Rgb8)
+        .map_err(|e| format!("Failed to encode screenshot: {e}"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn saves_a_valid_jpeg() {
+        let path = std::env::temp_dir().join(format!("{}.jpg", uuid::Uuid::new_v4()));
+        let bgra = [255, 0, 0, 255].repeat(16 * 8);
+
+        save_jpeg(&path, 16, 8, &bgra).unwrap();
+
+        assert!(path.exists());
+        let image = image::ImageReader::open(&path)
+            .unwrap()
+            .with_guessed_format()
+            .unwrap();
+        assert_eq!(image.format(), Some(image::ImageFormat::Jpeg));
+
+        let image = image.decode().unwrap().to_rgb8();
+        assert_eq!(image.dimensions(), (16, 8));
+        // blue in BGRA
+        assert!(image.get_pixel(0, 0)[2] > 200);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn lower_quality_makes_smaller_screenshots() {
+        let (width, height) = (64, 64);
+        // noise, so there's detail for the quality to make a difference to
+        let rgb = (0..width * height * 3)
+            .map(|i| (i * 7919 % 251) as u8)
+            .collect::<Vec<_>>();
+
+        let size_at = |quality: u8| {
+            let path = std::env::temp_dir().join(format!("{}.jpg", uuid::Uuid::new_v4()));
+            save_rgb_jpeg(&path, width, height, &rgb, quality).unwrap();
+            let size = std::fs::metadata(&path).unwrap().len();
+            std::fs::remove_file(&path).ok();
+            size
+        };
+
+        assert!(size_at(20) < size_at(DEFAULT_SCREENSHOT_QUALITY));
+        assert!(size_at(DEFAULT_SCREENSHOT_QUALITY) < size_at(100));
+
+        let settings = GeneralSettingsStore {
+            screenshot_quality: 0,
+            ..Default::default()
+        };
+        assert_eq!(screenshot_quality(Some(&settings)), 1);
+        assert_eq!(screenshot_quality(None), DEFAULT_SCREENSHOT_QUALITY);
+        assert_eq!(upload_screenshot_quality(DEFAULT_SCREENSHOT_QUALITY), 30);
+    }
+
+    #[test]
+    fn preview_gif_has_a_frame_per_tick() {
+        // a 30fps video longer than the preview
+        let mut sampler = PreviewSampler::default();
+        let frames = (0..150)
+            .map(|i| i as f64 / 30.0)
+            .map_while(|timestamp| sampler.sample(timestamp))
+            .filter(|keep| *keep)
+            .map(|_| RgbaImage::from_pixel(32, 16, image::Rgba([255, 0, 0, 255])))
+            .collect::<Vec<_>>();
+
+        let expected = (PREVIEW_GIF_SECS * PREVIEW_GIF_FPS as f64) as usize;
+        assert_eq!(frames.len(), expected);
+
+        let path = std::env::temp_dir().join(format!("{}.gif", uuid::Uuid::new_v4()));
+        save_gif(&path, frames, PREVIEW_GIF_FPS).unwrap();
+
+        let decoder = image::codecs::gif::GifDecoder::new(std::io::BufReader::new(
+            std::fs::File::open(&path).unwrap(),
+        ))
+        .unwrap();
+        let frames = image::AnimationDecoder::into_frames(decoder)
+            .collect_frames()
+            .unwrap();
+        assert_eq!(frames.len(), expected);
+        assert_eq!(frames[0].buffer().dimensions(), (32, 16));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn preview_starts_at_the_first_frame() {
+        let kept = |start: f64| {
+            let mut sampler = PreviewSampler::default();
+            // a 32fps video, so the offset timestamps are exact
+            (0..160)
+                .map(|i| start + i as f64 / 32.0)
+                .map_while(|timestamp| sampler.sample(timestamp))
+                .filter(|keep| *keep)
+                .count()
+        };
+
+        assert_eq!(kept(16.0), kept(0.0));
+        assert_eq!(
+            kept(16.0),
+            (PREVIEW_GIF_SECS * PREVIEW_GIF_FPS as f64) as usize
+        );
+    }
+}
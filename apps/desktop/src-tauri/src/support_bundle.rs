@@ -0,0 +1,250 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use cap_media::sources::CaptureScreen;
+use cap_project::{ProjectConfiguration, RecordingMeta};
+use serde::Serialize;
+use tauri::AppHandle;
+use tracing::info;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use crate::auth::{AuthSecret, AuthStore};
+
+const MEDIA_EXTENSIONS: &[&str] = &["mp4", "mov", "webm", "mp3", "m4a", "ogg", "wav"];
+const REDACTED: &str = "[redacted]";
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemInfo {
+    cap_version: String,
+    os: String,
+    os_version: String,
+    arch: String,
+    displays: Vec<CaptureScreen>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MediaProbe {
+    path: PathBuf,
+    size_bytes: u64,
+    duration_secs: Option<f64>,
+    streams: Vec<StreamProbe>,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamProbe {
+    kind: String,
+    codec: String,
+    width: Option<u32>,
+    height: Option<u32>,
+    frame_rate: Option<f64>,
+    sample_rate: Option<u32>,
+    channels: Option<u16>,
+}
+
+/// Zips up everything support needs to look into a problem with a recording:
+/// its logs, manifest, meta and project config, a probe of each media file, and system info.
+///
+/// With `redact` set, the recording's share link and the user's auth token are
+/// replaced wherever they appear.
+#[tauri::command]
+#[specta::specta]
+pub async fn create_support_bundle(
+    app: AppHandle,
+    project_path: PathBuf,
+    output_path: PathBuf,
+    redact: bool,
+) -> Result<PathBuf, String> {
+    let meta = RecordingMeta::load_for_project(&project_path)
+        .map_err(|e| format!("Failed to load recording meta: {e}"))?;
+
+    let mut secrets = vec![];
+    if redact {
+        if let Some(sharing) = &meta.sharing {
+            secrets.push(sharing.link.clone());
+        }
+
+        if let Ok(Some(auth)) = AuthStore::get(&app) {
+            secrets.push(match auth.secret {
+                AuthSecret::ApiKey { api_key } => api_key,
+                AuthSecret::Session { token, .. } => token,
+            });
+        }
+    }
+
+    let system_info = SystemInfo {
+        cap_version: app.package_info().version.to_string(),
+        os: tauri_plugin_os::platform().to_string(),
+        os_version: tauri_plugin_os::version().to_string(),
+        arch: tauri_plugin_os::arch().to_string(),
+        displays: cap_media::sources::list_screens()
+            .into_iter()
+            .map(|(screen, _)| screen)
+            .collect(),
+    };
+
+    let mut output_path = output_path;
+    output_path.set_extension("zip");
+
+    tokio::task::spawn_blocking(move || {
+        let redact_text = |text: String| {
+            secrets
+                .iter()
+                .filter(|secret| !secret.is_empty())
+                .fold(text, |text, secret| text.replace(secret.as_str(), REDACTED))
+        };
+
+        let mut entries: Vec<(&str, String)> = vec![];
+
+        if let Ok(logs) = std::fs::read_to_string(project_path.join("recording-logs.log")) {
+            entries.push(("recording-logs.log", logs));
+        }
+
+        if let Ok(manifest) = std::fs::read_to_string(project_path.join("recording-manifest.json"))
+        {
+            entries.push(("recording-manifest.json", manifest));
+        }
+
+        entries.push((
+            "recording-meta.json",
+            serde_json::to_string_pretty(&meta).map_err(|e| e.to_string())?,
+        ));
+
+        if let Ok(config) = ProjectConfiguration::load(&project_path) {
+            entries.push((
+                "project-config.json",
+                serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?,
+            ));
+        }
+
+        ffmpeg::init().map_err(|e| e.to_string())?;
+        let probes = media_files(&project_path)
+            .into_iter()
+            .map(|path| probe_media(&project_path, &path))
+            .collect::<Vec<_>>();
+        entries.push((
+            "media-probe.json",
+            serde_json::to_string_pretty(&probes).map_err(|e| e.to_string())?,
+        ));
+
+        entries.push((
+            "system-info.json",
+            serde_json::to_string_pretty(&system_info).map_err(|e| e.to_string())?,
+        ));
+
+        let file = std::fs::File::create(&output_path)
+            .map_err(|e| format!("Failed to create support bundle: {e}"))?;
+        let mut zip = ZipWriter::new(file);
+
+        for (name, contents) in entries {
+            zip.start_file(name, SimpleFileOptions::default())
+                .map_err(|e| e.to_string())
+                .and_then(|_| {
+                    zip.write_all(redact_text(contents).as_bytes())
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|e| format!("Failed to write {name} to support bundle: {e}"))?;
+        }
+
+        zip.finish()
+            .map_err(|e| format!("Failed to write support bundle: {e}"))?;
+
+        info!("Created support bundle at {}", output_path.display());
+
+        Ok(output_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn media_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut files = vec![];
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            files.extend(media_files(&path));
+        } else if path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| MEDIA_EXTENSIONS.contains(&ext))
+        {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    files
+}
+
+fn probe_media(project_path: &Path, path: &Path) -> MediaProbe {
+    let mut probe = MediaProbe {
+        path: path
+            .strip_prefix(project_path)
+            .unwrap_or(path)
+            .to_path_buf(),
+        size_bytes: path.metadata().map(|m| m.len()).unwrap_or_default(),
+        duration_secs: None,
+        streams: vec![],
+        error: None,
+    };
+
+    let input = match ffmpeg::format::input(&path) {
+        Ok(input) => input,
+        Err(e) => {
+            probe.error = Some(e.to_string());
+            return probe;
+        }
+    };
+
+    probe.duration_secs = Some(input.duration())
+        .filter(|duration| *duration > 0)
+        .map(|duration| duration as f64 / ffmpeg::ffi::AV_TIME_BASE as f64);
+
+    for stream in input.streams() {
+        let Ok(context) = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+        else {
+            continue;
+        };
+
+        let medium = context.medium();
+        let mut stream_probe = StreamProbe {
+            kind: format!("{medium:?}"),
+            codec: context.id().name().to_string(),
+            width: None,
+            height: None,
+            frame_rate: Some(f64::from(stream.avg_frame_rate())).filter(|fps| fps.is_finite()),
+            sample_rate: None,
+            channels: None,
+        };
+
+        let decoder = context.decoder();
+        match medium {
+            ffmpeg::media::Type::Video => {
+                if let Ok(video) = decoder.video() {
+                    stream_probe.width = Some(video.width());
+                    stream_probe.height = Some(video.height());
+                }
+            }
+            ffmpeg::media::Type::Audio => {
+                stream_probe.frame_rate = None;
+                if let Ok(audio) = decoder.audio() {
+                    stream_probe.sample_rate = Some(audio.rate());
+                    stream_probe.channels = Some(audio.channels());
+                }
+            }
+            _ => {}
+        }
+
+        probe.streams.push(stream_probe);
+    }
+
+    probe
+}
@@ -0,0 +1,278 @@
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::{AppHandle, Manager};
+use tauri_specta::Event;
+use tracing::{info, warn};
+
+use cap_recording::StudioRecordingHandle;
+
+use crate::{recording::InProgressRecording, App, ArcLock};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How hard the machine is working, from the thermal state on macOS and CPU usage on Windows
+#[derive(
+    Serialize, Deserialize, Type, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default,
+)]
+#[serde(rename_all = "camelCase")]
+pub enum PressureLevel {
+    Nominal,
+    Fair,
+    #[default]
+    Serious,
+    Critical,
+}
+
+#[derive(Serialize, Deserialize, Type, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum PressureResponse {
+    Pause,
+    /// Studio recordings only, instant recordings are paused instead
+    ReduceFps,
+}
+
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingPressureChanged {
+    pub level: PressureLevel,
+    pub response: PressureResponse,
+    /// whether the response is now in effect, false once it's been undone
+    pub active: bool,
+}
+
+#[derive(Default)]
+struct PressureSampler {
+    #[cfg(windows)]
+    last_cpu_times: Option<(u64, u64)>,
+}
+
+impl PressureSampler {
+    fn sample(&mut self) -> Option<PressureLevel> {
+        #[cfg(target_os = "macos")]
+        {
+            Some(match crate::platform::thermal_state() {
+                0 => PressureLevel::Nominal,
+                1 => PressureLevel::Fair,
+                2 => PressureLevel::Serious,
+                _ => PressureLevel::Critical,
+            })
+        }
+
+        #[cfg(windows)]
+        {
+            let (idle, total) = crate::platform::system_cpu_times()?;
+            let (last_idle, last_total) = self.last_cpu_times.replace((idle, total))?;
+
+            let total_delta = total.saturating_sub(last_total);
+            if total_delta == 0 {
+                return None;
+            }
+
+            let usage = 1.0 - idle.saturating_sub(last_idle) as f64 / total_delta as f64;
+
+            Some(cpu_usage_level(usage))
+        }
+
+        #[cfg(not(any(target_os = "macos", windows)))]
+        {
+            None
+        }
+    }
+}
+
+#[cfg(any(windows, test))]
+fn cpu_usage_level(usage: f64) -> PressureLevel {
+    match usage {
+        u if u < 0.7 => PressureLevel::Nominal,
+        u if u < 0.85 => PressureLevel::Fair,
+        u if u < 0.95 => PressureLevel::Serious,
+        _ => PressureLevel::Critical,
+    }
+}
+
+/// Whether `level` crossed the threshold in either direction since the response was last
+/// applied or undone, returning if the machine's now under pressure.
+fn threshold_crossed(level: PressureLevel, threshold: PressureLevel, active: bool) -> Option<bool> {
+    let under_pressure = level >= threshold;
+    (under_pressure != active).then_some(under_pressure)
+}
+
+enum PressureAction {
+    SetFpsLimit(StudioRecordingHandle, Option<u32>),
+    Pause,
+    Resume,
+    /// The user paused or resumed the recording themselves, so there's nothing to do
+    None,
+}
+
+pub struct PressureMonitorConfig {
+    pub response: PressureResponse,
+    pub threshold: PressureLevel,
+    pub reduced_fps: u32,
+}
+
+/// Watches system pressure for as long as the recording in `recording_dir` is in progress,
+/// applying `config.response` while pressure is at or above the threshold.
+pub fn spawn_pressure_monitor(
+    app: AppHandle,
+    recording_dir: PathBuf,
+    config: PressureMonitorConfig,
+) {
+    tokio::spawn(async move {
+        let mut sampler = PressureSampler::default();
+        let mut active = false;
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+
+        loop {
+            interval.tick().await;
+
+            let Some(level) = sampler.sample() else {
+                let state = app.state::<ArcLock<App>>();
+                if !is_current_recording(&*state.read().await, &recording_dir) {
+                    break;
+                }
+                continue;
+            };
+
+            let (response, action) = {
+                let state = app.state::<ArcLock<App>>();
+                let state = state.read().await;
+
+                let Some(recording) = state
+                    .current_recording
+                    .as_ref()
+                    .filter(|r| r.recording_dir() == &recording_dir)
+                else {
+                    break;
+                };
+
+                let Some(under_pressure) = threshold_crossed(level, config.threshold, active)
+                else {
+                    continue;
+                };
+
+                match (config.response, recording) {
+                    (PressureResponse::ReduceFps, InProgressRecording::Studio { handle, .. }) => (
+                        PressureResponse::ReduceFps,
+                        PressureAction::SetFpsLimit(
+                            handle.clone(),
+                            under_pressure.then_some(config.reduced_fps),
+                        ),
+                    ),
+                    // recordings the user paused themselves are left alone, and if they resumed
+                    // one we paused then there's nothing left to undo once pressure drops
+                    (_, recording) if recording.clock().is_paused() != active => {
+                        (PressureResponse::Pause, PressureAction::None)
+                    }
+                    _ if under_pressure => (PressureResponse::Pause, PressureAction::Pause),
+                    _ => (PressureResponse::Pause, PressureAction::Resume),
+                }
+            };
+
+            let under_pressure = !active;
+            let result = match action {
+                PressureAction::SetFpsLimit(handle, fps) => {
+                    handle.set_fps_limit(fps).await.map(|_| true)
+                }
+                PressureAction::None => Ok(!under_pressure),
+                PressureAction::Pause | PressureAction::Resume => {
+                    // pausing and resuming go through the same lock as the pause and resume
+                    // commands, so they can't interleave with the user's own
+                    let state = app.state::<ArcLock<App>>();
+                    let state = state.write().await;
+
+                    let Some(recording) = state
+                        .current_recording
+                        .as_ref()
+                        .filter(|r| r.recording_dir() == &recording_dir)
+                    else {
+                        break;
+                    };
+
+                    if recording.clock().is_paused() != active {
+                        Ok(!under_pressure)
+                    } else if under_pressure {
+                        recording.pause().await.map(|_| true)
+                    } else {
+                        recording.resume().await.map(|_| true)
+                    }
+                }
+            };
+
+            match result {
+                Ok(true) => {
+                    info!(
+                        "System pressure is {level:?}, {} {:?}",
+                        if under_pressure {
+                            "applying"
+                        } else {
+                            "undoing"
+                        },
+                        response
+                    );
+
+                    active = under_pressure;
+
+                    RecordingPressureChanged {
+                        level,
+                        response,
+                        active,
+                    }
+                    .emit(&app)
+                    .ok();
+                }
+                Ok(false) => {}
+                Err(e) => warn!("Failed to respond to system pressure: {e}"),
+            }
+        }
+    });
+}
+
+fn is_current_recording(state: &App, recording_dir: &Path) -> bool {
+    state
+        .current_recording
+        .as_ref()
+        .is_some_and(|r| r.recording_dir() == recording_dir)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cpu_usage_maps_to_levels_at_the_thresholds() {
+        assert_eq!(cpu_usage_level(0.0), PressureLevel::Nominal);
+        assert_eq!(cpu_usage_level(0.69), PressureLevel::Nominal);
+        assert_eq!(cpu_usage_level(0.7), PressureLevel::Fair);
+        assert_eq!(cpu_usage_level(0.85), PressureLevel::Serious);
+        assert_eq!(cpu_usage_level(0.95), PressureLevel::Critical);
+        assert_eq!(cpu_usage_level(1.0), PressureLevel::Critical);
+    }
+
+    #[test]
+    fn responses_toggle_only_when_the_threshold_is_crossed() {
+        let threshold = PressureLevel::Serious;
+
+        assert_eq!(
+            threshold_crossed(PressureLevel::Fair, threshold, false),
+            None
+        );
+        assert_eq!(
+            threshold_crossed(PressureLevel::Serious, threshold, false),
+            Some(true)
+        );
+        assert_eq!(
+            threshold_crossed(PressureLevel::Critical, threshold, true),
+            None
+        );
+        assert_eq!(
+            threshold_crossed(PressureLevel::Nominal, threshold, true),
+            Some(false)
+        );
+    }
+}
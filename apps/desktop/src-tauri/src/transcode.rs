@@ -0,0 +1,862 @@
+use std::{
+    ops::DerefMut,
+    path::{Path, PathBuf},
+};
+
+use cap_export::mp4::ExportCompression;
+use cap_media::{
+    data::{AudioInfo, FFAudio, FFVideo, VideoInfo},
+    encoders::{AACEncoder, AudioEncoder, H264Encoder, H264Preset, MP4File, VideoCodec},
+};
+use cap_project::{
+    fit_rect, AspectFillMode, Color, RecordingMeta, RecordingMetaInner, RenditionMeta,
+    StudioRecordingMeta, XY,
+};
+use ffmpeg::{codec::decoder, format::Pixel, software::scaling};
+use relative_path::RelativePathBuf;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use tauri::AppHandle;
+use tauri_specta::Event;
+use tokio_util::sync::CancellationToken;
+use tracing::{info, warn};
+
+use crate::processing::ProcessingTasks;
+
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TranscodeProgress {
+    pub project_path: PathBuf,
+    pub progress: f64,
+}
+
+/// An extra copy of every recording to encode once it finishes
+#[derive(Serialize, Deserialize, Type, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenditionProfile {
+    pub name: String,
+    /// recordings taller than this are scaled down to it, smaller ones are left as they are
+    pub max_height: u32,
+    #[serde(default)]
+    pub codec: VideoCodec,
+    pub quality: ExportCompression,
+    /// upload this rendition instead of the original for instant recordings,
+    /// only the first profile with it set is used
+    #[serde(default)]
+    pub share: bool,
+}
+
+// how much smaller the blurred background is drawn before being scaled up to the output
+const BLUR_DOWNSCALE: u32 = 24;
+
+/// A video file and the audio that plays alongside it
+struct TranscodeSource {
+    video: PathBuf,
+    audio: Option<PathBuf>,
+}
+
+fn transcode_sources(project_path: &Path, inner: &RecordingMetaInner) -> Vec<TranscodeSource> {
+    match inner {
+        RecordingMetaInner::Instant(meta) => {
+            let output = project_path.join(meta.output_format.output_path());
+
+            vec![TranscodeSource {
+                audio: Some(output.clone()),
+                video: output,
+            }]
+        }
+        RecordingMetaInner::Studio(StudioRecordingMeta::SingleSegment { segment }) => {
+            vec![TranscodeSource {
+                video: segment.display.path.to_path(project_path),
+                audio: segment
+                    .audio
+                    .as_ref()
+                    .map(|audio| audio.path.to_path(project_path)),
+            }]
+        }
+        RecordingMetaInner::Studio(StudioRecordingMeta::MultipleSegments { inner }) => inner
+            .segments
+            .iter()
+            .map(|segment| TranscodeSource {
+                video: segment.display.path.to_path(project_path),
+                // there's only one audio track in the output, prefer the mic over system audio
+                audio: segment
+                    .mic
+                    .as_ref()
+                    .or(segment.system_audio.as_ref())
+                    .map(|audio| audio.path.to_path(project_path)),
+            })
+            .collect(),
+    }
+}
+
+/// Re-encodes a recording's screen video and audio, leaving the original files untouched.
+///
+/// Multi-segment studio recordings are joined into `output_path` unless `per_segment` is set,
+/// in which case each segment is written next to it with a `-segment-N` suffix.
+#[tauri::command]
+#[specta::specta]
+pub async fn transcode_recording(
+    app: AppHandle,
+    project_path: PathBuf,
+    output_path: PathBuf,
+    codec: VideoCodec,
+    quality: ExportCompression,
+    per_segment: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let meta = RecordingMeta::load_for_project(&project_path)
+        .map_err(|e| format!("Failed to load recording meta: {e}"))?;
+
+    let sources = transcode_sources(&project_path, &meta.inner);
+
+    let mut output_path = output_path;
+    output_path.set_extension("mp4");
+
+    if sources
+        .iter()
+        .any(|s| s.video == output_path || s.audio.as_ref() == Some(&output_path))
+    {
+        return Err("Output path cannot overwrite the original recording".to_string());
+    }
+
+    let jobs: Vec<(PathBuf, Vec<TranscodeSource>)> = if per_segment && sources.len() > 1 {
+        let stem = output_path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        sources
+            .into_iter()
+            .enumerate()
+            .map(|(i, source)| {
+                (
+                    output_path.with_file_name(format!("{stem}-segment-{}.mp4", i + 1)),
+                    vec![source],
+                )
+            })
+            .collect()
+    } else {
+        vec![(output_path, sources)]
+    };
+
+    let processing = ProcessingTasks::get(&app).start(&project_path);
+
+    tokio::task::spawn_blocking(move || {
+        ffmpeg::init().map_err(|e| e.to_string())?;
+
+        let total_duration: f64 = jobs
+            .iter()
+            .flat_map(|(_, sources)| sources)
+            .filter_map(|source| ffmpeg::format::input(&source.video).ok())
+            .map(|input| input.duration().max(0) as f64 / ffmpeg::ffi::AV_TIME_BASE as f64)
+            .sum();
+
+        let mut on_progress = {
+            let mut completed = 0.0;
+            move |duration: f64, finished: bool| {
+                if finished {
+                    completed += duration;
+                }
+
+                let progress = if total_duration > 0.0 {
+                    ((completed + if finished { 0.0 } else { duration }) / total_duration).min(1.0)
+                } else {
+                    0.0
+                };
+
+                TranscodeProgress {
+                    project_path: project_path.clone(),
+                    progress,
+                }
+                .emit(&app)
+                .ok();
+            }
+        };
+
+        let mut outputs = vec![];
+
+        for (output_path, sources) in jobs {
+            if let Err(e) = transcode_to_file(
+                &sources,
+                &output_path,
+                codec,
+                quality,
+                None,
+                None,
+                processing.token(),
+                &mut on_progress,
+            ) {
+                // finished segments aren't much use without the rest
+                if processing.token().is_cancelled() {
+                    for output in &outputs {
+                        std::fs::remove_file(output).ok();
+                    }
+                }
+                return Err(e);
+            }
+            info!("Transcoded recording to {}", output_path.display());
+            outputs.push(output_path);
+        }
+
+        Ok(outputs)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// An aspect ratio to fit a video to and how to fill the space around it
+#[derive(Debug, Clone, Copy)]
+pub struct AspectFit {
+    pub aspect: (u32, u32),
+    pub fill_mode: AspectFillMode,
+    pub bar_color: Color,
+}
+
+impl AspectFit {
+    /// Size the video at `path` will be once fitted
+    pub fn output_size(&self, path: &Path) -> Result<XY<u32>, String> {
+        ffmpeg::init().map_err(|e| e.to_string())?;
+
+        let video = StreamDecoder::video(path)?
+            .ok_or_else(|| "Recording has no video stream".to_string())?;
+
+        Ok(self.fill_mode.output_size(
+            XY::new(video.decoder.width(), video.decoder.height()),
+            self.aspect,
+        ))
+    }
+}
+
+/// Re-encodes the video at `path` in place, fitted to `fit`.
+/// The original is only replaced once the fitted video has been written in full.
+pub fn fit_to_aspect(
+    path: &Path,
+    fit: &AspectFit,
+    cancel: &CancellationToken,
+) -> Result<(), String> {
+    if path.extension().and_then(|ext| ext.to_str()) != Some("mp4") {
+        return Err("Only MP4 recordings can be fitted to an aspect ratio".to_string());
+    }
+
+    ffmpeg::init().map_err(|e| e.to_string())?;
+
+    let fitted_path = path.with_extension("fitted.mp4");
+
+    transcode_to_file(
+        &[TranscodeSource {
+            video: path.to_path_buf(),
+            audio: Some(path.to_path_buf()),
+        }],
+        &fitted_path,
+        VideoCodec::H264,
+        ExportCompression::Minimal,
+        Some(fit),
+        None,
+        cancel,
+        &mut |_, _| {},
+    )
+    .inspect_err(|_| {
+        std::fs::remove_file(&fitted_path).ok();
+    })?;
+
+    std::fs::rename(&fitted_path, path)
+        .map_err(|e| format!("Failed to replace recording with fitted video: {e}"))
+}
+
+/// Encodes a recording's screen video and audio once per profile, all at the same time,
+/// into its `renditions` folder. Renditions that fail to encode are left out,
+/// and the folder is removed entirely if they're cancelled.
+pub fn render_renditions(
+    project_path: &Path,
+    inner: &RecordingMetaInner,
+    profiles: &[RenditionProfile],
+    cancel: &CancellationToken,
+) -> Vec<RenditionMeta> {
+    if profiles.is_empty() {
+        return vec![];
+    }
+
+    if let Err(e) = ffmpeg::init() {
+        warn!("Failed to initialize ffmpeg for renditions: {e}");
+        return vec![];
+    }
+
+    if let Err(e) = std::fs::create_dir_all(project_path.join("renditions")) {
+        warn!("Failed to create renditions directory: {e}");
+        return vec![];
+    }
+
+    let sources = transcode_sources(project_path, inner);
+    let share_index = profiles.iter().position(|profile| profile.share);
+
+    std::thread::scope(|scope| {
+        let handles = profiles
+            .iter()
+            .enumerate()
+            .map(|(i, profile)| {
+                let sources = &sources;
+                scope.spawn(move || {
+                    let slug = profile
+                        .name
+                        .chars()
+                        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                        .collect::<String>();
+                    let path = RelativePathBuf::from(format!("renditions/{}-{slug}.mp4", i + 1));
+
+                    let size = transcode_to_file(
+                        sources,
+                        &path.to_path(project_path),
+                        profile.codec,
+                        profile.quality,
+                        None,
+                        Some(profile.max_height),
+                        cancel,
+                        &mut |_, _| {},
+                    )
+                    .map_err(|e| warn!("Failed to encode rendition '{}': {e}", profile.name))
+                    .ok()?;
+
+                    info!("Encoded rendition '{}' to {path}", profile.name);
+
+                    Some(RenditionMeta {
+                        name: profile.name.clone(),
+                        path,
+                        width: size.x,
+                        height: size.y,
+                        share: share_index == Some(i),
+                    })
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let renditions = handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok().flatten())
+            .collect();
+
+        if cancel.is_cancelled() {
+            std::fs::remove_dir_all(project_path.join("renditions")).ok();
+            return vec![];
+        }
+
+        renditions
+    })
+}
+
+fn transcode_to_file(
+    sources: &[TranscodeSource],
+    output_path: &Path,
+    codec: VideoCodec,
+    quality: ExportCompression,
+    fit: Option<&AspectFit>,
+    max_height: Option<u32>,
+    cancel: &CancellationToken,
+    on_progress: &mut impl FnMut(f64, bool),
+) -> Result<XY<u32>, String> {
+    let first_video = StreamDecoder::video(
+        &sources
+            .first()
+            .ok_or_else(|| "Recording has no media".to_string())?
+            .video,
+    )?
+    .ok_or_else(|| "Recording has no video stream".to_string())?;
+
+    let fps = first_video.frame_rate.unwrap_or(30);
+    let source_size = XY::new(first_video.decoder.width(), first_video.decoder.height());
+    let output_size = match fit {
+        Some(fit) => fit.fill_mode.output_size(source_size, fit.aspect),
+        None => match max_height.filter(|height| *height < source_size.y) {
+            Some(height) => XY::new(source_size.x * height / source_size.y, height),
+            None => source_size,
+        }
+        .map(|v| v & !1),
+    };
+    let mut video_info =
+        VideoInfo::from_raw_ffmpeg(Pixel::YUV420P, output_size.x, output_size.y, fps);
+    video_info.time_base = ffmpeg::Rational::new(1, fps as i32);
+
+    let mut audio_info = None;
+    for source in sources {
+        if let Some(audio) = source.audio.as_ref().map(|p| StreamDecoder::audio(p)) {
+            if let Some(audio) = audio? {
+                audio_info =
+                    Some(AudioInfo::from_decoder(&audio.decoder).map_err(|e| e.to_string())?);
+                break;
+            }
+        }
+    }
+
+    let mut encoder = MP4File::init(
+        "transcode",
+        output_path.to_path_buf(),
+        |o| {
+            H264Encoder::builder("transcode_video", video_info)
+                .with_codec(codec)
+                .with_preset(H264Preset::Medium)
+                .with_bpp(quality.bits_per_pixel())
+                .build(o)
+        },
+        |o| {
+            audio_info.map(|info| {
+                AACEncoder::init("transcode_audio", info, None, o).map(|encoder| encoder.boxed())
+            })
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    let mut offset = 0.0;
+    let mut last_pts = None;
+
+    for source in sources {
+        let Some(mut video) = StreamDecoder::video(&source.video)? else {
+            warn!("No video stream in {}, skipping", source.video.display());
+            continue;
+        };
+
+        let mut audio = match (&source.audio, audio_info) {
+            (Some(path), Some(audio_info)) => StreamDecoder::audio(path)?.filter(|audio| {
+                let matches = AudioInfo::from_decoder(&audio.decoder).is_ok_and(|info| {
+                    (info.sample_format, info.sample_rate, info.channels)
+                        == (
+                            audio_info.sample_format,
+                            audio_info.sample_rate,
+                            audio_info.channels,
+                        )
+                });
+                if !matches {
+                    warn!(
+                        "Audio format of {} differs, replacing with silence",
+                        path.display()
+                    );
+                }
+                matches
+            }),
+            _ => None,
+        };
+
+        let mut scaler = FrameScaler::new(
+            video.decoder.format(),
+            XY::new(video.decoder.width(), video.decoder.height()),
+            output_size,
+            fit,
+        )?;
+
+        let mut video_secs = 0.0;
+        let mut audio_secs = 0.0;
+        let mut audio_samples = 0;
+        let mut video_done = false;
+
+        loop {
+            let audio_done = audio.is_none();
+
+            if video_done && audio_done {
+                break;
+            }
+
+            if cancel.is_cancelled() {
+                drop(encoder);
+                std::fs::remove_file(output_path).ok();
+                return Err("Cancelled".to_string());
+            }
+
+            // alternate between streams so the muxer doesn't have to buffer one of them entirely
+            if !video_done && (audio_done || video_secs <= audio_secs) {
+                let mut frame = FFVideo::empty();
+                if !video.receive(&mut frame)? {
+                    video_done = true;
+                    continue;
+                }
+
+                if let Some(timestamp) = frame.timestamp() {
+                    video_secs = timestamp as f64 * video.time_base;
+                }
+
+                let pts = ((offset + video_secs) * fps as f64).round() as i64;
+                if last_pts.is_some_and(|last| pts <= last) {
+                    continue;
+                }
+
+                let mut scaled = scaler.run(&frame)?;
+                scaled.set_pts(Some(pts));
+                encoder.queue_video_frame(scaled);
+                last_pts = Some(pts);
+
+                if pts % fps as i64 == 0 {
+                    on_progress(video_secs, false);
+                }
+            } else if let Some(decoder) = &mut audio {
+                let mut frame = FFAudio::empty();
+                if !decoder.receive(&mut frame)? {
+                    audio = None;
+                    continue;
+                }
+
+                if let Some(timestamp) = frame.timestamp() {
+                    audio_secs = timestamp as f64 * decoder.time_base;
+                }
+
+                audio_samples += frame.samples();
+                encoder.queue_audio_frame(frame);
+            }
+        }
+
+        let duration = video_secs + 1.0 / fps as f64;
+
+        // keep later segments in sync when this one has less (or no) audio
+        if let Some(audio_info) = audio_info {
+            let expected_samples = (duration * audio_info.sample_rate as f64) as usize;
+            if expected_samples > audio_samples {
+                let mut silence = audio_info.empty_frame(expected_samples - audio_samples);
+                for plane in 0..silence.planes() {
+                    silence.data_mut(plane).fill(0);
+                }
+                encoder.queue_audio_frame(silence);
+            }
+        }
+
+        offset += duration;
+        on_progress(duration, true);
+    }
+
+    encoder.finish();
+
+    Ok(output_size)
+}
+
+/// Scales decoded frames to the output size, placing them on a canvas when fitting
+/// them to a different aspect ratio
+struct FrameScaler {
+    foreground: scaling::Context,
+    canvas: Option<Canvas>,
+}
+
+struct Canvas {
+    size: XY<u32>,
+    /// top-left of the scaled frame, negative when it's cropped
+    position: XY<i32>,
+    fill: CanvasFill,
+}
+
+enum CanvasFill {
+    /// YUV
+    Color([u8; 3]),
+    /// The frame covers the canvas entirely
+    Cover,
+    Blur {
+        shrink: scaling::Context,
+        small_size: XY<u32>,
+        small_position: XY<i32>,
+        enlarge: scaling::Context,
+    },
+}
+
+impl FrameScaler {
+    fn new(
+        source_format: Pixel,
+        source_size: XY<u32>,
+        output_size: XY<u32>,
+        fit: Option<&AspectFit>,
+    ) -> Result<Self, String> {
+        let scaler = |size: XY<u32>, flags: scaling::Flags| {
+            scaling::Context::get(
+                source_format,
+                source_size.x,
+                source_size.y,
+                Pixel::YUV420P,
+                size.x,
+                size.y,
+                flags,
+            )
+            .map_err(|e| format!("Failed to create scaler: {e}"))
+        };
+
+        let Some(fit) = fit else {
+            return Ok(Self {
+                foreground: scaler(output_size, scaling::Flags::BILINEAR)?,
+                canvas: None,
+            });
+        };
+
+        let (position, size) = fit_rect(
+            source_size,
+            output_size,
+            fit.fill_mode == AspectFillMode::Crop,
+        );
+
+        let fill = match fit.fill_mode {
+            AspectFillMode::Bars => CanvasFill::Color(rgb_to_yuv(fit.bar_color)),
+            AspectFillMode::Crop => CanvasFill::Cover,
+            // shrinking the frame right down then scaling it back up blurs it cheaply
+            AspectFillMode::BlurFill => {
+                let small_size = output_size.map(|v| (v / BLUR_DOWNSCALE).max(2) & !1);
+                let (small_position, shrunk_size) = fit_rect(source_size, small_size, true);
+
+                CanvasFill::Blur {
+                    shrink: scaler(shrunk_size, scaling::Flags::AREA)?,
+                    small_size,
+                    small_position,
+                    enlarge: scaling::Context::get(
+                        Pixel::YUV420P,
+                        small_size.x,
+                        small_size.y,
+                        Pixel::YUV420P,
+                        output_size.x,
+                        output_size.y,
+                        scaling::Flags::BICUBIC,
+                    )
+                    .map_err(|e| format!("Failed to create scaler: {e}"))?,
+                }
+            }
+        };
+
+        Ok(Self {
+            foreground: scaler(size, scaling::Flags::BILINEAR)?,
+            canvas: Some(Canvas {
+                size: output_size,
+                position,
+                fill,
+            }),
+        })
+    }
+
+    fn run(&mut self, frame: &FFVideo) -> Result<FFVideo, String> {
+        let mut scaled = FFVideo::empty();
+        self.foreground
+            .run(frame, &mut scaled)
+            .map_err(|e| format!("Failed to scale frame: {e}"))?;
+
+        let Some(canvas) = &mut self.canvas else {
+            return Ok(scaled);
+        };
+
+        let mut output = match &mut canvas.fill {
+            CanvasFill::Color(yuv) => filled_frame(canvas.size, *yuv),
+            // rounding can leave the frame a pixel or two short of the edges
+            CanvasFill::Cover => filled_frame(canvas.size, BLACK_YUV),
+            CanvasFill::Blur {
+                shrink,
+                small_size,
+                small_position,
+                enlarge,
+            } => {
+                let mut shrunk = FFVideo::empty();
+                shrink
+                    .run(frame, &mut shrunk)
+                    .map_err(|e| format!("Failed to scale frame: {e}"))?;
+
+                let mut small = filled_frame(*small_size, BLACK_YUV);
+                blit(&mut small, &shrunk, *small_position);
+
+                let mut blurred = FFVideo::empty();
+                enlarge
+                    .run(&small, &mut blurred)
+                    .map_err(|e| format!("Failed to scale frame: {e}"))?;
+                blurred
+            }
+        };
+
+        blit(&mut output, &scaled, canvas.position);
+
+        Ok(output)
+    }
+}
+
+const BLACK_YUV: [u8; 3] = [16, 128, 128];
+
+/// A YUV420P frame of `size` filled with a single color, new frames' contents are undefined
+fn filled_frame(size: XY<u32>, yuv: [u8; 3]) -> FFVideo {
+    let mut frame = FFVideo::new(Pixel::YUV420P, size.x, size.y);
+    for (plane, value) in yuv.iter().enumerate() {
+        frame.data_mut(plane).fill(*value);
+    }
+    frame
+}
+
+/// Copies the part of YUV420P `src` that overlaps `dst` when placed at `position`
+fn blit(dst: &mut FFVideo, src: &FFVideo, position: XY<i32>) {
+    for plane in 0..3 {
+        // chroma planes are half size
+        let shift = if plane == 0 { 0 } else { 1 };
+        let (x, y) = (position.x >> shift, position.y >> shift);
+        let src_size = XY::new(src.plane_width(plane), src.plane_height(plane)).map(|v| v as i32);
+        let dst_size = XY::new(dst.plane_width(plane), dst.plane_height(plane)).map(|v| v as i32);
+
+        let (start_x, end_x) = (x.max(0), (x + src_size.x).min(dst_size.x));
+        if start_x >= end_x {
+            continue;
+        }
+        let len = (end_x - start_x) as usize;
+
+        let (src_stride, dst_stride) = (src.stride(plane), dst.stride(plane));
+
+        for row in y.max(0)..(y + src_size.y).min(dst_size.y) {
+            let src_start = (row - y) as usize * src_stride + (start_x - x) as usize;
+            let dst_start = row as usize * dst_stride + start_x as usize;
+
+            dst.data_mut(plane)[dst_start..dst_start + len]
+                .copy_from_slice(&src.data(plane)[src_start..src_start + len]);
+        }
+    }
+}
+
+/// BT.601 limited range, as swscale assumes when converting to YUV
+fn rgb_to_yuv(color: Color) -> [u8; 3] {
+    let [r, g, b] = color.map(|c| c.min(255) as f32 / 255.0);
+
+    [
+        16.0 + 65.481 * r + 128.553 * g + 24.966 * b,
+        128.0 - 37.797 * r - 74.203 * g + 112.0 * b,
+        128.0 + 112.0 * r - 93.786 * g - 18.214 * b,
+    ]
+    .map(|v| v.round().clamp(0.0, 255.0) as u8)
+}
+
+struct StreamDecoder<D> {
+    input: ffmpeg::format::context::Input,
+    stream_index: usize,
+    time_base: f64,
+    frame_rate: Option<u32>,
+    decoder: D,
+    flushed: bool,
+}
+
+impl StreamDecoder<decoder::Video> {
+    fn video(path: &Path) -> Result<Option<Self>, String> {
+        Self::open(path, ffmpeg::media::Type::Video, |d| d.video())
+    }
+}
+
+impl StreamDecoder<decoder::Audio> {
+    fn audio(path: &Path) -> Result<Option<Self>, String> {
+        Self::open(path, ffmpeg::media::Type::Audio, |d| d.audio())
+    }
+}
+
+impl<D: DerefMut<Target = decoder::Opened>> StreamDecoder<D> {
+    fn open(
+        path: &Path,
+        media_type: ffmpeg::media::Type,
+        open: impl FnOnce(decoder::Decoder) -> Result<D, ffmpeg::Error>,
+    ) -> Result<Option<Self>, String> {
+        let input = ffmpeg::format::input(&path)
+            .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+
+        let Some(stream) = input.streams().best(media_type) else {
+            return Ok(None);
+        };
+
+        let stream_index = stream.index();
+        let time_base = f64::from(stream.time_base());
+        let frame_rate = Some(f64::from(stream.avg_frame_rate()))
+            .filter(|fps| fps.is_finite() && *fps > 0.0)
+            .map(|fps| fps.round() as u32);
+
+        let decoder = ffmpeg::codec::context::Context::from_parameters(stream.parameters())
+            .and_then(|context| open(context.decoder()))
+            .map_err(|e| format!("Failed to create decoder for {}: {e}", path.display()))?;
+
+        Ok(Some(Self {
+            input,
+            stream_index,
+            time_base,
+            frame_rate,
+            decoder,
+            flushed: false,
+        }))
+    }
+
+    /// Decodes the next frame, returning false once the stream is exhausted
+    fn receive(&mut self, frame: &mut ffmpeg::Frame) -> Result<bool, String> {
+        loop {
+            if self.decoder.receive_frame(frame).is_ok() {
+                return Ok(true);
+            }
+
+            if self.flushed {
+                return Ok(false);
+            }
+
+            let stream_index = self.stream_index;
+            match self
+                .input
+                .packets()
+                .find(|(stream, _)| stream.index() == stream_index)
+            {
+                Some((_, packet)) => self
+                    .decoder
+                    .send_packet(&packet)
+                    .map_err(|e| format!("Failed to decode packet: {e}"))?,
+                None => {
+                    self.decoder.send_eof().ok();
+                    self.flushed = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rows(frame: &FFVideo, plane: usize) -> Vec<&[u8]> {
+        let width = frame.plane_width(plane) as usize;
+        frame
+            .data(plane)
+            .chunks(frame.stride(plane))
+            .take(frame.plane_height(plane) as usize)
+            .map(|row| &row[..width])
+            .collect()
+    }
+
+    fn fitted(fill_mode: AspectFillMode, output_size: XY<u32>) -> FFVideo {
+        ffmpeg::init().unwrap();
+
+        let source = filled_frame(XY::new(32, 16), [200, 100, 150]);
+        let fit = AspectFit {
+            aspect: (output_size.x, output_size.y),
+            fill_mode,
+            bar_color: [0, 0, 0],
+        };
+
+        FrameScaler::new(Pixel::YUV420P, XY::new(32, 16), output_size, Some(&fit))
+            .unwrap()
+            .run(&source)
+            .unwrap()
+    }
+
+    #[test]
+    fn bars_surround_the_fitted_frame() {
+        let output = fitted(AspectFillMode::Bars, XY::new(32, 32));
+
+        for (y, row) in rows(&output, 0).into_iter().enumerate() {
+            let expected = if (8..24).contains(&y) { 200 } else { 16 };
+            assert!(row.iter().all(|v| *v == expected), "luma row {y}");
+        }
+
+        for (y, row) in rows(&output, 1).into_iter().enumerate() {
+            let expected = if (4..12).contains(&y) { 100 } else { 128 };
+            assert!(row.iter().all(|v| *v == expected), "chroma row {y}");
+        }
+    }
+
+    #[test]
+    fn cropping_covers_the_canvas() {
+        let output = fitted(AspectFillMode::Crop, XY::new(16, 16));
+
+        assert_eq!((output.width(), output.height()), (16, 16));
+        for row in rows(&output, 0) {
+            assert!(row.iter().all(|v| *v == 200));
+        }
+    }
+
+    #[test]
+    fn blur_fill_has_no_uninitialized_edges() {
+        let output = fitted(AspectFillMode::BlurFill, XY::new(32, 32));
+
+        // a solid frame blurs to the same color, so the fill should match it everywhere
+        for row in rows(&output, 0) {
+            assert!(row.iter().all(|v| v.abs_diff(200) <= 2));
+        }
+    }
+}
@@ -136,7 +136,7 @@ pub fn create_tray(app: &AppHandle) -> tauri::Result<()> {
                     if is_recording.load(Ordering::Relaxed) {
                         let app = app_handle.clone();
                         tokio::spawn(async move {
-                            let _ = recording::stop_recording(app.clone(), app.state()).await;
+                            let _ = recording::stop_recording(app.clone(), app.state(), None).await;
                         });
                     } else {
                         let _ = tray.set_visible(true);
@@ -1,5 +1,7 @@
 // credit @filleduchaos
 
+use crate::general_settings::GeneralSettingsStore;
+use crate::screenshot::{screenshot_quality, upload_screenshot_quality};
 use axum::http::{HeaderMap, HeaderName, HeaderValue};
 use cap_utils::spawn_actor;
 use flume::Receiver;
@@ -10,8 +12,9 @@ use reqwest::header::CONTENT_LENGTH;
 use reqwest::{multipart::Form, StatusCode};
 use serde_json::json;
 use std::collections::HashMap;
+use std::future::Future;
 use std::io::SeekFrom;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -27,7 +30,12 @@ use tracing::{info, trace, warn};
 
 use crate::web_api::{self, ManagerExt};
 
-use crate::{notifications, App, MutableState, RecordingStopped, UploadProgress, VideoUploadInfo};
+use crate::{
+    dashboard, notifications,
+    recording::{existing_recording_dir, InProgressRecording},
+    recordings_path, App, MutableState, RecordingStopped, UploadProgress, VideoUploadInfo,
+};
+use cap_project::{InstantOutputFormat, RecordingMeta, SharingMeta};
 use serde::de::{self, Deserializer};
 use serde::{Deserialize, Serialize};
 use specta::Type;
@@ -145,20 +153,47 @@ pub async fn upload_video(
     file_path: PathBuf,
     existing_config: Option<S3UploadMeta>,
     screenshot_path: Option<PathBuf>,
+) -> Result<UploadedVideo, String> {
+    let progress_app = app.clone();
+    upload_video_with_progress(
+        app,
+        video_id,
+        file_path,
+        existing_config,
+        screenshot_path,
+        move |uploaded_bytes, total_bytes| {
+            let _ = UploadProgress {
+                progress: uploaded_bytes as f64 / total_bytes as f64,
+            }
+            .emit(&progress_app);
+        },
+    )
+    .await
+}
+
+/// `upload_video`, calling `on_progress` with the bytes sent so far and the file's size as
+/// the upload goes rather than emitting `UploadProgress`
+pub async fn upload_video_with_progress(
+    app: &AppHandle,
+    video_id: String,
+    file_path: PathBuf,
+    existing_config: Option<S3UploadMeta>,
+    screenshot_path: Option<PathBuf>,
+    mut on_progress: impl FnMut(u64, u64) + Send + 'static,
 ) -> Result<UploadedVideo, String> {
     println!("Uploading video {video_id}...");
 
     let client = reqwest::Client::new();
     let s3_config = match existing_config {
         Some(config) => config,
-        None => create_or_get_video(app, false, Some(video_id.clone()), None).await?,
+        None => create_or_get_video(app, false, Some(video_id.clone()), None, None).await?,
     };
 
     let body = build_video_upload_body(
         &file_path,
         S3UploadBody {
             video_id: video_id.clone(),
-            subpath: "result.mp4".to_string(),
+            subpath: format!("result.{}", video_output_format(&file_path).extension()),
         },
     )?;
 
@@ -178,19 +213,13 @@ pub async fn upload_video(
     let reader_stream = tokio_util::io::ReaderStream::new(file);
 
     let mut bytes_uploaded = 0;
-    let progress_stream = reader_stream.inspect({
-        let app = app.clone();
-        move |chunk| {
-            if bytes_uploaded > 0 {
-                let _ = UploadProgress {
-                    progress: bytes_uploaded as f64 / total_size as f64,
-                }
-                .emit(&app);
-            }
+    let progress_stream = reader_stream.inspect(move |chunk| {
+        if bytes_uploaded > 0 {
+            on_progress(bytes_uploaded as u64, total_size);
+        }
 
-            if let Ok(chunk) = chunk {
-                bytes_uploaded += chunk.len();
-            }
+        if let Ok(chunk) = chunk {
+            bytes_uploaded += chunk.len();
         }
     });
 
@@ -265,7 +294,7 @@ pub async fn upload_image(app: &AppHandle, file_path: PathBuf) -> Result<Uploade
         .to_string();
 
     let client = reqwest::Client::new();
-    let s3_config = create_or_get_video(app, true, None, None).await?;
+    let s3_config = create_or_get_video(app, true, None, None, None).await?;
 
     let body = S3ImageUploadBody {
         base: S3UploadBody {
@@ -312,11 +341,67 @@ pub async fn upload_image(app: &AppHandle, file_path: PathBuf) -> Result<Uploade
     ))
 }
 
+/// The video created for each idempotency key, once its request has succeeded
+type CreatedVideos = std::sync::Mutex<HashMap<String, Arc<tokio::sync::OnceCell<S3UploadMeta>>>>;
+
+lazy_static::lazy_static! {
+    // videos created for each idempotency key of recordings that haven't finished yet
+    static ref CREATED_VIDEOS: CreatedVideos = Default::default();
+}
+
+/// Forgets the video created for `idempotency_key`, once the recording it was
+/// created for has finished or been discarded
+pub fn forget_created_video(idempotency_key: &str) {
+    CREATED_VIDEOS.lock().unwrap().remove(idempotency_key);
+}
+
+/// Creates a video, or gets the one `video_id` refers to.
+/// Calls with the same `idempotency_key` get the same video back, so retrying after a
+/// dropped response doesn't leave an empty video behind in the user's account.
 pub async fn create_or_get_video(
     app: &AppHandle,
     is_screenshot: bool,
     video_id: Option<String>,
     name: Option<String>,
+    idempotency_key: Option<&str>,
+) -> Result<S3UploadMeta, String> {
+    create_video_once(&CREATED_VIDEOS, idempotency_key, || {
+        request_video(app, is_screenshot, video_id, name, idempotency_key)
+    })
+    .await
+}
+
+/// Only calls `create` once per `idempotency_key` unless it fails. Concurrent retries wait
+/// for the first rather than racing it, without holding up calls with other keys.
+async fn create_video_once<F, Fut>(
+    created: &CreatedVideos,
+    idempotency_key: Option<&str>,
+    create: F,
+) -> Result<S3UploadMeta, String>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<S3UploadMeta, String>>,
+{
+    let Some(key) = idempotency_key else {
+        return create().await;
+    };
+
+    let video = created
+        .lock()
+        .unwrap()
+        .entry(key.to_string())
+        .or_default()
+        .clone();
+
+    video.get_or_try_init(create).await.cloned()
+}
+
+async fn request_video(
+    app: &AppHandle,
+    is_screenshot: bool,
+    video_id: Option<String>,
+    name: Option<String>,
+    idempotency_key: Option<&str>,
 ) -> Result<S3UploadMeta, String> {
     let mut s3_config_url = if let Some(id) = video_id {
         format!("/api/desktop/video/create?recordingMode=desktopMP4&videoId={id}")
@@ -331,7 +416,14 @@ pub async fn create_or_get_video(
     }
 
     let response = app
-        .authed_api_request(s3_config_url, |client, url| client.get(url))
+        .authed_api_request(s3_config_url, |client, url| {
+            let request = client.get(url);
+            match idempotency_key {
+                // lets the server hand back the video it already created for this key
+                Some(key) => request.header("Idempotency-Key", key),
+                None => request,
+            }
+        })
         .await
         .map_err(|e| format!("Failed to send request to Next.js handler: {}", e))?;
 
@@ -450,6 +542,99 @@ fn build_audio_upload_body(
     })
 }
 
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase", tag = "status")]
+pub enum ShareLinkStatus {
+    NotShared,
+    Valid {
+        link: String,
+    },
+    /// The video is gone from the server, `reupload` wasn't requested
+    Broken {
+        link: String,
+    },
+    /// Couldn't reach the server to check
+    Unknown {
+        link: String,
+    },
+    /// The video was gone and has been uploaded again under a new link
+    Reuploaded {
+        link: String,
+        previous_link: String,
+    },
+}
+
+/// Checks that a recording's share link still points at a video,
+/// and if it doesn't and `reupload` is set, uploads the local copy again
+/// under a fresh link and saves that in the recording's meta.
+#[tauri::command]
+#[specta::specta]
+pub async fn verify_share_link(
+    app: AppHandle,
+    project_path: PathBuf,
+    reupload: bool,
+) -> Result<ShareLinkStatus, String> {
+    let mut meta = RecordingMeta::load_for_project(&project_path).map_err(|e| e.to_string())?;
+
+    let Some(sharing) = meta.sharing.clone() else {
+        return Ok(ShareLinkStatus::NotShared);
+    };
+
+    // share pages 404 once their video is deleted or expired
+    match reqwest::Client::new().get(&sharing.link).send().await {
+        Ok(response) if response.status() == StatusCode::NOT_FOUND => {}
+        Ok(response) if response.status().is_success() => {
+            return Ok(ShareLinkStatus::Valid { link: sharing.link })
+        }
+        Ok(response) => {
+            warn!(
+                "Unexpected status checking share link: {}",
+                response.status()
+            );
+            return Ok(ShareLinkStatus::Unknown { link: sharing.link });
+        }
+        Err(e) => {
+            warn!("Failed to check share link: {e}");
+            return Ok(ShareLinkStatus::Unknown { link: sharing.link });
+        }
+    }
+
+    info!("Share link {} is broken", sharing.link);
+
+    if !reupload {
+        return Ok(ShareLinkStatus::Broken { link: sharing.link });
+    }
+
+    let output_path = meta.output_path();
+    if !output_path.exists() {
+        return Err("Can't re-upload as the rendered video no longer exists".to_string());
+    }
+
+    let s3_config =
+        create_or_get_video(&app, false, None, Some(meta.pretty_name.clone()), None).await?;
+
+    let uploaded_video = upload_video(
+        &app,
+        s3_config.id().to_string(),
+        output_path,
+        Some(s3_config),
+        Some(meta.project_path.join("screenshots/display.jpg")),
+    )
+    .await?;
+
+    meta.sharing = Some(SharingMeta {
+        link: uploaded_video.link.clone(),
+        id: uploaded_video.id,
+    });
+    meta.save_for_project().map_err(|e| e.to_string())?;
+    dashboard::set_upload_failed(&meta.project_path, false);
+
+    Ok(ShareLinkStatus::Reuploaded {
+        link: uploaded_video.link,
+        previous_link: sharing.link,
+    })
+}
+
 pub async fn prepare_screenshot_upload(
     app: &AppHandle,
     s3_config: &S3UploadMeta,
@@ -464,7 +649,10 @@ pub async fn prepare_screenshot_upload(
 
     let presigned_put = presigned_s3_put(app, body).await?;
 
-    let compressed_image = compress_image(screenshot_path).await?;
+    let quality = upload_screenshot_quality(screenshot_quality(
+        GeneralSettingsStore::get(app).ok().flatten().as_ref(),
+    ));
+    let compressed_image = compress_image(screenshot_path, quality).await?;
 
     reqwest::Client::new()
         .put(presigned_put)
@@ -475,7 +663,61 @@ pub async fn prepare_screenshot_upload(
         .map_err(|e| format!("Error uploading screenshot: {}", e))
 }
 
-async fn compress_image(path: PathBuf) -> Result<Vec<u8>, String> {
+/// Uploads the looping preview GIF shown on the share page before the video plays
+pub async fn prepare_gif_upload(
+    app: &AppHandle,
+    s3_config: &S3UploadMeta,
+    gif_path: PathBuf,
+) -> Result<reqwest::Response, String> {
+    let body = S3ImageUploadBody {
+        base: S3UploadBody {
+            video_id: s3_config.id.clone(),
+            subpath: "screenshot/preview.gif".to_string(),
+        },
+    };
+
+    let presigned_put = presigned_s3_put(app, body).await?;
+
+    let gif = tokio::fs::read(&gif_path)
+        .await
+        .map_err(|e| format!("Failed to read preview GIF: {}", e))?;
+
+    reqwest::Client::new()
+        .put(presigned_put)
+        .header(CONTENT_LENGTH, gif.len())
+        .body(gif)
+        .send()
+        .await
+        .map_err(|e| format!("Error uploading preview GIF: {}", e))
+}
+
+/// Uploads a WebVTT chapters track next to the video for the share page
+pub async fn prepare_chapters_upload(
+    app: &AppHandle,
+    s3_config: &S3UploadMeta,
+    chapters_path: PathBuf,
+) -> Result<reqwest::Response, String> {
+    let body = S3UploadBody {
+        video_id: s3_config.id.clone(),
+        subpath: "chapters.vtt".to_string(),
+    };
+
+    let presigned_put = presigned_s3_put(app, body).await?;
+
+    let chapters = tokio::fs::read(&chapters_path)
+        .await
+        .map_err(|e| format!("Failed to read chapters: {}", e))?;
+
+    reqwest::Client::new()
+        .put(presigned_put)
+        .header(CONTENT_LENGTH, chapters.len())
+        .body(chapters)
+        .send()
+        .await
+        .map_err(|e| format!("Error uploading chapters: {}", e))
+}
+
+async fn compress_image(path: PathBuf, quality: u8) -> Result<Vec<u8>, String> {
     task::spawn_blocking(move || {
         let img = ImageReader::open(&path)
             .map_err(|e| format!("Failed to open image: {}", e))?
@@ -488,7 +730,7 @@ async fn compress_image(path: PathBuf) -> Result<Vec<u8>, String> {
         let resized_img = img.resize(new_width, new_height, image::imageops::FilterType::Nearest);
 
         let mut buffer = Vec::new();
-        let mut encoder = JpegEncoder::new_with_quality(&mut buffer, 30);
+        let mut encoder = JpegEncoder::new_with_quality(&mut buffer, quality);
         encoder
             .encode(
                 resized_img.as_bytes(),
@@ -505,13 +747,438 @@ async fn compress_image(path: PathBuf) -> Result<Vec<u8>, String> {
 }
 
 // a typical recommended chunk size is 5MB (AWS min part size).
-const CHUNK_SIZE: u64 = 5 * 1024 * 1024; // 5MB
 const MIN_PART_SIZE: u64 = 5 * 1024 * 1024; // For non-final parts
+const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024; // AWS max part size
+/// The largest part a flush sends. Parts are read into memory, so this is
+/// well below `MAX_PART_SIZE`.
+const FLUSH_PART_SIZE: u64 = 100 * 1024 * 1024;
 
 pub struct InstantMultipartUpload {
     pub handle: tokio::task::JoinHandle<Result<(), String>>,
 }
 
+/// Progressive uploads that are still running, keyed by video id
+#[derive(Default)]
+pub struct ActiveUploads(std::sync::Mutex<HashMap<String, ActiveUpload>>);
+
+impl ActiveUploads {
+    /// Forgets a progressive upload that was aborted before it could clean up after itself
+    pub fn remove(app: &AppHandle, video_id: &str) {
+        app.state::<ActiveUploads>()
+            .0
+            .lock()
+            .unwrap()
+            .remove(video_id);
+    }
+
+    /// The files being uploaded
+    pub fn files(app: &AppHandle) -> Vec<PathBuf> {
+        app.state::<ActiveUploads>()
+            .0
+            .lock()
+            .unwrap()
+            .values()
+            .map(|upload| upload.file_path.clone())
+            .collect()
+    }
+}
+
+/// Links of the videos whose progressive upload completed since the app started, keyed by video id
+#[derive(Default)]
+pub struct CompletedUploads(std::sync::Mutex<HashMap<String, String>>);
+
+struct ActiveUpload {
+    file_path: PathBuf,
+    link: String,
+    flush_tx: flume::Sender<()>,
+    paused_tx: tokio::sync::watch::Sender<bool>,
+    done_rx: tokio::sync::watch::Receiver<Option<Result<(), String>>>,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum UploadFinalizeMethod {
+    /// The progressive upload sent what was left and completed
+    Multipart,
+    /// The whole file was uploaded again
+    FullUpload,
+}
+
+#[derive(Serialize, Type, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FinalizedUpload {
+    pub link: String,
+    pub method: UploadFinalizeMethod,
+}
+
+/// Finishes uploading a stopped instant recording now instead of at the progressive
+/// upload's own pace. A running progressive upload sends everything that's left in as
+/// few parts as possible and completes, otherwise the whole file is uploaded.
+#[tauri::command]
+#[specta::specta]
+pub async fn finalize_upload_now(
+    app: AppHandle,
+    state: MutableState<'_, App>,
+    video_id: String,
+) -> Result<FinalizedUpload, String> {
+    if let Some(InProgressRecording::Instant {
+        video_upload_info: Some(video_upload_info),
+        ..
+    }) = &state.read().await.current_recording
+    {
+        if video_upload_info.id == video_id {
+            return Err("Stop the recording before finalizing its upload".to_string());
+        }
+    }
+
+    let active = app
+        .state::<ActiveUploads>()
+        .0
+        .lock()
+        .unwrap()
+        .get(&video_id)
+        .map(|upload| {
+            (
+                upload.link.clone(),
+                upload.flush_tx.clone(),
+                upload.done_rx.clone(),
+            )
+        });
+
+    if let Some((link, flush_tx, mut done_rx)) = active {
+        info!("Flushing progressive upload for {video_id}");
+        flush_tx.try_send(()).ok();
+
+        let result = done_rx
+            .wait_for(|result| result.is_some())
+            .await
+            .map_err(|e| e.to_string())?
+            .clone();
+
+        // handle_recording_finish falls back to a full upload when this fails
+        result.unwrap_or_else(|| Err("Progressive upload ended unexpectedly".to_string()))?;
+
+        return Ok(FinalizedUpload {
+            link,
+            method: UploadFinalizeMethod::Multipart,
+        });
+    }
+
+    // the progressive upload may have finished on its own, leaving nothing to redo
+    let completed = app
+        .state::<CompletedUploads>()
+        .0
+        .lock()
+        .unwrap()
+        .get(&video_id)
+        .cloned();
+    if let Some(link) = completed {
+        return Ok(FinalizedUpload {
+            link,
+            method: UploadFinalizeMethod::Multipart,
+        });
+    }
+
+    let meta = recording_for_video(&app, &video_id)?;
+    let screenshot = meta.project_path.join("screenshots/display.jpg");
+
+    let uploaded = upload_video(
+        &app,
+        video_id,
+        meta.output_path(),
+        None,
+        screenshot.exists().then_some(screenshot),
+    )
+    .await;
+
+    dashboard::set_upload_failed(&meta.project_path, uploaded.is_err());
+
+    Ok(FinalizedUpload {
+        link: uploaded?.link,
+        method: UploadFinalizeMethod::FullUpload,
+    })
+}
+
+fn recording_for_video(app: &AppHandle, video_id: &str) -> Result<RecordingMeta, String> {
+    std::fs::read_dir(recordings_path(app)?)
+        .map_err(|e| format!("Failed to read recordings directory: {e}"))?
+        .flatten()
+        .filter_map(|entry| RecordingMeta::load_for_project(&entry.path()).ok())
+        .find(|meta| meta.sharing.as_ref().is_some_and(|s| s.id == video_id))
+        .ok_or_else(|| format!("No recording found for video {video_id}"))
+}
+
+/// Re-uploads an instant recording whose upload failed to the video its share link
+/// already points to, so the link starts working. Returns the link.
+///
+/// `output_dir` is where the recording was saved if it was started with one, otherwise
+/// it's looked for in the default output directory and the recordings directory.
+#[tauri::command]
+#[specta::specta]
+pub async fn retry_upload(
+    app: AppHandle,
+    recording_id: String,
+    output_dir: Option<PathBuf>,
+) -> Result<String, String> {
+    let project_path = locate_recording_dir(&app, &recording_id, output_dir)?;
+    let meta = RecordingMeta::load_for_project(&project_path)
+        .map_err(|e| format!("Failed to load recording meta: {e}"))?;
+
+    retry_upload_with(
+        &project_path,
+        upload_path(&meta),
+        meta.sharing.as_ref(),
+        |video_id, video_path, screenshot_path| {
+            let app = app.clone();
+            async move {
+                let progress = {
+                    let video_id = video_id.clone();
+                    move |uploaded_bytes, total_bytes| MultipartUploadProgress {
+                        video_id: video_id.clone(),
+                        uploaded_bytes,
+                        total_bytes: Some(total_bytes),
+                    }
+                };
+
+                let total_bytes = std::fs::metadata(&video_path)
+                    .map(|m| m.len())
+                    .unwrap_or_default();
+                progress(0, total_bytes).emit(&app).ok();
+
+                let uploaded = upload_video_with_progress(
+                    &app,
+                    video_id.clone(),
+                    video_path,
+                    Some(S3UploadMeta::new(video_id.clone())),
+                    screenshot_path,
+                    {
+                        let app = app.clone();
+                        let progress = progress.clone();
+                        move |uploaded_bytes, total_bytes| {
+                            progress(uploaded_bytes, total_bytes).emit(&app).ok();
+                        }
+                    },
+                )
+                .await?;
+                progress(total_bytes, total_bytes).emit(&app).ok();
+
+                info!("Retried upload to {}", uploaded.link);
+                Ok(())
+            }
+        },
+    )
+    .await
+}
+
+/// The `.cap` directory of the recording with `recording_id`, looked for in `output_dir`
+/// if it was started with one, then the default output directory and the recordings directory
+pub fn locate_recording_dir(
+    app: &AppHandle,
+    recording_id: &str,
+    output_dir: Option<PathBuf>,
+) -> Result<PathBuf, String> {
+    let default_output_dir = GeneralSettingsStore::get(app)
+        .ok()
+        .flatten()
+        .and_then(|s| s.default_output_dir);
+
+    find_recording_dir(
+        recording_id,
+        output_dir
+            .into_iter()
+            .chain(default_output_dir)
+            .chain(recordings_path(app).ok()),
+    )
+}
+
+/// The `.cap` directory of the recording with `recording_id` in the first of `dirs` that has it
+fn find_recording_dir(
+    recording_id: &str,
+    dirs: impl IntoIterator<Item = PathBuf>,
+) -> Result<PathBuf, String> {
+    let mut error = format!("Recording {recording_id} wasn't found");
+
+    for dir in dirs {
+        match existing_recording_dir(&dir, recording_id) {
+            Ok(recording_dir) => return Ok(recording_dir),
+            Err(e) => error = e,
+        }
+    }
+
+    Err(error)
+}
+
+/// The file a recording's upload sends, its share rendition if it has one
+fn upload_path(meta: &RecordingMeta) -> PathBuf {
+    meta.share_rendition()
+        .map(|rendition| meta.path(&rendition.path))
+        .unwrap_or_else(|| meta.output_path())
+}
+
+/// The format a recording's video was written in, going by its extension
+fn video_output_format(path: &Path) -> InstantOutputFormat {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("webm") => InstantOutputFormat::WebM,
+        _ => InstantOutputFormat::Mp4,
+    }
+}
+
+async fn retry_upload_with<F, Fut>(
+    project_path: &Path,
+    video_path: PathBuf,
+    sharing: Option<&SharingMeta>,
+    upload: F,
+) -> Result<String, String>
+where
+    F: FnOnce(String, PathBuf, Option<PathBuf>) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
+    let Some(sharing) = sharing else {
+        return Err("This recording was never uploaded".to_string());
+    };
+
+    if !video_path.exists() {
+        return Err("Can't retry the upload as the recording's video no longer exists".to_string());
+    }
+
+    let screenshot_path = project_path.join("screenshots/display.jpg");
+
+    let uploaded = upload(
+        sharing.id.clone(),
+        video_path,
+        screenshot_path.exists().then_some(screenshot_path),
+    )
+    .await;
+    dashboard::set_upload_failed(project_path, uploaded.is_err());
+    uploaded?;
+
+    info!("Retried upload of {}", project_path.display());
+
+    Ok(sharing.link.clone())
+}
+
+/// Emitted as an instant recording's upload progresses, for progressive uploads and retries.
+/// `UploadProgress` covers other uploads of whole files.
+#[derive(Serialize, Type, tauri_specta::Event, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MultipartUploadProgress {
+    pub video_id: String,
+    pub uploaded_bytes: u64,
+    /// `None` until recording stops, as the file's still growing
+    pub total_bytes: Option<u64>,
+}
+
+/// How much of a progressive upload has been sent
+struct ProgressReporter {
+    video_id: String,
+    uploaded_bytes: u64,
+    total_bytes: Option<u64>,
+}
+
+impl ProgressReporter {
+    fn new(video_id: String) -> Self {
+        Self {
+            video_id,
+            uploaded_bytes: 0,
+            total_bytes: None,
+        }
+    }
+
+    fn progress(&self) -> MultipartUploadProgress {
+        MultipartUploadProgress {
+            video_id: self.video_id.clone(),
+            uploaded_bytes: self.uploaded_bytes,
+            total_bytes: self.total_bytes,
+        }
+    }
+
+    /// Everything before `position` has been uploaded.
+    /// Re-uploading an earlier part, like the header, doesn't count again.
+    fn uploaded_to(&mut self, position: u64) -> MultipartUploadProgress {
+        self.uploaded_bytes = self.uploaded_bytes.max(position);
+        self.progress()
+    }
+
+    fn recording_stopped(&mut self, file_size: u64) {
+        self.total_bytes = Some(file_size.max(self.uploaded_bytes));
+    }
+
+    fn finished(&mut self) -> MultipartUploadProgress {
+        let total = self.total_bytes.unwrap_or(self.uploaded_bytes);
+        self.total_bytes = Some(total);
+        self.uploaded_to(total)
+    }
+}
+
+enum UploadStep {
+    /// Upload a part of this many bytes
+    Part(u64),
+    /// Everything's uploaded, complete the upload
+    Complete,
+    Wait,
+}
+
+/// What a progressive upload does next with `new_data_size` bytes written since its
+/// last part. `realtime_is_done` is None when the file's already complete.
+fn next_upload_step(
+    new_data_size: u64,
+    chunk_size: u64,
+    realtime_is_done: Option<bool>,
+    flushing: bool,
+    paused: bool,
+) -> UploadStep {
+    let file_complete = realtime_is_done.unwrap_or(true);
+
+    // finalizing or the recording stopping overrides a pause, as they're waiting on the
+    // upload and nothing would resume it
+    if paused && !flushing && !file_complete {
+        return UploadStep::Wait;
+    }
+
+    if new_data_size >= chunk_size || (new_data_size > 0 && file_complete) {
+        UploadStep::Part(if flushing && file_complete {
+            new_data_size.min(chunk_size.max(FLUSH_PART_SIZE))
+        } else {
+            new_data_size.min(chunk_size)
+        })
+    } else if new_data_size == 0 && file_complete {
+        UploadStep::Complete
+    } else {
+        UploadStep::Wait
+    }
+}
+
+/// Stops a progressive upload sending parts, for when the connection's metered.
+/// Recording carries on, and what's written is uploaded once resumed or recording stops.
+#[tauri::command]
+#[specta::specta]
+pub fn pause_upload(app: AppHandle, video_id: String) -> Result<(), String> {
+    set_upload_paused(&app, &video_id, true)
+}
+
+/// Resumes a paused progressive upload from the last part it sent
+#[tauri::command]
+#[specta::specta]
+pub fn resume_upload(app: AppHandle, video_id: String) -> Result<(), String> {
+    set_upload_paused(&app, &video_id, false)
+}
+
+fn set_upload_paused(app: &AppHandle, video_id: &str, paused: bool) -> Result<(), String> {
+    let uploads = app.state::<ActiveUploads>();
+    let uploads = uploads.0.lock().unwrap();
+    let upload = uploads
+        .get(video_id)
+        .ok_or_else(|| format!("No upload in progress for video {video_id}"))?;
+
+    upload.paused_tx.send_replace(paused);
+    info!(
+        "{} progressive upload for {video_id}",
+        if paused { "Paused" } else { "Resumed" }
+    );
+
+    Ok(())
+}
+
 impl InstantMultipartUpload {
     /// starts a progressive (multipart) upload that runs until recording stops
     /// and the file has stabilized (no additional data is being written).
@@ -522,23 +1189,91 @@ impl InstantMultipartUpload {
         pre_created_video: VideoUploadInfo,
         realtime_upload_done: Option<Receiver<()>>,
     ) -> Self {
+        let chunk_size = Self::chunk_size(&app);
+
+        let (flush_tx, flush_rx) = flume::bounded(1);
+        let (paused_tx, paused_rx) = tokio::sync::watch::channel(false);
+        let (done_tx, done_rx) = tokio::sync::watch::channel(None);
+        let upload_id = pre_created_video.id.clone();
+        let link = pre_created_video.link.clone();
+
+        app.state::<ActiveUploads>().0.lock().unwrap().insert(
+            upload_id.clone(),
+            ActiveUpload {
+                file_path: file_path.clone(),
+                link: pre_created_video.link.clone(),
+                flush_tx,
+                paused_tx,
+                done_rx,
+            },
+        );
+
         Self {
-            handle: spawn_actor(Self::run(
-                app,
-                video_id,
-                file_path,
-                pre_created_video,
-                realtime_upload_done,
-            )),
+            handle: spawn_actor(async move {
+                let result = Self::run(
+                    app.clone(),
+                    video_id,
+                    file_path,
+                    pre_created_video,
+                    realtime_upload_done,
+                    flush_rx,
+                    paused_rx,
+                    chunk_size,
+                )
+                .await;
+
+                // recorded before it's removed from the active ones so finalizing always finds it
+                if result.is_ok() {
+                    app.state::<CompletedUploads>()
+                        .0
+                        .lock()
+                        .unwrap()
+                        .insert(upload_id.clone(), link);
+                }
+                app.state::<ActiveUploads>()
+                    .0
+                    .lock()
+                    .unwrap()
+                    .remove(&upload_id);
+                done_tx.send(Some(result.clone())).ok();
+
+                result
+            }),
         }
     }
 
+    /// The configured part size, kept within the multipart limits S3 enforces
+    fn chunk_size(app: &AppHandle) -> u64 {
+        let Some(chunk_size_mb) = GeneralSettingsStore::get(app)
+            .ok()
+            .flatten()
+            .map(|s| s.upload_chunk_size_mb)
+        else {
+            return MIN_PART_SIZE;
+        };
+
+        let chunk_size = chunk_size_mb as u64 * 1024 * 1024;
+        let clamped = chunk_size.clamp(MIN_PART_SIZE, MAX_PART_SIZE);
+
+        if clamped != chunk_size {
+            warn!(
+                "Upload chunk size of {chunk_size_mb}MB is outside the allowed range, using {}MB",
+                clamped / 1024 / 1024
+            );
+        }
+
+        clamped
+    }
+
     pub async fn run(
         app: AppHandle,
         video_id: String,
         file_path: PathBuf,
         pre_created_video: VideoUploadInfo,
         realtime_video_done: Option<Receiver<()>>,
+        flush: Receiver<()>,
+        paused: tokio::sync::watch::Receiver<bool>,
+        chunk_size: u64,
     ) -> Result<(), String> {
         use std::time::Duration;
         use tokio::sync::mpsc;
@@ -560,13 +1295,14 @@ impl InstantMultipartUpload {
         // initiate the multipart upload
         // --------------------------------------------
         println!("Initiating multipart upload for {video_id}...");
+        let content_type = video_output_format(&file_path).content_type();
         let initiate_response = match app
             .authed_api_request("/api/upload/multipart/initiate", |c, url| {
                 c.post(url)
                     .header("Content-Type", "application/json")
                     .json(&serde_json::json!({
                         "videoId": s3_config.id(),
-                        "contentType": "video/mp4"
+                        "contentType": content_type
                     }))
             })
             .await
@@ -610,10 +1346,13 @@ impl InstantMultipartUpload {
         println!("Multipart upload initiated with ID: {}", upload_id);
 
         let mut realtime_is_done = realtime_video_done.as_ref().map(|_| false);
+        let mut flushing = false;
+        let mut progress = ProgressReporter::new(video_id.clone());
+        progress.progress().emit(&app).ok();
 
         // --------------------------------------------
         // Main loop while upload not complete:
-        //   - If we have >= CHUNK_SIZE new data, upload.
+        //   - If we have >= chunk_size new data, upload.
         //   - If recording hasn't stopped, keep waiting.
         //   - If recording stopped, do leftover final(s).
         // --------------------------------------------
@@ -635,6 +1374,11 @@ impl InstantMultipartUpload {
                 }
             }
 
+            if flush.try_recv().is_ok() {
+                info!("Flushing upload once recording has stopped");
+                flushing = true;
+            }
+
             // Check the file's current size
             if !file_path.exists() {
                 println!("File no longer exists, aborting upload");
@@ -650,14 +1394,21 @@ impl InstantMultipartUpload {
                 }
             };
 
+            // the file's only complete once recording's stopped
+            if realtime_is_done.unwrap_or(true) {
+                progress.recording_stopped(file_size);
+            }
+
             let new_data_size = file_size - last_uploaded_position;
 
-            if ((new_data_size >= CHUNK_SIZE)
-                || new_data_size > 0 && realtime_is_done.unwrap_or(false))
-                || (realtime_is_done.is_none() && new_data_size > 0)
-            {
-                // We have a full chunk to send
-                match Self::upload_chunk(
+            match next_upload_step(
+                new_data_size,
+                chunk_size,
+                realtime_is_done,
+                flushing,
+                *paused.borrow(),
+            ) {
+                UploadStep::Part(size) => match Self::upload_chunk(
                     &app,
                     &client,
                     &file_path,
@@ -665,12 +1416,13 @@ impl InstantMultipartUpload {
                     &upload_id,
                     &mut part_number,
                     &mut last_uploaded_position,
-                    new_data_size.min(CHUNK_SIZE),
+                    size,
                 )
                 .await
                 {
                     Ok(part) => {
                         uploaded_parts.push(part);
+                        progress.uploaded_to(last_uploaded_position).emit(&app).ok();
                     }
                     Err(e) => {
                         println!(
@@ -679,50 +1431,52 @@ impl InstantMultipartUpload {
                         );
                         sleep(Duration::from_secs(1)).await;
                     }
-                }
-            } else if new_data_size == 0 && realtime_is_done.unwrap_or(true) {
-                if realtime_is_done.unwrap_or(false) {
-                    info!("realtime video done, uploading header chunk");
+                },
+                UploadStep::Complete => {
+                    if realtime_is_done.unwrap_or(false) {
+                        info!("realtime video done, uploading header chunk");
+
+                        match Self::upload_chunk(
+                            &app,
+                            &client,
+                            &file_path,
+                            s3_config.id(),
+                            &upload_id,
+                            &mut 1,
+                            &mut 0,
+                            uploaded_parts[0].size as u64,
+                        )
+                        .await
+                        {
+                            Ok(part) => {
+                                uploaded_parts[0] = part;
+                                println!("Successfully re-uploaded first chunk",);
+                            }
+                            Err(e) => {
+                                return Err(format!("Failed to re-upload first chunk"));
+                            }
+                        }
+                    }
 
-                    match Self::upload_chunk(
+                    // All leftover chunks are now uploaded. We finalize.
+                    println!(
+                        "Completing multipart upload with {} parts",
+                        uploaded_parts.len()
+                    );
+                    Self::finalize_upload(
                         &app,
-                        &client,
                         &file_path,
-                        s3_config.id(),
+                        &s3_config.id(),
                         &upload_id,
-                        &mut 1,
-                        &mut 0,
-                        uploaded_parts[0].size as u64,
+                        &uploaded_parts,
                     )
-                    .await
-                    {
-                        Ok(part) => {
-                            uploaded_parts[0] = part;
-                            println!("Successfully re-uploaded first chunk",);
-                        }
-                        Err(e) => {
-                            return Err(format!("Failed to re-upload first chunk"));
-                        }
-                    }
-                }
+                    .await?;
 
-                // All leftover chunks are now uploaded. We finalize.
-                println!(
-                    "Completing multipart upload with {} parts",
-                    uploaded_parts.len()
-                );
-                Self::finalize_upload(
-                    &app,
-                    &file_path,
-                    &s3_config.id(),
-                    &upload_id,
-                    &uploaded_parts,
-                )
-                .await?;
+                    progress.finished().emit(&app).ok();
 
-                break;
-            } else {
-                tokio::time::sleep(Duration::from_secs(1)).await;
+                    break;
+                }
+                UploadStep::Wait => sleep(Duration::from_secs(1)).await,
             }
         }
 
@@ -1068,3 +1822,277 @@ struct UploadedPart {
     etag: String,
     size: usize,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn progress_only_increases() {
+        // file sizes seen by the uploader on each pass, then the header re-upload
+        let file_sizes = [6, 12, 15, 20, 20];
+        let chunk_size = 5;
+
+        let mut progress = ProgressReporter::new("video".to_string());
+        let mut events = vec![progress.progress()];
+        let mut position = 0;
+
+        for (pass, file_size) in file_sizes.into_iter().enumerate() {
+            if pass >= 3 {
+                progress.recording_stopped(file_size);
+            }
+
+            // the mock uploader sends whole chunks while recording, and the rest once stopped
+            while file_size - position >= chunk_size
+                || (progress.total_bytes.is_some() && position < file_size)
+            {
+                position += (file_size - position).min(chunk_size);
+                events.push(progress.uploaded_to(position));
+            }
+        }
+        events.push(progress.uploaded_to(chunk_size));
+        events.push(progress.finished());
+
+        assert!(events
+            .windows(2)
+            .all(|pair| pair[0].uploaded_bytes <= pair[1].uploaded_bytes));
+        assert!(events
+            .iter()
+            .take_while(|event| event.uploaded_bytes < 15)
+            .all(|event| event.total_bytes.is_none()));
+        assert_eq!(
+            events.last(),
+            Some(&MultipartUploadProgress {
+                video_id: "video".to_string(),
+                uploaded_bytes: 20,
+                total_bytes: Some(20),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_upload_can_be_retried() {
+        let project_path = std::env::temp_dir().join(format!("{}.cap", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(project_path.join("content")).unwrap();
+        std::fs::write(project_path.join("content/output.mp4"), b"video").unwrap();
+        dashboard::set_upload_failed(&project_path, true);
+
+        let sharing = SharingMeta {
+            id: "video".to_string(),
+            link: "https://cap.so/s/video".to_string(),
+        };
+
+        let video_path = project_path.join("content/output.mp4");
+        let failed = retry_upload_with(
+            &project_path,
+            video_path.clone(),
+            Some(&sharing),
+            |_, _, _| async { Err("offline".to_string()) },
+        )
+        .await;
+        assert!(failed.is_err());
+        assert!(project_path.join(".upload-failed").exists());
+
+        let mut uploaded = None;
+        let link = retry_upload_with(
+            &project_path,
+            video_path.clone(),
+            Some(&sharing),
+            |id, path, screenshot| {
+                uploaded = Some((id, path, screenshot));
+                async { Ok(()) }
+            },
+        )
+        .await;
+
+        assert_eq!(link.as_deref(), Ok(sharing.link.as_str()));
+        assert_eq!(
+            uploaded,
+            Some(("video".to_string(), video_path.clone(), None))
+        );
+        assert!(!project_path.join(".upload-failed").exists());
+        assert!(
+            retry_upload_with(&project_path, video_path, None, |_, _, _| async { Ok(()) })
+                .await
+                .is_err()
+        );
+
+        std::fs::remove_dir_all(&project_path).ok();
+    }
+
+    #[test]
+    fn pausing_uploads_every_part_once() {
+        let chunk_size = 5;
+        // the file grows 3 bytes a pass until recording stops on the 10th
+        let mut file_size = 0;
+        let mut position = 0;
+        let mut parts = vec![];
+
+        for pass in 0.. {
+            if pass < 10 {
+                file_size += 3;
+            }
+            let realtime_is_done = Some(pass >= 10);
+            let paused = (3..7).contains(&pass);
+
+            match next_upload_step(
+                file_size - position,
+                chunk_size,
+                realtime_is_done,
+                false,
+                paused,
+            ) {
+                UploadStep::Part(size) => {
+                    assert!(!paused, "uploaded while paused");
+                    parts.push((position, size));
+                    position += size;
+                }
+                UploadStep::Complete => break,
+                UploadStep::Wait => {}
+            }
+        }
+
+        // contiguous parts covering the whole file, with nothing sent twice
+        let mut expected_start = 0;
+        for (start, size) in &parts {
+            assert_eq!(*start, expected_start);
+            expected_start += size;
+        }
+        assert_eq!(expected_start, file_size);
+    }
+
+    #[tokio::test]
+    async fn same_idempotency_key_gets_the_same_video() {
+        let created = CreatedVideos::default();
+        let requests = &std::sync::atomic::AtomicUsize::new(0);
+        // a mock API that creates a new video on every request
+        let create = move || async move {
+            let n = requests.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(S3UploadMeta {
+                id: format!("video-{n}"),
+            })
+        };
+
+        let first = create_video_once(&created, Some("recording"), create)
+            .await
+            .unwrap();
+        let retried = create_video_once(&created, Some("recording"), create)
+            .await
+            .unwrap();
+        assert_eq!(first.id(), retried.id());
+        assert_eq!(requests.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let other = create_video_once(&created, Some("other-recording"), create)
+            .await
+            .unwrap();
+        assert_ne!(other.id(), first.id());
+        let unkeyed = create_video_once(&created, None, create).await.unwrap();
+        assert_ne!(unkeyed.id(), other.id());
+    }
+
+    #[tokio::test]
+    async fn other_keys_dont_wait_for_a_slow_creation() {
+        let created = CreatedVideos::default();
+        let (respond_tx, respond_rx) = tokio::sync::oneshot::channel::<()>();
+
+        let slow = create_video_once(&created, Some("slow"), || async move {
+            respond_rx.await.ok();
+            Ok(S3UploadMeta {
+                id: "slow".to_string(),
+            })
+        });
+        tokio::pin!(slow);
+        // mid-request, waiting on the response
+        assert!(futures::poll!(&mut slow).is_pending());
+
+        let fast = tokio::time::timeout(
+            Duration::from_secs(1),
+            create_video_once(&created, Some("fast"), || async {
+                Ok(S3UploadMeta {
+                    id: "fast".to_string(),
+                })
+            }),
+        )
+        .await
+        .expect("waited for the other key's request");
+        assert_eq!(fast.unwrap().id(), "fast");
+
+        respond_tx.send(()).unwrap();
+        assert_eq!(slow.await.unwrap().id(), "slow");
+    }
+
+    #[test]
+    fn retries_find_recordings_saved_elsewhere() {
+        let root = std::env::temp_dir().join(format!("cap-retry-{}", uuid::Uuid::new_v4()));
+        let recordings_dir = root.join("recordings");
+        let output_dir = root.join("output");
+        std::fs::create_dir_all(recordings_dir.join("library.cap")).unwrap();
+        std::fs::create_dir_all(output_dir.join("elsewhere.cap")).unwrap();
+
+        let dirs = || [output_dir.clone(), recordings_dir.clone()];
+        assert_eq!(
+            find_recording_dir("elsewhere", dirs()),
+            Ok(output_dir.join("elsewhere.cap"))
+        );
+        assert_eq!(
+            find_recording_dir("library", dirs()),
+            Ok(recordings_dir.join("library.cap"))
+        );
+        assert!(find_recording_dir("missing", dirs()).is_err());
+
+        std::fs::remove_dir_all(&root).ok();
+    }
+
+    #[test]
+    fn retries_upload_the_share_rendition() {
+        let mut meta: RecordingMeta = serde_json::from_value(serde_json::json!({
+            "platform": null,
+            "pretty_name": "Recording",
+            "fps": 30,
+            "sample_rate": null,
+        }))
+        .unwrap();
+        meta.project_path = std::env::temp_dir().join("recording.cap");
+        assert_eq!(upload_path(&meta), meta.output_path());
+
+        meta.renditions.push(cap_project::RenditionMeta {
+            name: "720p".to_string(),
+            path: "content/renditions/720p.mp4".into(),
+            width: 1280,
+            height: 720,
+            share: true,
+        });
+        assert_eq!(
+            upload_path(&meta),
+            meta.project_path.join("content/renditions/720p.mp4")
+        );
+    }
+
+    #[test]
+    fn stopping_while_paused_finishes_the_upload() {
+        let chunk_size = 5;
+        let mut position = 0;
+
+        // paused with 12 bytes written, 5 of them uploaded, when recording stops
+        let file_size = 12;
+        assert!(matches!(
+            next_upload_step(file_size - 5, chunk_size, Some(false), false, true),
+            UploadStep::Wait
+        ));
+        position += 5;
+
+        for _ in 0..10 {
+            match next_upload_step(file_size - position, chunk_size, Some(true), false, true) {
+                UploadStep::Part(size) => position += size,
+                UploadStep::Complete => break,
+                UploadStep::Wait => panic!("waited for a resume after recording stopped"),
+            }
+        }
+
+        assert_eq!(position, file_size);
+        assert!(matches!(
+            next_upload_step(0, chunk_size, Some(true), false, true),
+            UploadStep::Complete
+        ));
+    }
+}
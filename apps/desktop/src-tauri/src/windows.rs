@@ -1,7 +1,7 @@
 #![allow(unused_mut)]
 #![allow(unused_imports)]
 
-use crate::{fake_window, general_settings::AppTheme, permissions, App, ArcLock};
+use crate::{fake_window, general_settings::AppTheme, permissions, recording, App, ArcLock};
 use cap_flags::FLAGS;
 use cap_media::{platform::logical_monitor_bounds, sources::CaptureScreen};
 use futures::pin_mut;
@@ -32,11 +32,14 @@ pub enum CapWindowId {
     Editor { id: u32 },
     RecordingsOverlay,
     WindowCaptureOccluder { screen_id: u32 },
+    BeRightBack { screen_id: u32 },
+    Countdown { screen_id: u32 },
     CaptureArea,
     Camera,
     InProgressRecording,
     Upgrade,
     ModeSelect,
+    SharePage,
     Debug,
 }
 
@@ -54,6 +57,7 @@ impl FromStr for CapWindowId {
             "recordings-overlay" => Self::RecordingsOverlay,
             "upgrade" => Self::Upgrade,
             "mode-select" => Self::ModeSelect,
+            "share-page" => Self::SharePage,
             "debug" => Self::Debug,
             s if s.starts_with("editor-") => Self::Editor {
                 id: s
@@ -67,6 +71,18 @@ impl FromStr for CapWindowId {
                     .parse::<u32>()
                     .map_err(|e| e.to_string())?,
             },
+            s if s.starts_with("countdown-") => Self::Countdown {
+                screen_id: s
+                    .replace("countdown-", "")
+                    .parse::<u32>()
+                    .map_err(|e| e.to_string())?,
+            },
+            s if s.starts_with("be-right-back-") => Self::BeRightBack {
+                screen_id: s
+                    .replace("be-right-back-", "")
+                    .parse::<u32>()
+                    .map_err(|e| e.to_string())?,
+            },
             _ => return Err(format!("unknown window label: {}", s)),
         })
     }
@@ -82,11 +98,14 @@ impl std::fmt::Display for CapWindowId {
             Self::WindowCaptureOccluder { screen_id } => {
                 write!(f, "window-capture-occluder-{screen_id}")
             }
+            Self::BeRightBack { screen_id } => write!(f, "be-right-back-{screen_id}"),
+            Self::Countdown { screen_id } => write!(f, "countdown-{screen_id}"),
             Self::CaptureArea => write!(f, "capture-area"),
             Self::InProgressRecording => write!(f, "in-progress-recording"),
             Self::RecordingsOverlay => write!(f, "recordings-overlay"),
             Self::Upgrade => write!(f, "upgrade"),
             Self::ModeSelect => write!(f, "mode-select"),
+            Self::SharePage => write!(f, "share-page"),
             Self::Editor { id } => write!(f, "editor-{id}"),
             Self::Debug => write!(f, "debug"),
         }
@@ -103,12 +122,16 @@ impl CapWindowId {
             Self::Setup => "Cap Setup".to_string(),
             Self::Settings => "Cap Settings".to_string(),
             Self::WindowCaptureOccluder { .. } => "Cap Window Capture Occluder".to_string(),
+            // this window is meant to be captured, so it must not be in the excluded windows list
+            Self::BeRightBack { .. } => "Cap Be Right Back".to_string(),
+            Self::Countdown { .. } => "Cap Countdown".to_string(),
             Self::CaptureArea => "Cap Capture Area".to_string(),
             Self::InProgressRecording => "Cap In Progress Recording".to_string(),
             Self::Editor { .. } => "Cap Editor".to_string(),
             Self::ModeSelect => "Cap Mode Selection".to_string(),
             Self::Camera => "Cap Camera".to_string(),
             Self::RecordingsOverlay => "Cap Recordings Overlay".to_string(),
+            Self::SharePage => "Cap Share".to_string(),
             _ => "Cap".to_string(),
         }
     }
@@ -122,6 +145,7 @@ impl CapWindowId {
                 | Self::Settings
                 | Self::Upgrade
                 | Self::ModeSelect
+                | Self::SharePage
         )
     }
 
@@ -145,8 +169,11 @@ impl CapWindowId {
             Self::InProgressRecording => Some(Some(LogicalPosition::new(-100.0, -100.0))),
             Self::Camera
             | Self::WindowCaptureOccluder { .. }
+            | Self::BeRightBack { .. }
+            | Self::Countdown { .. }
             | Self::CaptureArea
-            | Self::RecordingsOverlay => None,
+            | Self::RecordingsOverlay
+            | Self::SharePage => None,
             _ => Some(None),
         }
     }
@@ -173,11 +200,29 @@ pub enum ShowCapWindow {
     Editor { project_path: PathBuf },
     RecordingsOverlay,
     WindowCaptureOccluder { screen_id: u32 },
+    BeRightBack { card: BeRightBackCard },
+    // counts down from `secs` over the screen that's about to be recorded
+    Countdown { screen_id: u32, secs: u32 },
     CaptureArea { screen_id: u32 },
     Camera,
     InProgressRecording { position: Option<(f64, f64)> },
     Upgrade,
     ModeSelect,
+    // a recording's share page, loaded from the web app rather than bundled
+    SharePage { url: String },
+}
+
+/// What's shown over a screen while its recording is paused with a card
+#[derive(Clone, Type, Deserialize)]
+pub struct BeRightBackCard {
+    pub screen_id: u32,
+    pub text: String,
+    pub image_path: Option<PathBuf>,
+}
+
+fn share_page_url(url: &str) -> tauri::Result<tauri::Url> {
+    url.parse::<tauri::Url>()
+        .map_err(|e| tauri::Error::Anyhow(e.into()))
 }
 
 impl ShowCapWindow {
@@ -196,6 +241,10 @@ impl ShowCapWindow {
         }
 
         if let Some(window) = self.id(app).get(app) {
+            if let Self::SharePage { url } = self {
+                window.navigate(share_page_url(url)?)?;
+            }
+
             window.set_focus().ok();
             return Ok(window);
         }
@@ -317,7 +366,14 @@ impl ShowCapWindow {
             Self::Camera => {
                 const WINDOW_SIZE: f64 = 230.0 * 2.0;
 
-                let port = app.state::<Arc<RwLock<App>>>().read().await.camera_ws_port;
+                let (port, recording_mode) = {
+                    let state = app.state::<Arc<RwLock<App>>>();
+                    let state = state.read().await;
+                    (
+                        state.camera_ws_port,
+                        state.current_recording.as_ref().map(|r| r.inputs().mode),
+                    )
+                };
 
                 let mut window_builder = self
                     .window_builder(app, "/camera")
@@ -344,6 +400,8 @@ impl ShowCapWindow {
 
                 let window = window_builder.build()?;
 
+                recording::apply_camera_content_protection(app, recording_mode);
+
                 #[cfg(target_os = "macos")]
                 {
                     _ = window.run_on_main_thread({
@@ -389,6 +447,78 @@ impl ShowCapWindow {
 
                 window
             }
+            Self::BeRightBack { card } => {
+                let Some(bounds) = logical_monitor_bounds(card.screen_id) else {
+                    return Err(tauri::Error::WindowNotFound);
+                };
+
+                let card = serde_json::json!({ "text": card.text, "imagePath": card.image_path });
+
+                let mut window_builder = self
+                    .window_builder(app, "/be-right-back")
+                    .maximized(false)
+                    .resizable(false)
+                    .fullscreen(false)
+                    .shadow(false)
+                    .always_on_top(true)
+                    .visible_on_all_workspaces(true)
+                    .skip_taskbar(true)
+                    .inner_size(bounds.size.width, bounds.size.height)
+                    .position(bounds.position.x, bounds.position.y)
+                    .initialization_script(&format!(
+                        "
+			                window.__CAP__ = window.__CAP__ ?? {{}};
+			                window.__CAP__.beRightBackCard = {card};
+		                ",
+                    ));
+
+                let window = window_builder.build()?;
+
+                window.set_ignore_cursor_events(true).ok();
+
+                #[cfg(target_os = "macos")]
+                {
+                    crate::platform::set_window_level(window.as_ref().window(), 900);
+                }
+
+                window
+            }
+            Self::Countdown { screen_id, secs } => {
+                let Some(bounds) = logical_monitor_bounds(*screen_id) else {
+                    return Err(tauri::Error::WindowNotFound);
+                };
+
+                let mut window_builder = self
+                    .window_builder(app, "/countdown")
+                    .maximized(false)
+                    .resizable(false)
+                    .fullscreen(false)
+                    .shadow(false)
+                    .always_on_top(true)
+                    .visible_on_all_workspaces(true)
+                    .content_protected(true)
+                    .skip_taskbar(true)
+                    .inner_size(bounds.size.width, bounds.size.height)
+                    .position(bounds.position.x, bounds.position.y)
+                    .transparent(true)
+                    .initialization_script(&format!(
+                        "
+			                window.__CAP__ = window.__CAP__ ?? {{}};
+			                window.__CAP__.countdownSecs = {secs};
+		                ",
+                    ));
+
+                let window = window_builder.build()?;
+
+                window.set_ignore_cursor_events(true).ok();
+
+                #[cfg(target_os = "macos")]
+                {
+                    crate::platform::set_window_level(window.as_ref().window(), 900);
+                }
+
+                window
+            }
             Self::CaptureArea { screen_id } => {
                 let mut window_builder = self
                     .window_builder(app, "/capture-area")
@@ -523,6 +653,17 @@ impl ShowCapWindow {
 
                 window
             }
+            // not built with window_builder as the page brings no title bar of its own
+            Self::SharePage { url } => {
+                WebviewWindow::builder(app, id.label(), WebviewUrl::External(share_page_url(url)?))
+                    .title(id.title())
+                    .inner_size(1100.0, 750.0)
+                    .min_inner_size(600.0, 400.0)
+                    .resizable(true)
+                    .center()
+                    .focused(true)
+                    .build()?
+            }
         };
 
         // removing this for now as it causes windows to just stay hidden sometimes -_-
@@ -591,11 +732,18 @@ impl ShowCapWindow {
                     screen_id: *screen_id,
                 }
             }
+            ShowCapWindow::BeRightBack { card } => CapWindowId::BeRightBack {
+                screen_id: card.screen_id,
+            },
+            ShowCapWindow::Countdown { screen_id, .. } => CapWindowId::Countdown {
+                screen_id: *screen_id,
+            },
             ShowCapWindow::CaptureArea { .. } => CapWindowId::CaptureArea,
             ShowCapWindow::Camera { .. } => CapWindowId::Camera,
             ShowCapWindow::InProgressRecording { .. } => CapWindowId::InProgressRecording,
             ShowCapWindow::Upgrade => CapWindowId::Upgrade,
             ShowCapWindow::ModeSelect => CapWindowId::ModeSelect,
+            ShowCapWindow::SharePage { .. } => CapWindowId::SharePage,
         }
     }
 }
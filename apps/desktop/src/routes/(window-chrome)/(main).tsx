@@ -7,11 +7,13 @@ import {
   UseQueryResult,
 } from "@tanstack/solid-query";
 import { getVersion } from "@tauri-apps/api/app";
+import { message } from "@tauri-apps/plugin-dialog";
 import { getCurrentWindow, LogicalSize } from "@tauri-apps/api/window";
 import { cx } from "cva";
 import {
   ComponentProps,
   createEffect,
+  createMemo,
   createResource,
   createSignal,
   ErrorBoundary,
@@ -189,15 +191,62 @@ function Page() {
     }
   });
 
+  // keeps a pre-roll capturing with the selected options so starting a recording includes it
+  const preRollInputs = createMemo(
+    () => {
+      if (isRecording() || !generalSettings.data?.preRollSecs) return;
+      if (rawOptions.mode !== "studio") return;
+
+      const target = options.target();
+      if ("id" in target ? target.id === -1 : target.screen === -1) return;
+
+      return {
+        capture_target: target,
+        mode: rawOptions.mode,
+        capture_system_audio: rawOptions.captureSystemAudio,
+        // re-arm when these change so the pre-roll records with them
+        _mic: rawOptions.micName,
+        _camera: rawOptions.cameraLabel,
+      };
+    },
+    undefined,
+    { equals: (a, b) => JSON.stringify(a) === JSON.stringify(b) }
+  );
+
+  createEffect(() => {
+    const inputs = preRollInputs();
+    if (inputs) {
+      const { _mic, _camera, ...startInputs } = inputs;
+      commands.armPreRoll(startInputs).catch(console.error);
+    } else if (!isRecording()) commands.disarmPreRoll();
+  });
+
+  onCleanup(() => {
+    if (!isRecording()) commands.disarmPreRoll();
+  });
+
+  onMount(() => {
+    const unlisten = events.queuedRecordingFailed.listen(({ payload }) =>
+      message(payload.error, { title: "Queued recording couldn't start" })
+    );
+    onCleanup(() => unlisten.then((f) => f()));
+  });
+
   const toggleRecording = createMutation(() => ({
     mutationFn: async () => {
       if (!isRecording()) {
-        await commands.startRecording({
+        const started = await commands.startRecording({
           capture_target: options.target(),
           mode: rawOptions.mode,
           capture_system_audio: rawOptions.captureSystemAudio,
         });
-      } else await commands.stopRecording();
+        // eg. one started by a hotkey while this window was open
+        if (started.queued)
+          await message(
+            "This recording will start once the one in progress has ended",
+            { title: "Recording queued" }
+          );
+      } else await commands.stopRecording(null);
     },
   }));
 
@@ -15,6 +15,7 @@ import {
   type AppTheme,
   type GeneralSettingsStore,
   type MainWindowRecordingStartBehaviour,
+  type ShareLinkOpenBehaviour,
   type PostStudioRecordingBehaviour,
 } from "~/utils/tauri";
 // import { themeStore } from "~/store/theme";
@@ -223,6 +224,34 @@ function Inner(props: { initialStore: GeneralSettingsStore | null }) {
               <IconCapChevronDown class="size-4" />
             </button>
           </Setting>
+          <Setting
+            label="Instant recording link behaviour"
+            description="Where the shareable link opens when an instant recording finishes"
+          >
+            <button
+              class="flex flex-row gap-1 items-center px-2 py-1 rounded-md border border-gray-300"
+              onClick={async () => {
+                const item = (text: string, value: ShareLinkOpenBehaviour) =>
+                  CheckMenuItem.new({
+                    text,
+                    checked: settings.shareLinkOpenBehaviour === value,
+                    action: () => handleChange("shareLinkOpenBehaviour", value),
+                  });
+                const menu = await Menu.new({
+                  items: await Promise.all([
+                    item("Open in browser", "browser"),
+                    item("Open in Cap", "capWindow"),
+                  ]),
+                });
+                menu.popup();
+              }}
+            >
+              {settings.shareLinkOpenBehaviour === "capWindow"
+                ? "Open in Cap"
+                : "Open in browser"}
+              <IconCapChevronDown class="size-4" />
+            </button>
+          </Setting>
           <Setting
             label="Main window recording start behaviour"
             description="What should the main window do when starting a recording"
@@ -55,7 +55,9 @@ const recordingsQuery = queryOptions({
     const recordings = await Promise.all(
       result.map(async (file) => {
         const [path, meta] = file;
-        const thumbnailPath = `${path}/screenshots/display.jpg`;
+        const thumbnailPath = meta.cover
+          ? `${path}/${meta.cover}`
+          : `${path}/screenshots/display.jpg`;
 
         return {
           meta,
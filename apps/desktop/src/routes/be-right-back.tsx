@@ -0,0 +1,30 @@
+import { convertFileSrc } from "@tauri-apps/api/core";
+import { Show } from "solid-js";
+
+type BeRightBackCard = {
+  text: string;
+  imagePath: string | null;
+};
+
+const { beRightBackCard } = (window as any).__CAP__ as {
+  beRightBackCard: BeRightBackCard;
+};
+
+// Shown over the captured screen while paused so it gets recorded into the video
+export default function () {
+  return (
+    <div class="flex flex-col gap-8 justify-center items-center w-screen h-screen bg-black">
+      <Show when={beRightBackCard.imagePath}>
+        {(imagePath) => (
+          <img
+            src={convertFileSrc(imagePath())}
+            class="object-contain max-w-[40%] max-h-[50%]"
+          />
+        )}
+      </Show>
+      <span class="text-6xl font-medium text-white">
+        {beRightBackCard.text}
+      </span>
+    </div>
+  );
+}
@@ -19,7 +19,9 @@ import {
 } from "solid-js";
 import { createStore } from "solid-js/store";
 
+import { generalSettingsStore } from "~/store";
 import { createCameraMutation } from "~/utils/queries";
+import { commands } from "~/utils/tauri";
 import { createImageDataWS, createLazySignal } from "~/utils/socket";
 import {
   RecordingOptionsProvider,
@@ -32,7 +34,6 @@ namespace CameraWindow {
   export type State = {
     size: Size;
     shape: Shape;
-    mirrored: boolean;
   };
 }
 
@@ -57,11 +58,14 @@ function Page() {
     createStore<CameraWindow.State>({
       size: "sm",
       shape: "round",
-      mirrored: false,
     }),
     { name: "cameraWindowState" }
   );
 
+  // shared with recording so studio recordings come out mirrored too
+  const generalSettings = generalSettingsStore.createQuery();
+  const mirrored = () => generalSettings.data?.cameraMirror ?? false;
+
   const [latestFrame, setLatestFrame] = createLazySignal<{
     width: number;
     data: ImageData;
@@ -201,8 +205,8 @@ function Page() {
               )}
             </ControlButton>
             <ControlButton
-              pressed={state.mirrored}
-              onClick={() => setState("mirrored", (m) => !m)}
+              pressed={mirrored()}
+              onClick={() => commands.toggleCameraMirror()}
             >
               <IconCapArrows class="size-5.5" />
             </ControlButton>
@@ -231,7 +235,7 @@ function Page() {
                   return {
                     width: `${winWidth}px`,
                     height: `${winHeight}px`,
-                    transform: state.mirrored ? "scaleX(-1)" : "scaleX(1)",
+                    transform: mirrored() ? "scaleX(-1)" : "scaleX(1)",
                   };
                 }
 
@@ -256,7 +260,7 @@ function Page() {
                   height: `${size.height}px`,
                   left: `-${left}px`,
                   top: `-${top}px`,
-                  transform: state.mirrored ? "scaleX(-1)" : "scaleX(1)",
+                  transform: mirrored() ? "scaleX(-1)" : "scaleX(1)",
                 };
               };
 
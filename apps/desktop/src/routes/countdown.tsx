@@ -0,0 +1,22 @@
+import { createSignal, onCleanup } from "solid-js";
+
+const { countdownSecs } = (window as any).__CAP__ as {
+  countdownSecs: number;
+};
+
+// Shown over the screen that's about to be recorded, and kept out of the capture
+export default function () {
+  const [remaining, setRemaining] = createSignal(countdownSecs);
+
+  const interval = setInterval(
+    () => setRemaining((secs) => Math.max(secs - 1, 1)),
+    1000
+  );
+  onCleanup(() => clearInterval(interval));
+
+  return (
+    <div class="flex justify-center items-center w-screen h-screen bg-black/40">
+      <span class="font-medium text-white text-[12rem]">{remaining()}</span>
+    </div>
+  );
+}
@@ -52,7 +52,7 @@ export default function () {
   const stopRecording = createMutation(() => ({
     mutationFn: async () => {
       setState("stopped");
-      await commands.stopRecording();
+      await commands.stopRecording(null);
     },
   }));
 
@@ -76,6 +76,26 @@ export default function () {
     },
   }));
 
+  // keep in sync with pauses made in response to system pressure
+  events.recordingPressureChanged.listen(({ payload }) => {
+    if (payload.response !== "pause" || state() === "stopped") return;
+    if (payload.active === (state() === "paused")) return;
+
+    if (payload.active) {
+      setPauseResumes((a) => [...a, { pause: Date.now() }]);
+      setState("paused");
+    } else {
+      setPauseResumes(
+        produce((a) => {
+          if (a.length === 0) return a;
+          a[a.length - 1].resume = Date.now();
+        })
+      );
+      setState("recording");
+    }
+    setTime(Date.now());
+  });
+
   const restartRecording = createMutation(() => ({
     mutationFn: async () => {
       const shouldRestart = await dialog.confirm(
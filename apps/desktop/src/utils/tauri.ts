@@ -11,11 +11,35 @@ async setMicInput(label: string | null) : Promise<null> {
 async setCameraInput(label: string | null) : Promise<boolean> {
     return await TAURI_INVOKE("set_camera_input", { label });
 },
-async startRecording(inputs: StartRecordingInputs) : Promise<null> {
+/**
+ * Starts recording with `inputs`. If a recording's already in progress it's queued
+ * instead, and starts once that one has ended.
+ */
+async startRecording(inputs: StartRecordingInputs) : Promise<StartedRecording> {
     return await TAURI_INVOKE("start_recording", { inputs });
 },
-async stopRecording() : Promise<null> {
-    return await TAURI_INVOKE("stop_recording");
+/**
+ * Starts capturing in the background so that starting a studio recording with the same
+ * inputs includes the `pre_roll_secs` before it. Does nothing if pre-roll is turned off.
+ */
+async armPreRoll(inputs: StartRecordingInputs) : Promise<null> {
+    return await TAURI_INVOKE("arm_pre_roll", { inputs });
+},
+/**
+ * Stops the background capture started by `arm_pre_roll`
+ */
+async disarmPreRoll() : Promise<null> {
+    return await TAURI_INVOKE("disarm_pre_roll");
+},
+async stopRecording(postStudioBehaviour: PostStudioRecordingBehaviour | null) : Promise<StoppedRecording> {
+    return await TAURI_INVOKE("stop_recording", { postStudioBehaviour });
+},
+/**
+ * Marks the current point in the recording as the start of a chapter.
+ * Unnamed markers are numbered from 1 for each recording.
+ */
+async addChapterMarker(name: string | null) : Promise<ChapterMarker> {
+    return await TAURI_INVOKE("add_chapter_marker", { name });
 },
 async pauseRecording() : Promise<null> {
     return await TAURI_INVOKE("pause_recording");
@@ -65,6 +89,28 @@ async exportVideo(projectPath: string, progress: TAURI_CHANNEL<FramesRendered>,
 async getExportEstimates(path: string, resolution: XY<number>, fps: number) : Promise<ExportEstimates> {
     return await TAURI_INVOKE("get_export_estimates", { path, resolution, fps });
 },
+async getCameraMirror() : Promise<boolean> {
+    return await TAURI_INVOKE("get_camera_mirror");
+},
+/**
+ * Flips the camera preview and the camera in new studio recordings
+ */
+async setCameraMirror(mirror: boolean) : Promise<null> {
+    return await TAURI_INVOKE("set_camera_mirror", { mirror });
+},
+async toggleCameraMirror() : Promise<boolean> {
+    return await TAURI_INVOKE("toggle_camera_mirror");
+},
+async getPostStudioBehaviour() : Promise<PostStudioRecordingBehaviour> {
+    return await TAURI_INVOKE("get_post_studio_behaviour");
+},
+/**
+ * What happens once a studio recording stops, unless overridden when stopping it.
+ * Unknown behaviours are rejected when deserializing the argument.
+ */
+async setPostStudioBehaviour(behaviour: PostStudioRecordingBehaviour) : Promise<null> {
+    return await TAURI_INVOKE("set_post_studio_behaviour", { behaviour });
+},
 async copyFileToPath(src: string, dst: string) : Promise<null> {
     return await TAURI_INVOKE("copy_file_to_path", { src, dst });
 },
@@ -119,6 +165,14 @@ async uploadScreenshot(screenshotPath: string) : Promise<UploadResult> {
 async getRecordingMeta(path: string, fileType: string) : Promise<RecordingMetaWithType> {
     return await TAURI_INVOKE("get_recording_meta", { path, fileType });
 },
+/**
+ * Renders the frame at `timestamp` (in output time) to `cover.jpg` and uses it as the library thumbnail.
+ * 
+ * The share screenshot is left alone unless `sync_share_thumbnail` is set.
+ */
+async setCoverFrame(projectPath: string, timestamp: number, syncShareThumbnail: boolean) : Promise<string> {
+    return await TAURI_INVOKE("set_cover_frame", { projectPath, timestamp, syncShareThumbnail });
+},
 async saveFileDialog(fileName: string, fileType: string) : Promise<string | null> {
     return await TAURI_INVOKE("save_file_dialog", { fileName, fileType });
 },
@@ -253,7 +307,10 @@ editorStateChanged: EditorStateChanged,
 newNotification: NewNotification,
 newScreenshotAdded: NewScreenshotAdded,
 newStudioRecordingAdded: NewStudioRecordingAdded,
+queuedRecordingFailed: QueuedRecordingFailed,
+queuedRecordingStarted: QueuedRecordingStarted,
 recordingOptionsChanged: RecordingOptionsChanged,
+recordingPressureChanged: RecordingPressureChanged,
 recordingStarted: RecordingStarted,
 recordingStopped: RecordingStopped,
 renderFrameEvent: RenderFrameEvent,
@@ -270,7 +327,10 @@ editorStateChanged: "editor-state-changed",
 newNotification: "new-notification",
 newScreenshotAdded: "new-screenshot-added",
 newStudioRecordingAdded: "new-studio-recording-added",
+queuedRecordingFailed: "queued-recording-failed",
+queuedRecordingStarted: "queued-recording-started",
 recordingOptionsChanged: "recording-options-changed",
+recordingPressureChanged: "recording-pressure-changed",
 recordingStarted: "recording-started",
 recordingStopped: "recording-stopped",
 renderFrameEvent: "render-frame-event",
@@ -301,6 +361,10 @@ export type AuthStore = { secret: AuthSecret; user_id: string | null; plan: Plan
 export type AuthenticationInvalid = null
 export type BackgroundConfiguration = { source: BackgroundSource; blur: number; padding: number; rounding: number; inset: number; crop: Crop | null; shadow?: number; advancedShadow?: ShadowConfiguration | null }
 export type BackgroundSource = { type: "wallpaper"; path: string | null } | { type: "image"; path: string | null } | { type: "color"; value: [number, number, number] } | { type: "gradient"; from: [number, number, number]; to: [number, number, number]; angle?: number }
+/**
+ * What's shown over a screen while its recording is paused with a card
+ */
+export type BeRightBackCard = { screen_id: number; text: string; image_path: string | null }
 export type Bounds = { x: number; y: number; width: number; height: number }
 export type Camera = { hide: boolean; mirror: boolean; position: CameraPosition; size: number; zoom_size: number | null; rounding?: number; shadow?: number; advanced_shadow?: ShadowConfiguration | null; shape?: CameraShape }
 export type CameraPosition = { x: CameraXPosition; y: CameraYPosition }
@@ -313,6 +377,14 @@ export type CaptionSettings = { enabled: boolean; font: string; size: number; co
 export type CaptionsData = { segments: CaptionSegment[]; settings: CaptionSettings }
 export type CaptureScreen = { id: number; name: string; refresh_rate: number }
 export type CaptureWindow = { id: number; owner_name: string; name: string; bounds: Bounds; refresh_rate: number }
+/**
+ * A named point in a recording, added while it was recording
+ */
+export type ChapterMarker = { name: string; 
+/**
+ * seconds into the recording, not counting time spent paused
+ */
+time: number }
 export type CommercialLicense = { licenseKey: string; expiryDate: number | null; refresh: number; activatedOn: number }
 export type Crop = { position: XY<number>; size: XY<number> }
 export type CurrentRecording = { target: CurrentRecordingTarget; type: RecordingType }
@@ -330,7 +402,29 @@ export type ExportEstimates = { duration_seconds: number; estimated_time_seconds
 export type ExportSettings = ({ format: "Mp4" } & Mp4ExportSettings) | ({ format: "Gif" } & GifExportSettings)
 export type Flags = { captions: boolean }
 export type FramesRendered = { renderedCount: number; totalFrames: number; type: "FramesRendered" }
-export type GeneralSettingsStore = { instanceId?: string; uploadIndividualFiles?: boolean; hideDockIcon?: boolean; hapticsEnabled?: boolean; autoCreateShareableLink?: boolean; enableNotifications?: boolean; disableAutoOpenLinks?: boolean; hasCompletedStartup?: boolean; theme?: AppTheme; commercialLicense?: CommercialLicense | null; lastVersion?: string | null; windowTransparency?: boolean; postStudioRecordingBehaviour?: PostStudioRecordingBehaviour; mainWindowRecordingStartBehaviour?: MainWindowRecordingStartBehaviour; customCursorCapture?: boolean; serverUrl?: string; 
+export type GeneralSettingsStore = { instanceId?: string; uploadIndividualFiles?: boolean; hideDockIcon?: boolean; hapticsEnabled?: boolean; autoCreateShareableLink?: boolean; enableNotifications?: boolean; disableAutoOpenLinks?: boolean; 
+/**
+ * Where instant recordings' share links are opened once recording stops
+ */
+shareLinkOpenBehaviour?: ShareLinkOpenBehaviour; hasCompletedStartup?: boolean; theme?: AppTheme; commercialLicense?: CommercialLicense | null; lastVersion?: string | null; windowTransparency?: boolean; postStudioRecordingBehaviour?: PostStudioRecordingBehaviour; mainWindowRecordingStartBehaviour?: MainWindowRecordingStartBehaviour; customCursorCapture?: boolean; cameraMirror?: boolean; 
+/**
+ * Seconds captured before a studio recording is started to include at its start,
+ * up to 5. Capture runs in the background while the main window is open to
+ * make this possible, so it isn't free even when not recording.
+ */
+preRollSecs?: number; 
+/**
+ * What to do while the machine is under thermal or CPU pressure, nothing if unset
+ */
+pressureResponse?: PressureResponse | null; 
+/**
+ * Pressure level at which `pressure_response` kicks in
+ */
+pressureThreshold?: PressureLevel; 
+/**
+ * Frame rate studio recordings drop to with `PressureResponse::ReduceFps`
+ */
+pressureReducedFps?: number; serverUrl?: string; 
 /**
  * @deprecated
  */
@@ -339,7 +433,7 @@ export type GifExportSettings = { fps: number; resolution_base: XY<number> }
 export type HapticPattern = "Alignment" | "LevelChange" | "Generic"
 export type HapticPerformanceTime = "Default" | "Now" | "DrawCompleted"
 export type Hotkey = { code: string; meta: boolean; ctrl: boolean; alt: boolean; shift: boolean }
-export type HotkeyAction = "startRecording" | "stopRecording" | "restartRecording"
+export type HotkeyAction = "startRecording" | "stopRecording" | "restartRecording" | "addChapterMarker"
 export type HotkeysConfiguration = { show: boolean }
 export type HotkeysStore = { hotkeys: { [key in HotkeyAction]: Hotkey } }
 export type InstantRecordingMeta = { fps: number; sample_rate: number | null }
@@ -359,12 +453,39 @@ export type Platform = "MacOS" | "Windows"
 export type PostStudioRecordingBehaviour = "openEditor" | "showOverlay"
 export type Preset = { name: string; config: ProjectConfiguration }
 export type PresetsStore = { presets: Preset[]; default: number | null }
+export type PressureLevel = "nominal" | "fair" | "serious" | "critical"
+export type PressureResponse = "pause" | 
+/**
+ * Studio recordings only, instant recordings are paused instead
+ */
+"reduceFps"
 export type ProjectConfiguration = { aspectRatio: AspectRatio | null; background: BackgroundConfiguration; camera: Camera; audio: AudioConfiguration; cursor: CursorConfiguration; hotkeys: HotkeysConfiguration; timeline?: TimelineConfiguration | null; captions?: CaptionsData | null }
 export type ProjectRecordingsMeta = { segments: SegmentRecordings[] }
-export type RecordingMeta = (StudioRecordingMeta | InstantRecordingMeta) & { platform: Platform | null; pretty_name: string; sharing?: SharingMeta | null }
-export type RecordingMetaWithType = ((StudioRecordingMeta | InstantRecordingMeta) & { platform: Platform | null; pretty_name: string; sharing?: SharingMeta | null }) & { type: RecordingType }
+/**
+ * Emitted when a recording that was queued behind another fails to start
+ */
+export type QueuedRecordingFailed = { id: string; error: string }
+/**
+ * Emitted when a recording that was queued behind another starts
+ */
+export type QueuedRecordingStarted = { id: string; recordingDir: string }
+export type RecordingMeta = (StudioRecordingMeta | InstantRecordingMeta) & { platform: Platform | null; pretty_name: string; sharing?: SharingMeta | null; 
+/**
+ * user-chosen library thumbnail, separate from the uploaded share screenshot
+ */
+cover?: string | null }
+export type RecordingMetaWithType = ((StudioRecordingMeta | InstantRecordingMeta) & { platform: Platform | null; pretty_name: string; sharing?: SharingMeta | null; 
+/**
+ * user-chosen library thumbnail, separate from the uploaded share screenshot
+ */
+cover?: string | null }) & { type: RecordingType }
 export type RecordingMode = "studio" | "instant"
 export type RecordingOptionsChanged = null
+export type RecordingPressureChanged = { level: PressureLevel; response: PressureResponse; 
+/**
+ * whether the response is now in effect, false once it's been undone
+ */
+active: boolean }
 export type RecordingStarted = null
 export type RecordingStopped = null
 export type RecordingType = "studio" | "instant"
@@ -377,11 +498,29 @@ export type ScreenCaptureTarget = { variant: "window"; id: number } | { variant:
 export type SegmentRecordings = { display: Video; camera: Video | null; mic: Audio | null; system_audio: Audio | null }
 export type SerializedEditorInstance = { framesSocketUrl: string; recordingDuration: number; savedProjectConfig: ProjectConfiguration; recordings: ProjectRecordingsMeta; path: string }
 export type ShadowConfiguration = { size: number; opacity: number; blur: number }
+export type ShareLinkOpenBehaviour = "browser" | "capWindow"
 export type SharingMeta = { id: string; link: string }
-export type ShowCapWindow = "Setup" | "Main" | { Settings: { page: string | null } } | { Editor: { project_path: string } } | "RecordingsOverlay" | { WindowCaptureOccluder: { screen_id: number } } | { CaptureArea: { screen_id: number } } | "Camera" | { InProgressRecording: { position: [number, number] | null } } | "Upgrade" | "ModeSelect"
+export type ShowCapWindow = "Setup" | "Main" | { Settings: { page: string | null } } | { Editor: { project_path: string } } | "RecordingsOverlay" | { WindowCaptureOccluder: { screen_id: number } } | { BeRightBack: { card: BeRightBackCard } } | { Countdown: { screen_id: number; secs: number } } | { CaptureArea: { screen_id: number } } | "Camera" | { InProgressRecording: { position: [number, number] | null } } | "Upgrade" | "ModeSelect" | { SharePage: { url: string } }
 export type SingleSegment = { display: VideoMeta; camera?: VideoMeta | null; audio?: AudioMeta | null; cursor?: string | null }
 export type StartRecordingInputs = { capture_target: ScreenCaptureTarget; capture_system_audio?: boolean; mode: RecordingMode }
+/**
+ * Identifies a recording that's started, for matching it up with later events
+ */
+export type StartedRecording = { id: string; 
+/**
+ * the `.cap` directory it's being saved to, named after `id`
+ */
+recordingDir: string; 
+/**
+ * another recording was in progress, so this one starts once it's ended
+ */
+queued: boolean }
 export type StereoMode = "stereo" | "monoL" | "monoR"
+export type StoppedRecording = { projectPath: string; mode: RecordingMode; 
+/**
+ * only instant recordings have a link as soon as they stop
+ */
+shareLink: string | null }
 export type StudioRecordingMeta = { segment: SingleSegment } | { inner: MultipleSegments }
 export type TimelineConfiguration = { segments: TimelineSegment[]; zoomSegments: ZoomSegment[] }
 export type TimelineSegment = { recordingSegment?: number; timescale: number; start: number; end: number }
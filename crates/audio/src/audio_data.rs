@@ -7,8 +7,8 @@ use std::path::Path;
 
 // F32 Packed 48kHz audio
 pub struct AudioData {
-    samples: Vec<f32>,
-    channels: u16,
+    pub(crate) samples: Vec<f32>,
+    pub(crate) channels: u16,
 }
 
 impl AudioData {
@@ -1,5 +1,9 @@
 mod audio_data;
+mod loudness;
+mod mix;
 mod renderer;
 
 pub use audio_data::*;
+pub use loudness::*;
+pub use mix::*;
 pub use renderer::*;
@@ -0,0 +1,193 @@
+use std::f64::consts::PI;
+
+use crate::AudioData;
+
+const BLOCK_SECS: f64 = 0.4;
+const BLOCK_STEP_SECS: f64 = 0.1;
+const ABSOLUTE_GATE_LUFS: f64 = -70.0;
+const RELATIVE_GATE_LU: f64 = -10.0;
+
+/// Integrated loudness of interleaved samples as per EBU R128 / ITU-R BS.1770, in LUFS.
+///
+/// All channels are weighted equally, which matches the spec for mono and stereo.
+/// Returns `None` if there's less than one block of audio or it's all below the absolute gate.
+pub fn integrated_loudness(samples: &[f32], channels: u16, sample_rate: u32) -> Option<f64> {
+    let channels = channels.max(1) as usize;
+    let frames = samples.len() / channels;
+
+    let block_len = (BLOCK_SECS * sample_rate as f64) as usize;
+    let step_len = (BLOCK_STEP_SECS * sample_rate as f64) as usize;
+
+    if block_len == 0 || frames < block_len {
+        return None;
+    }
+
+    // sum of squared k-weighted samples over all channels for each 100ms step
+    let mut step_energy = vec![0.0; frames / step_len];
+
+    for channel in 0..channels {
+        let mut filter = KWeighting::new(sample_rate);
+
+        for (i, sample) in samples
+            .iter()
+            .skip(channel)
+            .step_by(channels)
+            .take(step_energy.len() * step_len)
+            .enumerate()
+        {
+            let weighted = filter.process(*sample as f64);
+            step_energy[i / step_len] += weighted * weighted;
+        }
+    }
+
+    let steps_per_block = block_len / step_len;
+    let block_powers = step_energy
+        .windows(steps_per_block)
+        .map(|steps| steps.iter().sum::<f64>() / block_len as f64)
+        .filter(|power| power_to_lufs(*power) > ABSOLUTE_GATE_LUFS)
+        .collect::<Vec<_>>();
+
+    if block_powers.is_empty() {
+        return None;
+    }
+
+    let relative_gate = power_to_lufs(block_powers.iter().sum::<f64>() / block_powers.len() as f64)
+        + RELATIVE_GATE_LU;
+
+    let gated = block_powers
+        .iter()
+        .filter(|power| power_to_lufs(**power) > relative_gate)
+        .collect::<Vec<_>>();
+
+    if gated.is_empty() {
+        return None;
+    }
+
+    Some(power_to_lufs(
+        gated.iter().copied().sum::<f64>() / gated.len() as f64,
+    ))
+}
+
+impl AudioData {
+    pub fn integrated_loudness(&self) -> Option<f64> {
+        integrated_loudness(self.samples(), self.channels(), Self::SAMPLE_RATE)
+    }
+}
+
+fn power_to_lufs(power: f64) -> f64 {
+    -0.691 + 10.0 * power.log10()
+}
+
+/// The two biquads making up the K-weighting pre-filter,
+/// with coefficients derived for any sample rate rather than just 48kHz
+struct KWeighting {
+    stages: [Biquad; 2],
+}
+
+impl KWeighting {
+    fn new(sample_rate: u32) -> Self {
+        let rate = sample_rate as f64;
+
+        // high shelf modelling the acoustic effect of the head
+        let shelf = {
+            let f0 = 1681.974450955533;
+            let gain = 3.999843853973347;
+            let q = 0.7071752369554196;
+
+            let k = (PI * f0 / rate).tan();
+            let vh = 10f64.powf(gain / 20.0);
+            let vb = vh.powf(0.4996667741545416);
+            let a0 = 1.0 + k / q + k * k;
+
+            Biquad::new(
+                [
+                    (vh + vb * k / q + k * k) / a0,
+                    2.0 * (k * k - vh) / a0,
+                    (vh - vb * k / q + k * k) / a0,
+                ],
+                [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            )
+        };
+
+        // high pass removing content below ~38Hz
+        let high_pass = {
+            let f0 = 38.13547087602444;
+            let q = 0.5003270373238773;
+
+            let k = (PI * f0 / rate).tan();
+            let a0 = 1.0 + k / q + k * k;
+
+            Biquad::new(
+                [1.0, -2.0, 1.0],
+                [2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0],
+            )
+        };
+
+        Self {
+            stages: [shelf, high_pass],
+        }
+    }
+
+    fn process(&mut self, sample: f64) -> f64 {
+        self.stages
+            .iter_mut()
+            .fold(sample, |sample, stage| stage.process(sample))
+    }
+}
+
+struct Biquad {
+    b: [f64; 3],
+    a: [f64; 2],
+    z: [f64; 2],
+}
+
+impl Biquad {
+    fn new(b: [f64; 3], a: [f64; 2]) -> Self {
+        Self { b, a, z: [0.0; 2] }
+    }
+
+    // transposed direct form II
+    fn process(&mut self, x: f64) -> f64 {
+        let y = self.b[0] * x + self.z[0];
+        self.z[0] = self.b[1] * x - self.a[0] * y + self.z[1];
+        self.z[1] = self.b[2] * x - self.a[1] * y;
+        y
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sine(amplitude: f32, secs: f32, channels: u16) -> Vec<f32> {
+        let rate = AudioData::SAMPLE_RATE as f32;
+        (0..(secs * rate) as usize)
+            .flat_map(|i| {
+                let sample =
+                    amplitude * (2.0 * std::f32::consts::PI * 1000.0 * i as f32 / rate).sin();
+                std::iter::repeat(sample).take(channels as usize)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn full_scale_sine_reads_minus_three_lufs() {
+        let loudness = integrated_loudness(&sine(1.0, 5.0, 1), 1, AudioData::SAMPLE_RATE).unwrap();
+        assert!((loudness + 3.01).abs() < 0.1, "{loudness}");
+    }
+
+    #[test]
+    fn halving_amplitude_drops_six_lu() {
+        let full = integrated_loudness(&sine(1.0, 5.0, 2), 2, AudioData::SAMPLE_RATE).unwrap();
+        let half = integrated_loudness(&sine(0.5, 5.0, 2), 2, AudioData::SAMPLE_RATE).unwrap();
+        assert!((full - half - 6.02).abs() < 0.05, "{full} {half}");
+    }
+
+    #[test]
+    fn silence_is_gated() {
+        assert_eq!(
+            integrated_loudness(&vec![0.0; 48_000 * 2], 1, AudioData::SAMPLE_RATE),
+            None
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use crate::AudioData;
+
+impl AudioData {
+    pub fn silence(channels: u16, frames: usize) -> Self {
+        Self {
+            samples: vec![0.0; frames * channels.max(1) as usize],
+            channels: channels.max(1),
+        }
+    }
+
+    /// Pads with silence or truncates to exactly `frames` frames
+    pub fn resize(&mut self, frames: usize) {
+        self.samples.resize(frames * self.channels as usize, 0.0);
+    }
+
+    /// Copies mono audio to every channel, and otherwise maps each output channel
+    /// onto an input channel so nothing is lost going from stereo to more channels.
+    pub fn with_channels(&self, channels: u16) -> Self {
+        let channels = channels.max(1);
+        if channels == self.channels {
+            return Self {
+                samples: self.samples.clone(),
+                channels,
+            };
+        }
+
+        let samples = self
+            .samples
+            .chunks_exact(self.channels as usize)
+            .flat_map(|frame| (0..channels as usize).map(move |c| frame[c % frame.len()]))
+            .collect();
+
+        Self { samples, channels }
+    }
+
+    /// Joins audio end to end, upmixing everything to the widest channel count
+    pub fn concat(parts: impl IntoIterator<Item = AudioData>) -> Self {
+        let parts = parts.into_iter().collect::<Vec<_>>();
+        let channels = parts.iter().map(|p| p.channels).max().unwrap_or(1);
+
+        let mut samples = vec![];
+        for part in parts {
+            samples.extend(part.with_channels(channels).samples);
+        }
+
+        Self { samples, channels }
+    }
+
+    /// 16-bit PCM WAV file contents
+    pub fn to_wav(&self) -> Vec<u8> {
+        const BITS_PER_SAMPLE: u16 = 16;
+
+        let block_align = self.channels * BITS_PER_SAMPLE / 8;
+        let data_len = (self.samples.len() * 2) as u32;
+
+        let mut wav = Vec::with_capacity(44 + data_len as usize);
+        wav.extend(b"RIFF");
+        wav.extend((36 + data_len).to_le_bytes());
+        wav.extend(b"WAVE");
+
+        wav.extend(b"fmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(self.channels.to_le_bytes());
+        wav.extend(Self::SAMPLE_RATE.to_le_bytes());
+        wav.extend((Self::SAMPLE_RATE * block_align as u32).to_le_bytes());
+        wav.extend(block_align.to_le_bytes());
+        wav.extend(BITS_PER_SAMPLE.to_le_bytes());
+
+        wav.extend(b"data");
+        wav.extend(data_len.to_le_bytes());
+        for sample in &self.samples {
+            wav.extend(((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+        }
+
+        wav
+    }
+}
+
+/// Sums tracks after applying their gain in dB, lasting as long as the longest track.
+///
+/// The result has the widest channel count of the tracks and is clipped to [-1, 1].
+pub fn mix_tracks(tracks: &[(&AudioData, f32)]) -> AudioData {
+    let channels = tracks.iter().map(|(t, _)| t.channels).max().unwrap_or(1);
+    let frames = tracks
+        .iter()
+        .map(|(t, _)| t.sample_count())
+        .max()
+        .unwrap_or(0);
+
+    let mut mixed = AudioData::silence(channels, frames);
+
+    for (track, gain_db) in tracks {
+        let gain = 10f32.powf(gain_db / 20.0);
+        let track = track.with_channels(channels);
+
+        for (out, sample) in mixed.samples.iter_mut().zip(track.samples) {
+            *out += sample * gain;
+        }
+    }
+
+    for sample in &mut mixed.samples {
+        *sample = sample.clamp(-1.0, 1.0);
+    }
+
+    mixed
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn audio(samples: &[f32], channels: u16) -> AudioData {
+        AudioData {
+            samples: samples.to_vec(),
+            channels,
+        }
+    }
+
+    #[test]
+    fn mono_is_upmixed_when_mixing_with_stereo() {
+        let mono = audio(&[0.1, 0.2], 1);
+        let stereo = audio(&[0.1, -0.1, 0.1, -0.1], 2);
+
+        let mixed = mix_tracks(&[(&mono, 0.0), (&stereo, 0.0)]);
+
+        assert_eq!(mixed.channels(), 2);
+        let expected = [0.2, 0.0, 0.3, 0.1];
+        for (a, b) in mixed.samples().iter().zip(expected) {
+            assert!((a - b).abs() < 1e-6, "{:?}", mixed.samples());
+        }
+    }
+
+    #[test]
+    fn gain_is_applied_in_db_and_output_clipped() {
+        let quiet = audio(&[0.5], 1);
+        let loud = audio(&[0.9], 1);
+
+        let halved = mix_tracks(&[(&quiet, -6.0206)]);
+        assert!((halved.samples()[0] - 0.25).abs() < 1e-4);
+
+        let clipped = mix_tracks(&[(&quiet, 0.0), (&loud, 0.0)]);
+        assert_eq!(clipped.samples(), &[1.0]);
+    }
+
+    #[test]
+    fn shorter_tracks_are_padded_with_silence() {
+        let long = audio(&[0.1, 0.1, 0.1], 1);
+        let short = audio(&[0.1], 1);
+
+        let mixed = mix_tracks(&[(&long, 0.0), (&short, 0.0)]);
+        assert_eq!(mixed.sample_count(), 3);
+        assert!((mixed.samples()[2] - 0.1).abs() < 1e-6);
+    }
+
+    #[test]
+    fn concat_resized_segments() {
+        let mut first = audio(&[0.1], 1);
+        first.resize(2);
+        let second = audio(&[0.2, 0.3], 2);
+
+        let joined = AudioData::concat([first, second]);
+        assert_eq!(joined.channels(), 2);
+        assert_eq!(joined.samples(), &[0.1, 0.1, 0.0, 0.0, 0.2, 0.3]);
+    }
+
+    #[test]
+    fn wav_header_describes_samples() {
+        let wav = audio(&[0.0, 1.0, -1.0, 0.0], 2).to_wav();
+
+        assert_eq!(wav.len(), 44 + 8);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+        assert_eq!(
+            u32::from_le_bytes([wav[24], wav[25], wav[26], wav[27]]),
+            AudioData::SAMPLE_RATE
+        );
+        assert_eq!(i16::from_le_bytes([wav[46], wav[47]]), i16::MAX);
+    }
+}
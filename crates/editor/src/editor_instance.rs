@@ -5,7 +5,9 @@ use cap_media::data::RawVideoFormat;
 use cap_media::data::VideoInfo;
 // use cap_media::feeds::AudioData;
 use cap_media::frame_ws::create_frame_ws;
-use cap_project::{CursorEvents, ProjectConfiguration, RecordingMeta, RecordingMetaInner, XY};
+use cap_project::{
+    CursorEvents, CursorFollowPath, ProjectConfiguration, RecordingMeta, RecordingMetaInner, XY,
+};
 use cap_project::{RecordingConfig, StudioRecordingMeta};
 use cap_rendering::{
     get_duration, ProjectRecordingsMeta, ProjectUniforms, RecordingSegmentDecoders, RenderOptions,
@@ -239,6 +241,7 @@ impl EditorInstance {
                         fps,
                         resolution_base,
                         &segment.cursor,
+                        segment.cursor_follow.as_deref(),
                         &segment_frames,
                     );
                     self.renderer
@@ -292,6 +295,7 @@ pub struct Segment {
     pub audio: Option<Arc<AudioData>>,
     pub system_audio: Option<Arc<AudioData>>,
     pub cursor: Arc<CursorEvents>,
+    pub cursor_follow: Option<Arc<CursorFollowPath>>,
     pub decoders: RecordingSegmentDecoders,
 }
 
@@ -327,6 +331,7 @@ pub async fn create_segments(
                 audio,
                 system_audio: None,
                 cursor: Default::default(),
+                cursor_follow: None,
                 decoders,
             }])
         }
@@ -372,6 +377,7 @@ pub async fn create_segments(
                     audio,
                     system_audio,
                     cursor,
+                    cursor_follow: s.cursor_follow.clone().map(Arc::new),
                     decoders,
                 });
             }
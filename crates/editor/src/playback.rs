@@ -95,6 +95,7 @@ impl Playback {
                             fps,
                             resolution_base,
                             &segment.cursor,
+                            segment.cursor_follow.as_deref(),
                             &segment_frames,
                         );
 
@@ -89,6 +89,7 @@ impl GifExportSettings {
                 .iter()
                 .map(|s| RenderSegment {
                     cursor: s.cursor.clone(),
+                    cursor_follow: s.cursor_follow.clone(),
                     decoders: s.decoders.clone(),
                 })
                 .collect(),
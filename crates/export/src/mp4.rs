@@ -11,11 +11,11 @@ use cap_project::XY;
 use cap_rendering::{ProjectUniforms, RenderSegment, RenderedFrame};
 use futures::FutureExt;
 use image::ImageBuffer;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use specta::Type;
 use tracing::{info, trace, warn};
 
-#[derive(Deserialize, Type, Clone, Copy, Debug)]
+#[derive(Serialize, Deserialize, Type, Clone, Copy, Debug)]
 pub enum ExportCompression {
     Minimal,
     Social,
@@ -89,7 +89,7 @@ impl Mp4ExportSettings {
                 },
                 |o| {
                     has_audio.then(|| {
-                        AACEncoder::init("output_audio", AudioRenderer::info(), o)
+                        AACEncoder::init("output_audio", AudioRenderer::info(), None, o)
                             .map(|v| v.boxed())
                     })
                 },
@@ -219,6 +219,7 @@ impl Mp4ExportSettings {
                 .iter()
                 .map(|s| RenderSegment {
                     cursor: s.cursor.clone(),
+                    cursor_follow: s.cursor_follow.clone(),
                     decoders: s.decoders.clone(),
                 })
                 .collect(),
@@ -32,13 +32,17 @@ impl AACEncoder {
     pub fn factory(
         tag: &'static str,
         input_config: AudioInfo,
+        output_rate: Option<u32>,
     ) -> impl FnOnce(&mut format::context::Output) -> Result<Self, MediaError> {
-        move |o| Self::init(tag, input_config, o)
+        move |o| Self::init(tag, input_config, output_rate, o)
     }
 
+    /// `output_rate` is used if the codec supports it, otherwise the closest higher rate it does.
+    /// Without it the input's rate is kept where possible.
     pub fn init(
         tag: &'static str,
         input_config: AudioInfo,
+        output_rate: Option<u32>,
         output: &mut format::context::Output,
     ) -> Result<Self, MediaError> {
         let codec = encoder::find_by_name("aac")
@@ -47,6 +51,8 @@ impl AACEncoder {
         encoder_ctx.set_threading(Config::count(4));
         let mut encoder = encoder_ctx.encoder().audio()?;
 
+        let target_rate = output_rate.unwrap_or(input_config.sample_rate);
+
         let rate = {
             let mut rates = codec
                 .audio()
@@ -59,12 +65,11 @@ impl AACEncoder {
 
             let Some(&rate) = rates
                 .iter()
-                .find(|r| **r >= input_config.rate())
+                .find(|r| **r as u32 >= target_rate)
                 .or(rates.first())
             else {
                 return Err(MediaError::TaskLaunch(format!(
-                    "Opus Codec does not support sample rate {}",
-                    input_config.rate()
+                    "AAC Codec does not support sample rate {target_rate}"
                 )));
             };
             rate
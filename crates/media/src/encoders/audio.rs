@@ -2,6 +2,9 @@ use ffmpeg::format;
 
 use crate::data::FFAudio;
 
+/// Sample rates recordings' audio can be encoded at
+pub const SUPPORTED_SAMPLE_RATES: [u32; 2] = [44_100, 48_000];
+
 pub trait AudioEncoder {
     fn boxed(self) -> Box<dyn AudioEncoder + Send + 'static>
     where
@@ -9,11 +9,15 @@ use ffmpeg::{
     Dictionary,
 };
 
+pub use cap_project::VideoCodec;
+
 pub struct H264EncoderBuilder {
     name: &'static str,
     bpp: f32,
     input_config: VideoInfo,
     preset: H264Preset,
+    codec: VideoCodec,
+    output_size: Option<(u32, u32)>,
 }
 
 #[derive(Clone, Copy)]
@@ -32,9 +36,22 @@ impl H264EncoderBuilder {
             input_config,
             bpp: Self::QUALITY_BPP,
             preset: H264Preset::Ultrafast,
+            codec: VideoCodec::H264,
+            output_size: None,
         }
     }
 
+    /// Scales frames to this size before encoding them
+    pub fn with_output_size(mut self, width: u32, height: u32) -> Self {
+        self.output_size = Some((width, height));
+        self
+    }
+
+    pub fn with_codec(mut self, codec: VideoCodec) -> Self {
+        self.codec = codec;
+        self
+    }
+
     pub fn with_preset(mut self, preset: H264Preset) -> Self {
         self.preset = preset;
         self
@@ -47,41 +64,57 @@ impl H264EncoderBuilder {
 
     pub fn build(self, output: &mut format::context::Output) -> Result<H264Encoder, MediaError> {
         let input_config = &self.input_config;
-        let (codec, encoder_options) = get_codec_and_options(&input_config, self.preset)?;
+        let (codec, encoder_options) =
+            get_codec_and_options(&input_config, self.preset, self.codec)?;
+
+        let (output_width, output_height) = self
+            .output_size
+            .unwrap_or((input_config.width, input_config.height));
 
-        let (format, converter) = if !codec
+        let format_supported = codec
             .video()
             .unwrap()
             .formats()
             .unwrap()
-            .any(|f| f == input_config.pixel_format)
+            .any(|f| f == input_config.pixel_format);
+        let format = if format_supported {
+            input_config.pixel_format
+        } else {
+            ffmpeg::format::Pixel::YUV420P
+        };
+
+        let converter = if !format_supported
+            || (output_width, output_height) != (input_config.width, input_config.height)
         {
-            let format = ffmpeg::format::Pixel::YUV420P;
             tracing::debug!(
-                "Converting from {:?} to {:?} for H264 encoding",
+                "Converting from {:?} {}x{} to {:?} {output_width}x{output_height} for H264 encoding",
                 input_config.pixel_format,
+                input_config.width,
+                input_config.height,
                 format
             );
-            (
-                format,
-                Some(
-                    ffmpeg::software::converter(
-                        (input_config.width, input_config.height),
+            Some(
+                ffmpeg::software::scaling::Context::get(
+                    input_config.pixel_format,
+                    input_config.width,
+                    input_config.height,
+                    format,
+                    output_width,
+                    output_height,
+                    ffmpeg::software::scaling::Flags::BILINEAR,
+                )
+                .map_err(|e| {
+                    tracing::error!(
+                        "Failed to create converter from {:?} to {:?}: {:?}",
                         input_config.pixel_format,
                         format,
-                    )
-                    .map_err(|e| {
-                        tracing::error!(
-                            "Failed to create converter from {:?} to YUV420P: {:?}",
-                            input_config.pixel_format,
-                            e
-                        );
-                        MediaError::Any("Failed to create frame converter".into())
-                    })?,
-                ),
+                        e
+                    );
+                    MediaError::Any("Failed to create frame converter".into())
+                })?,
             )
         } else {
-            (input_config.pixel_format, None)
+            None
         };
 
         let mut encoder_ctx = context::Context::new_with_codec(codec);
@@ -89,16 +122,16 @@ impl H264EncoderBuilder {
         encoder_ctx.set_threading(Config::count(4));
         let mut encoder = encoder_ctx.encoder().video()?;
 
-        encoder.set_width(input_config.width);
-        encoder.set_height(input_config.height);
+        encoder.set_width(output_width);
+        encoder.set_height(output_height);
         encoder.set_format(format);
         encoder.set_time_base(input_config.frame_rate.invert());
         encoder.set_frame_rate(Some(input_config.frame_rate));
 
         // let target_bitrate = compression.bitrate();
         let bitrate = get_bitrate(
-            input_config.width,
-            input_config.height,
+            output_width,
+            output_height,
             input_config.frame_rate.0 as f32 / input_config.frame_rate.1 as f32,
             self.bpp,
         );
@@ -114,6 +147,13 @@ impl H264EncoderBuilder {
         output_stream.set_rate(input_config.frame_rate);
         output_stream.set_parameters(&video_encoder);
 
+        // ffmpeg tags HEVC in mp4s as hev1 by default, which QuickTime and Safari won't play
+        if let VideoCodec::H265 = self.codec {
+            unsafe {
+                (*(*output_stream.as_mut_ptr()).codecpar).codec_tag = u32::from_le_bytes(*b"hvc1");
+            }
+        }
+
         Ok(H264Encoder {
             tag: self.name,
             encoder: video_encoder,
@@ -149,7 +189,7 @@ impl H264Encoder {
                 }
                 Err(e) => {
                     tracing::error!(
-                        "Failed to convert frame: {:?} from format {:?} to YUV420P",
+                        "Failed to convert frame: {:?} from format {:?}",
                         e,
                         frame.format()
                     );
@@ -192,21 +232,35 @@ impl H264Encoder {
     }
 }
 
+fn encoder_name(codec: VideoCodec) -> &'static str {
+    match codec {
+        VideoCodec::H264 => {
+            if cfg!(target_os = "macos") {
+                "libx264"
+                // looks terrible rn :(
+                // "h264_videotoolbox"
+            } else {
+                "libx264"
+            }
+        }
+        VideoCodec::H265 => "libx265",
+        VideoCodec::Vp9 => "libvpx-vp9",
+    }
+}
+
+/// Whether ffmpeg was built with an encoder for `codec`
+pub fn video_codec_available(codec: VideoCodec) -> bool {
+    encoder::find_by_name(encoder_name(codec)).is_some()
+}
+
 fn get_codec_and_options(
     config: &VideoInfo,
     preset: H264Preset,
+    codec: VideoCodec,
 ) -> Result<(Codec, Dictionary), MediaError> {
-    let encoder_name = {
-        if cfg!(target_os = "macos") {
-            "libx264"
-            // looks terrible rn :(
-            // "h264_videotoolbox"
-        } else {
-            "libx264"
-        }
-    };
+    let encoder_name = encoder_name(codec);
 
-    if let Some(codec) = encoder::find_by_name(encoder_name) {
+    if let Some(found) = encoder::find_by_name(encoder_name) {
         let mut options = Dictionary::new();
 
         if encoder_name == "h264_videotoolbox" {
@@ -216,6 +270,17 @@ fn get_codec_and_options(
             let keyframe_interval = keyframe_interval_secs * config.frame_rate.numerator();
             let keyframe_interval_str = keyframe_interval.to_string();
 
+            if let VideoCodec::Vp9 = codec {
+                // libvpx has no presets, these keep it fast enough to encode live
+                options.set("deadline", "realtime");
+                options.set("cpu-used", "8");
+                options.set("row-mt", "1");
+                options.set("g", &keyframe_interval_str);
+                options.set("keyint_min", &keyframe_interval_str);
+
+                return Ok((found, options));
+            }
+
             options.set(
                 "preset",
                 match preset {
@@ -227,15 +292,32 @@ fn get_codec_and_options(
             if let H264Preset::Ultrafast = preset {
                 options.set("tune", "zerolatency");
             }
+
+            if let VideoCodec::H265 = codec {
+                // libx265 takes its keyframe interval through its own params
+                options.set(
+                    "x265-params",
+                    &format!(
+                        "keyint={keyframe_interval}:min-keyint={keyframe_interval}:log-level=error"
+                    ),
+                );
+
+                return Ok((found, options));
+            }
+
             options.set("vsync", "1");
             options.set("g", &keyframe_interval_str);
             options.set("keyint_min", &keyframe_interval_str);
         }
 
-        return Ok((codec, options));
+        return Ok((found, options));
     }
 
-    Err(MediaError::MissingCodec("H264 video"))
+    Err(MediaError::MissingCodec(match codec {
+        VideoCodec::H264 => "H264 video",
+        VideoCodec::H265 => "H265 video",
+        VideoCodec::Vp9 => "VP9 video",
+    }))
 }
 
 fn get_bitrate(width: u32, height: u32, frame_rate: f32, bpp: f32) -> usize {
@@ -6,6 +6,7 @@ mod mp4;
 #[cfg(target_os = "macos")]
 mod mp4_avassetwriter;
 mod opus;
+mod static_frames;
 
 pub use aac::*;
 pub use audio::*;
@@ -15,3 +16,4 @@ pub use mp4::*;
 #[cfg(target_os = "macos")]
 pub use mp4_avassetwriter::*;
 pub use opus::*;
+pub use static_frames::*;
@@ -41,14 +41,27 @@ impl From<InitError> for MediaError {
 
 impl MP4File {
     pub fn init(
+        tag: &'static str,
+        output: PathBuf,
+        video: impl FnOnce(&mut format::context::Output) -> Result<H264Encoder, MediaError>,
+        audio: impl FnOnce(
+            &mut format::context::Output,
+        ) -> Option<Result<Box<dyn AudioEncoder + Send>, MediaError>>,
+    ) -> Result<Self, InitError> {
+        Self::init_with_extension(tag, output, "mp4", video, audio)
+    }
+
+    /// Like `init`, but the container is picked from `extension`, eg. "webm"
+    pub fn init_with_extension(
         tag: &'static str,
         mut output: PathBuf,
+        extension: &str,
         video: impl FnOnce(&mut format::context::Output) -> Result<H264Encoder, MediaError>,
         audio: impl FnOnce(
             &mut format::context::Output,
         ) -> Option<Result<Box<dyn AudioEncoder + Send>, MediaError>>,
     ) -> Result<Self, InitError> {
-        output.set_extension("mp4");
+        output.set_extension(extension);
 
         if let Some(parent) = output.parent() {
             let _ = std::fs::create_dir_all(parent);
@@ -6,6 +6,7 @@ use std::{
 
 use crate::{
     data::{AudioInfo, FFAudio, PlanarData, VideoInfo},
+    encoders::VideoCodec,
     pipeline::task::PipelineSinkTask,
     MediaError,
 };
@@ -32,12 +33,25 @@ pub struct MP4AVAssetWriterEncoder {
 }
 
 impl MP4AVAssetWriterEncoder {
+    /// Whether the asset writer can encode `codec` on this Mac
+    pub fn codec_available(codec: VideoCodec) -> bool {
+        match codec {
+            VideoCodec::H264 => true,
+            VideoCodec::H265 => {
+                av::OutputSettingsAssistant::with_preset(av::OutputSettingsPreset::hevc_3840x2160())
+                    .is_some()
+            }
+            VideoCodec::Vp9 => false,
+        }
+    }
+
     pub fn init(
         tag: &'static str,
         video_config: VideoInfo,
         audio_config: Option<AudioInfo>,
         output: PathBuf,
         output_height: Option<u32>,
+        codec: VideoCodec,
     ) -> Result<Self, MediaError> {
         debug!("{video_config:#?}");
         debug!("{audio_config:#?}");
@@ -51,12 +65,14 @@ impl MP4AVAssetWriterEncoder {
         .map_err(|_| MediaError::Any("Failed to create AVAssetWriter".into()))?;
 
         let video_input = {
-            let assistant = av::OutputSettingsAssistant::with_preset(
-                av::OutputSettingsPreset::h264_3840x2160(),
-            )
-            .ok_or(MediaError::Any(
-                "Failed to create output settings assistant".into(),
-            ))?;
+            let preset = match codec {
+                VideoCodec::H264 => av::OutputSettingsPreset::h264_3840x2160(),
+                VideoCodec::H265 => av::OutputSettingsPreset::hevc_3840x2160(),
+                VideoCodec::Vp9 => return Err(MediaError::MissingCodec("VP9 video")),
+            };
+            let assistant = av::OutputSettingsAssistant::with_preset(preset).ok_or(
+                MediaError::Any("Failed to create output settings assistant".into()),
+            )?;
 
             let mut output_settings = assistant
                 .video_settings()
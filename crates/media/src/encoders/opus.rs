@@ -44,6 +44,10 @@ impl OggFile {
         self.encoder.finish(&mut self.output);
         self.output.write_trailer().unwrap();
     }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.encoder.sample_rate()
+    }
 }
 
 pub struct OpusEncoder {
@@ -57,19 +61,25 @@ pub struct OpusEncoder {
 }
 
 impl OpusEncoder {
+    /// Sample rates libopus encodes at, others are encoded at the closest higher one
+    pub const SAMPLE_RATES: [u32; 5] = [8_000, 12_000, 16_000, 24_000, 48_000];
     const OUTPUT_BITRATE: usize = 128 * 1000; // 128k
     const SAMPLE_FORMAT: Sample = Sample::F32(Type::Packed);
 
     pub fn factory(
         tag: &'static str,
         input_config: AudioInfo,
+        output_rate: Option<u32>,
     ) -> impl FnOnce(&mut format::context::Output) -> Result<Self, MediaError> {
-        move |o| Self::init(tag, input_config, o)
+        move |o| Self::init(tag, input_config, output_rate, o)
     }
 
+    /// `output_rate` is used if the codec supports it, otherwise the closest higher rate it does.
+    /// Without it the input's rate is kept where possible.
     pub fn init(
         tag: &'static str,
         input_config: AudioInfo,
+        output_rate: Option<u32>,
         output: &mut format::context::Output,
     ) -> Result<Self, MediaError> {
         let codec = encoder::find_by_name("libopus")
@@ -78,6 +88,8 @@ impl OpusEncoder {
         encoder_ctx.set_threading(Config::count(4));
         let mut encoder = encoder_ctx.encoder().audio()?;
 
+        let target_rate = output_rate.unwrap_or(input_config.sample_rate);
+
         let rate = {
             let mut rates = codec
                 .audio()
@@ -90,12 +102,11 @@ impl OpusEncoder {
 
             let Some(&rate) = rates
                 .iter()
-                .find(|r| **r >= input_config.rate())
+                .find(|r| **r as u32 >= target_rate)
                 .or(rates.first())
             else {
                 return Err(MediaError::TaskLaunch(format!(
-                    "Opus Codec does not support sample rate {}",
-                    input_config.rate()
+                    "Opus Codec does not support sample rate {target_rate}"
                 )));
             };
             rate
@@ -157,6 +168,10 @@ impl OpusEncoder {
         })
     }
 
+    pub fn sample_rate(&self) -> u32 {
+        self.encoder.rate()
+    }
+
     pub fn queue_frame(&mut self, frame: FFAudio, output: &mut format::context::Output) {
         if let Some(resampler) = &mut self.resampler {
             resampler.run(&frame, &mut self.resampled_frame).unwrap();
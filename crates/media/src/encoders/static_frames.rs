@@ -0,0 +1,65 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use crate::data::FFVideo;
+
+/// Longest a static frame is held for before an identical one is encoded anyway,
+/// so there's always a recent frame to seek to
+pub const MAX_STATIC_FRAME_HOLD_SECS: f64 = 1.0;
+
+/// Drops frames identical to the last one that was kept, so static content is encoded
+/// as a few long frames rather than many identical ones and the output has a variable frame rate.
+#[derive(Default)]
+pub struct StaticFrameFilter {
+    last_kept: Option<(u64, f64)>,
+}
+
+impl StaticFrameFilter {
+    /// Whether a frame captured `timestamp` seconds in should be encoded
+    pub fn keep_frame(&mut self, frame: &FFVideo, timestamp: f64) -> bool {
+        self.keep(video_fingerprint(frame), timestamp)
+    }
+
+    fn keep(&mut self, fingerprint: u64, timestamp: f64) -> bool {
+        if let Some((last_fingerprint, last_timestamp)) = self.last_kept {
+            if last_fingerprint == fingerprint
+                && timestamp - last_timestamp < MAX_STATIC_FRAME_HOLD_SECS
+            {
+                return false;
+            }
+        }
+
+        self.last_kept = Some((fingerprint, timestamp));
+        true
+    }
+}
+
+// every other row is plenty to catch a visible change, even a blinking text cursor
+fn video_fingerprint(frame: &FFVideo) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for row in frame.data(0).chunks(frame.stride(0).max(1)).step_by(2) {
+        row.hash(&mut hasher);
+    }
+
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn holds_static_frames_up_to_max() {
+        let mut filter = StaticFrameFilter::default();
+
+        assert!(filter.keep(1, 0.0));
+        assert!(!filter.keep(1, 0.5));
+        assert!(filter.keep(1, MAX_STATIC_FRAME_HOLD_SECS));
+        assert!(!filter.keep(1, MAX_STATIC_FRAME_HOLD_SECS + 0.1));
+        assert!(filter.keep(2, MAX_STATIC_FRAME_HOLD_SECS + 0.2));
+        assert!(filter.keep(1, MAX_STATIC_FRAME_HOLD_SECS + 0.3));
+    }
+}
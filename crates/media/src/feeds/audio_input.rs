@@ -49,6 +49,7 @@ pub const MAX_AUDIO_CHANNELS: u16 = 2;
 pub struct AudioInputFeed {
     pub control_tx: Sender<AudioInputControl>,
     audio_info: AudioInfo,
+    device_name: String,
     // rx: Receiver<AudioInputSamples>,
 }
 
@@ -91,6 +92,7 @@ impl AudioInputFeed {
         Ok(Self {
             control_tx,
             audio_info,
+            device_name: selected_input.to_string(),
         })
     }
 
@@ -117,33 +119,49 @@ impl AudioInputFeed {
                             .cmp(&a.sample_format().sample_size())
                             .then(b.max_sample_rate().cmp(&a.max_sample_rate()))
                     });
+                    // virtual devices (BlackHole, VB-Cable) often advertise rates far above
+                    // 48kHz, so cap the rate rather than discarding the config
                     configs
                         .into_iter()
-                        .filter(|c| {
-                            c.min_sample_rate().0 <= 48000 && c.max_sample_rate().0 <= 48000
-                        })
+                        .filter(|c| c.min_sample_rate().0 <= 48000)
                         .find(|c| ffmpeg_sample_format_for(c.sample_format()).is_some())
                 })
                 .and_then(|config| {
-                    device
-                        .name()
-                        .ok()
-                        .map(|name| (name, device, config.with_max_sample_rate()))
+                    let sample_rate = config.max_sample_rate().0.min(48000);
+
+                    device.name().ok().map(|name| {
+                        (
+                            name,
+                            device,
+                            config.with_sample_rate(cpal::SampleRate(sample_rate)),
+                        )
+                    })
                 })
         };
 
-        if let Some((name, device, config)) =
-            host.default_input_device().and_then(get_usable_device)
-        {
-            device_map.insert(name, (device, config));
-        } else {
-            warn!("No default input device found or it's not usable");
-        }
+        let mut devices = vec![];
+
+        let default_name = match host.default_input_device().and_then(get_usable_device) {
+            Some((name, device, config)) => {
+                devices.push((name.clone(), device, config));
+                Some(name)
+            }
+            None => {
+                warn!("No default input device found or it's not usable");
+                None
+            }
+        };
 
         match host.input_devices() {
-            Ok(devices) => {
-                for (name, device, config) in devices.filter_map(get_usable_device) {
-                    device_map.entry(name).or_insert((device, config));
+            Ok(input_devices) => {
+                let mut skipped_default = false;
+                for (name, device, config) in input_devices.filter_map(get_usable_device) {
+                    // the default device is listed again here
+                    if !skipped_default && default_name.as_ref() == Some(&name) {
+                        skipped_default = true;
+                        continue;
+                    }
+                    devices.push((name, device, config));
                 }
             }
             Err(error) => {
@@ -151,6 +169,11 @@ impl AudioInputFeed {
             }
         }
 
+        let names = unique_device_names(devices.iter().map(|(name, _, _)| name.as_str()));
+        for (name, (_, device, config)) in names.into_iter().zip(devices) {
+            device_map.insert(name, (device, config));
+        }
+
         device_map
     }
 
@@ -176,6 +199,7 @@ impl AudioInputFeed {
         })??;
 
         self.audio_info = AudioInfo::from_stream_config(&config);
+        self.device_name = name.to_string();
 
         Ok(())
     }
@@ -196,6 +220,10 @@ impl AudioInputFeed {
         self.audio_info
     }
 
+    pub fn device_name(&self) -> &str {
+        &self.device_name
+    }
+
     pub fn create_connection(&self) -> AudioInputConnection {
         AudioInputConnection {
             control: self.control_tx.clone(),
@@ -350,3 +378,45 @@ fn start_capturing(
         }
     }
 }
+
+/// Numbers devices that share a name, like two of the same mic,
+/// so each can be told apart and selected: "Shure MV7", "Shure MV7 (2)"
+fn unique_device_names<'a>(names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut seen = std::collections::HashMap::<&str, usize>::new();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let count = seen.entry(name).or_default();
+            *count += 1;
+            match *count {
+                1 => name.to_string(),
+                n => format!("{name} ({n})"),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn duplicate_device_names_are_numbered() {
+        assert_eq!(
+            unique_device_names([
+                "MacBook Pro Microphone",
+                "Shure MV7",
+                "Shure MV7",
+                "Shure MV7"
+            ]),
+            vec![
+                "MacBook Pro Microphone",
+                "Shure MV7",
+                "Shure MV7 (2)",
+                "Shure MV7 (3)"
+            ]
+        );
+        assert!(unique_device_names([]).is_empty());
+    }
+}
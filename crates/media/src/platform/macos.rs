@@ -97,6 +97,11 @@ pub fn get_on_screen_windows() -> Vec<Window> {
     windows
 }
 
+/// The frontmost regular window, as window info is returned in front-to-back order.
+pub fn get_focused_window() -> Option<Window> {
+    get_on_screen_windows().into_iter().next()
+}
+
 unsafe fn get_nullable_value_from_dict(
     cf_dictionary_ref: CFDictionaryRef,
     key: CFStringRef,
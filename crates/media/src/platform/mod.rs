@@ -1,3 +1,4 @@
+use cap_project::WindowCrop;
 use serde::{Deserialize, Serialize};
 use specta::Type;
 
@@ -11,7 +12,7 @@ mod platform_impl;
 
 pub use platform_impl::*;
 
-#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Type)]
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize, Type, PartialEq)]
 pub struct Bounds {
     pub x: f64,
     pub y: f64,
@@ -19,6 +20,32 @@ pub struct Bounds {
     pub height: f64,
 }
 
+impl Bounds {
+    /// The part of these bounds covered by a crop relative to their size
+    pub fn crop(&self, crop: &WindowCrop) -> Bounds {
+        Bounds {
+            x: self.x + self.width * crop.x,
+            y: self.y + self.height * crop.y,
+            width: self.width * crop.width,
+            height: self.height * crop.height,
+        }
+    }
+
+    /// The part of these bounds at `area`, an offset from their top left corner and a size,
+    /// clamped to fit inside them
+    pub fn anchored_area(&self, area: &Bounds) -> Bounds {
+        let x = area.x.clamp(0.0, self.width);
+        let y = area.y.clamp(0.0, self.height);
+
+        Bounds {
+            x: self.x + x,
+            y: self.y + y,
+            width: area.width.min(self.width - x),
+            height: area.height.min(self.height - y),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Window {
     pub window_id: u32,
@@ -22,10 +22,11 @@ use windows::Win32::System::Threading::{
 use windows::Win32::UI::HiDpi::GetDpiForWindow;
 use windows::Win32::UI::WindowsAndMessaging::{DrawIconEx, GetIconInfo, DI_NORMAL, ICONINFO};
 use windows::Win32::UI::WindowsAndMessaging::{
-    EnumWindows, GetCursorInfo, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
-    IsWindowVisible, LoadCursorW, SetForegroundWindow, CURSORINFO, IDC_APPSTARTING, IDC_ARROW,
-    IDC_CROSS, IDC_HAND, IDC_HELP, IDC_IBEAM, IDC_NO, IDC_PERSON, IDC_PIN, IDC_SIZEALL,
-    IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE, IDC_SIZEWE, IDC_UPARROW, IDC_WAIT,
+    EnumWindows, GetCursorInfo, GetForegroundWindow, GetWindowTextLengthW, GetWindowTextW,
+    GetWindowThreadProcessId, IsWindowVisible, LoadCursorW, SetForegroundWindow, CURSORINFO,
+    IDC_APPSTARTING, IDC_ARROW, IDC_CROSS, IDC_HAND, IDC_HELP, IDC_IBEAM, IDC_NO, IDC_PERSON,
+    IDC_PIN, IDC_SIZEALL, IDC_SIZENESW, IDC_SIZENS, IDC_SIZENWSE, IDC_SIZEWE, IDC_UPARROW,
+    IDC_WAIT,
 };
 
 #[inline]
@@ -233,6 +234,18 @@ pub fn get_on_screen_windows() -> Vec<Window> {
     windows
 }
 
+pub fn get_focused_window() -> Option<Window> {
+    let foreground = unsafe { GetForegroundWindow() };
+
+    if foreground.is_invalid() {
+        return None;
+    }
+
+    get_on_screen_windows()
+        .into_iter()
+        .find(|window| window.window_id == foreground.0 as u32)
+}
+
 pub fn monitor_bounds(id: u32) -> Bounds {
     let bounds = None::<Bounds>;
 
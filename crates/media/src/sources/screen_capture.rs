@@ -8,9 +8,19 @@ use scap::{
     Target,
 };
 
+use cap_project::WindowCrop;
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use std::{collections::HashMap, ops::ControlFlow, sync::Arc, time::SystemTime};
+use std::{
+    collections::HashMap,
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::RecvTimeoutError,
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
 use tracing::{debug, error, info, trace, warn};
 
 use crate::{
@@ -48,12 +58,30 @@ pub struct CaptureArea {
     pub bounds: Bounds,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq)]
 #[serde(rename_all = "camelCase", tag = "variant")]
 pub enum ScreenCaptureTarget {
-    Window { id: u32 },
-    Screen { id: u32 },
-    Area { screen: u32, bounds: Bounds },
+    Window {
+        id: u32,
+        /// captures only this part of the window, which studio recordings
+        /// recompute by starting a new segment whenever the window resizes
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        crop: Option<WindowCrop>,
+    },
+    Screen {
+        id: u32,
+    },
+    Area {
+        screen: u32,
+        bounds: Bounds,
+        /// window `bounds` are relative to rather than the screen, so the area follows it.
+        /// Studio recordings start a new segment whenever the window moves or resizes.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        anchor_window: Option<u32>,
+    },
+    /// Every screen, composited into a frame covering the whole desktop.
+    /// Only `AVFrameCapture` supports it.
+    AllScreens,
 }
 
 impl ScreenCaptureTarget {
@@ -68,7 +96,7 @@ impl ScreenCaptureTarget {
         let targets = scap::get_all_targets();
 
         match self {
-            ScreenCaptureTarget::Window { id } => targets.into_iter().find(|t| match t {
+            ScreenCaptureTarget::Window { id, .. } => targets.into_iter().find(|t| match t {
                 scap::Target::Window(window) => window.id == *id,
                 _ => false,
             }),
@@ -80,10 +108,21 @@ impl ScreenCaptureTarget {
                 scap::Target::Display(display) => display.id == *screen,
                 _ => false,
             }),
+            ScreenCaptureTarget::AllScreens => {
+                let primary = scap::get_main_display().id;
+                targets.into_iter().find(|t| match t {
+                    scap::Target::Display(display) => display.id == primary,
+                    _ => false,
+                })
+            }
         }
     }
 
     pub fn get_title(&self) -> Option<String> {
+        if let ScreenCaptureTarget::AllScreens = self {
+            return Some("All Screens".to_string());
+        }
+
         let target = self.get_target();
 
         match target {
@@ -141,12 +180,18 @@ impl<T: ScreenCaptureFormat> std::fmt::Debug for ScreenCaptureSource<T> {
 pub trait ScreenCaptureFormat {
     type VideoFormat;
 
+    /// Whether frames from multiple screens can be composited into one,
+    /// for `ScreenCaptureTarget::AllScreens`
+    const COMPOSITES_SCREENS: bool = false;
+
     fn audio_info() -> AudioInfo;
 }
 
 impl ScreenCaptureFormat for AVFrameCapture {
     type VideoFormat = FFVideo;
 
+    const COMPOSITES_SCREENS: bool = true;
+
     fn audio_info() -> AudioInfo {
         let host = cpal::default_host();
         let output_device = host.default_output_device().unwrap();
@@ -203,12 +248,19 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
         show_camera: bool,
         force_show_cursor: bool,
         max_fps: u32,
+        excluded_apps: &[String],
         video_tx: Sender<(TCaptureFormat::VideoFormat, f64)>,
         audio_tx: Option<Sender<(ffmpeg::frame::Audio, f64)>>,
         start_time: SystemTime,
     ) -> Result<Self, String> {
         cap_fail::fail!("media::screen_capture::init");
 
+        if let ScreenCaptureTarget::AllScreens = target {
+            if !TCaptureFormat::COMPOSITES_SCREENS {
+                return Err("This capture method can't record all screens".to_string());
+            }
+        }
+
         let OptionsConfig {
             scap_target,
             bounds,
@@ -242,7 +294,7 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
             start_time,
         };
 
-        let options = this.create_options(scap_target, crop_area, captures_audio)?;
+        let options = this.create_options(scap_target, crop_area, captures_audio, excluded_apps)?;
 
         this.options = Arc::new(options);
 
@@ -260,6 +312,24 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
             (bounds.width as u32, bounds.height as u32)
         };
 
+        // the primary screen's frames are captured and composited at their native scale,
+        // other screens' are scaled to match
+        let video_size = match target {
+            ScreenCaptureTarget::AllScreens => {
+                let [primary_frame_width, _] = scap::capturer::get_output_frame_size(&this.options);
+                let scale = logical_monitor_bounds(scap::get_main_display().id)
+                    .map(|bounds| bounds.size.width)
+                    .filter(|width| *width > 0.0)
+                    .map_or(1.0, |width| primary_frame_width as f64 / width);
+
+                (
+                    (bounds.width * scale) as u32 & !1,
+                    (bounds.height * scale) as u32 & !1,
+                )
+            }
+            _ => video_size,
+        };
+
         this.video_info =
             VideoInfo::from_raw(RawVideoFormat::Bgra, video_size.0, video_size.1, fps);
 
@@ -270,7 +340,59 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
         &self.bounds
     }
 
+    /// Captures a single frame with the options a recording of the target would use,
+    /// as tightly packed BGRA rows
+    pub fn capture_frame(&self) -> Result<(u32, u32, Vec<u8>), String> {
+        if let ScreenCaptureTarget::AllScreens = self.target {
+            return Err("Can't capture a single frame of all screens".to_string());
+        }
+
+        let mut options = self.options.as_ref().clone();
+        options.output_type = FrameType::BGRAFrame;
+        options.captures_audio = false;
+
+        let mut capturer =
+            Capturer::build(options).map_err(|e| format!("Failed to build capturer: {e}"))?;
+        capturer.start_capture();
+
+        let frame = loop {
+            match capturer.get_next_frame() {
+                Ok(Frame::Video(VideoFrame::BGRA(frame)))
+                    if frame.width > 0 && frame.height > 0 =>
+                {
+                    break Ok(frame)
+                }
+                Ok(_) => continue,
+                Err(e) => break Err(format!("Failed to get frame: {e}")),
+            }
+        };
+        capturer.stop_capture();
+        let frame = frame?;
+
+        let width_in_bytes = frame.width as usize * 4;
+        let height = frame.height as usize;
+        let stride = frame.data.len() / height;
+        if stride < width_in_bytes {
+            return Err("Frame is smaller than its dimensions".to_string());
+        }
+
+        let data = frame
+            .data
+            .chunks_exact(stride)
+            .flat_map(|row| &row[..width_in_bytes])
+            .copied()
+            .collect();
+
+        Ok((frame.width as u32, frame.height as u32, data))
+    }
+
     pub fn crop_ratio(&self) -> CropRatio {
+        if let ScreenCaptureTarget::AllScreens = self.target {
+            if let Some(primary) = screen_logical_bounds(scap::get_main_display().id) {
+                return all_screens_crop_ratio(&primary, &self.bounds);
+            }
+        }
+
         if let Some(crop_area) = &self.options.crop_area {
             CropRatio {
                 position: (
@@ -294,7 +416,7 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
         let targets = scap::get_all_targets();
 
         Ok(match target {
-            ScreenCaptureTarget::Window { id } => {
+            ScreenCaptureTarget::Window { id, crop } => {
                 let windows = list_windows();
 
                 let (mut window_info, target) = windows
@@ -325,6 +447,10 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
                 window_info.bounds.x -= monitor_bounds.position.x;
                 window_info.bounds.y -= monitor_bounds.position.y;
 
+                if let Some(crop) = crop {
+                    window_info.bounds = window_info.bounds.crop(crop);
+                }
+
                 fn div_by_2able(n: f64) -> f64 {
                     n + n % 2.0
                 }
@@ -372,9 +498,15 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
                     display_size: (bounds.width as f32, bounds.height as f32),
                 }
             }
-            ScreenCaptureTarget::Area { screen, bounds } => {
+            ScreenCaptureTarget::Area {
+                screen,
+                bounds,
+                anchor_window,
+            } => {
                 let screen_bounds = platform::monitor_bounds(*screen);
 
+                let bounds = &area_bounds(*screen, bounds, *anchor_window)?;
+
                 let screens = list_screens();
                 let screen = screens
                     .into_iter()
@@ -397,6 +529,32 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
                     display_size: (screen_bounds.width as f32, screen_bounds.height as f32),
                 }
             }
+            // captured with the primary screen's options, which each screen's capturer adapts
+            ScreenCaptureTarget::AllScreens => {
+                let primary = scap::get_main_display().id;
+                let screens = list_screens();
+
+                let scap_target = screens
+                    .iter()
+                    .find(|(screen, _)| screen.id == primary)
+                    .map(|(_, target)| target.clone())
+                    .ok_or_else(|| "Primary screen not found".to_string())?;
+
+                let bounds = all_screens_bounds(
+                    &screens
+                        .iter()
+                        .filter_map(|(screen, _)| screen_logical_bounds(screen.id))
+                        .collect::<Vec<_>>(),
+                )
+                .ok_or_else(|| "No screens to capture".to_string())?;
+
+                OptionsConfig {
+                    scap_target,
+                    bounds,
+                    crop_area: None,
+                    display_size: (bounds.width as f32, bounds.height as f32),
+                }
+            }
         })
     }
 
@@ -405,8 +563,10 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
         target: scap::Target,
         crop_area: Option<Area>,
         captures_audio: bool,
+        excluded_apps: &[String],
     ) -> Result<Options, String> {
         let targets = scap::get_all_targets();
+        let excluded_app_windows = excluded_app_window_ids(excluded_apps);
 
         let excluded_targets: Vec<scap::Target> = targets
             .iter()
@@ -416,6 +576,7 @@ impl<TCaptureFormat: ScreenCaptureFormat> ScreenCaptureSource<TCaptureFormat> {
                         false
                     } else {
                         EXCLUDED_WINDOWS.contains(&scap_window.title.as_str())
+                            || excluded_app_windows.contains(&scap_window.id)
                     }
                 }
                 Target::Display(_) => false,
@@ -461,6 +622,10 @@ impl PipelineSourceTask for ScreenCaptureSource<AVFrameCapture> {
         ready_signal: crate::pipeline::task::PipelineReadySignal,
         control_signal: crate::pipeline::control::PipelineControlSignal,
     ) {
+        if let ScreenCaptureTarget::AllScreens = self.target {
+            return composite_all_screens(self, ready_signal, control_signal);
+        }
+
         let video_info = self.video_info;
         let video_tx = self.video_tx.clone();
         let audio_tx = self.audio_tx.clone();
@@ -562,6 +727,304 @@ impl PipelineSourceTask for ScreenCaptureSource<AVFrameCapture> {
     }
 }
 
+/// Where an area target currently is relative to its screen's top left,
+/// following the window it's anchored to if there is one
+pub fn area_bounds(
+    screen: u32,
+    area: &Bounds,
+    anchor_window: Option<u32>,
+) -> Result<Bounds, String> {
+    let Some(window) = anchor_window else {
+        return Ok(*area);
+    };
+
+    let window_bounds = window_bounds(window)
+        .ok_or_else(|| "Window the area is anchored to not found".to_string())?;
+    let screen_bounds = screen_logical_bounds(screen)
+        .ok_or_else(|| "Screen for capture area not found".to_string())?;
+
+    Ok(window_anchored_area(&window_bounds, &screen_bounds, area))
+}
+
+/// `area`, relative to `window`, as it is relative to `screen`
+fn window_anchored_area(window: &Bounds, screen: &Bounds, area: &Bounds) -> Bounds {
+    Bounds {
+        x: window.x - screen.x,
+        y: window.y - screen.y,
+        ..*window
+    }
+    .anchored_area(area)
+}
+
+fn screen_logical_bounds(id: u32) -> Option<Bounds> {
+    logical_monitor_bounds(id).map(|bounds| Bounds {
+        x: bounds.position.x,
+        y: bounds.position.y,
+        width: bounds.size.width,
+        height: bounds.size.height,
+    })
+}
+
+/// The rectangle enclosing all of `screens`, in the logical coordinate space they share
+pub fn all_screens_bounds(screens: &[Bounds]) -> Option<Bounds> {
+    let first = screens.first()?;
+
+    let (mut left, mut top) = (first.x, first.y);
+    let (mut right, mut bottom) = (first.x + first.width, first.y + first.height);
+    for screen in &screens[1..] {
+        left = left.min(screen.x);
+        top = top.min(screen.y);
+        right = right.max(screen.x + screen.width);
+        bottom = bottom.max(screen.y + screen.height);
+    }
+
+    Some(Bounds {
+        x: left,
+        y: top,
+        width: right - left,
+        height: bottom - top,
+    })
+}
+
+/// The composite of all screens as a crop of the primary screen, which positions relative to the
+/// primary screen can be mapped onto the composite with
+fn all_screens_crop_ratio(primary: &Bounds, all_bounds: &Bounds) -> CropRatio {
+    CropRatio {
+        position: (
+            ((all_bounds.x - primary.x) / primary.width) as f32,
+            ((all_bounds.y - primary.y) / primary.height) as f32,
+        ),
+        size: (
+            (all_bounds.width / primary.width) as f32,
+            (all_bounds.height / primary.height) as f32,
+        ),
+    }
+}
+
+/// Where a screen's frame goes in a composited frame of `output_size` pixels covering `all_bounds`,
+/// as x, y, width and height in pixels. Sizing by logical bounds means every screen is drawn at the
+/// same scale, whatever its own scale factor.
+fn composited_rect(
+    screen: &Bounds,
+    all_bounds: &Bounds,
+    output_size: (u32, u32),
+) -> (usize, usize, usize, usize) {
+    let scale_x = output_size.0 as f64 / all_bounds.width;
+    let scale_y = output_size.1 as f64 / all_bounds.height;
+
+    let x = (((screen.x - all_bounds.x) * scale_x).round() as usize).min(output_size.0 as usize);
+    let y = (((screen.y - all_bounds.y) * scale_y).round() as usize).min(output_size.1 as usize);
+
+    (
+        x,
+        y,
+        ((screen.width * scale_x).round() as usize).min(output_size.0 as usize - x),
+        ((screen.height * scale_y).round() as usize).min(output_size.1 as usize - y),
+    )
+}
+
+/// Copies a BGRA frame into `rect` of `dst`, scaled with nearest-neighbour sampling
+fn blit_scaled(
+    dst: &mut [u8],
+    dst_stride: usize,
+    (x, y, width, height): (usize, usize, usize, usize),
+    src: &[u8],
+    src_width: usize,
+    src_height: usize,
+) {
+    if src_width == 0 || src_height == 0 {
+        return;
+    }
+    let src_stride = src.len() / src_height;
+
+    for row in 0..height {
+        let src_row = &src[(row * src_height / height) * src_stride..];
+        let dst_row = &mut dst[(y + row) * dst_stride + x * 4..];
+
+        for col in 0..width {
+            let src_col = col * src_width / width * 4;
+            dst_row[col * 4..col * 4 + 4].copy_from_slice(&src_row[src_col..src_col + 4]);
+        }
+    }
+}
+
+// scap's targets hold raw display handles, which are only used on the capturer's own thread
+struct DisplayOptions(Options);
+unsafe impl Send for DisplayOptions {}
+
+type LatestFrame = Arc<Mutex<Option<(usize, usize, Vec<u8>)>>>;
+
+/// Captures each screen on its own thread, and composites their latest frames at the capture's
+/// frame rate. The primary screen's capturer also captures system audio.
+fn composite_all_screens(
+    source: &mut ScreenCaptureSource<AVFrameCapture>,
+    ready_signal: crate::pipeline::task::PipelineReadySignal,
+    mut control_signal: crate::pipeline::control::PipelineControlSignal,
+) {
+    let video_info = source.video_info;
+    let start_time = source.start_time;
+    let primary = scap::get_main_display().id;
+    let stop = Arc::new(AtomicBool::new(false));
+
+    let screens = list_screens()
+        .into_iter()
+        .filter_map(|(screen, target)| Some((screen.id, screen_logical_bounds(screen.id)?, target)))
+        .collect::<Vec<_>>();
+
+    let _ = ready_signal.send(Ok(()));
+
+    let mut displays = None::<Vec<(Bounds, LatestFrame)>>;
+    let frame_interval = Duration::from_secs_f64(1.0 / video_info.fps().max(1) as f64);
+
+    loop {
+        match control_signal.last() {
+            Some(Control::Shutdown) | None => {
+                trace!("Received shutdown signal");
+                break;
+            }
+            Some(Control::Play) => {
+                let displays = displays.get_or_insert_with(|| {
+                    info!("Capturing {} screens", screens.len());
+
+                    screens
+                        .iter()
+                        .map(|(id, bounds, target)| {
+                            let mut options = source.options.as_ref().clone();
+                            options.target = Some(target.clone());
+                            options.crop_area = None;
+                            options.captures_audio = *id == primary && source.audio_tx.is_some();
+
+                            let latest = LatestFrame::default();
+                            let audio_tx = source.audio_tx.clone().filter(|_| *id == primary);
+
+                            std::thread::spawn({
+                                let options = DisplayOptions(options);
+                                let latest = latest.clone();
+                                let stop = stop.clone();
+                                move || capture_screen(options, latest, audio_tx, start_time, stop)
+                            });
+
+                            (*bounds, latest)
+                        })
+                        .collect()
+                });
+
+                std::thread::sleep(frame_interval);
+
+                let Ok(elapsed) = SystemTime::now().duration_since(start_time) else {
+                    continue;
+                };
+
+                let mut frame =
+                    FFVideo::new(video_info.pixel_format, video_info.width, video_info.height);
+                let stride = frame.stride(0);
+                let data = frame.data_mut(0);
+                data.fill(0);
+
+                for (bounds, latest) in displays.iter() {
+                    let Some((width, height, bgra)) = &*latest.lock().unwrap() else {
+                        continue;
+                    };
+
+                    blit_scaled(
+                        data,
+                        stride,
+                        composited_rect(
+                            bounds,
+                            &source.bounds,
+                            (video_info.width, video_info.height),
+                        ),
+                        bgra,
+                        *width,
+                        *height,
+                    );
+                }
+
+                frame.set_pts(Some(
+                    (elapsed.as_secs_f64() * AV_TIME_BASE_Q.den as f64) as i64,
+                ));
+
+                if source
+                    .video_tx
+                    .send((frame, elapsed.as_secs_f64()))
+                    .is_err()
+                {
+                    error!("Pipeline is unreachable. Shutting down recording.");
+                    break;
+                }
+            }
+        }
+    }
+
+    stop.store(true, Ordering::Relaxed);
+
+    info!("Shut down screen capture source thread.");
+}
+
+fn capture_screen(
+    options: DisplayOptions,
+    latest: LatestFrame,
+    audio_tx: Option<Sender<(ffmpeg::frame::Audio, f64)>>,
+    start_time: SystemTime,
+    stop: Arc<AtomicBool>,
+) {
+    // `get_next_frame` waits for the screen to change, so frames are read on their own thread
+    // and `stop` is checked between them, rather than only once a static screen next changes
+    let (frame_tx, frame_rx) = std::sync::mpsc::sync_channel(2);
+    std::thread::spawn(move || {
+        let options = options;
+        let mut capturer = match Capturer::build(options.0) {
+            Ok(capturer) => capturer,
+            Err(e) => {
+                error!("Failed to build capturer: {e}");
+                return;
+            }
+        };
+        capturer.start_capture();
+
+        loop {
+            match capturer.get_next_frame() {
+                // stopped listening
+                Ok(frame) if frame_tx.send(frame).is_err() => break,
+                Ok(_) => {}
+                Err(e) => {
+                    error!("Capture error: {e}");
+                    break;
+                }
+            }
+        }
+
+        capturer.stop_capture();
+    });
+
+    while !stop.load(Ordering::Relaxed) {
+        match frame_rx.recv_timeout(Duration::from_millis(100)) {
+            Ok(Frame::Video(VideoFrame::BGRA(frame))) => {
+                if frame.width > 0 && frame.height > 0 {
+                    *latest.lock().unwrap() =
+                        Some((frame.width as usize, frame.height as usize, frame.data));
+                }
+            }
+            Ok(Frame::Audio(frame)) => {
+                let Some(audio_tx) = &audio_tx else {
+                    continue;
+                };
+                let Ok(elapsed) = frame.time().duration_since(start_time) else {
+                    continue;
+                };
+
+                let mut frame = scap_audio_to_ffmpeg(frame);
+                frame.set_pts(Some(
+                    (elapsed.as_secs_f64() * AV_TIME_BASE_Q.den as f64) as i64,
+                ));
+                let _ = audio_tx.send((frame, elapsed.as_secs_f64()));
+            }
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+}
+
 fn inner<T: ScreenCaptureFormat>(
     source: &mut ScreenCaptureSource<T>,
     ready_signal: crate::pipeline::task::PipelineReadySignal,
@@ -571,7 +1034,7 @@ fn inner<T: ScreenCaptureFormat>(
     trace!("Preparing screen capture source thread...");
 
     let maybe_capture_window_id = match &source.target {
-        ScreenCaptureTarget::Window { id } => Some(*id),
+        ScreenCaptureTarget::Window { id, .. } => Some(*id),
         _ => None,
     };
 
@@ -801,6 +1264,28 @@ pub fn list_screens() -> Vec<(CaptureScreen, Target)> {
     targets
 }
 
+pub fn window_bounds(id: u32) -> Option<Bounds> {
+    crate::platform::get_on_screen_windows()
+        .into_iter()
+        .find(|window| window.window_id == id)
+        .map(|window| window.bounds)
+}
+
+/// Ids of the on-screen windows belonging to the apps with these owner names, sorted
+pub fn excluded_app_window_ids(excluded_apps: &[String]) -> Vec<u32> {
+    if excluded_apps.is_empty() {
+        return vec![];
+    }
+
+    let mut ids = crate::platform::get_on_screen_windows()
+        .into_iter()
+        .filter(|window| excluded_apps.contains(&window.owner_name))
+        .map(|window| window.window_id)
+        .collect::<Vec<_>>();
+    ids.sort_unstable();
+    ids
+}
+
 pub fn list_windows() -> Vec<(CaptureWindow, Target)> {
     if !scap::has_permission() {
         return vec![];
@@ -925,3 +1410,116 @@ fn scap_audio_to_ffmpeg(scap_frame: scap::frame::AudioFrame) -> ffmpeg::frame::A
 
     ffmpeg_frame
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bounds(x: f64, y: f64, width: f64, height: f64) -> Bounds {
+        Bounds {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn all_screens_bounds_enclose_every_screen() {
+        // a retina primary, a wider screen to its left and one above it
+        let screens = [
+            bounds(0.0, 0.0, 1512.0, 982.0),
+            bounds(-2560.0, -200.0, 2560.0, 1440.0),
+            bounds(200.0, -1080.0, 1920.0, 1080.0),
+        ];
+
+        let all = all_screens_bounds(&screens).unwrap();
+        assert_eq!(all, bounds(-2560.0, -1080.0, 4680.0, 2062.0));
+
+        for screen in &screens {
+            assert!(screen.x >= all.x && screen.y >= all.y);
+            assert!(screen.x + screen.width <= all.x + all.width);
+            assert!(screen.y + screen.height <= all.y + all.height);
+        }
+
+        assert_eq!(all_screens_bounds(&[]), None);
+    }
+
+    #[test]
+    fn composited_screens_share_a_scale() {
+        let all = bounds(0.0, 0.0, 3000.0, 1000.0);
+        // the primary's frames are twice its logical size
+        let output = (6000, 2000);
+
+        assert_eq!(
+            composited_rect(&bounds(0.0, 0.0, 1000.0, 1000.0), &all, output),
+            (0, 0, 2000, 2000)
+        );
+        assert_eq!(
+            composited_rect(&bounds(1000.0, 0.0, 2000.0, 800.0), &all, output),
+            (2000, 0, 4000, 1600)
+        );
+    }
+
+    #[test]
+    fn anchored_area_follows_window() {
+        let area = bounds(20.0, 40.0, 300.0, 200.0);
+
+        let window = bounds(100.0, 100.0, 800.0, 600.0);
+        assert_eq!(
+            window.anchored_area(&area),
+            bounds(120.0, 140.0, 300.0, 200.0)
+        );
+
+        let moved = bounds(400.0, 50.0, 800.0, 600.0);
+        assert_eq!(
+            moved.anchored_area(&area),
+            bounds(420.0, 90.0, 300.0, 200.0)
+        );
+
+        // shrunk so the area no longer fits
+        let shrunk = bounds(400.0, 50.0, 220.0, 100.0);
+        assert_eq!(
+            shrunk.anchored_area(&area),
+            bounds(420.0, 90.0, 200.0, 60.0)
+        );
+    }
+
+    #[test]
+    fn anchored_area_is_relative_to_its_screen() {
+        let area = bounds(20.0, 40.0, 300.0, 200.0);
+        let window = bounds(1600.0, 100.0, 800.0, 600.0);
+        let screen = bounds(1440.0, 0.0, 1920.0, 1080.0);
+
+        assert_eq!(
+            window_anchored_area(&window, &screen, &area),
+            bounds(180.0, 140.0, 300.0, 200.0)
+        );
+    }
+
+    #[test]
+    fn all_screens_crop_ratio_maps_onto_the_composite() {
+        let primary = bounds(0.0, 0.0, 1512.0, 982.0);
+        let all = bounds(-2560.0, -1080.0, 4680.0, 2062.0);
+        let crop = all_screens_crop_ratio(&primary, &all);
+
+        // positions normalized to the primary screen, mapped the way `with_crop` does
+        let onto_composite = |x: f64, y: f64| {
+            (
+                ((x / primary.width) as f32 - crop.position.0) / crop.size.0,
+                ((y / primary.height) as f32 - crop.position.1) / crop.size.1,
+            )
+        };
+
+        let (x, y) = onto_composite(all.x, all.y);
+        assert!(x.abs() < 1e-5 && y.abs() < 1e-5);
+
+        let (x, y) = onto_composite(all.x + all.width, all.y + all.height);
+        assert!((x - 1.0).abs() < 1e-5 && (y - 1.0).abs() < 1e-5);
+
+        // the primary screen's origin, 2560 points from the composite's left edge
+        let (x, y) = onto_composite(0.0, 0.0);
+        assert!((x - 2560.0 / 4680.0).abs() < 1e-5);
+        assert!((y - 1080.0 / 2062.0).abs() < 1e-5);
+    }
+}
@@ -415,6 +415,43 @@ pub struct ZoomSegment {
     pub end: f64,
     pub amount: f64,
     pub mode: ZoomMode,
+    /// Where an auto zoom is centred over time, smoothed from the cursor's movement
+    /// so it pans instead of jumping. Without one it's centred on the cursor.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub follow_path: Vec<ZoomFollowPoint>,
+}
+
+impl ZoomSegment {
+    /// The follow path's position at `time`, interpolated between its points
+    pub fn follow_position_at(&self, time: f64) -> Option<XY<f64>> {
+        let (first, last) = (self.follow_path.first()?, self.follow_path.last()?);
+
+        if time <= first.time {
+            return Some(first.position);
+        }
+
+        if time >= last.time {
+            return Some(last.position);
+        }
+
+        let next_index = self.follow_path.partition_point(|p| p.time <= time);
+        let (prev, next) = (
+            &self.follow_path[next_index - 1],
+            &self.follow_path[next_index],
+        );
+        let t = (time - prev.time) / (next.time - prev.time);
+
+        Some(prev.position + (next.position - prev.position) * t)
+    }
+}
+
+#[derive(Type, Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoomFollowPoint {
+    /// seconds on the timeline
+    pub time: f64,
+    /// normalized to the display, like cursor positions
+    pub position: XY<f64>,
 }
 
 #[derive(Type, Serialize, Deserialize, Clone, Debug)]
@@ -36,6 +36,12 @@ impl PartialOrd for CursorClickEvent {
     }
 }
 
+/// A key being pressed. Only when is recorded, not which key, for finding where the user's typing.
+#[derive(Serialize, Deserialize, Clone, Type, Debug, PartialEq)]
+pub struct KeyPressEvent {
+    pub time_ms: f64,
+}
+
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
 #[serde(transparent)]
 pub struct CursorImages(pub HashMap<String, CursorImage>);
@@ -50,6 +56,8 @@ pub struct CursorImage {
 pub struct CursorData {
     pub clicks: Vec<CursorClickEvent>,
     pub moves: Vec<CursorMoveEvent>,
+    #[serde(default)]
+    pub keys: Vec<KeyPressEvent>,
     pub cursor_images: CursorImages,
 }
 
@@ -64,6 +72,8 @@ impl CursorData {
 pub struct CursorEvents {
     pub clicks: Vec<CursorClickEvent>,
     pub moves: Vec<CursorMoveEvent>,
+    #[serde(default)]
+    pub keys: Vec<KeyPressEvent>,
 }
 
 impl CursorEvents {
@@ -135,11 +145,174 @@ impl CursorEvents {
     }
 }
 
+/// A fixed-size region of the display that pans to keep the cursor centered,
+/// sampled at regular intervals in the same time base as the cursor events.
+#[derive(Serialize, Deserialize, Clone, Type, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorFollowPath {
+    pub size: XY<u32>,
+    pub points: Vec<CursorFollowPoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Type, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CursorFollowPoint {
+    pub time: f64,
+    /// top-left of the region in display pixels
+    pub position: XY<u32>,
+}
+
+impl CursorFollowPath {
+    const SAMPLE_INTERVAL: f64 = 0.1;
+    // roughly how long the region takes to catch up with the cursor
+    const SMOOTHING_SECS: f64 = 0.3;
+
+    pub fn from_cursor_moves(
+        moves: &[CursorMoveEvent],
+        display_size: XY<u32>,
+        size: XY<u32>,
+    ) -> Self {
+        let size = XY::new(size.x.min(display_size.x), size.y.min(display_size.y));
+        let max_position = XY::new(
+            (display_size.x - size.x) as f64,
+            (display_size.y - size.y) as f64,
+        );
+
+        let target_for = |event: Option<&CursorMoveEvent>| {
+            let cursor = event.map_or(XY::new(0.5, 0.5), |e| XY::new(e.x, e.y));
+
+            XY::new(
+                (cursor.x * display_size.x as f64 - size.x as f64 / 2.0).clamp(0.0, max_position.x),
+                (cursor.y * display_size.y as f64 - size.y as f64 / 2.0).clamp(0.0, max_position.y),
+            )
+        };
+
+        let end = moves.last().map_or(0.0, |e| e.time_ms / 1000.0);
+        let smoothing = 1.0 - (-Self::SAMPLE_INTERVAL / Self::SMOOTHING_SECS).exp();
+
+        let mut current = target_for(moves.first());
+        let mut move_index = 0;
+        let mut points = vec![];
+
+        for i in 0.. {
+            let time = i as f64 * Self::SAMPLE_INTERVAL;
+
+            while moves
+                .get(move_index + 1)
+                .is_some_and(|e| e.time_ms / 1000.0 <= time)
+            {
+                move_index += 1;
+            }
+
+            let target = target_for(moves.get(move_index));
+            current = current + (target - current) * smoothing;
+
+            points.push(CursorFollowPoint {
+                time,
+                position: current.map(|v| v.round() as u32),
+            });
+
+            if time >= end {
+                break;
+            }
+        }
+
+        Self { size, points }
+    }
+
+    pub fn position_at(&self, time: f64) -> XY<u32> {
+        let (Some(first), Some(last)) = (self.points.first(), self.points.last()) else {
+            return XY::new(0, 0);
+        };
+
+        if time <= first.time {
+            return first.position;
+        }
+
+        if time >= last.time {
+            return last.position;
+        }
+
+        let next_index = self.points.partition_point(|p| p.time <= time);
+        let (prev, next) = (&self.points[next_index - 1], &self.points[next_index]);
+        let t = (time - prev.time) / (next.time - prev.time);
+
+        let lerp = |a: u32, b: u32| (a as f64 + (b as f64 - a as f64) * t).round() as u32;
+
+        XY::new(
+            lerp(prev.position.x, next.position.x),
+            lerp(prev.position.y, next.position.y),
+        )
+    }
+}
+
 impl From<CursorData> for CursorEvents {
     fn from(value: CursorData) -> Self {
         Self {
             clicks: value.clicks,
             moves: value.moves,
+            keys: value.keys,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn move_event(time_ms: f64, x: f64, y: f64) -> CursorMoveEvent {
+        CursorMoveEvent {
+            active_modifiers: vec![],
+            cursor_id: "0".to_string(),
+            time_ms,
+            x,
+            y,
+        }
+    }
+
+    #[test]
+    fn follow_path_centers_on_cursor() {
+        let path = CursorFollowPath::from_cursor_moves(
+            &[move_event(0.0, 0.5, 0.5), move_event(2000.0, 0.5, 0.5)],
+            XY::new(1920, 1080),
+            XY::new(640, 360),
+        );
+
+        assert_eq!(path.position_at(1.0), XY::new(640, 360));
+        assert!((path.points.last().unwrap().time - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn follow_path_stays_on_display() {
+        let path = CursorFollowPath::from_cursor_moves(
+            &[move_event(0.0, 1.0, 1.0), move_event(1000.0, 0.0, 0.0)],
+            XY::new(1920, 1080),
+            XY::new(640, 360),
+        );
+
+        assert_eq!(path.position_at(0.0), XY::new(1280, 720));
+        assert!(path
+            .points
+            .iter()
+            .all(|p| p.position.x <= 1280 && p.position.y <= 720));
+    }
+
+    #[test]
+    fn follow_path_pans_smoothly() {
+        let path = CursorFollowPath::from_cursor_moves(
+            &[
+                move_event(0.0, 0.25, 0.5),
+                move_event(100.0, 0.75, 0.5),
+                move_event(3000.0, 0.75, 0.5),
+            ],
+            XY::new(1920, 1080),
+            XY::new(640, 360),
+        );
+
+        let xs = path.points.iter().map(|p| p.position.x).collect::<Vec<_>>();
+
+        assert!(xs.windows(2).all(|w| w[0] <= w[1]));
+        assert!(xs[2] < 1120);
+        assert_eq!(*xs.last().unwrap(), 1120);
+    }
+}
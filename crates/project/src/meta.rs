@@ -10,7 +10,10 @@ use std::{
 use tracing::{debug, info, warn};
 // use tracing::{debug, warn};
 
-use crate::{CaptionsData, CursorEvents, CursorImage, CursorImages, ProjectConfiguration, XY};
+use crate::{
+    CaptionsData, CursorEvents, CursorFollowPath, CursorImage, CursorImages, ProjectConfiguration,
+    XY,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct VideoMeta {
@@ -21,6 +24,22 @@ pub struct VideoMeta {
     /// unix time of the first frame
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub start_time: Option<f64>,
+    /// set when the capture was downscaled before being encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_scale: Option<CaptureScaleMeta>,
+    /// static frames were held instead of repeated, so `fps` is only the maximum
+    #[serde(default)]
+    pub variable_frame_rate: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureScaleMeta {
+    pub scale: f32,
+    /// size the screen was captured at
+    pub native_size: XY<u32>,
+    /// size the recording was encoded at
+    pub scaled_size: XY<u32>,
 }
 
 fn legacy_static_video_fps() -> u32 {
@@ -34,6 +53,8 @@ pub struct AudioMeta {
     /// unix time of the first frame
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub start_time: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -67,10 +88,104 @@ pub struct RecordingMeta {
     pub pretty_name: String,
     #[serde(default)]
     pub sharing: Option<SharingMeta>,
+    /// user-chosen library thumbnail, separate from the uploaded share screenshot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[specta(type = Option<String>)]
+    pub cover: Option<RelativePathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_marker: Option<RecordingStartMarker>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chapters: Vec<ChapterMarker>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quality: Option<RecordingQuality>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renditions: Vec<RenditionMeta>,
     #[serde(flatten)]
     pub inner: RecordingMetaInner,
 }
 
+/// An extra copy of a recording encoded once it finished, eg. a smaller one for sharing
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RenditionMeta {
+    pub name: String,
+    #[specta(type = String)]
+    pub path: RelativePathBuf,
+    pub width: u32,
+    pub height: u32,
+    /// uploaded instead of the original recording
+    pub share: bool,
+}
+
+/// How much of what was captured made it into a recording, worked out once it finished
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingQuality {
+    pub expected_frames: u64,
+    pub written_frames: u64,
+    pub dropped_frames: u64,
+    /// frames written per second of video
+    pub average_fps: f64,
+    /// times the audio cut out
+    pub audio_underruns: u32,
+}
+
+/// A named point in a recording, added while it was recording
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct ChapterMarker {
+    pub name: String,
+    /// seconds into the recording, not counting time spent paused
+    pub time: f64,
+}
+
+/// Renders chapters as a WebVTT chapters track for a recording `duration` seconds long,
+/// each chapter running until the next one starts.
+pub fn chapters_webvtt(chapters: &[ChapterMarker], duration: f64) -> String {
+    let mut chapters = chapters
+        .iter()
+        .filter(|chapter| chapter.time < duration)
+        .collect::<Vec<_>>();
+    chapters.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+    let mut vtt = "WEBVTT\n".to_string();
+    for (i, chapter) in chapters.iter().enumerate() {
+        let end = chapters.get(i + 1).map_or(duration, |next| next.time);
+        vtt.push_str(&format!(
+            "\n{}\n{} --> {}\n{}\n",
+            i + 1,
+            webvtt_timestamp(chapter.time.max(0.0)),
+            webvtt_timestamp(end),
+            // a cue's text ends at the first blank line and can't contain "-->"
+            chapter.name.replace('\n', " ").replace("-->", "->")
+        ));
+    }
+
+    vtt
+}
+
+fn webvtt_timestamp(secs: f64) -> String {
+    let millis = (secs * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1000 % 60,
+        millis % 1000
+    )
+}
+
+/// When the first screen frame was captured, for lining recordings up with external data.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RecordingStartMarker {
+    /// wall clock time of the first frame, in seconds since the unix epoch
+    pub unix_time: f64,
+    /// seconds between the capture starting and the first frame arriving, measured
+    /// with the capture's monotonic clock so it isn't affected by clock adjustments
+    pub first_frame_offset: f64,
+}
+
 impl specta::Flatten for RecordingMetaInner {}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -84,12 +199,81 @@ pub enum RecordingMetaInner {
 pub struct InstantRecordingMeta {
     pub fps: u32,
     pub sample_rate: Option<u32>,
+    /// set when the output was fitted to a different aspect ratio as the recording finished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aspect_fit: Option<AspectFitMeta>,
+    /// set when the capture was downscaled before being encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capture_scale: Option<CaptureScaleMeta>,
+    /// static frames were held instead of repeated, so `fps` is only the maximum
+    #[serde(default)]
+    pub variable_frame_rate: bool,
+    /// seconds of silence at the start of the output that playback should skip,
+    /// set with `auto_trim_leading_silence`. The file itself is left as recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_start_secs: Option<f64>,
+    #[serde(default)]
+    pub output_format: InstantOutputFormat,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, Type)]
+pub enum VideoCodec {
+    #[default]
+    H264,
+    /// Smaller files at the same quality, but slower to encode and less widely supported
+    H265,
+    /// Only used for WebM output, which can't hold H264
+    Vp9,
+}
+
+/// The container and codecs an instant recording is written with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, Type)]
+pub enum InstantOutputFormat {
+    /// H264 and AAC
+    #[default]
+    Mp4,
+    /// VP9 and Opus
+    WebM,
+}
+
+impl InstantOutputFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "mp4",
+            Self::WebM => "webm",
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Mp4 => "video/mp4",
+            Self::WebM => "video/webm",
+        }
+    }
+
+    /// Where the output is written, relative to the recording's directory
+    pub fn output_path(&self) -> PathBuf {
+        PathBuf::from(format!("content/output.{}", self.extension()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct AspectFitMeta {
+    pub width: u32,
+    pub height: u32,
+    pub fill_mode: AspectFillMode,
 }
 
 impl RecordingMeta {
     pub fn path(&self, relative: &RelativePathBuf) -> PathBuf {
         relative.to_path(&self.project_path)
     }
+    /// The rendition to upload in place of the original recording, if there is one
+    pub fn share_rendition(&self) -> Option<&RenditionMeta> {
+        self.renditions.iter().find(|rendition| rendition.share)
+    }
+
     pub fn load_for_project(project_path: &PathBuf) -> Result<Self, Box<dyn Error>> {
         let meta_path = project_path.join("recording-meta.json");
         let mut meta: Self = serde_json::from_str(&std::fs::read_to_string(&meta_path)?)?;
@@ -132,7 +316,9 @@ impl RecordingMeta {
 
     pub fn output_path(&self) -> PathBuf {
         match &self.inner {
-            RecordingMetaInner::Instant(_) => self.project_path.join("content/output.mp4"),
+            RecordingMetaInner::Instant(meta) => {
+                self.project_path.join(meta.output_format.output_path())
+            }
             RecordingMetaInner::Studio(_) => self.project_path.join("output").join("result.mp4"),
         }
     }
@@ -211,6 +397,98 @@ pub struct MultipleSegments {
     pub segments: Vec<MultipleSegment>,
     #[serde(default, skip_serializing_if = "Cursors::is_empty")]
     pub cursors: Cursors,
+    /// whether the camera was mirrored while recording
+    #[serde(default)]
+    pub camera_mirror: bool,
+    /// set when a window was recorded with a crop relative to its size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_crop: Option<WindowCrop>,
+    /// what the segments' displays were encoded with, which can differ from
+    /// what was chosen if its encoder wasn't available
+    #[serde(default)]
+    pub display_codec: VideoCodec,
+}
+
+/// A region of a window as fractions of its size, eg. `{ x: 0, y: 0, width: 0.5, height: 0.5 }`
+/// for its top-left quarter, so the captured area scales with the window as it resizes.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq)]
+pub struct WindowCrop {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl WindowCrop {
+    pub fn is_valid(&self) -> bool {
+        self.x >= 0.0
+            && self.y >= 0.0
+            && self.width > 0.0
+            && self.height > 0.0
+            && self.x + self.width <= 1.0
+            && self.y + self.height <= 1.0
+    }
+}
+
+/// How a video is fitted to an aspect ratio other than its own
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum AspectFillMode {
+    /// Letterbox or pillarbox with a solid colour
+    #[default]
+    Bars,
+    /// Fill the empty space with a blurred, zoomed in copy of the video
+    BlurFill,
+    /// Zoom in until there's no empty space, cutting off the edges
+    Crop,
+}
+
+impl AspectFillMode {
+    /// Size of a `source` sized video fitted to `aspect` (width:height).
+    ///
+    /// Bars and BlurFill keep every pixel of the source so the output grows around it, while
+    /// Crop shrinks to fit within it. Dimensions are rounded to even numbers for the encoder.
+    pub fn output_size(&self, source: XY<u32>, aspect: (u32, u32)) -> XY<u32> {
+        let ratio = aspect.0.max(1) as f64 / aspect.1.max(1) as f64;
+        let (width, height) = (source.x as f64, source.y as f64);
+        let wider = ratio > width / height;
+
+        let (width, height) = match (self, wider) {
+            (Self::Bars | Self::BlurFill, true) | (Self::Crop, false) => (height * ratio, height),
+            (Self::Bars | Self::BlurFill, false) | (Self::Crop, true) => (width, width / ratio),
+        };
+
+        XY::new(round_even(width), round_even(height))
+    }
+}
+
+/// Size and top-left position of `source` scaled to fit within `canvas`, or to cover it
+/// entirely when `cover` is set, centered. The position is negative on axes that overflow.
+pub fn fit_rect(source: XY<u32>, canvas: XY<u32>, cover: bool) -> (XY<i32>, XY<u32>) {
+    let scale_x = canvas.x as f64 / source.x.max(1) as f64;
+    let scale_y = canvas.y as f64 / source.y.max(1) as f64;
+    let scale = if cover {
+        scale_x.max(scale_y)
+    } else {
+        scale_x.min(scale_y)
+    };
+
+    let size = XY::new(
+        round_even(source.x as f64 * scale),
+        round_even(source.y as f64 * scale),
+    );
+
+    // keep chroma planes aligned
+    let position = XY::new(
+        ((canvas.x as i32 - size.x as i32) / 2) & !1,
+        ((canvas.y as i32 - size.y as i32) / 2) & !1,
+    );
+
+    (position, size)
+}
+
+fn round_even(value: f64) -> u32 {
+    ((value / 2.0).round() as u32 * 2).max(2)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -275,11 +553,17 @@ pub struct MultipleSegment {
     pub camera: Option<VideoMeta>,
     #[serde(default, skip_serializing_if = "Option::is_none", alias = "audio")]
     pub mic: Option<AudioMeta>,
+    /// mics recorded alongside `mic`, each in its own file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub additional_mics: Vec<AudioMeta>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub system_audio: Option<AudioMeta>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     #[specta(type = Option<String>)]
     pub cursor: Option<RelativePathBuf>,
+    /// set when recorded with a region that follows the cursor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor_follow: Option<CursorFollowPath>,
 }
 
 impl MultipleSegment {
@@ -311,7 +595,7 @@ impl MultipleSegment {
             value = value.max(camera.start_time?);
         }
 
-        if let Some(mic) = &self.mic {
+        for mic in self.mic.iter().chain(&self.additional_mics) {
             value = value.max(mic.start_time?);
         }
 
@@ -325,7 +609,10 @@ impl MultipleSegment {
 
 #[cfg(test)]
 mod test {
-    use super::RecordingMeta;
+    use super::{
+        chapters_webvtt, fit_rect, AspectFillMode, ChapterMarker, RecordingMeta, WindowCrop,
+    };
+    use crate::XY;
 
     fn test_meta_deserialize(s: &str) {
         let _: RecordingMeta = serde_json::from_str(s).unwrap();
@@ -432,4 +719,79 @@ mod test {
 		        }"#,
         );
     }
+
+    #[test]
+    fn window_crop_must_fit_within_window() {
+        let crop = |x, y, width, height| WindowCrop {
+            x,
+            y,
+            width,
+            height,
+        };
+
+        assert!(crop(0.0, 0.0, 1.0, 1.0).is_valid());
+        assert!(crop(0.5, 0.5, 0.5, 0.5).is_valid());
+        assert!(!crop(0.6, 0.0, 0.5, 1.0).is_valid());
+        assert!(!crop(-0.1, 0.0, 0.5, 0.5).is_valid());
+        assert!(!crop(0.0, 0.0, 0.0, 0.5).is_valid());
+    }
+
+    #[test]
+    fn aspect_fit_output_size() {
+        let landscape = XY::new(1920, 1080);
+
+        assert_eq!(
+            AspectFillMode::Bars.output_size(landscape, (1, 1)),
+            XY::new(1920, 1920)
+        );
+        assert_eq!(
+            AspectFillMode::BlurFill.output_size(landscape, (9, 16)),
+            XY::new(1920, 3414)
+        );
+        assert_eq!(
+            AspectFillMode::Crop.output_size(landscape, (1, 1)),
+            XY::new(1080, 1080)
+        );
+        assert_eq!(
+            AspectFillMode::Crop.output_size(landscape, (21, 9)),
+            XY::new(1920, 822)
+        );
+        assert_eq!(
+            AspectFillMode::Bars.output_size(landscape, (16, 9)),
+            landscape
+        );
+    }
+
+    #[test]
+    fn fit_rect_centers_source() {
+        let (position, size) = fit_rect(XY::new(1920, 1080), XY::new(1080, 1920), false);
+        assert_eq!(size, XY::new(1080, 608));
+        assert_eq!(position, XY::new(0, 656));
+
+        let (position, size) = fit_rect(XY::new(1920, 1080), XY::new(1080, 1080), true);
+        assert_eq!(size, XY::new(1920, 1080));
+        assert_eq!(position, XY::new(-420, 0));
+    }
+
+    #[test]
+    fn chapters_webvtt_cues() {
+        let chapter = |name: &str, time| ChapterMarker {
+            name: name.to_string(),
+            time,
+        };
+
+        let vtt = chapters_webvtt(
+            &[
+                chapter("Demo --> Q&A", 3665.25),
+                chapter("Intro", 0.0),
+                chapter("After the end", 4000.0),
+            ],
+            3725.5,
+        );
+
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n1\n00:00:00.000 --> 01:01:05.250\nIntro\n\n2\n01:01:05.250 --> 01:02:05.500\nDemo -> Q&A\n"
+        );
+    }
 }
@@ -20,10 +20,24 @@ pub async fn main() {
         RecordingBaseInputs {
             capture_target: ScreenCaptureTarget::primary_display(),
             capture_system_audio: false,
-            mic_feed: &None,
+            mic_feeds: &[],
+            system_audio_feed: &None,
+            audio_sample_rate: None,
+            excluded_apps: vec![],
+            capture_scale: 1.0,
+            skip_static_frames: false,
+            target_fps: None,
         },
         None,
         false,
+        None,
+        None,
+        None,
+        None,
+        false,
+        false,
+        None,
+        Default::default(),
     )
     .await
     .unwrap();
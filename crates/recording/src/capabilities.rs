@@ -0,0 +1,49 @@
+use serde::Serialize;
+
+use crate::{instant_recording, studio_recording};
+
+/// What recording supports with this platform and build, so options that
+/// aren't available can be hidden instead of failing once recording starts.
+#[derive(specta::Type, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureCapabilities {
+    /// Audio the system plays, captured alongside the screen
+    pub system_audio: bool,
+    /// Recording an input device, eg. a virtual loopback device, as system audio
+    pub system_audio_device: bool,
+    /// Capturing a single app's audio rather than the whole system's
+    pub per_app_audio: bool,
+    pub hdr: bool,
+    /// Highest frame rate studio recordings capture at, if the display refreshes that fast
+    pub max_studio_fps: u32,
+    /// Frame rate instant recordings capture at
+    pub instant_fps: u32,
+    /// Recording a single window, following it as it moves
+    pub window_capture: bool,
+    /// Recording part of a window
+    pub window_crop: bool,
+    /// Recording the cursor separately so it can be restyled in the editor
+    pub cursor_capture: bool,
+    pub keystroke_capture: bool,
+    /// Encoding screen capture with the platform's hardware encoder
+    pub hardware_encoding: bool,
+}
+
+/// Computed from compile-time platform support only, so it's cheap to call.
+pub fn capture_capabilities() -> CaptureCapabilities {
+    let supported_platform = cfg!(any(target_os = "macos", windows));
+
+    CaptureCapabilities {
+        system_audio: supported_platform,
+        system_audio_device: supported_platform,
+        per_app_audio: false,
+        hdr: false,
+        max_studio_fps: studio_recording::MAX_FPS,
+        instant_fps: instant_recording::FPS,
+        window_capture: supported_platform,
+        window_crop: supported_platform,
+        cursor_capture: supported_platform,
+        keystroke_capture: false,
+        hardware_encoding: cfg!(target_os = "macos"),
+    }
+}
@@ -6,26 +6,74 @@ use std::{
 };
 
 use cap_media::{
-    data::AudioInfo,
-    encoders::{AACEncoder, AudioEncoder, H264Encoder, MP4File, OpusEncoder},
+    data::{AudioInfo, VideoInfo},
+    encoders::{
+        video_codec_available, AACEncoder, AudioEncoder, H264Encoder, MP4File, OpusEncoder,
+        StaticFrameFilter, VideoCodec,
+    },
     feeds::AudioInputFeed,
     pipeline::{builder::PipelineBuilder, task::PipelineSinkTask, RealTimeClock},
+    platform::Bounds,
     sources::{
-        AVFrameCapture, AudioInputSource, AudioMixer, CMSampleBufferCapture, ScreenCaptureFormat,
-        ScreenCaptureSource, ScreenCaptureTarget,
+        AVFrameCapture, AudioInputSource, AudioMixer, CMSampleBufferCapture, CropRatio,
+        ScreenCaptureFormat, ScreenCaptureSource, ScreenCaptureTarget,
     },
     MediaError,
 };
+use cap_project::{CaptureScaleMeta, InstantOutputFormat, XY};
 use ffmpeg::ffi::AV_TIME_BASE_Q;
 use flume::{Receiver, Sender};
 use tokio::sync::oneshot;
 use tracing::error;
 
-use crate::RecordingError;
+use crate::{RecordingError, MIN_CAPTURE_SCALE};
 
 pub type CapturePipelineBuilder = PipelineBuilder<RealTimeClock<()>>;
 
+/// What a screen capture of `info` is encoded as after downscaling it by `capture_scale`
+pub fn scaled_video_info(info: VideoInfo, capture_scale: f32) -> VideoInfo {
+    let width = info.width as f32 * capture_scale.clamp(MIN_CAPTURE_SCALE, 1.0);
+    info.scaled(width.round() as u32, info.fps())
+}
+
+/// What an instant recording of a capture of `info` is encoded as
+pub fn instant_mode_video_info(info: VideoInfo, capture_scale: f32) -> VideoInfo {
+    #[cfg(target_os = "macos")]
+    {
+        asset_writer_video_info(info, capture_scale)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    scaled_video_info(info, capture_scale)
+}
+
+/// The asset writer always outputs 1080p, which is then scaled. Both sides are kept even,
+/// as H264 can't encode odd sizes.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+fn asset_writer_video_info(info: VideoInfo, capture_scale: f32) -> VideoInfo {
+    let height = (1080.0 * capture_scale.clamp(MIN_CAPTURE_SCALE, 1.0)) as u32 & !1;
+    let width = (info.width as f32 * (height as f32 / info.height as f32)).round() as u32;
+
+    VideoInfo {
+        width: width & !1,
+        height,
+        ..info
+    }
+}
+
+/// Records how a capture was downscaled, if it was
+pub fn capture_scale_meta(native: VideoInfo, scaled: VideoInfo) -> Option<CaptureScaleMeta> {
+    ((native.width, native.height) != (scaled.width, scaled.height)).then(|| CaptureScaleMeta {
+        scale: scaled.width as f32 / native.width as f32,
+        native_size: XY::new(native.width, native.height),
+        scaled_size: XY::new(scaled.width, scaled.height),
+    })
+}
+
 pub trait MakeCapturePipeline: ScreenCaptureFormat + std::fmt::Debug + 'static {
+    /// Whether studio mode pipelines can encode with `codec`
+    fn codec_available(codec: VideoCodec) -> bool;
+
     fn make_studio_mode_pipeline(
         builder: CapturePipelineBuilder,
         source: (
@@ -33,6 +81,9 @@ pub trait MakeCapturePipeline: ScreenCaptureFormat + std::fmt::Debug + 'static {
             flume::Receiver<(Self::VideoFormat, f64)>,
         ),
         output_path: PathBuf,
+        capture_scale: f32,
+        skip_static_frames: bool,
+        codec: VideoCodec,
     ) -> Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError>
     where
         Self: Sized;
@@ -45,15 +96,23 @@ pub trait MakeCapturePipeline: ScreenCaptureFormat + std::fmt::Debug + 'static {
         ),
         audio: Option<&AudioInputFeed>,
         system_audio: Option<(Receiver<(ffmpeg::frame::Audio, f64)>, AudioInfo)>,
+        audio_sample_rate: Option<u32>,
         output_path: PathBuf,
+        output_format: InstantOutputFormat,
         pause_flag: Arc<AtomicBool>,
-    ) -> impl Future<Output = Result<CapturePipelineBuilder, MediaError>> + Send
+        capture_scale: f32,
+        skip_static_frames: bool,
+    ) -> impl Future<Output = Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError>> + Send
     where
         Self: Sized;
 }
 
 #[cfg(target_os = "macos")]
 impl MakeCapturePipeline for cap_media::sources::CMSampleBufferCapture {
+    fn codec_available(codec: VideoCodec) -> bool {
+        cap_media::encoders::MP4AVAssetWriterEncoder::codec_available(codec)
+    }
+
     fn make_studio_mode_pipeline(
         mut builder: CapturePipelineBuilder,
         source: (
@@ -61,14 +120,20 @@ impl MakeCapturePipeline for cap_media::sources::CMSampleBufferCapture {
             flume::Receiver<(Self::VideoFormat, f64)>,
         ),
         output_path: PathBuf,
+        capture_scale: f32,
+        // ScreenCaptureKit only delivers frames when something changed
+        _skip_static_frames: bool,
+        codec: VideoCodec,
     ) -> Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError> {
         let screen_config = source.0.info();
+        let output_height = scaled_video_info(screen_config, capture_scale).height;
         let mut screen_encoder = cap_media::encoders::MP4AVAssetWriterEncoder::init(
             "screen",
             screen_config,
             None,
             output_path.into(),
-            None,
+            (output_height != screen_config.height).then_some(output_height),
+            codec,
         )?;
 
         let (timestamp_tx, timestamp_rx) = flume::bounded(1);
@@ -121,9 +186,19 @@ impl MakeCapturePipeline for cap_media::sources::CMSampleBufferCapture {
         ),
         audio: Option<&AudioInputFeed>,
         system_audio: Option<(Receiver<(ffmpeg::frame::Audio, f64)>, AudioInfo)>,
+        audio_sample_rate: Option<u32>,
         output_path: PathBuf,
+        output_format: InstantOutputFormat,
         pause_flag: Arc<AtomicBool>,
-    ) -> Result<CapturePipelineBuilder, MediaError> {
+        capture_scale: f32,
+        _skip_static_frames: bool,
+    ) -> Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError> {
+        if output_format != InstantOutputFormat::Mp4 {
+            return Err(MediaError::Any(
+                "The asset writer can only write MP4 files".into(),
+            ));
+        }
+
         let (audio_tx, audio_rx) = flume::bounded(64);
         let mut audio_mixer = AudioMixer::new(audio_tx);
 
@@ -144,9 +219,15 @@ impl MakeCapturePipeline for cap_media::sources::CMSampleBufferCapture {
             cap_media::encoders::MP4AVAssetWriterEncoder::init(
                 "mp4",
                 source.0.info(),
-                has_audio_sources.then_some(AudioMixer::info()),
+                // the writer converts to its output settings' rate
+                has_audio_sources.then(|| {
+                    let mut info = AudioMixer::info();
+                    info.sample_rate = audio_sample_rate.unwrap_or(info.sample_rate);
+                    info
+                }),
                 output_path.into(),
-                Some(1080),
+                Some(instant_mode_video_info(source.0.info(), capture_scale).height),
+                VideoCodec::H264,
             )?,
         ));
 
@@ -194,8 +275,11 @@ impl MakeCapturePipeline for cap_media::sources::CMSampleBufferCapture {
             });
         }
 
+        let (timestamp_tx, timestamp_rx) = flume::bounded(1);
+
         let mut first_frame_tx = Some(first_frame_tx);
         builder.spawn_task("screen_capture_encoder", move |ready| {
+            let mut timestamp_tx = Some(timestamp_tx);
             let _ = ready.send(Ok(()));
             while let Ok((frame, unix_time)) = source.1.recv() {
                 if let Ok(mut mp4) = mp4.lock() {
@@ -209,6 +293,10 @@ impl MakeCapturePipeline for cap_media::sources::CMSampleBufferCapture {
                         let _ = first_frame_tx.send((frame.pts(), unix_time));
                     }
 
+                    if let Some(timestamp_tx) = timestamp_tx.take() {
+                        let _ = timestamp_tx.send(unix_time);
+                    }
+
                     mp4.queue_video_frame(frame.as_ref());
                 }
             }
@@ -221,11 +309,15 @@ impl MakeCapturePipeline for cap_media::sources::CMSampleBufferCapture {
 
         builder.spawn_source("screen_capture", source.0);
 
-        Ok(builder)
+        Ok((builder, timestamp_rx))
     }
 }
 
 impl MakeCapturePipeline for AVFrameCapture {
+    fn codec_available(codec: VideoCodec) -> bool {
+        video_codec_available(codec)
+    }
+
     fn make_studio_mode_pipeline(
         mut builder: CapturePipelineBuilder,
         source: (
@@ -233,15 +325,24 @@ impl MakeCapturePipeline for AVFrameCapture {
             flume::Receiver<(Self::VideoFormat, f64)>,
         ),
         output_path: PathBuf,
+        capture_scale: f32,
+        skip_static_frames: bool,
+        codec: VideoCodec,
     ) -> Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError>
     where
         Self: Sized,
     {
         let screen_config = source.0.info();
+        let output_config = scaled_video_info(screen_config, capture_scale);
         let mut screen_encoder = MP4File::init(
             "screen",
             output_path.into(),
-            |o| H264Encoder::builder("screen", screen_config).build(o),
+            |o| {
+                H264Encoder::builder("screen", screen_config)
+                    .with_output_size(output_config.width, output_config.height)
+                    .with_codec(codec)
+                    .build(o)
+            },
             |_| None,
         )?;
 
@@ -251,14 +352,29 @@ impl MakeCapturePipeline for AVFrameCapture {
 
         builder.spawn_task("screen_capture_encoder", move |ready| {
             let mut timestamp_tx = Some(timestamp_tx);
+            let mut static_frames = skip_static_frames.then(StaticFrameFilter::default);
+            // the last frame is always encoded so the recording doesn't end early
+            let mut held_frame = None;
             let _ = ready.send(Ok(()));
 
             while let Ok(frame) = source.1.recv() {
                 if let Some(timestamp_tx) = timestamp_tx.take() {
                     timestamp_tx.send(frame.1).unwrap();
                 }
+
+                if let Some(static_frames) = &mut static_frames {
+                    if !static_frames.keep_frame(&frame.0, frame.1) {
+                        held_frame = Some(frame.0);
+                        continue;
+                    }
+                }
+                held_frame = None;
+
                 screen_encoder.queue_video_frame(frame.0);
             }
+            if let Some(frame) = held_frame {
+                screen_encoder.queue_video_frame(frame);
+            }
             screen_encoder.finish();
             Ok(())
         });
@@ -274,9 +390,13 @@ impl MakeCapturePipeline for AVFrameCapture {
         ),
         audio: Option<&AudioInputFeed>,
         system_audio: Option<(Receiver<(ffmpeg::frame::Audio, f64)>, AudioInfo)>,
+        audio_sample_rate: Option<u32>,
         output_path: PathBuf,
+        output_format: InstantOutputFormat,
         _pause_flag: Arc<AtomicBool>,
-    ) -> Result<CapturePipelineBuilder, MediaError>
+        capture_scale: f32,
+        skip_static_frames: bool,
+    ) -> Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError>
     where
         Self: Sized,
     {
@@ -297,15 +417,13 @@ impl MakeCapturePipeline for AVFrameCapture {
         let has_audio_sources = audio_mixer.has_sources();
 
         let screen_config = source.0.info();
-        let mp4 = Arc::new(std::sync::Mutex::new(MP4File::init(
-            "screen",
-            output_path.into(),
-            |o| H264Encoder::builder("screen", screen_config).build(o),
-            |o| {
-                has_audio_sources.then(|| {
-                    AACEncoder::init("mic_audio", AudioMixer::info(), o).map(|v| v.boxed())
-                })
-            },
+        let output_config = instant_mode_video_info(screen_config, capture_scale);
+        let mp4 = Arc::new(std::sync::Mutex::new(instant_output_file(
+            output_format,
+            output_path,
+            screen_config,
+            output_config,
+            has_audio_sources.then_some(audio_sample_rate),
         )?));
 
         if has_audio_sources {
@@ -325,9 +443,27 @@ impl MakeCapturePipeline for AVFrameCapture {
 
         builder.spawn_source("screen_capture", source.0);
 
+        let (timestamp_tx, timestamp_rx) = flume::bounded(1);
+
         builder.spawn_task("screen_encoder", move |ready| {
+            let mut timestamp_tx = Some(timestamp_tx);
+            let mut static_frames = skip_static_frames.then(StaticFrameFilter::default);
+            // the last frame is always encoded so the recording doesn't end early
+            let mut held_frame = None;
             let _ = ready.send(Ok(()));
             while let Ok((frame, unix_time)) = source.1.recv() {
+                if let Some(timestamp_tx) = timestamp_tx.take() {
+                    let _ = timestamp_tx.send(unix_time);
+                }
+
+                if let Some(static_frames) = &mut static_frames {
+                    if !static_frames.keep_frame(&frame, unix_time) {
+                        held_frame = Some(frame);
+                        continue;
+                    }
+                }
+                held_frame = None;
+
                 if let Ok(mut mp4) = mp4.lock() {
                     // if pause_flag.load(std::sync::atomic::Ordering::Relaxed) {
                     //     mp4.pause();
@@ -339,20 +475,193 @@ impl MakeCapturePipeline for AVFrameCapture {
                 }
             }
             if let Ok(mut mp4) = mp4.lock() {
+                if let Some(frame) = held_frame {
+                    mp4.queue_video_frame(frame);
+                }
                 mp4.finish();
             }
             Ok(())
         });
 
-        Ok(builder)
+        Ok((builder, timestamp_rx))
     }
 }
 
+/// Opens the file an instant recording made with `AVFrameCapture` is encoded into.
+/// `audio` is the output's sample rate, if the recording has audio.
+fn instant_output_file(
+    output_format: InstantOutputFormat,
+    output_path: PathBuf,
+    screen_config: VideoInfo,
+    output_config: VideoInfo,
+    audio: Option<Option<u32>>,
+) -> Result<MP4File, MediaError> {
+    let codec = match output_format {
+        InstantOutputFormat::Mp4 => VideoCodec::H264,
+        InstantOutputFormat::WebM => VideoCodec::Vp9,
+    };
+
+    Ok(MP4File::init_with_extension(
+        "screen",
+        output_path,
+        output_format.extension(),
+        |o| {
+            H264Encoder::builder("screen", screen_config)
+                .with_output_size(output_config.width, output_config.height)
+                .with_codec(codec)
+                .build(o)
+        },
+        |o| {
+            audio.map(|sample_rate| match output_format {
+                InstantOutputFormat::Mp4 => {
+                    AACEncoder::init("mic_audio", AudioMixer::info(), sample_rate, o)
+                        .map(|v| v.boxed())
+                }
+                InstantOutputFormat::WebM => {
+                    OpusEncoder::init("mic_audio", AudioMixer::info(), sample_rate, o)
+                        .map(|v| v.boxed())
+                }
+            })
+        },
+    )?)
+}
+
 type ScreenCaptureReturn<T> = (
     ScreenCaptureSource<T>,
     Receiver<(<T as ScreenCaptureFormat>::VideoFormat, f64)>,
 );
 
+/// A capture using the platform's capture method, or an `AVFrameCapture` for
+/// `ScreenCaptureTarget::AllScreens`, as it composites the screens itself, and when
+/// ffmpeg has to encode the frames
+#[derive(Debug, Clone)]
+pub enum ScreenCapture {
+    Native(ScreenCaptureReturn<ScreenCaptureMethod>),
+    AllScreens(ScreenCaptureReturn<AVFrameCapture>),
+}
+
+impl ScreenCapture {
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            Self::Native((source, _)) => *source.get_bounds(),
+            Self::AllScreens((source, _)) => *source.get_bounds(),
+        }
+    }
+
+    pub fn info(&self) -> VideoInfo {
+        match self {
+            Self::Native((source, _)) => source.info(),
+            Self::AllScreens((source, _)) => source.info(),
+        }
+    }
+
+    pub fn audio_info(&self) -> AudioInfo {
+        match self {
+            Self::Native((source, _)) => source.audio_info(),
+            Self::AllScreens((source, _)) => source.audio_info(),
+        }
+    }
+
+    pub fn crop_ratio(&self) -> CropRatio {
+        match self {
+            Self::Native((source, _)) => source.crop_ratio(),
+            Self::AllScreens((source, _)) => source.crop_ratio(),
+        }
+    }
+
+    pub fn make_studio_mode_pipeline(
+        self,
+        builder: CapturePipelineBuilder,
+        output_path: PathBuf,
+        capture_scale: f32,
+        skip_static_frames: bool,
+        codec: VideoCodec,
+    ) -> Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError> {
+        match self {
+            Self::Native(source) => ScreenCaptureMethod::make_studio_mode_pipeline(
+                builder,
+                source,
+                output_path,
+                capture_scale,
+                skip_static_frames,
+                codec,
+            ),
+            Self::AllScreens(source) => AVFrameCapture::make_studio_mode_pipeline(
+                builder,
+                source,
+                output_path,
+                capture_scale,
+                skip_static_frames,
+                codec,
+            ),
+        }
+    }
+
+    pub async fn make_instant_mode_pipeline(
+        self,
+        builder: CapturePipelineBuilder,
+        audio: Option<&AudioInputFeed>,
+        system_audio: Option<(Receiver<(ffmpeg::frame::Audio, f64)>, AudioInfo)>,
+        audio_sample_rate: Option<u32>,
+        output_path: PathBuf,
+        output_format: InstantOutputFormat,
+        pause_flag: Arc<AtomicBool>,
+        capture_scale: f32,
+        skip_static_frames: bool,
+    ) -> Result<(CapturePipelineBuilder, flume::Receiver<f64>), MediaError> {
+        match self {
+            Self::Native(source) => {
+                ScreenCaptureMethod::make_instant_mode_pipeline(
+                    builder,
+                    source,
+                    audio,
+                    system_audio,
+                    audio_sample_rate,
+                    output_path,
+                    output_format,
+                    pause_flag,
+                    capture_scale,
+                    skip_static_frames,
+                )
+                .await
+            }
+            Self::AllScreens(source) => {
+                AVFrameCapture::make_instant_mode_pipeline(
+                    builder,
+                    source,
+                    audio,
+                    system_audio,
+                    audio_sample_rate,
+                    output_path,
+                    output_format,
+                    pause_flag,
+                    capture_scale,
+                    skip_static_frames,
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// `requested` if it can be encoded with, otherwise H264, which always can
+pub fn available_codec(
+    requested: VideoCodec,
+    codec_available: impl Fn(VideoCodec) -> bool,
+) -> VideoCodec {
+    if codec_available(requested) {
+        requested
+    } else {
+        VideoCodec::H264
+    }
+}
+
+/// Whether a studio recording can be encoded with `codec` whatever it captures. Recordings
+/// can be switched to and from all screens, which are captured differently on macOS.
+pub fn studio_codec_available(codec: VideoCodec) -> bool {
+    ScreenCaptureMethod::codec_available(codec) && AVFrameCapture::codec_available(codec)
+}
+
 #[cfg(target_os = "macos")]
 pub type ScreenCaptureMethod = CMSampleBufferCapture;
 
@@ -364,22 +673,121 @@ pub async fn create_screen_capture(
     show_camera: bool,
     force_show_cursor: bool,
     max_fps: u32,
+    excluded_apps: &[String],
     audio_tx: Option<Sender<(ffmpeg::frame::Audio, f64)>>,
     start_time: SystemTime,
-) -> Result<ScreenCaptureReturn<ScreenCaptureMethod>, RecordingError> {
-    let (video_tx, video_rx) = flume::bounded(16);
-
-    ScreenCaptureSource::<ScreenCaptureMethod>::init(
-        capture_target,
-        None,
-        show_camera,
-        force_show_cursor,
-        max_fps,
-        video_tx,
-        audio_tx,
-        start_time,
-    )
-    .await
-    .map(|v| (v, video_rx))
-    .map_err(|e| RecordingError::Media(MediaError::TaskLaunch(e)))
+    ffmpeg_frames: bool,
+) -> Result<ScreenCapture, RecordingError> {
+    let capture = if ffmpeg_frames || matches!(capture_target, ScreenCaptureTarget::AllScreens) {
+        let (video_tx, video_rx) = flume::bounded(16);
+        ScreenCaptureSource::<AVFrameCapture>::init(
+            capture_target,
+            None,
+            show_camera,
+            force_show_cursor,
+            max_fps,
+            excluded_apps,
+            video_tx,
+            audio_tx,
+            start_time,
+        )
+        .await
+        .map(|v| ScreenCapture::AllScreens((v, video_rx)))
+    } else {
+        let (video_tx, video_rx) = flume::bounded(16);
+        ScreenCaptureSource::<ScreenCaptureMethod>::init(
+            capture_target,
+            None,
+            show_camera,
+            force_show_cursor,
+            max_fps,
+            excluded_apps,
+            video_tx,
+            audio_tx,
+            start_time,
+        )
+        .await
+        .map(|v| ScreenCapture::Native((v, video_rx)))
+    };
+
+    capture.map_err(|e| RecordingError::Media(MediaError::TaskLaunch(e)))
+}
+
+#[cfg(test)]
+mod test {
+    use cap_media::data::RawVideoFormat;
+    use cap_project::{InstantRecordingMeta, RecordingMeta, RecordingMetaInner};
+
+    use super::*;
+
+    #[test]
+    fn webm_output_is_where_the_meta_points() {
+        ffmpeg::init().unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let format = InstantOutputFormat::WebM;
+        let info = VideoInfo::from_raw(RawVideoFormat::YUYV420, 64, 64, 30);
+
+        let mut file = instant_output_file(
+            format,
+            dir.path().join(format.output_path()),
+            info,
+            info,
+            None,
+        )
+        .unwrap();
+        for i in 0..30 {
+            let mut frame = ffmpeg::frame::Video::new(info.pixel_format, info.width, info.height);
+            frame.set_pts(Some(i));
+            file.queue_video_frame(frame);
+        }
+        file.finish();
+
+        let meta = RecordingMeta {
+            platform: None,
+            project_path: dir.path().to_path_buf(),
+            pretty_name: "WebM".to_string(),
+            sharing: None,
+            cover: None,
+            start_marker: None,
+            chapters: vec![],
+            quality: None,
+            renditions: vec![],
+            inner: RecordingMetaInner::Instant(InstantRecordingMeta {
+                fps: 30,
+                sample_rate: None,
+                aspect_fit: None,
+                capture_scale: None,
+                variable_frame_rate: false,
+                trim_start_secs: None,
+                output_format: format,
+            }),
+        };
+
+        let output_path = meta.output_path();
+        assert_eq!(output_path.extension().unwrap(), "webm");
+        assert!(std::fs::metadata(&output_path).unwrap().len() > 0);
+        assert!(!dir.path().join("content/output.mp4").exists());
+    }
+
+    #[test]
+    fn instant_output_sizes_are_even() {
+        for (width, height) in [(1366, 768), (1512, 982), (3024, 1964), (1279, 719)] {
+            let info = VideoInfo::from_raw(RawVideoFormat::Bgra, width, height, 30);
+
+            for capture_scale in [1.0, 0.75, 0.5, 0.3] {
+                let scaled = asset_writer_video_info(info, capture_scale);
+                assert_eq!(scaled.width % 2, 0, "{width}x{height} at {capture_scale}");
+                assert_eq!(scaled.height % 2, 0, "{width}x{height} at {capture_scale}");
+
+                // still the capture's aspect ratio, give or take the rounding
+                let expected_width = width as f32 * scaled.height as f32 / height as f32;
+                assert!((scaled.width as f32 - expected_width).abs() <= 2.0);
+            }
+        }
+
+        let info = VideoInfo::from_raw(RawVideoFormat::Bgra, 1366, 768, 30);
+        let scaled = asset_writer_video_info(info, 1.0);
+        assert_eq!((scaled.width, scaled.height), (1920, 1080));
+    }
 }
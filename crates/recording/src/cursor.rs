@@ -9,13 +9,17 @@ use std::{
 use cap_cursor_capture::RawCursorPosition;
 use cap_displays::Display;
 use cap_media::{platform::Bounds, sources::CropRatio};
-use cap_project::{CursorClickEvent, CursorMoveEvent, XY};
+use cap_project::{CursorClickEvent, CursorMoveEvent, KeyPressEvent, XY};
 use cap_utils::spawn_actor;
 use device_query::{DeviceQuery, DeviceState};
 use futures::future::Either;
 use tokio::sync::oneshot;
 use tracing::{error, info};
 
+/// Moves closer together than this are collapsed into the latest one, the cursor's
+/// polled far more often than anything downstream needs
+const MOVE_SAMPLE_INTERVAL_MS: f64 = 1000.0 / 30.0;
+
 pub struct Cursor {
     pub file_name: String,
     pub id: u32,
@@ -30,6 +34,17 @@ pub struct CursorActorResponse {
     pub next_cursor_id: u32,
     pub moves: Vec<CursorMoveEvent>,
     pub clicks: Vec<CursorClickEvent>,
+    pub keys: Vec<KeyPressEvent>,
+}
+
+/// A click sent as it happens, at the cursor's last recorded position.
+/// Positions are normalized to the captured area, and the centre until the cursor first moves.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LiveCursorClick {
+    pub x: f64,
+    pub y: f64,
+    pub down: bool,
+    pub time_ms: f64,
 }
 
 pub struct CursorActor {
@@ -53,6 +68,8 @@ pub fn spawn_cursor_recorder(
     prev_cursors: Cursors,
     next_cursor_id: u32,
     start_time: SystemTime,
+    click_tx: Option<flume::Sender<LiveCursorClick>>,
+    record_key_presses: bool,
 ) -> CursorActor {
     let (shutdown_tx, mut shutdown_rx) = oneshot::channel::<()>();
     let (tx, rx) = oneshot::channel();
@@ -60,6 +77,12 @@ pub fn spawn_cursor_recorder(
     spawn_actor(async move {
         let device_state = DeviceState::new();
         let mut last_mouse_state = device_state.get_mouse();
+        let mut last_keys = if record_key_presses {
+            device_state.get_keys()
+        } else {
+            vec![]
+        };
+        let mut last_xy = (0.5, 0.5);
 
         #[cfg(target_os = "macos")]
         let mut last_position = RawCursorPosition::get();
@@ -72,6 +95,7 @@ pub fn spawn_cursor_recorder(
             next_cursor_id,
             moves: vec![],
             clicks: vec![],
+            keys: vec![],
         };
 
         loop {
@@ -189,6 +213,8 @@ pub fn spawn_cursor_recorder(
             };
 
             if let Some((x, y)) = position {
+                last_xy = (x, y);
+
                 let mouse_event = CursorMoveEvent {
                     active_modifiers: vec![],
                     cursor_id: cursor_id.clone(),
@@ -197,7 +223,7 @@ pub fn spawn_cursor_recorder(
                     y,
                 };
 
-                response.moves.push(mouse_event);
+                record_move(&mut response.moves, mouse_event);
             }
 
             for (num, &pressed) in mouse_state.button_pressed.iter().enumerate() {
@@ -216,10 +242,23 @@ pub fn spawn_cursor_recorder(
                     cursor_id: cursor_id.clone(),
                     time_ms: elapsed,
                 };
-                response.clicks.push(mouse_event);
+                record_click(
+                    &mut response.clicks,
+                    mouse_event,
+                    last_xy,
+                    click_tx.as_ref(),
+                );
             }
 
             last_mouse_state = mouse_state;
+
+            if record_key_presses {
+                let keys = device_state.get_keys();
+                for _ in keys.iter().filter(|key| !last_keys.contains(key)) {
+                    response.keys.push(KeyPressEvent { time_ms: elapsed });
+                }
+                last_keys = keys;
+            }
         }
 
         info!("cursor recorder done");
@@ -230,6 +269,38 @@ pub fn spawn_cursor_recorder(
     CursorActor { shutdown_tx, rx }
 }
 
+fn record_click(
+    clicks: &mut Vec<CursorClickEvent>,
+    click: CursorClickEvent,
+    (x, y): (f64, f64),
+    click_tx: Option<&flume::Sender<LiveCursorClick>>,
+) {
+    if let Some(click_tx) = click_tx {
+        // nothing's listening if it's disconnected, which is fine
+        let _ = click_tx.send(LiveCursorClick {
+            x,
+            y,
+            down: click.down,
+            time_ms: click.time_ms,
+        });
+    }
+
+    clicks.push(click);
+}
+
+/// Keeps at most one move per `MOVE_SAMPLE_INTERVAL_MS`, replacing the newest move
+/// until the interval's passed so the cursor's last position is never dropped
+fn record_move(moves: &mut Vec<CursorMoveEvent>, event: CursorMoveEvent) {
+    if let [.., prev, last] = moves.as_mut_slice() {
+        if last.time_ms - prev.time_ms < MOVE_SAMPLE_INTERVAL_MS {
+            *last = event;
+            return;
+        }
+    }
+
+    moves.push(event);
+}
+
 #[derive(Debug)]
 struct CursorData {
     image: Vec<u8>,
@@ -594,3 +665,62 @@ fn get_cursor_image_data() -> Option<CursorData> {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn streamed_clicks_match_recorded_clicks() {
+        let (tx, rx) = flume::unbounded();
+        let mut clicks = vec![];
+
+        for (time_ms, down, xy) in [
+            (100.0, true, (0.5, 0.5)),
+            (180.0, false, (0.5, 0.5)),
+            (950.0, true, (0.25, 0.75)),
+            (1010.0, false, (0.3, 0.75)),
+        ] {
+            let click = CursorClickEvent {
+                active_modifiers: vec![],
+                cursor_num: 0,
+                cursor_id: "0".to_string(),
+                time_ms,
+                down,
+            };
+            record_click(&mut clicks, click, xy, Some(&tx));
+        }
+
+        let streamed = rx.drain().collect::<Vec<_>>();
+        assert_eq!(streamed.len(), clicks.len());
+        for (live, recorded) in streamed.iter().zip(&clicks) {
+            assert_eq!(live.time_ms, recorded.time_ms);
+            assert_eq!(live.down, recorded.down);
+        }
+        assert_eq!((streamed[2].x, streamed[2].y), (0.25, 0.75));
+    }
+
+    #[test]
+    fn recorded_moves_are_downsampled() {
+        let mut moves = vec![];
+
+        // 10 seconds polled every 10ms
+        for i in 0..=1000 {
+            let time_ms = i as f64 * 10.0;
+            record_move(
+                &mut moves,
+                CursorMoveEvent {
+                    active_modifiers: vec![],
+                    cursor_id: "0".to_string(),
+                    time_ms,
+                    x: time_ms / 10_000.0,
+                    y: 0.5,
+                },
+            );
+        }
+
+        assert!(moves.len() <= (10_000.0 / MOVE_SAMPLE_INTERVAL_MS) as usize + 2);
+        assert!(moves.windows(2).all(|w| w[0].time_ms < w[1].time_ms));
+        assert_eq!(moves.last().unwrap().time_ms, 10_000.0);
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    future::Future,
     path::PathBuf,
     sync::{atomic::AtomicBool, Arc},
     time::{SystemTime, UNIX_EPOCH},
@@ -9,24 +10,31 @@ use cap_media::{
     feeds::AudioInputFeed,
     pipeline::{Pipeline, RealTimeClock},
     platform::Bounds,
-    sources::{ScreenCaptureSource, ScreenCaptureTarget},
+    sources::{AudioInputSource, AudioMixer, ScreenCaptureTarget},
     MediaError,
 };
-use cap_project::InstantRecordingMeta;
+use cap_project::{
+    CaptureScaleMeta, InstantOutputFormat, InstantRecordingMeta, RecordingStartMarker,
+};
 use cap_utils::{ensure_dir, spawn_actor};
 use flume::Receiver;
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, watch};
 use tracing::{debug, error, info, trace, Instrument};
 
 use crate::{
-    capture_pipeline::{create_screen_capture, MakeCapturePipeline},
-    ActorError, RecordingBaseInputs, RecordingError,
+    capture_pipeline::{
+        capture_scale_meta, create_screen_capture, instant_mode_video_info, ScreenCapture,
+    },
+    clamp_fps,
+    quality::QualityInputs,
+    start_marker, ActorError, RecordingBaseInputs, RecordingError,
 };
 
 struct InstantRecordingPipeline {
     pub inner: Pipeline<RealTimeClock<()>>,
     pub output_path: PathBuf,
     pub pause_flag: Arc<AtomicBool>,
+    pub first_timestamp_rx: flume::Receiver<f64>,
 }
 
 enum InstantRecordingActorState {
@@ -42,11 +50,15 @@ enum InstantRecordingActorState {
     },
 }
 
+pub(crate) const FPS: u32 = 30;
+
 #[derive(Clone)]
 pub struct InstantRecordingHandle {
     ctrl_tx: flume::Sender<InstantRecordingActorControlMessage>,
+    released_rx: watch::Receiver<()>,
     pub capture_target: ScreenCaptureTarget,
     pub bounds: Bounds,
+    pub output_format: InstantOutputFormat,
 }
 
 macro_rules! send_message {
@@ -76,6 +88,13 @@ impl InstantRecordingHandle {
     pub async fn cancel(&self) -> Result<(), RecordingError> {
         send_message!(self.ctrl_tx, InstantRecordingActorControlMessage::Cancel)
     }
+
+    /// Resolves once the actor has exited and dropped everything it held,
+    /// including its capture sources and input feeds.
+    pub fn released(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut released_rx = self.released_rx.clone();
+        async move { while released_rx.changed().await.is_ok() {} }
+    }
 }
 
 pub enum InstantRecordingActorControlMessage {
@@ -101,6 +120,12 @@ pub struct InstantRecordingActor {
     recording_dir: PathBuf,
     capture_target: ScreenCaptureTarget,
     video_info: VideoInfo,
+    start_time: SystemTime,
+    /// the rate the output's audio is encoded at, if it has any
+    sample_rate: Option<u32>,
+    capture_scale: Option<CaptureScaleMeta>,
+    variable_frame_rate: bool,
+    output_format: InstantOutputFormat,
 }
 
 pub struct CompletedInstantRecording {
@@ -108,17 +133,22 @@ pub struct CompletedInstantRecording {
     pub project_path: PathBuf,
     pub display_source: ScreenCaptureTarget,
     pub meta: InstantRecordingMeta,
+    pub start_marker: Option<RecordingStartMarker>,
+    pub quality_inputs: QualityInputs,
 }
 
 #[tracing::instrument(skip_all, name = "instant")]
-async fn create_pipeline<TCaptureFormat: MakeCapturePipeline>(
+async fn create_pipeline(
     output_path: PathBuf,
-    screen_source: (
-        ScreenCaptureSource<TCaptureFormat>,
-        flume::Receiver<(TCaptureFormat::VideoFormat, f64)>,
-    ),
+    output_format: InstantOutputFormat,
+    screen_capture: ScreenCapture,
     audio_input_feed: Option<&AudioInputFeed>,
     system_audio: Option<Receiver<(ffmpeg::frame::Audio, f64)>>,
+    system_audio_feed: Option<&AudioInputFeed>,
+    audio_sample_rate: Option<u32>,
+    capture_scale: f32,
+    skip_static_frames: bool,
+    start_time: SystemTime,
 ) -> Result<
     (
         InstantRecordingPipeline,
@@ -127,19 +157,35 @@ async fn create_pipeline<TCaptureFormat: MakeCapturePipeline>(
     MediaError,
 > {
     let clock = RealTimeClock::<()>::new();
-    let pipeline_builder = Pipeline::builder(clock);
+    let mut pipeline_builder = Pipeline::builder(clock);
 
     let pause_flag = Arc::new(AtomicBool::new(false));
-    let system_audio = system_audio.map(|v| (v, screen_source.0.audio_info()));
-    let pipeline_builder = TCaptureFormat::make_instant_mode_pipeline(
-        pipeline_builder,
-        screen_source,
-        audio_input_feed,
-        system_audio,
-        output_path.clone(),
-        pause_flag.clone(),
-    )
-    .await?;
+    let system_audio = match system_audio_feed {
+        Some(system_audio_feed) => {
+            let (tx, rx) = flume::bounded(64);
+
+            let source = AudioInputSource::init(system_audio_feed, tx, start_time);
+            let info = source.info();
+
+            pipeline_builder.spawn_source("system_audio_capture", source);
+
+            Some((rx, info))
+        }
+        None => system_audio.map(|v| (v, screen_capture.audio_info())),
+    };
+    let (pipeline_builder, first_timestamp_rx) = screen_capture
+        .make_instant_mode_pipeline(
+            pipeline_builder,
+            audio_input_feed,
+            system_audio,
+            audio_sample_rate,
+            output_path.clone(),
+            output_format,
+            pause_flag.clone(),
+            capture_scale,
+            skip_static_frames,
+        )
+        .await?;
 
     let (mut pipeline, pipeline_done_rx) = pipeline_builder.build().await?;
 
@@ -150,6 +196,7 @@ async fn create_pipeline<TCaptureFormat: MakeCapturePipeline>(
             inner: pipeline,
             output_path,
             pause_flag,
+            first_timestamp_rx,
         },
         pipeline_done_rx,
     ))
@@ -159,6 +206,7 @@ pub async fn spawn_instant_recording_actor<'a>(
     id: String,
     recording_dir: PathBuf,
     inputs: RecordingBaseInputs<'a>,
+    output_format: InstantOutputFormat,
 ) -> Result<
     (
         InstantRecordingHandle,
@@ -174,54 +222,88 @@ pub async fn spawn_instant_recording_actor<'a>(
 
     trace!("creating recording actor");
 
-    let content_dir = ensure_dir(&recording_dir.join("content"))?;
+    ensure_dir(&recording_dir.join("content"))?;
 
-    let system_audio = if inputs.capture_system_audio {
+    let system_audio = if inputs.capture_system_audio && inputs.system_audio_feed.is_none() {
         let (tx, rx) = flume::bounded(64);
         (Some(tx), Some(rx))
     } else {
         (None, None)
     };
 
-    let (screen_source, screen_rx) = create_screen_capture(
+    let screen_capture = create_screen_capture(
         &inputs.capture_target,
         true,
         true,
-        30,
+        inputs.target_fps.map_or(FPS, |fps| clamp_fps(fps, None)),
+        &inputs.excluded_apps,
         system_audio.0,
         start_time,
+        // the asset writer only writes mp4s, so other formats are encoded with ffmpeg
+        output_format != InstantOutputFormat::Mp4,
     )
     .await?;
 
-    debug!("screen capture: {screen_source:#?}");
+    debug!("screen capture: {screen_capture:#?}");
 
-    if let Some(audio_feed) = inputs.mic_feed {
+    if inputs.mic_feeds.len() > 1 {
+        return Err(RecordingError::Media(MediaError::Any(
+            "Instant recordings only support one microphone".into(),
+        )));
+    }
+    let mic_feed = inputs.mic_feeds.first();
+
+    if let Some(audio_feed) = mic_feed {
         debug!("mic audio info: {:#?}", audio_feed.audio_info())
     }
 
+    let has_audio =
+        mic_feed.is_some() || inputs.system_audio_feed.is_some() || system_audio.1.is_some();
+    let sample_rate = has_audio.then(|| {
+        inputs
+            .audio_sample_rate
+            .unwrap_or(AudioMixer::info().sample_rate)
+    });
+
     let (pipeline, pipeline_done_rx) = create_pipeline(
-        content_dir.join("output.mp4"),
-        (screen_source.clone(), screen_rx.clone()),
-        inputs.mic_feed.as_ref(),
+        recording_dir.join(output_format.output_path()),
+        output_format,
+        screen_capture.clone(),
+        mic_feed,
         system_audio.1,
+        inputs.system_audio_feed.as_ref(),
+        inputs.audio_sample_rate,
+        inputs.capture_scale,
+        inputs.skip_static_frames,
+        start_time,
     )
     .await?;
 
     let segment_start_time = current_time_f64();
 
     let (ctrl_tx, ctrl_rx) = flume::bounded(1);
+    let (released_tx, released_rx) = watch::channel(());
 
     trace!("spawning recording actor");
 
     spawn_actor({
         let inputs = inputs.clone();
-        let video_info = screen_source.info();
+        let video_info = screen_capture.info();
+        let capture_scale = capture_scale_meta(
+            video_info,
+            instant_mode_video_info(video_info, inputs.capture_scale),
+        );
         async move {
             let mut actor = InstantRecordingActor {
                 id,
                 recording_dir,
                 capture_target: inputs.capture_target,
                 video_info,
+                start_time,
+                sample_rate,
+                capture_scale,
+                variable_frame_rate: inputs.skip_static_frames,
+                output_format,
             };
 
             let mut state = InstantRecordingActorState::Recording {
@@ -244,6 +326,7 @@ pub async fn spawn_instant_recording_actor<'a>(
             info!("recording actor finished");
 
             let _ = done_tx.send(result.map_err(|v| v.to_string()));
+            drop(released_tx);
         }
         .in_current_span()
     });
@@ -251,8 +334,10 @@ pub async fn spawn_instant_recording_actor<'a>(
     Ok((
         InstantRecordingHandle {
             ctrl_tx,
+            released_rx,
             capture_target: inputs.capture_target,
-            bounds: screen_source.get_bounds().clone(),
+            bounds: screen_capture.bounds(),
+            output_format,
         },
         done_rx,
     ))
@@ -368,9 +453,11 @@ async fn run_actor_iteration(
                 State::Paused { pipeline, .. } => pipeline,
             };
 
+            let first_timestamp = pipeline.first_timestamp_rx.try_recv().ok();
+
             let res = shutdown(pipeline).await;
             let res = match res {
-                Ok(_) => stop_recording(actor).await,
+                Ok(_) => stop_recording(actor, first_timestamp).await,
                 Err(e) => Err(e),
             };
 
@@ -429,17 +516,31 @@ async fn run_actor_iteration(
 
 async fn stop_recording(
     actor: InstantRecordingActor,
+    first_timestamp: Option<f64>,
 ) -> Result<CompletedInstantRecording, RecordingError> {
     use cap_project::*;
 
+    let output_path = actor.recording_dir.join(actor.output_format.output_path());
+    let quality_inputs = QualityInputs {
+        videos: vec![(output_path.clone(), actor.video_info.fps())],
+        audio: vec![output_path],
+    };
+
     Ok(CompletedInstantRecording {
         id: actor.id,
         project_path: actor.recording_dir.clone(),
         meta: InstantRecordingMeta {
             fps: actor.video_info.fps(),
-            sample_rate: None,
+            sample_rate: actor.sample_rate,
+            aspect_fit: None,
+            capture_scale: actor.capture_scale,
+            variable_frame_rate: actor.variable_frame_rate,
+            trim_start_secs: None,
+            output_format: actor.output_format,
         },
         display_source: actor.capture_target,
+        start_marker: first_timestamp.map(|offset| start_marker(actor.start_time, offset)),
+        quality_inputs,
     })
 }
 
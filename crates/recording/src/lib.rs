@@ -1,12 +1,14 @@
+pub mod capabilities;
 mod capture_pipeline;
 pub mod cursor;
 pub mod instant_recording;
+pub mod quality;
 pub mod studio_recording;
 
 use std::sync::Arc;
 
 pub use studio_recording::{
-    spawn_studio_recording_actor, CompletedStudioRecording, StudioRecordingHandle,
+    spawn_studio_recording_actor, CompletedSegment, CompletedStudioRecording, StudioRecordingHandle,
 };
 
 use cap_media::{
@@ -19,7 +21,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::sync::Mutex;
 
-#[derive(specta::Type, Serialize, Deserialize, Clone, Debug, Copy)]
+#[derive(specta::Type, Serialize, Deserialize, Clone, Debug, Copy, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub enum RecordingMode {
     Studio,
@@ -42,7 +44,36 @@ pub struct RecordingOptions {
 pub struct RecordingBaseInputs<'a> {
     pub capture_target: ScreenCaptureTarget,
     pub capture_system_audio: bool,
-    pub mic_feed: &'a Option<AudioInputFeed>,
+    /// Each mic is recorded to its own file, instant recordings only support one
+    pub mic_feeds: &'a [AudioInputFeed],
+    /// Input device (eg. a virtual loopback device) recorded as system audio
+    /// instead of the screen capture's own audio
+    pub system_audio_feed: &'a Option<AudioInputFeed>,
+    /// Sample rate to encode audio at, otherwise the sources' rate is kept
+    pub audio_sample_rate: Option<u32>,
+    /// Owner names of apps whose windows are left out of the screen capture
+    pub excluded_apps: Vec<String>,
+    /// Fraction of its captured size the screen is downscaled to before encoding,
+    /// from `MIN_CAPTURE_SCALE` to 1
+    pub capture_scale: f32,
+    /// Encodes unchanging frames as one long frame, giving a variable frame rate
+    pub skip_static_frames: bool,
+    /// Frame rate to capture at instead of the mode's default, see `clamp_fps`
+    pub target_fps: Option<u32>,
+}
+
+pub const MIN_CAPTURE_SCALE: f32 = 0.25;
+
+/// `fps` kept between 1 and the most a recording can be captured at, which is
+/// also limited to the display's `refresh_rate` when it's known
+pub fn clamp_fps(fps: u32, refresh_rate: Option<u32>) -> u32 {
+    let max_fps = refresh_rate
+        .filter(|rate| *rate > 0)
+        .map_or(studio_recording::MAX_FPS, |rate| {
+            rate.min(studio_recording::MAX_FPS)
+        });
+
+    fps.clamp(1, max_fps)
 }
 
 #[derive(specta::Type, Serialize, Deserialize, Clone, Debug)]
@@ -53,6 +84,22 @@ pub enum RecordingOptionCaptureTarget {
     Area { screen_id: u32, bounds: Bounds },
 }
 
+/// `first_frame_offset` is a screen frame timestamp, which are relative to the capture's start time
+fn start_marker(
+    start_time: std::time::SystemTime,
+    first_frame_offset: f64,
+) -> cap_project::RecordingStartMarker {
+    let start_unix = start_time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs_f64())
+        .unwrap_or_default();
+
+    cap_project::RecordingStartMarker {
+        unix_time: start_unix + first_frame_offset,
+        first_frame_offset,
+    }
+}
+
 impl RecordingOptions {
     pub fn camera_label(&self) -> Option<&str> {
         self.camera_label.as_deref()
@@ -83,6 +130,77 @@ pub enum RecordingError {
     #[error("Serde/{0}")]
     Serde(#[from] serde_json::Error),
 
+    #[error("Out of disk space")]
+    DiskFull,
+
+    #[error("Couldn't find the display being recorded, it may have been disconnected")]
+    DisplayNotFound,
+
     #[error("IO/{0}")]
-    Io(#[from] std::io::Error),
+    Io(std::io::Error),
+}
+
+impl From<std::io::Error> for RecordingError {
+    fn from(e: std::io::Error) -> Self {
+        if is_disk_full(&e) {
+            Self::DiskFull
+        } else {
+            Self::Io(e)
+        }
+    }
+}
+
+impl RecordingError {
+    /// Whether a recording failed because the disk filled up. Pipeline errors only
+    /// reach the actor as text, so this goes by the OS's and ffmpeg's wording.
+    /// Errors that are still `io::Error`s are checked by their kind instead.
+    pub fn is_disk_full_message(message: &str) -> bool {
+        const DISK_FULL_MESSAGES: &[&str] = &[
+            "Out of disk space",
+            "No space left on device",
+            "not enough space on the disk",
+            "disk is full",
+        ];
+
+        DISK_FULL_MESSAGES
+            .iter()
+            .any(|disk_full| message.contains(disk_full))
+    }
+}
+
+fn is_disk_full(e: &std::io::Error) -> bool {
+    // ENOSPC on unix, ERROR_HANDLE_DISK_FULL and ERROR_DISK_FULL on windows
+    e.kind() == std::io::ErrorKind::StorageFull
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn out_of_space_write_errors_are_disk_full() {
+        let enospc = if cfg!(windows) { 112 } else { 28 };
+
+        assert!(matches!(
+            RecordingError::from(std::io::Error::from_raw_os_error(enospc)),
+            RecordingError::DiskFull
+        ));
+        assert!(matches!(
+            RecordingError::from(std::io::Error::from(std::io::ErrorKind::StorageFull)),
+            RecordingError::DiskFull
+        ));
+        assert!(matches!(
+            RecordingError::from(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                "denied"
+            )),
+            RecordingError::Io(_)
+        ));
+        assert!(RecordingError::is_disk_full_message(
+            "Failed to write trailer: No space left on device"
+        ));
+        assert!(RecordingError::is_disk_full_message(
+            &RecordingError::DiskFull.to_string()
+        ));
+    }
 }
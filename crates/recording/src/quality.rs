@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+
+use cap_project::RecordingQuality;
+use ffmpeg::media::Type;
+
+/// Audio packets further apart than this are counted as an underrun
+const AUDIO_GAP_TOLERANCE_SECS: f64 = 0.02;
+
+/// The files of a finished recording to analyze once it's stopped, as reading them back
+/// shouldn't hold up stopping.
+#[derive(Debug, Clone, Default)]
+pub struct QualityInputs {
+    /// video files and the frame rate each was recorded at
+    pub videos: Vec<(PathBuf, u32)>,
+    pub audio: Vec<PathBuf>,
+}
+
+impl QualityInputs {
+    pub fn analyze(&self) -> Result<RecordingQuality, String> {
+        analyze_recording(&self.videos, &self.audio)
+    }
+}
+
+/// Works out how many frames a recording's video files are missing from the gaps between
+/// the timestamps of the frames that were written, and how often its audio cut out.
+///
+/// Captures that only deliver frames when the screen changes leave gaps while it's static too,
+/// so the dropped frame count is an upper bound.
+pub fn analyze_recording(
+    videos: &[(PathBuf, u32)],
+    audio: &[PathBuf],
+) -> Result<RecordingQuality, String> {
+    ffmpeg::init().map_err(|e| e.to_string())?;
+
+    let videos = videos
+        .iter()
+        .map(|(path, fps)| Ok((packet_timestamps(path, Type::Video)?, *fps)))
+        .collect::<Result<Vec<_>, String>>()?;
+    let audio = audio
+        .iter()
+        .map(|path| packet_timestamps(path, Type::Audio))
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(score(&videos, &audio))
+}
+
+/// Scores packet timestamps from [`packet_timestamps`], the videos' alongside their frame rates
+fn score(videos: &[(Vec<(f64, f64)>, u32)], audio: &[Vec<(f64, f64)>]) -> RecordingQuality {
+    let mut written_frames = 0;
+    let mut expected_frames = 0;
+    let mut duration = 0.0;
+
+    for (timestamps, fps) in videos {
+        let (Some(first), Some(last)) = (timestamps.first(), timestamps.last()) else {
+            continue;
+        };
+
+        let frame_duration = 1.0 / (*fps).max(1) as f64;
+        let video_duration = last.0 - first.0 + frame_duration;
+        let written = timestamps.len() as u64;
+
+        written_frames += written;
+        expected_frames += ((video_duration * *fps as f64).round() as u64).max(written);
+        duration += video_duration;
+    }
+
+    let mut audio_underruns = 0;
+    for packets in audio {
+        audio_underruns += packets
+            .windows(2)
+            .filter(|pair| pair[1].0 > pair[0].0 + pair[0].1 + AUDIO_GAP_TOLERANCE_SECS)
+            .count() as u32;
+    }
+
+    RecordingQuality {
+        expected_frames,
+        written_frames,
+        dropped_frames: expected_frames - written_frames,
+        average_fps: if duration > 0.0 {
+            written_frames as f64 / duration
+        } else {
+            0.0
+        },
+        audio_underruns,
+    }
+}
+
+/// Start time and duration in seconds of each packet of the file's first stream of `medium`, in order
+fn packet_timestamps(path: &Path, medium: Type) -> Result<Vec<(f64, f64)>, String> {
+    let mut input = ffmpeg::format::input(&path)
+        .map_err(|e| format!("Failed to open {}: {e}", path.display()))?;
+
+    let Some(stream) = input.streams().best(medium) else {
+        return Ok(vec![]);
+    };
+    let index = stream.index();
+    let time_base = f64::from(stream.time_base());
+
+    let mut timestamps = input
+        .packets()
+        .filter(|(stream, _)| stream.index() == index)
+        .filter_map(|(_, packet)| {
+            let pts = packet.pts()?;
+            Some((pts as f64 * time_base, packet.duration() as f64 * time_base))
+        })
+        .collect::<Vec<_>>();
+    timestamps.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    Ok(timestamps)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frames(fps: u32, indices: impl IntoIterator<Item = u32>) -> Vec<(f64, f64)> {
+        let frame_duration = 1.0 / fps as f64;
+
+        indices
+            .into_iter()
+            .map(|i| (i as f64 * frame_duration, frame_duration))
+            .collect()
+    }
+
+    #[test]
+    fn complete_video_drops_nothing() {
+        let quality = score(&[(frames(30, 0..30), 30)], &[]);
+
+        assert_eq!(quality.expected_frames, 30);
+        assert_eq!(quality.written_frames, 30);
+        assert_eq!(quality.dropped_frames, 0);
+        assert!((quality.average_fps - 30.0).abs() < 1e-6);
+        assert_eq!(quality.audio_underruns, 0);
+    }
+
+    #[test]
+    fn gaps_between_frames_count_as_dropped() {
+        let quality = score(&[(frames(30, (0..10).chain(20..30)), 30)], &[]);
+
+        assert_eq!(quality.expected_frames, 30);
+        assert_eq!(quality.written_frames, 20);
+        assert_eq!(quality.dropped_frames, 10);
+        assert!((quality.average_fps - 20.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn segments_add_up() {
+        let quality = score(
+            &[
+                (frames(30, 0..30), 30),
+                (frames(60, (0..30).chain(40..60)), 60),
+                (vec![], 30),
+            ],
+            &[],
+        );
+
+        assert_eq!(quality.expected_frames, 90);
+        assert_eq!(quality.written_frames, 80);
+        assert_eq!(quality.dropped_frames, 10);
+        assert!((quality.average_fps - 40.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn empty_recording_scores_zero() {
+        let quality = score(&[(vec![], 30)], &[vec![]]);
+
+        assert_eq!(quality.expected_frames, 0);
+        assert_eq!(quality.dropped_frames, 0);
+        assert_eq!(quality.average_fps, 0.0);
+        assert_eq!(quality.audio_underruns, 0);
+    }
+
+    #[test]
+    fn audio_gaps_beyond_the_tolerance_are_underruns() {
+        let audio = vec![
+            (0.0, 0.02),
+            (0.02, 0.02),
+            // within the tolerance
+            (0.05, 0.02),
+            (0.2, 0.02),
+            (0.22, 0.02),
+            (0.5, 0.02),
+        ];
+
+        assert_eq!(score(&[], &[audio.clone(), audio]).audio_underruns, 4);
+    }
+}
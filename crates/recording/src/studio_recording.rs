@@ -1,5 +1,6 @@
 use std::{
     collections::HashMap,
+    future::Future,
     path::PathBuf,
     sync::Arc,
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
@@ -7,24 +8,32 @@ use std::{
 
 use cap_media::{
     data::VideoInfo,
-    encoders::{H264Encoder, MP4File, OggFile, OpusEncoder},
+    encoders::{H264Encoder, MP4File, OggFile, OpusEncoder, VideoCodec},
     feeds::{AudioInputFeed, CameraFeed},
-    pipeline::{Pipeline, RealTimeClock},
-    platform::Bounds,
-    sources::{AudioInputSource, CameraSource, ScreenCaptureFormat, ScreenCaptureTarget},
+    pipeline::{builder::PipelineBuilder, Pipeline, RealTimeClock},
+    platform::{get_on_screen_windows, Bounds},
+    sources::{
+        excluded_app_window_ids, list_screens, window_bounds, AudioInputSource, CameraSource,
+        ScreenCaptureFormat, ScreenCaptureTarget,
+    },
     MediaError,
 };
-use cap_project::{CursorEvents, StudioRecordingMeta};
+use cap_project::{CaptureScaleMeta, CursorEvents, StudioRecordingMeta, WindowCrop};
 use cap_utils::spawn_actor;
 use flume::Receiver;
 use relative_path::RelativePathBuf;
-use tokio::sync::{oneshot, Mutex};
-use tracing::{debug, info, trace};
+use tokio::sync::{oneshot, watch, Mutex};
+use tracing::{debug, info, trace, warn};
 
 use crate::{
-    capture_pipeline::{create_screen_capture, MakeCapturePipeline, ScreenCaptureMethod},
-    cursor::{spawn_cursor_recorder, CursorActor, Cursors},
-    ActorError, RecordingBaseInputs, RecordingError,
+    capture_pipeline::{
+        available_codec, capture_scale_meta, create_screen_capture, scaled_video_info,
+        studio_codec_available, ScreenCaptureMethod,
+    },
+    clamp_fps,
+    cursor::{spawn_cursor_recorder, CursorActor, Cursors, LiveCursorClick},
+    quality::QualityInputs,
+    start_marker, ActorError, RecordingBaseInputs, RecordingError,
 };
 
 enum StudioRecordingActorState {
@@ -43,23 +52,57 @@ enum StudioRecordingActorState {
 }
 
 pub enum StudioRecordingActorControlMessage {
-    Pause(oneshot::Sender<Result<(), RecordingError>>),
+    Pause(oneshot::Sender<Result<PathBuf, RecordingError>>),
     Resume(oneshot::Sender<Result<(), RecordingError>>),
     Stop(oneshot::Sender<Result<CompletedStudioRecording, RecordingError>>),
     Cancel(oneshot::Sender<Result<(), RecordingError>>),
+    SetFpsLimit(Option<u32>, oneshot::Sender<Result<(), RecordingError>>),
+    CommitPreRoll(oneshot::Sender<Result<(), RecordingError>>),
+    SwitchTarget(
+        ScreenCaptureTarget,
+        oneshot::Sender<Result<Option<Bounds>, RecordingError>>,
+    ),
 }
 
+pub(crate) const MAX_FPS: u32 = 120;
+const WINDOW_RESIZE_POLL_INTERVAL: Duration = Duration::from_millis(500);
+const EXCLUDED_APPS_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const TARGET_LOST_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
 pub struct StudioRecordingActor {
     id: String,
     recording_dir: PathBuf,
-    fps: u32,
     segments: Vec<StudioRecordingSegment>,
     start_time: SystemTime,
+    segment_duration: Option<Duration>,
+    segment_completed_tx: Option<flume::Sender<CompletedSegment>>,
+    /// window whose crop needs recomputing when it resizes
+    cropped_window: Option<(u32, WindowCrop)>,
+    /// window an area target follows, and the area relative to it
+    anchored_area: Option<(u32, Bounds)>,
+    /// set until the recording is committed, segments roll over this often
+    /// and only the last finished one is kept
+    pre_roll: Option<Duration>,
+    /// when the kept pre-roll begins, set on commit
+    pre_roll_start: Option<f64>,
+    /// the target watched for disappearing, updated when it's switched
+    capture_target_tx: watch::Sender<ScreenCaptureTarget>,
+    display_codec: VideoCodec,
+}
+
+/// Sent whenever a segment's files are finalized, either by pausing,
+/// stopping or rolling over to a new segment.
+#[derive(Debug, Clone)]
+pub struct CompletedSegment {
+    pub index: usize,
+    pub display_path: PathBuf,
+    pub duration: f64,
 }
 
 pub struct StudioRecordingSegment {
     pub start: f64,
     pub end: f64,
+    pub capture_target: ScreenCaptureTarget,
     pipeline: StudioRecordingPipeline,
 }
 
@@ -72,15 +115,21 @@ pub struct ScreenPipelineOutput {
     pub inner: PipelineOutput,
     pub bounds: Bounds,
     pub video_info: VideoInfo,
+    pub capture_scale: Option<CaptureScaleMeta>,
+    pub variable_frame_rate: bool,
 }
 
 struct StudioRecordingPipeline {
     pub inner: Pipeline<RealTimeClock<()>>,
     pub screen: ScreenPipelineOutput,
-    pub microphone: Option<PipelineOutput>,
+    /// one for each mic, in the order they were given
+    pub microphones: Vec<AudioPipelineInfo>,
     pub camera: Option<CameraPipelineInfo>,
     pub cursor: Option<CursorPipeline>,
-    pub system_audio: Option<PipelineOutput>,
+    pub system_audio: Option<AudioPipelineInfo>,
+    /// windows of excluded apps that were open when the segment started
+    pub excluded_windows: Vec<u32>,
+    pub capture_target: ScreenCaptureTarget,
 }
 
 struct CursorPipeline {
@@ -91,6 +140,7 @@ struct CursorPipeline {
 #[derive(Clone)]
 pub struct StudioRecordingHandle {
     ctrl_tx: flume::Sender<StudioRecordingActorControlMessage>,
+    released_rx: watch::Receiver<()>,
     pub capture_target: ScreenCaptureTarget,
     pub bounds: Bounds,
 }
@@ -104,6 +154,14 @@ macro_rules! send_message {
             .map_err(ActorError::from)?;
         rx.await.map_err(|_| ActorError::ActorStopped)?
     }};
+    ($ctrl_tx:expr, $variant:path, $($arg:expr),+) => {{
+        let (tx, rx) = oneshot::channel();
+        $ctrl_tx
+            .send($variant($($arg,)+ tx))
+            .map_err(|_| flume::SendError(()))
+            .map_err(ActorError::from)?;
+        rx.await.map_err(|_| ActorError::ActorStopped)?
+    }};
 }
 
 impl StudioRecordingHandle {
@@ -112,6 +170,12 @@ impl StudioRecordingHandle {
     }
 
     pub async fn pause(&self) -> Result<(), RecordingError> {
+        self.pause_segment().await.map(|_| ())
+    }
+
+    /// Pauses, returning the path of the display recording of the segment that
+    /// pausing ended. It's finalized by the time this returns.
+    pub async fn pause_segment(&self) -> Result<PathBuf, RecordingError> {
         send_message!(self.ctrl_tx, StudioRecordingActorControlMessage::Pause)
     }
 
@@ -122,6 +186,52 @@ impl StudioRecordingHandle {
     pub async fn cancel(&self) -> Result<(), RecordingError> {
         send_message!(self.ctrl_tx, StudioRecordingActorControlMessage::Cancel)
     }
+
+    /// Resolves once the actor has exited and dropped everything it held,
+    /// including its capture sources and input feeds.
+    pub fn released(&self) -> impl Future<Output = ()> + Send + 'static {
+        let mut released_rx = self.released_rx.clone();
+        async move { while released_rx.changed().await.is_ok() {} }
+    }
+
+    /// Caps the screen capture frame rate, starting a new segment if currently recording.
+    pub async fn set_fps_limit(&self, fps: Option<u32>) -> Result<(), RecordingError> {
+        send_message!(
+            self.ctrl_tx,
+            StudioRecordingActorControlMessage::SetFpsLimit,
+            fps
+        )
+    }
+
+    /// Formally starts a recording spawned with a pre-roll, keeping the last
+    /// pre-roll's worth of what's been captured so far.
+    pub async fn commit_pre_roll(&self) -> Result<(), RecordingError> {
+        send_message!(
+            self.ctrl_tx,
+            StudioRecordingActorControlMessage::CommitPreRoll
+        )
+    }
+
+    /// Captures `target` from now on, finishing the current segment and starting a new one
+    /// with it if currently recording, otherwise it's used when resuming.
+    pub async fn switch_capture_target(
+        &mut self,
+        target: ScreenCaptureTarget,
+    ) -> Result<(), RecordingError> {
+        let (tx, rx) = oneshot::channel();
+        self.ctrl_tx
+            .send(StudioRecordingActorControlMessage::SwitchTarget(target, tx))
+            .map_err(|_| flume::SendError(()))
+            .map_err(ActorError::from)?;
+        let bounds = rx.await.map_err(|_| ActorError::ActorStopped)??;
+
+        self.capture_target = target;
+        if let Some(bounds) = bounds {
+            self.bounds = bounds;
+        }
+
+        Ok(())
+    }
 }
 
 pub async fn spawn_studio_recording_actor<'a>(
@@ -130,6 +240,14 @@ pub async fn spawn_studio_recording_actor<'a>(
     base_inputs: RecordingBaseInputs<'a>,
     camera_feed: Option<Arc<Mutex<CameraFeed>>>,
     custom_cursor_capture: bool,
+    segment_duration: Option<Duration>,
+    segment_completed_tx: Option<flume::Sender<CompletedSegment>>,
+    target_lost_tx: Option<flume::Sender<()>>,
+    cursor_click_tx: Option<flume::Sender<LiveCursorClick>>,
+    record_key_presses: bool,
+    camera_only: bool,
+    pre_roll: Option<Duration>,
+    display_codec: VideoCodec,
 ) -> Result<(StudioRecordingHandle, oneshot::Receiver<Result<(), String>>), RecordingError> {
     ensure_dir(&recording_dir)?;
 
@@ -154,19 +272,42 @@ pub async fn spawn_studio_recording_actor<'a>(
         debug!("camera video info: {:#?}", camera_feed.video_info());
     }
 
-    if let Some(audio_feed) = base_inputs.mic_feed {
-        debug!("mic audio info: {:#?}", audio_feed.audio_info())
+    for audio_feed in base_inputs.mic_feeds {
+        debug!(
+            "mic {} audio info: {:#?}",
+            audio_feed.device_name(),
+            audio_feed.audio_info()
+        )
+    }
+    let mic_feeds = base_inputs.mic_feeds.to_vec();
+
+    let requested_codec = display_codec;
+    let display_codec = studio_display_codec(requested_codec, studio_codec_available);
+    if display_codec != requested_codec {
+        warn!("No {requested_codec:?} encoder available, recording with {display_codec:?}");
     }
-    let audio_input_feed = base_inputs.mic_feed.clone();
 
     let mut segment_pipeline_factory = SegmentPipelineFactory::new(
         segments_dir,
         cursors_dir,
         base_inputs.capture_target.clone(),
-        audio_input_feed,
+        mic_feeds,
         base_inputs.capture_system_audio,
+        base_inputs.system_audio_feed.clone(),
+        base_inputs.audio_sample_rate,
+        base_inputs.excluded_apps.clone(),
+        base_inputs.capture_scale,
+        base_inputs.skip_static_frames,
         camera_feed,
-        custom_cursor_capture,
+        // there's no screen to track the cursor on
+        custom_cursor_capture && !camera_only,
+        cursor_click_tx,
+        record_key_presses,
+        camera_only,
+        base_inputs
+            .target_fps
+            .map_or(MAX_FPS, |fps| clamp_fps(fps, None)),
+        display_codec,
         start_time,
     );
 
@@ -178,6 +319,7 @@ pub async fn spawn_studio_recording_actor<'a>(
     let segment_start_time = current_time_f64();
 
     let (ctrl_tx, ctrl_rx) = flume::bounded(1);
+    let (released_tx, released_rx) = watch::channel(());
 
     trace!("spawning recording actor");
 
@@ -186,15 +328,34 @@ pub async fn spawn_studio_recording_actor<'a>(
     debug!("screen bounds: {bounds:?}");
 
     let base_inputs = base_inputs.clone();
-    let fps = pipeline.screen.video_info.fps();
+
+    let (capture_target_tx, capture_target_rx) = watch::channel(base_inputs.capture_target);
+    if let Some(target_lost_tx) = target_lost_tx.filter(|_| !camera_only) {
+        tokio::spawn(watch_capture_target(capture_target_rx, target_lost_tx));
+    }
 
     spawn_actor(async move {
         let mut actor = StudioRecordingActor {
             id,
             recording_dir,
-            fps,
             segments: Vec::new(),
             start_time,
+            segment_duration,
+            segment_completed_tx,
+            cropped_window: if camera_only {
+                None
+            } else {
+                cropped_window(&base_inputs.capture_target)
+            },
+            anchored_area: if camera_only {
+                None
+            } else {
+                anchored_area(&base_inputs.capture_target)
+            },
+            pre_roll,
+            pre_roll_start: None,
+            capture_target_tx,
+            display_codec,
         };
 
         let mut state = StudioRecordingActorState::Recording {
@@ -218,12 +379,17 @@ pub async fn spawn_studio_recording_actor<'a>(
 
         info!("recording actor finished");
 
+        // the factory holds the input feeds, which need to be free before `released` resolves
+        drop(segment_pipeline_factory);
+
         let _ = done_tx.send(result.map_err(|v| v.to_string()));
+        drop(released_tx);
     });
 
     Ok((
         StudioRecordingHandle {
             ctrl_tx,
+            released_rx,
             capture_target: base_inputs.capture_target,
             bounds,
         },
@@ -231,6 +397,182 @@ pub async fn spawn_studio_recording_actor<'a>(
     ))
 }
 
+/// Output file names for mics with the given device names. The first keeps the usual name,
+/// the others are named after their device, numbered when devices share a name.
+fn mic_file_names<'a>(device_names: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+
+    for (i, device_name) in device_names.into_iter().enumerate() {
+        if i == 0 {
+            names.push("audio-input.ogg".to_string());
+            continue;
+        }
+
+        let slug = device_name
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '-'
+                }
+            })
+            .collect::<String>()
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        let base = format!(
+            "audio-input-{}",
+            if slug.is_empty() { "mic" } else { &slug }
+        );
+
+        let mut name = format!("{base}.ogg");
+        let mut n = 2;
+        while names.contains(&name) {
+            name = format!("{base}-{n}.ogg");
+            n += 1;
+        }
+        names.push(name);
+    }
+
+    names
+}
+
+/// `requested` if studio displays can be encoded with it, otherwise H264.
+/// Displays are only ever encoded as H264 or H265.
+fn studio_display_codec(
+    requested: VideoCodec,
+    encoder_available: impl Fn(VideoCodec) -> bool,
+) -> VideoCodec {
+    available_codec(requested, |codec| {
+        matches!(codec, VideoCodec::H264 | VideoCodec::H265) && encoder_available(codec)
+    })
+}
+
+fn cropped_window(target: &ScreenCaptureTarget) -> Option<(u32, WindowCrop)> {
+    match *target {
+        ScreenCaptureTarget::Window {
+            id,
+            crop: Some(crop),
+        } => Some((id, crop)),
+        _ => None,
+    }
+}
+
+fn anchored_area(target: &ScreenCaptureTarget) -> Option<(u32, Bounds)> {
+    match *target {
+        ScreenCaptureTarget::Area {
+            bounds,
+            anchor_window: Some(window),
+            ..
+        } => Some((window, bounds)),
+        _ => None,
+    }
+}
+
+/// Sends on `target_lost_tx` when the capture target's window closes or its screen is
+/// disconnected, once each time it goes missing. Stops when the actor drops the target sender.
+async fn watch_capture_target(
+    mut capture_target_rx: watch::Receiver<ScreenCaptureTarget>,
+    target_lost_tx: flume::Sender<()>,
+) {
+    let mut monitor = TargetLostMonitor::default();
+    let mut interval = tokio::time::interval(TARGET_LOST_POLL_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            changed = capture_target_rx.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                monitor = TargetLostMonitor::default();
+                continue;
+            }
+        }
+
+        let target = *capture_target_rx.borrow();
+        let present = tokio::task::spawn_blocking(move || target_present(&target))
+            .await
+            .unwrap_or(true);
+
+        if monitor.update(present) {
+            warn!("capture target {target:?} is no longer available");
+            if target_lost_tx.send(()).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Whether an area's moved or resized, allowing for captured sizes being rounded up to even
+fn area_moved(captured: &Bounds, current: &Bounds) -> bool {
+    (current.x - captured.x).abs() > 2.0
+        || (current.y - captured.y).abs() > 2.0
+        || (current.width - captured.width).abs() > 2.0
+        || (current.height - captured.height).abs() > 2.0
+}
+
+fn target_present(target: &ScreenCaptureTarget) -> bool {
+    let window_ids = match target {
+        ScreenCaptureTarget::Window { .. }
+        | ScreenCaptureTarget::Area {
+            anchor_window: Some(_),
+            ..
+        } => get_on_screen_windows()
+            .into_iter()
+            .map(|w| w.window_id)
+            .collect::<Vec<_>>(),
+        _ => vec![],
+    };
+    let screen_ids = match target {
+        ScreenCaptureTarget::Screen { .. } | ScreenCaptureTarget::Area { .. } => list_screens()
+            .into_iter()
+            .map(|(screen, _)| screen.id)
+            .collect::<Vec<_>>(),
+        _ => vec![],
+    };
+
+    // nothing listed means we couldn't check, like without screen recording permission
+    if window_ids.is_empty() && screen_ids.is_empty() {
+        return true;
+    }
+
+    target_in(target, &window_ids, &screen_ids)
+}
+
+fn target_in(target: &ScreenCaptureTarget, window_ids: &[u32], screen_ids: &[u32]) -> bool {
+    match target {
+        ScreenCaptureTarget::Window { id, .. } => window_ids.contains(id),
+        ScreenCaptureTarget::Screen { id } => screen_ids.contains(id),
+        ScreenCaptureTarget::Area {
+            screen,
+            anchor_window,
+            ..
+        } => {
+            screen_ids.contains(screen)
+                && anchor_window.map_or(true, |window| window_ids.contains(&window))
+        }
+        ScreenCaptureTarget::AllScreens => true,
+    }
+}
+
+/// Reports the target going missing only on the poll it disappears,
+/// so it's reported again only if it comes back and goes missing again
+#[derive(Default)]
+struct TargetLostMonitor {
+    lost: bool,
+}
+
+impl TargetLostMonitor {
+    fn update(&mut self, present: bool) -> bool {
+        let newly_lost = !present && !self.lost;
+        self.lost = !present;
+        newly_lost
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 enum StudioRecordingActorError {
     #[error("Pipeline receiver dropped")]
@@ -276,6 +618,7 @@ async fn run_actor_iteration(
                     serde_json::to_string_pretty(&CursorEvents {
                         clicks: res.clicks,
                         moves: res.moves,
+                        keys: res.keys,
                     })?,
                 )?;
 
@@ -287,9 +630,23 @@ async fn run_actor_iteration(
             (Default::default(), 0)
         };
 
+        // pre-roll segments are likely to be discarded
+        if let Some(tx) = actor
+            .segment_completed_tx
+            .as_ref()
+            .filter(|_| actor.pre_roll.is_none())
+        {
+            let _ = tx.send(CompletedSegment {
+                index: actor.segments.len(),
+                display_path: pipeline.screen.inner.path.clone(),
+                duration: segment_stop_time - segment_start_time,
+            });
+        }
+
         actor.segments.push(StudioRecordingSegment {
             start: segment_start_time,
             end: segment_stop_time,
+            capture_target: pipeline.capture_target,
             pipeline,
         });
 
@@ -314,6 +671,94 @@ async fn run_actor_iteration(
             segment_start_time,
             segment_start_instant,
         } => {
+            let segment_duration = actor.pre_roll.or(actor.segment_duration);
+            let segment_elapsed = async move {
+                match segment_duration {
+                    Some(duration) => {
+                        tokio::time::sleep_until((segment_start_instant + duration).into()).await
+                    }
+                    None => std::future::pending().await,
+                }
+            };
+
+            let cropped_window = actor.cropped_window;
+            let captured_bounds = pipeline.screen.bounds;
+            let window_resized = async move {
+                let Some((id, crop)) = cropped_window else {
+                    return std::future::pending().await;
+                };
+
+                let mut interval = tokio::time::interval(WINDOW_RESIZE_POLL_INTERVAL);
+                loop {
+                    interval.tick().await;
+
+                    let Some(bounds) = window_bounds(id).map(|b| b.crop(&crop)) else {
+                        continue;
+                    };
+
+                    // captured bounds are rounded up to even sizes
+                    if (bounds.width - captured_bounds.width).abs() > 2.0
+                        || (bounds.height - captured_bounds.height).abs() > 2.0
+                    {
+                        break;
+                    }
+                }
+            };
+
+            let anchored_area = actor.anchored_area;
+            let anchor_window_moved = async move {
+                let Some((id, area)) = anchored_area else {
+                    return std::future::pending().await;
+                };
+
+                let mut interval = tokio::time::interval(WINDOW_RESIZE_POLL_INTERVAL);
+                let mut captured_area = None;
+                loop {
+                    interval.tick().await;
+
+                    let Some(area) = window_bounds(id).map(|b| b.anchored_area(&area)) else {
+                        continue;
+                    };
+
+                    match captured_area {
+                        Some(captured_area) if area_moved(&captured_area, &area) => break,
+                        Some(_) => {}
+                        None => captured_area = Some(area),
+                    }
+                }
+            };
+
+            let excluded_apps = segment_pipeline_factory.excluded_apps.clone();
+            let excluded_windows = pipeline.excluded_windows.clone();
+            let camera_only = segment_pipeline_factory.camera_only;
+            let excluded_window_opened = async move {
+                if excluded_apps.is_empty() || camera_only {
+                    return std::future::pending().await;
+                }
+
+                // exclusions are set when the capture starts, so new windows need a new segment
+                let mut interval = tokio::time::interval(EXCLUDED_APPS_POLL_INTERVAL);
+                loop {
+                    interval.tick().await;
+
+                    if excluded_app_window_ids(&excluded_apps)
+                        .iter()
+                        .any(|id| !excluded_windows.contains(id))
+                    {
+                        break;
+                    }
+                }
+            };
+
+            let roll_over = async move {
+                tokio::select! {
+                    _ = segment_elapsed => "segment duration reached",
+                    _ = window_resized => "captured window resized",
+                    _ = anchor_window_moved => "window the area is anchored to moved",
+                    _ = excluded_window_opened => "excluded app opened a window",
+                }
+            };
+
             tokio::select! {
                 result = &mut pipeline_done_rx => {
                     return match result {
@@ -326,10 +771,46 @@ async fn run_actor_iteration(
 
                             Ok(None)
                         },
+                        Ok(Err(e)) if RecordingError::is_disk_full_message(&e) => {
+                            Err(StudioRecordingActorError::Other(RecordingError::DiskFull.to_string()))
+                        }
                         Ok(Err(e)) => Err(StudioRecordingActorError::Other(e)),
                         Err(_) => Err(StudioRecordingActorError::PipelineReceiverDropped),
                     }
                 },
+                reason = roll_over => {
+                    info!("{reason}, starting segment {}", index + 1);
+
+                    let (cursors, next_cursor_id) = shutdown(pipeline, &mut actor, segment_start_time)
+                        .await
+                        .map_err(|e| StudioRecordingActorError::Other(e.to_string()))?;
+
+                    if actor.pre_roll.is_some() {
+                        // the segment that just finished is a full pre-roll long, so nothing before it is needed
+                        let discarded = actor.segments.len().saturating_sub(1);
+                        for segment in actor.segments.drain(..discarded) {
+                            if let Some(dir) = segment.pipeline.screen.inner.path.parent() {
+                                std::fs::remove_dir_all(dir).ok();
+                            }
+                        }
+                    }
+
+                    let (pipeline, pipeline_done_rx) = segment_pipeline_factory
+                        .create_next(cursors, next_cursor_id)
+                        .await
+                        .map_err(|e| StudioRecordingActorError::Other(e.to_string()))?;
+
+                    return Ok(Some((
+                        State::Recording {
+                            pipeline,
+                            pipeline_done_rx,
+                            index: index + 1,
+                            segment_start_time: current_time_f64(),
+                            segment_start_instant: Instant::now(),
+                        },
+                        actor,
+                    )));
+                },
                 msg = ctrl_rx.recv_async() => {
                     match msg {
                         Ok(msg) => (
@@ -367,9 +848,11 @@ async fn run_actor_iteration(
                 ..
             },
         ) => {
+            let display_path = pipeline.screen.inner.path.clone();
+
             let (res, cursors, next_cursor_id) =
                 match shutdown(pipeline, &mut actor, segment_start_time).await {
-                    Ok((cursors, next_cursor_id)) => (Ok(()), cursors, next_cursor_id),
+                    Ok((cursors, next_cursor_id)) => (Ok(display_path), cursors, next_cursor_id),
                     Err(e) => (Err(e), HashMap::new(), 0),
                 };
 
@@ -456,6 +939,130 @@ async fn run_actor_iteration(
             None
         }
 
+        (
+            Msg::SetFpsLimit(fps, tx),
+            State::Recording {
+                pipeline,
+                index,
+                segment_start_time,
+                ..
+            },
+        ) => {
+            segment_pipeline_factory.fps_limit = fps;
+
+            let result = async {
+                let (cursors, next_cursor_id) =
+                    shutdown(pipeline, &mut actor, segment_start_time).await?;
+                segment_pipeline_factory
+                    .create_next(cursors, next_cursor_id)
+                    .await
+            }
+            .await;
+
+            match result {
+                Ok((pipeline, pipeline_done_rx)) => {
+                    send_response!(tx, Ok(()));
+                    Some((
+                        State::Recording {
+                            pipeline,
+                            pipeline_done_rx,
+                            index: index + 1,
+                            segment_start_time: current_time_f64(),
+                            segment_start_instant: Instant::now(),
+                        },
+                        actor,
+                    ))
+                }
+                Err(e) => {
+                    send_response!(tx, Err(e));
+                    None
+                }
+            }
+        }
+
+        // Applies to the next segment when resuming
+        (Msg::SetFpsLimit(fps, tx), state @ State::Paused { .. }) => {
+            segment_pipeline_factory.fps_limit = fps;
+            send_response!(tx, Ok(()));
+            Some((state, actor))
+        }
+
+        (Msg::SwitchTarget(_, tx), state) if segment_pipeline_factory.camera_only => {
+            send_response!(
+                tx,
+                Err(RecordingError::Media(MediaError::Any(
+                    "Camera only recordings have no capture target".into()
+                )))
+            );
+            Some((state, actor))
+        }
+
+        (
+            Msg::SwitchTarget(target, tx),
+            State::Recording {
+                pipeline,
+                index,
+                segment_start_time,
+                ..
+            },
+        ) => {
+            info!("switching capture target, starting segment {}", index + 1);
+
+            segment_pipeline_factory.capture_target = target;
+            actor.cropped_window = cropped_window(&target);
+            actor.anchored_area = anchored_area(&target);
+            actor.capture_target_tx.send_replace(target);
+
+            let result = async {
+                let (cursors, next_cursor_id) =
+                    shutdown(pipeline, &mut actor, segment_start_time).await?;
+                segment_pipeline_factory
+                    .create_next(cursors, next_cursor_id)
+                    .await
+            }
+            .await;
+
+            match result {
+                Ok((pipeline, pipeline_done_rx)) => {
+                    send_response!(tx, Ok(Some(pipeline.screen.bounds)));
+                    Some((
+                        State::Recording {
+                            pipeline,
+                            pipeline_done_rx,
+                            index: index + 1,
+                            segment_start_time: current_time_f64(),
+                            segment_start_instant: Instant::now(),
+                        },
+                        actor,
+                    ))
+                }
+                Err(e) => {
+                    send_response!(tx, Err(e));
+                    None
+                }
+            }
+        }
+
+        // Applies to the next segment when resuming
+        (Msg::SwitchTarget(target, tx), state @ State::Paused { .. }) => {
+            segment_pipeline_factory.capture_target = target;
+            actor.cropped_window = cropped_window(&target);
+            actor.anchored_area = anchored_area(&target);
+            actor.capture_target_tx.send_replace(target);
+            send_response!(tx, Ok(None));
+            Some((state, actor))
+        }
+
+        (Msg::CommitPreRoll(tx), state) => {
+            if let Some(pre_roll) = actor.pre_roll.take() {
+                info!("committing pre-roll of {pre_roll:?}");
+                actor.pre_roll_start = Some(current_time_f64() - pre_roll.as_secs_f64());
+            }
+
+            send_response!(tx, Ok(()));
+            Some((state, actor))
+        }
+
         (_, state) => Some((state, actor)),
     })
 }
@@ -466,6 +1073,11 @@ pub struct CompletedStudioRecording {
     pub meta: StudioRecordingMeta,
     pub cursor_data: cap_project::CursorImages,
     pub segments: Vec<StudioRecordingSegment>,
+    pub start_marker: Option<cap_project::RecordingStartMarker>,
+    /// Seconds into the first segment that the pre-roll starts,
+    /// anything before it was only captured to fill the pre-roll
+    pub pre_roll_offset: Option<f64>,
+    pub quality_inputs: QualityInputs,
 }
 
 async fn stop_recording(
@@ -490,26 +1102,44 @@ async fn stop_recording(
                     .map(|s| MultipleSegment {
                         display: VideoMeta {
                             path: make_relative(&s.pipeline.screen.inner.path),
-                            fps: actor.fps,
+                            fps: s.pipeline.screen.video_info.fps(),
                             start_time: recv_timestamp(&s.pipeline.screen.inner),
+                            capture_scale: s.pipeline.screen.capture_scale,
+                            variable_frame_rate: s.pipeline.screen.variable_frame_rate,
                         },
                         camera: s.pipeline.camera.as_ref().map(|camera| VideoMeta {
                             path: make_relative(&camera.inner.path),
                             fps: camera.fps,
                             start_time: recv_timestamp(&camera.inner),
+                            capture_scale: None,
+                            variable_frame_rate: false,
                         }),
-                        mic: s.pipeline.microphone.as_ref().map(|mic| AudioMeta {
-                            path: make_relative(&mic.path),
-                            start_time: recv_timestamp(&mic),
+                        mic: s.pipeline.microphones.first().map(|mic| AudioMeta {
+                            path: make_relative(&mic.inner.path),
+                            start_time: recv_timestamp(&mic.inner),
+                            sample_rate: Some(mic.sample_rate),
                         }),
+                        additional_mics: s
+                            .pipeline
+                            .microphones
+                            .iter()
+                            .skip(1)
+                            .map(|mic| AudioMeta {
+                                path: make_relative(&mic.inner.path),
+                                start_time: recv_timestamp(&mic.inner),
+                                sample_rate: Some(mic.sample_rate),
+                            })
+                            .collect(),
                         cursor: s
                             .pipeline
                             .cursor
                             .as_ref()
                             .map(|cursor| make_relative(&cursor.output_path)),
+                        cursor_follow: None,
                         system_audio: s.pipeline.system_audio.as_ref().map(|audio| AudioMeta {
-                            path: make_relative(&audio.path),
-                            start_time: recv_timestamp(audio),
+                            path: make_relative(&audio.inner.path),
+                            start_time: recv_timestamp(&audio.inner),
+                            sample_rate: Some(audio.sample_rate),
                         }),
                     })
                     .collect()
@@ -529,14 +1159,58 @@ async fn stop_recording(
                     })
                     .collect(),
             ),
+            // set by the app, which owns the camera preferences
+            camera_mirror: false,
+            window_crop: actor.cropped_window.map(|(_, crop)| crop),
+            display_codec: actor.display_codec,
         },
     };
 
+    let pre_roll_offset = actor
+        .pre_roll_start
+        .zip(actor.segments.first())
+        .map(|(start, segment)| (start - segment.start).clamp(0.0, segment.end - segment.start));
+
     let project_config = cap_project::ProjectConfiguration::default();
     project_config
         .write(&actor.recording_dir)
         .map_err(RecordingError::from)?;
 
+    let start_marker = match &meta {
+        StudioRecordingMeta::MultipleSegments { inner } => inner
+            .segments
+            .first()
+            .and_then(|s| s.display.start_time)
+            .map(|offset| start_marker(actor.start_time, offset)),
+        StudioRecordingMeta::SingleSegment { .. } => None,
+    };
+
+    let quality_inputs = {
+        let videos = actor
+            .segments
+            .iter()
+            .map(|s| {
+                (
+                    s.pipeline.screen.inner.path.clone(),
+                    s.pipeline.screen.video_info.fps(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let audio = actor
+            .segments
+            .iter()
+            .flat_map(|s| {
+                s.pipeline
+                    .microphones
+                    .iter()
+                    .chain(&s.pipeline.system_audio)
+            })
+            .map(|audio| audio.inner.path.clone())
+            .collect::<Vec<_>>();
+
+        QualityInputs { videos, audio }
+    };
+
     Ok(CompletedStudioRecording {
         id: actor.id,
         project_path: actor.recording_dir.clone(),
@@ -544,6 +1218,9 @@ async fn stop_recording(
         cursor_data: Default::default(),
         // display_source: actor.options.capture_target,
         segments: actor.segments,
+        start_marker,
+        pre_roll_offset,
+        quality_inputs,
     })
 }
 
@@ -551,10 +1228,22 @@ struct SegmentPipelineFactory {
     segments_dir: PathBuf,
     cursors_dir: PathBuf,
     capture_target: ScreenCaptureTarget,
-    audio_input_feed: Option<AudioInputFeed>,
+    mic_feeds: Vec<AudioInputFeed>,
     capture_system_audio: bool,
+    system_audio_feed: Option<AudioInputFeed>,
+    audio_sample_rate: Option<u32>,
+    excluded_apps: Vec<String>,
+    capture_scale: f32,
+    skip_static_frames: bool,
     camera_feed: Option<Arc<Mutex<CameraFeed>>>,
     custom_cursor_capture: bool,
+    cursor_click_tx: Option<flume::Sender<LiveCursorClick>>,
+    record_key_presses: bool,
+    camera_only: bool,
+    /// the most frames a second are captured at, set with `target_fps`
+    max_fps: u32,
+    fps_limit: Option<u32>,
+    display_codec: VideoCodec,
     start_time: SystemTime,
     index: u32,
 }
@@ -564,20 +1253,41 @@ impl SegmentPipelineFactory {
         segments_dir: PathBuf,
         cursors_dir: PathBuf,
         capture_target: ScreenCaptureTarget,
-        audio_input_feed: Option<AudioInputFeed>,
+        mic_feeds: Vec<AudioInputFeed>,
         capture_system_audio: bool,
+        system_audio_feed: Option<AudioInputFeed>,
+        audio_sample_rate: Option<u32>,
+        excluded_apps: Vec<String>,
+        capture_scale: f32,
+        skip_static_frames: bool,
         camera_feed: Option<Arc<Mutex<CameraFeed>>>,
         custom_cursor_capture: bool,
+        cursor_click_tx: Option<flume::Sender<LiveCursorClick>>,
+        record_key_presses: bool,
+        camera_only: bool,
+        max_fps: u32,
+        display_codec: VideoCodec,
         start_time: SystemTime,
     ) -> Self {
         Self {
             segments_dir,
             cursors_dir,
             capture_target,
-            audio_input_feed,
+            mic_feeds,
             capture_system_audio,
+            system_audio_feed,
+            audio_sample_rate,
+            excluded_apps,
+            capture_scale,
+            skip_static_frames,
             camera_feed,
             custom_cursor_capture,
+            cursor_click_tx,
+            record_key_presses,
+            camera_only,
+            max_fps,
+            fps_limit: None,
+            display_codec,
             start_time,
             index: 0,
         }
@@ -599,12 +1309,23 @@ impl SegmentPipelineFactory {
             &self.cursors_dir,
             self.index,
             self.capture_target,
-            &self.audio_input_feed,
+            &self.mic_feeds,
             self.capture_system_audio,
+            &self.system_audio_feed,
+            self.audio_sample_rate,
+            &self.excluded_apps,
+            self.capture_scale,
+            self.skip_static_frames,
             self.camera_feed.as_deref(),
             cursors,
             next_cursors_id,
             self.custom_cursor_capture,
+            self.cursor_click_tx.clone(),
+            self.record_key_presses,
+            self.camera_only,
+            self.max_fps,
+            self.fps_limit,
+            self.display_codec,
             self.start_time.clone(),
         )
         .await?;
@@ -621,12 +1342,23 @@ async fn create_segment_pipeline(
     cursors_dir: &PathBuf,
     index: u32,
     capture_target: ScreenCaptureTarget,
-    mic_feed: &Option<AudioInputFeed>,
+    mic_feeds: &[AudioInputFeed],
     capture_system_audio: bool,
+    system_audio_feed: &Option<AudioInputFeed>,
+    audio_sample_rate: Option<u32>,
+    excluded_apps: &[String],
+    capture_scale: f32,
+    skip_static_frames: bool,
     camera_feed: Option<&Mutex<CameraFeed>>,
     prev_cursors: Cursors,
     next_cursors_id: u32,
     custom_cursor_capture: bool,
+    cursor_click_tx: Option<flume::Sender<LiveCursorClick>>,
+    record_key_presses: bool,
+    camera_only: bool,
+    max_fps: u32,
+    fps_limit: Option<u32>,
+    display_codec: VideoCodec,
     start_time: SystemTime,
 ) -> Result<
     (
@@ -635,23 +1367,43 @@ async fn create_segment_pipeline(
     ),
     RecordingError,
 > {
-    let system_audio = if capture_system_audio {
+    // camera-only recordings have no screen capture to take system audio from,
+    // only a system audio device can be recorded with them
+    let system_audio = if capture_system_audio && system_audio_feed.is_none() && !camera_only {
         let (tx, rx) = flume::bounded(64);
         (Some(tx), Some(rx))
     } else {
         (None, None)
     };
 
-    let (screen_source, screen_rx) = create_screen_capture(
-        &capture_target,
-        false,
-        !custom_cursor_capture,
-        120,
-        system_audio.0,
-        start_time,
-    )
-    .await?;
-    let screen_crop_ratio = screen_source.crop_ratio();
+    // taken before the capture so a window opened in between is caught by the next poll
+    let excluded_windows = excluded_app_window_ids(excluded_apps);
+
+    let screen_capture = if camera_only {
+        None
+    } else {
+        Some(
+            create_screen_capture(
+                &capture_target,
+                false,
+                !custom_cursor_capture,
+                fps_limit.map_or(max_fps, |fps| fps.clamp(1, max_fps)),
+                excluded_apps,
+                system_audio.0,
+                start_time,
+                false,
+            )
+            .await?,
+        )
+    };
+    let screen_crop_ratio = screen_capture
+        .as_ref()
+        .map(|screen_capture| screen_capture.crop_ratio());
+    let screen_audio_info = screen_capture
+        .as_ref()
+        .map_or_else(ScreenCaptureMethod::audio_info, |screen_capture| {
+            screen_capture.audio_info()
+        });
 
     let camera_feed = match camera_feed.as_ref() {
         Some(camera_feed) => Some(camera_feed.lock().await),
@@ -668,16 +1420,19 @@ async fn create_segment_pipeline(
 
     trace!("preparing segment pipeline {index}");
 
-    let screen = {
-        let bounds = screen_source.get_bounds().clone();
-        let video_info = screen_source.info();
-
-        let (pipeline_builder_, screen_timestamp_rx) =
-            ScreenCaptureMethod::make_studio_mode_pipeline(
-                pipeline_builder,
-                (screen_source, screen_rx),
-                screen_output_path.clone(),
-            )?;
+    // camera-only recordings use the camera as their screen, so the editor treats it like any other
+    let screen = if let Some(screen_capture) = screen_capture {
+        let bounds = screen_capture.bounds();
+        let native_video_info = screen_capture.info();
+        let video_info = scaled_video_info(native_video_info, capture_scale);
+
+        let (pipeline_builder_, screen_timestamp_rx) = screen_capture.make_studio_mode_pipeline(
+            pipeline_builder,
+            screen_output_path.clone(),
+            capture_scale,
+            skip_static_frames,
+            display_codec,
+        )?;
         pipeline_builder = pipeline_builder_;
 
         info!(
@@ -695,27 +1450,68 @@ async fn create_segment_pipeline(
             },
             bounds,
             video_info,
+            capture_scale: capture_scale_meta(native_video_info, video_info),
+            variable_frame_rate: skip_static_frames,
+        }
+    } else {
+        let camera_feed = camera_feed
+            .ok_or_else(|| MediaError::Any("Camera-only recordings require a camera".into()))?;
+
+        let (inner, video_info) = create_camera_pipeline(
+            &mut pipeline_builder,
+            camera_feed,
+            screen_output_path,
+            start_time,
+        )?;
+
+        info!("camera-only pipeline prepared");
+
+        ScreenPipelineOutput {
+            inner,
+            bounds: Bounds {
+                x: 0.0,
+                y: 0.0,
+                width: video_info.width as f64,
+                height: video_info.height as f64,
+            },
+            video_info,
+            capture_scale: None,
+            variable_frame_rate: false,
         }
     };
 
-    let microphone = if let Some(mic_source) = mic_feed {
+    let mut microphones = vec![];
+    for (i, (mic_source, file_name)) in mic_feeds
+        .iter()
+        .zip(mic_file_names(
+            mic_feeds.iter().map(|feed| feed.device_name()),
+        ))
+        .enumerate()
+    {
+        // the first mic keeps the names it's always had
+        let task_name = |name: &str| match i {
+            0 => name.to_string(),
+            i => format!("{name}_{i}"),
+        };
+
         let (tx, rx) = flume::bounded(8);
 
         let mic_source = AudioInputSource::init(mic_source, tx, start_time);
 
         let mic_config = mic_source.info();
-        let output_path = dir.join("audio-input.ogg");
+        let output_path = dir.join(file_name);
 
         let mut mic_encoder = OggFile::init(
             output_path.clone(),
-            OpusEncoder::factory("microphone", mic_config),
+            OpusEncoder::factory("microphone", mic_config, audio_sample_rate),
         )?;
+        let sample_rate = mic_encoder.sample_rate();
 
-        pipeline_builder.spawn_source("microphone_capture", mic_source);
+        pipeline_builder.spawn_source(task_name("microphone_capture"), mic_source);
 
         let (timestamp_tx, timestamp_rx) = flume::bounded(1);
 
-        pipeline_builder.spawn_task("microphone_encoder", move |ready| {
+        pipeline_builder.spawn_task(task_name("microphone_encoder"), move |ready| {
             let mut timestamp_tx = Some(timestamp_tx);
             let _ = ready.send(Ok(()));
 
@@ -735,23 +1531,37 @@ async fn create_segment_pipeline(
             output_path.strip_prefix(&segments_dir).unwrap().display()
         );
 
-        Some(PipelineOutput {
-            path: output_path,
-            first_timestamp_rx: timestamp_rx,
-        })
-    } else {
-        None
+        microphones.push(AudioPipelineInfo {
+            inner: PipelineOutput {
+                path: output_path,
+                first_timestamp_rx: timestamp_rx,
+            },
+            sample_rate,
+        });
+    }
+
+    let system_audio_source = match system_audio_feed {
+        Some(system_audio_feed) => {
+            let (tx, rx) = flume::bounded(64);
+
+            let source = AudioInputSource::init(system_audio_feed, tx, start_time);
+            let config = source.info();
+
+            pipeline_builder.spawn_source("system_audio_capture", source);
+
+            Some((config, rx))
+        }
+        None => Some(screen_audio_info).zip(system_audio.1.clone()),
     };
 
-    let system_audio = if let Some((config, channel)) =
-        Some(ScreenCaptureMethod::audio_info()).zip(system_audio.1.clone())
-    {
+    let system_audio = if let Some((config, channel)) = system_audio_source {
         let output_path = dir.join("system_audio.ogg");
 
         let mut system_audio_encoder = OggFile::init(
             output_path.clone(),
-            OpusEncoder::factory("system_audio", config),
+            OpusEncoder::factory("system_audio", config, audio_sample_rate),
         )?;
+        let sample_rate = system_audio_encoder.sample_rate();
 
         let (timestamp_tx, timestamp_rx) = flume::bounded(1);
 
@@ -770,106 +1580,67 @@ async fn create_segment_pipeline(
             Ok(())
         });
 
-        Some(PipelineOutput {
-            path: output_path,
-            first_timestamp_rx: timestamp_rx,
+        Some(AudioPipelineInfo {
+            inner: PipelineOutput {
+                path: output_path,
+                first_timestamp_rx: timestamp_rx,
+            },
+            sample_rate,
         })
     } else {
         None
     };
 
-    let camera = if let Some(camera_feed) = camera_feed {
-        let (tx, rx) = flume::bounded(8);
-
-        let camera_source = CameraSource::init(camera_feed, tx, start_time);
-        let camera_config = camera_source.info();
-        let output_path = dir.join("camera.mp4");
-
-        let mut camera_encoder = MP4File::init(
-            "camera",
-            output_path.clone(),
-            |o| H264Encoder::builder("camera", camera_config).build(o),
-            |_| None,
-        )
-        .map_err(|e| RecordingError::Media(e.into()))?;
-
-        pipeline_builder.spawn_source("camera_capture", camera_source);
-
-        let (timestamp_tx, timestamp_rx) = flume::bounded(1);
-
-        pipeline_builder.spawn_task("camera_encoder", move |ready| {
-            let mut timestamp_tx = Some(timestamp_tx);
-            let _ = ready.send(Ok(()));
-
-            let mut start = None;
-            while let Ok(mut frame) = rx.recv() {
-                if let Some(timestamp_tx) = timestamp_tx.take() {
-                    timestamp_tx.send(frame.1).unwrap();
-                }
-
-                if let Some(start) = start {
-                    frame.0.set_pts(Some(
-                        ((camera_config.time_base.denominator() as f64
-                            / camera_config.time_base.numerator() as f64)
-                            * (frame.1 - start)) as i64,
-                    ));
-                } else {
-                    start = Some(frame.1);
-                    frame.0.set_pts(Some(0));
-                }
+    let camera = match camera_feed.filter(|_| !camera_only) {
+        Some(camera_feed) => {
+            let output_path = dir.join("camera.mp4");
 
-                camera_encoder.queue_video_frame(frame.0);
-            }
-            camera_encoder.finish();
-            Ok(())
-        });
+            let (inner, camera_config) = create_camera_pipeline(
+                &mut pipeline_builder,
+                camera_feed,
+                output_path.clone(),
+                start_time,
+            )?;
 
-        info!(
-            "camera pipeline prepared, will output to {}",
-            output_path.strip_prefix(&segments_dir).unwrap().display()
-        );
+            info!(
+                "camera pipeline prepared, will output to {}",
+                output_path.strip_prefix(&segments_dir).unwrap().display()
+            );
 
-        Some(CameraPipelineInfo {
-            inner: PipelineOutput {
-                path: output_path,
-                first_timestamp_rx: timestamp_rx,
-            },
-            fps: (camera_config.frame_rate.0 / camera_config.frame_rate.1) as u32,
-        })
-    } else {
-        None
+            Some(CameraPipelineInfo {
+                inner,
+                fps: (camera_config.frame_rate.0 / camera_config.frame_rate.1) as u32,
+            })
+        }
+        None => None,
     };
 
-    let cursor = custom_cursor_capture.then(move || {
+    let cursor = if custom_cursor_capture {
+        #[cfg(target_os = "macos")]
+        let display = cursor_display(&capture_target).ok_or(RecordingError::DisplayNotFound)?;
+
         let cursor = spawn_cursor_recorder(
             screen.bounds.clone(),
             #[cfg(target_os = "macos")]
-            cap_displays::Display::list()
-                .into_iter()
-                .find(|m| match &capture_target {
-                    ScreenCaptureTarget::Screen { id }
-                    | ScreenCaptureTarget::Area { screen: id, .. } => {
-                        m.raw_handle().inner().id == *id
-                    }
-                    ScreenCaptureTarget::Window { id } => {
-                        m.raw_handle().inner().id
-                            == cap_media::platform::display_for_window(*id).unwrap().id
-                    }
-                })
-                .unwrap(),
+            display,
+            // custom cursor capture is disabled for camera-only recordings
             #[cfg(target_os = "macos")]
-            screen_crop_ratio,
+            screen_crop_ratio.unwrap(),
             cursors_dir.clone(),
             prev_cursors,
             next_cursors_id,
             start_time,
+            cursor_click_tx,
+            record_key_presses,
         );
 
-        CursorPipeline {
+        Some(CursorPipeline {
             output_path: dir.join("cursor.json"),
             actor: Some(cursor),
-        }
-    });
+        })
+    } else {
+        None
+    };
 
     let (mut pipeline, pipeline_done_rx) = pipeline_builder.build().await?;
 
@@ -881,20 +1652,46 @@ async fn create_segment_pipeline(
         StudioRecordingPipeline {
             inner: pipeline,
             screen,
-            microphone,
+            microphones,
             camera,
             cursor,
             system_audio,
+            excluded_windows,
+            capture_target,
         },
         pipeline_done_rx,
     ))
 }
 
+/// The display cursor positions are recorded relative to, none if it's been disconnected
+#[cfg(target_os = "macos")]
+fn cursor_display(capture_target: &ScreenCaptureTarget) -> Option<cap_displays::Display> {
+    let id = match capture_target {
+        ScreenCaptureTarget::Screen { id } | ScreenCaptureTarget::Area { screen: id, .. } => *id,
+        ScreenCaptureTarget::Window { id, .. } => cap_media::platform::display_for_window(*id)?.id,
+        // positions are relative to the primary screen, and mapped onto the composite's bounds
+        // by the source's crop ratio
+        ScreenCaptureTarget::AllScreens => match ScreenCaptureTarget::primary_display() {
+            ScreenCaptureTarget::Screen { id } => id,
+            _ => return None,
+        },
+    };
+
+    cap_displays::Display::list()
+        .into_iter()
+        .find(|m| m.raw_handle().inner().id == id)
+}
+
 struct CameraPipelineInfo {
     inner: PipelineOutput,
     fps: u32,
 }
 
+struct AudioPipelineInfo {
+    inner: PipelineOutput,
+    sample_rate: u32,
+}
+
 fn ensure_dir(path: &PathBuf) -> Result<PathBuf, MediaError> {
     std::fs::create_dir_all(&path)?;
     Ok(path.clone())
@@ -906,3 +1703,276 @@ fn current_time_f64() -> f64 {
         .unwrap()
         .as_secs_f64()
 }
+
+fn create_camera_pipeline(
+    pipeline_builder: &mut PipelineBuilder<RealTimeClock<()>>,
+    camera_feed: &CameraFeed,
+    output_path: PathBuf,
+    start_time: SystemTime,
+) -> Result<(PipelineOutput, VideoInfo), RecordingError> {
+    let (tx, rx) = flume::bounded(8);
+
+    let camera_source = CameraSource::init(camera_feed, tx, start_time);
+    let camera_config = camera_source.info();
+
+    let mut camera_encoder = MP4File::init(
+        "camera",
+        output_path.clone(),
+        |o| H264Encoder::builder("camera", camera_config).build(o),
+        |_| None,
+    )
+    .map_err(|e| RecordingError::Media(e.into()))?;
+
+    pipeline_builder.spawn_source("camera_capture", camera_source);
+
+    let (timestamp_tx, timestamp_rx) = flume::bounded(1);
+
+    pipeline_builder.spawn_task("camera_encoder", move |ready| {
+        let mut timestamp_tx = Some(timestamp_tx);
+        let _ = ready.send(Ok(()));
+
+        let mut start = None;
+        while let Ok(mut frame) = rx.recv() {
+            if let Some(timestamp_tx) = timestamp_tx.take() {
+                timestamp_tx.send(frame.1).unwrap();
+            }
+
+            if let Some(start) = start {
+                frame.0.set_pts(Some(
+                    ((camera_config.time_base.denominator() as f64
+                        / camera_config.time_base.numerator() as f64)
+                        * (frame.1 - start)) as i64,
+                ));
+            } else {
+                start = Some(frame.1);
+                frame.0.set_pts(Some(0));
+            }
+
+            camera_encoder.queue_video_frame(frame.0);
+        }
+        camera_encoder.finish();
+        Ok(())
+    });
+
+    Ok((
+        PipelineOutput {
+            path: output_path,
+            first_timestamp_rx: timestamp_rx,
+        },
+        camera_config,
+    ))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn mic_file_names_are_distinct() {
+        assert_eq!(
+            mic_file_names(["MacBook Pro Microphone", "Shure MV7", "Shure MV7"]),
+            vec![
+                "audio-input.ogg",
+                "audio-input-shure-mv7.ogg",
+                "audio-input-shure-mv7-2.ogg"
+            ]
+        );
+        assert_eq!(
+            mic_file_names(["Shure MV7", "Shure MV7"]),
+            vec!["audio-input.ogg", "audio-input-shure-mv7.ogg"]
+        );
+        assert_eq!(
+            mic_file_names(["a", "???"]),
+            vec!["audio-input.ogg", "audio-input-mic.ogg"]
+        );
+    }
+
+    #[test]
+    fn removing_target_window_is_reported_once() {
+        let target = ScreenCaptureTarget::Window { id: 7, crop: None };
+        let mut monitor = TargetLostMonitor::default();
+
+        let polls = [vec![3, 7], vec![3, 7], vec![3], vec![3], vec![]];
+        let reported = polls
+            .iter()
+            .filter(|window_ids| monitor.update(target_in(&target, window_ids, &[1])))
+            .count();
+
+        assert_eq!(reported, 1);
+    }
+
+    #[test]
+    fn disconnecting_area_screen_loses_target() {
+        let area = ScreenCaptureTarget::Area {
+            screen: 2,
+            bounds: Bounds {
+                x: 0.0,
+                y: 0.0,
+                width: 400.0,
+                height: 300.0,
+            },
+            anchor_window: None,
+        };
+
+        assert!(target_in(&area, &[], &[1, 2]));
+        assert!(!target_in(&area, &[], &[1]));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a display and screen recording permission"]
+    async fn switching_target_starts_a_new_segment() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let screen = ScreenCaptureTarget::primary_display();
+        let ScreenCaptureTarget::Screen { id } = screen else {
+            unreachable!();
+        };
+        let area = ScreenCaptureTarget::Area {
+            screen: id,
+            bounds: Bounds {
+                x: 0.0,
+                y: 0.0,
+                width: 400.0,
+                height: 300.0,
+            },
+            anchor_window: None,
+        };
+
+        let (mut handle, _done_rx) = spawn_studio_recording_actor(
+            "test".to_string(),
+            dir.path().into(),
+            RecordingBaseInputs {
+                capture_target: screen,
+                capture_system_audio: false,
+                mic_feeds: &[],
+                system_audio_feed: &None,
+                audio_sample_rate: None,
+                excluded_apps: vec![],
+                capture_scale: 1.0,
+                skip_static_frames: false,
+                target_fps: None,
+            },
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+            VideoCodec::H264,
+        )
+        .await
+        .unwrap();
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        handle.switch_capture_target(area).await.unwrap();
+        assert_eq!(handle.capture_target, area);
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let completed = handle.stop().await.unwrap();
+
+        let StudioRecordingMeta::MultipleSegments { inner } = &completed.meta else {
+            panic!("expected multiple segments");
+        };
+        assert_eq!(inner.segments.len(), 2);
+        assert_eq!(
+            completed
+                .segments
+                .iter()
+                .map(|s| s.capture_target)
+                .collect::<Vec<_>>(),
+            vec![screen, area]
+        );
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a display and screen recording permission"]
+    async fn unsupported_target_fps_is_clamped_in_meta() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let screen = ScreenCaptureTarget::primary_display();
+        let ScreenCaptureTarget::Screen { id } = screen else {
+            unreachable!();
+        };
+        let refresh_rate = cap_media::sources::list_screens()
+            .into_iter()
+            .find(|(screen, _)| screen.id == id)
+            .map(|(screen, _)| screen.refresh_rate);
+
+        let (handle, _done_rx) = spawn_studio_recording_actor(
+            "test".to_string(),
+            dir.path().into(),
+            RecordingBaseInputs {
+                capture_target: screen,
+                capture_system_audio: false,
+                mic_feeds: &[],
+                system_audio_feed: &None,
+                audio_sample_rate: None,
+                excluded_apps: vec![],
+                capture_scale: 1.0,
+                skip_static_frames: false,
+                target_fps: Some(1000),
+            },
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            false,
+            false,
+            None,
+            VideoCodec::H264,
+        )
+        .await
+        .unwrap();
+
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let completed = handle.stop().await.unwrap();
+
+        let StudioRecordingMeta::MultipleSegments { inner } = &completed.meta else {
+            panic!("expected multiple segments");
+        };
+        assert_eq!(
+            inner.segments[0].display.fps,
+            crate::clamp_fps(1000, refresh_rate)
+        );
+    }
+
+    #[test]
+    fn unavailable_h265_falls_back_to_h264() {
+        let no_h265 = |codec| codec == VideoCodec::H264;
+        assert_eq!(
+            studio_display_codec(VideoCodec::H265, no_h265),
+            VideoCodec::H264
+        );
+        assert_eq!(
+            studio_display_codec(VideoCodec::H265, |_| true),
+            VideoCodec::H265
+        );
+
+        // VP9 is only for instant recordings' WebMs
+        assert_eq!(
+            studio_display_codec(VideoCodec::Vp9, |_| true),
+            VideoCodec::H264
+        );
+    }
+
+    #[test]
+    fn display_codec_round_trips_through_the_meta() {
+        let meta: StudioRecordingMeta = serde_json::from_value(serde_json::json!({
+            "segments": [],
+            "displayCodec": VideoCodec::H265,
+        }))
+        .unwrap();
+        let StudioRecordingMeta::MultipleSegments { inner } = &meta else {
+            panic!("expected multiple segments");
+        };
+        assert_eq!(inner.display_codec, VideoCodec::H265);
+
+        let json = serde_json::to_value(&meta).unwrap();
+        assert_eq!(json["displayCodec"], serde_json::json!(VideoCodec::H265));
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use cap_project::{
     AspectRatio, CameraShape, CameraXPosition, CameraYPosition, Crop, CursorEvents,
-    ProjectConfiguration, RecordingMeta, StudioRecordingMeta, XY,
+    CursorFollowPath, ProjectConfiguration, RecordingMeta, StudioRecordingMeta, XY,
 };
 use composite_frame::CompositeVideoFrameUniforms;
 use core::f64;
@@ -33,7 +33,7 @@ mod zoom;
 pub use coord::*;
 pub use decoder::DecodedFrame;
 pub use frame_pipeline::RenderedFrame;
-pub use project_recordings::{ProjectRecordingsMeta, SegmentRecordings};
+pub use project_recordings::{Audio, ProjectRecordingsMeta, SegmentRecordings, Video};
 
 use zoom::*;
 
@@ -171,6 +171,7 @@ pub enum RenderingError {
 
 pub struct RenderSegment {
     pub cursor: Arc<CursorEvents>,
+    pub cursor_follow: Option<Arc<CursorFollowPath>>,
     pub decoders: RecordingSegmentDecoders,
 }
 
@@ -231,6 +232,7 @@ pub async fn render_video_to_channel(
                 fps,
                 resolution_base,
                 &segment.cursor,
+                segment.cursor_follow.as_deref(),
                 &segment_frames,
             );
 
@@ -482,6 +484,36 @@ impl ProjectUniforms {
         basis as f64 * padding_factor
     }
 
+    /// Pans the crop so it stays centered on where a cursor-follow recording's region was
+    fn get_follow_crop(
+        options: &RenderOptions,
+        project: &ProjectConfiguration,
+        cursor_follow: &CursorFollowPath,
+        recording_time: f64,
+    ) -> Crop {
+        let crop = Self::get_crop(options, project);
+
+        let region = cursor_follow.position_at(recording_time);
+        let center = XY::new(
+            region.x + cursor_follow.size.x / 2,
+            region.y + cursor_follow.size.y / 2,
+        );
+
+        Crop {
+            position: XY::new(
+                center
+                    .x
+                    .saturating_sub(crop.size.x / 2)
+                    .min(options.screen_size.x.saturating_sub(crop.size.x)),
+                center
+                    .y
+                    .saturating_sub(crop.size.y / 2)
+                    .min(options.screen_size.y.saturating_sub(crop.size.y)),
+            ),
+            size: crop.size,
+        }
+    }
+
     pub fn get_output_size(
         options: &RenderOptions,
         project: &ProjectConfiguration,
@@ -619,6 +651,7 @@ impl ProjectUniforms {
         fps: u32,
         resolution_base: XY<u32>,
         cursor_events: &CursorEvents,
+        cursor_follow: Option<&CursorFollowPath>,
         segment_frames: &DecodedSegmentFrames,
     ) -> Self {
         let options = &constants.options;
@@ -629,7 +662,15 @@ impl ProjectUniforms {
 
         let motion_blur_amount = 0.0;
 
-        let crop = Self::get_crop(options, project);
+        let crop = match cursor_follow {
+            Some(cursor_follow) => Self::get_follow_crop(
+                options,
+                project,
+                cursor_follow,
+                segment_frames.recording_time as f64,
+            ),
+            None => Self::get_crop(options, project),
+        };
 
         let interpolated_cursor = interpolate_cursor(
             cursor_events,
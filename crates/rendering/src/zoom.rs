@@ -54,9 +54,17 @@ pub struct SegmentBounds {
 }
 
 impl SegmentBounds {
-    fn from_segment(segment: &ZoomSegment, interpolated_cursor: Coord<RawDisplayUVSpace>) -> Self {
+    fn from_segment(
+        segment: &ZoomSegment,
+        time: f64,
+        interpolated_cursor: Coord<RawDisplayUVSpace>,
+    ) -> Self {
         let position = match segment.mode {
-            cap_project::ZoomMode::Auto => (interpolated_cursor.x, interpolated_cursor.y),
+            cap_project::ZoomMode::Auto => segment
+                .follow_position_at(time)
+                .map_or((interpolated_cursor.x, interpolated_cursor.y), |p| {
+                    (p.x, p.y)
+                }),
             cap_project::ZoomMode::Manual { x, y } => (x as f64, y as f64),
         };
 
@@ -120,8 +128,11 @@ impl InterpolatedZoom {
                 Self {
                     t: 1.0 - zoom_t,
                     bounds: {
-                        let prev_segment_bounds =
-                            SegmentBounds::from_segment(prev_segment, interpolated_cursor);
+                        let prev_segment_bounds = SegmentBounds::from_segment(
+                            prev_segment,
+                            cursor.time,
+                            interpolated_cursor,
+                        );
 
                         SegmentBounds::new(
                             prev_segment_bounds.top_left * (1.0 - zoom_t)
@@ -140,7 +151,7 @@ impl InterpolatedZoom {
                     t,
                     bounds: {
                         let segment_bounds =
-                            SegmentBounds::from_segment(segment, interpolated_cursor);
+                            SegmentBounds::from_segment(segment, cursor.time, interpolated_cursor);
 
                         SegmentBounds::new(
                             default.top_left * (1.0 - t) + segment_bounds.top_left * t,
@@ -151,8 +162,9 @@ impl InterpolatedZoom {
             }
             (Some(prev_segment), Some(segment)) => {
                 let prev_segment_bounds =
-                    SegmentBounds::from_segment(prev_segment, interpolated_cursor);
-                let segment_bounds = SegmentBounds::from_segment(segment, interpolated_cursor);
+                    SegmentBounds::from_segment(prev_segment, cursor.time, interpolated_cursor);
+                let segment_bounds =
+                    SegmentBounds::from_segment(segment, cursor.time, interpolated_cursor);
 
                 let zoom_t =
                     ease_in(t_clamp((cursor.time - segment.start) / ZOOM_DURATION) as f32) as f64;
@@ -268,6 +280,7 @@ mod test {
             end: 4.0,
             amount: 2.0,
             mode: ZoomMode::Manual { x: 0.5, y: 0.5 },
+            follow_path: vec![],
         }];
 
         test_interp(
@@ -343,12 +356,14 @@ mod test {
                 end: 4.0,
                 amount: 2.0,
                 mode: ZoomMode::Manual { x: 0.0, y: 0.0 },
+                follow_path: vec![],
             },
             ZoomSegment {
                 start: 4.0,
                 end: 6.0,
                 amount: 4.0,
                 mode: ZoomMode::Manual { x: 0.5, y: 0.5 },
+                follow_path: vec![],
             },
         ];
 
@@ -390,12 +405,14 @@ mod test {
                 end: 4.0,
                 amount: 2.0,
                 mode: ZoomMode::Manual { x: 0.5, y: 0.5 },
+                follow_path: vec![],
             },
             ZoomSegment {
                 start: 4.0 + ZOOM_DURATION * 0.75,
                 end: 6.0,
                 amount: 4.0,
                 mode: ZoomMode::Manual { x: 0.5, y: 0.5 },
+                follow_path: vec![],
             },
         ];
 
@@ -444,12 +461,14 @@ mod test {
                 end: 4.0,
                 amount: 2.0,
                 mode: ZoomMode::Manual { x: 0.5, y: 0.5 },
+                follow_path: vec![],
             },
             ZoomSegment {
                 start: 7.0,
                 end: 9.0,
                 amount: 4.0,
                 mode: ZoomMode::Manual { x: 0.0, y: 0.0 },
+                follow_path: vec![],
             },
         ];
 